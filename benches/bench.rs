@@ -123,6 +123,17 @@ impl io::Write for ByteCounter {
     }
 }
 
+struct ReadCounter<R> {
+    rdr: R,
+    reads: usize,
+}
+impl<R: io::Read> io::Read for ReadCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        self.rdr.read(buf)
+    }
+}
+
 macro_rules! bench {
     ($name:ident, $data:ident, $counter:ident, $result:expr) => {
         #[bench]
@@ -349,6 +360,22 @@ bench!(count_game_iter_bytes, GAME, count_iter_bytes, 600000);
 bench!(count_game_iter_str, GAME, count_iter_str, 600000);
 bench!(count_game_read_bytes, GAME, count_read_bytes, 600000);
 bench!(count_game_read_str, GAME, count_read_str, 600000);
+
+#[bench]
+fn clone_game_records(b: &mut Bencher) {
+    let data = GAME.as_bytes();
+    b.bytes = data.len() as u64;
+    let records = collect_records(data);
+    b.iter(|| records.clone())
+}
+
+#[bench]
+fn clone_nfl_records(b: &mut Bencher) {
+    let data = NFL.as_bytes();
+    b.bytes = data.len() as u64;
+    let records = collect_records(data);
+    b.iter(|| records.clone())
+}
 bench_serde!(
     count_pop_deserialize_owned_bytes,
     count_pop_serialize_owned_bytes,
@@ -418,6 +445,22 @@ bench!(count_mbta_iter_str, MBTA, count_iter_str, 90000);
 bench!(count_mbta_read_bytes, MBTA, count_read_bytes, 90000);
 bench!(count_mbta_read_str, MBTA, count_read_str, 90000);
 
+#[bench]
+fn count_long_fields_read_bytes(b: &mut Bencher) {
+    let long = "x".repeat(4 << 20);
+    let data = format!("a,{}\n\"{}\",b\n", long, long);
+    b.bytes = data.len() as u64;
+    b.iter(|| {
+        let counter = ReadCounter { rdr: data.as_bytes(), reads: 0 };
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(counter);
+        assert_eq!(count_read_bytes(&mut rdr), 4);
+        // With the default 8 KiB buffer and no growth, this would take over
+        // 1000 reads.
+        assert!(rdr.get_ref().reads < 30);
+    })
+}
+
 macro_rules! bench_write {
     ($name:ident, $data:ident) => {
         #[bench]
@@ -459,6 +502,58 @@ macro_rules! bench_write_bytes {
 bench_write!(write_nfl_record, NFL);
 bench_write_bytes!(write_nfl_bytes, NFL);
 
+#[bench]
+fn write_long_fields(b: &mut Bencher) {
+    let long = "x".repeat(4 << 20);
+    let quoted = format!("{},{}", long, long);
+    let do_it = || {
+        let mut counter = ByteCounter::default();
+        {
+            let mut wtr = Writer::from_writer(&mut counter);
+            wtr.write_record(&[&long, &quoted]).unwrap();
+            wtr.write_record(&["a", "b"]).unwrap();
+        }
+        counter.count
+    };
+    b.bytes = do_it() as u64;
+    b.iter(do_it)
+}
+
+macro_rules! bench_deserialize_record {
+    ($name:ident, $data:ident, $convert:expr, $type:ty) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let data = $data.as_bytes();
+            b.bytes = data.len() as u64;
+            let mut rdr = ReaderBuilder::new().from_reader(data);
+            let headers = $convert(rdr.byte_headers().unwrap().clone());
+            let records = rdr
+                .byte_records()
+                .map(|r| $convert(r.unwrap()))
+                .collect::<Vec<_>>();
+
+            b.iter(|| {
+                for r in &records {
+                    let _: $type = r.deserialize(Some(&headers)).unwrap();
+                }
+            })
+        }
+    };
+}
+
+bench_deserialize_record!(
+    deserialize_nfl_string_record,
+    NFL,
+    |r| StringRecord::from_byte_record(r).unwrap(),
+    NFLRowOwned
+);
+bench_deserialize_record!(
+    deserialize_nfl_byte_record,
+    NFL,
+    |r: ByteRecord| r,
+    NFLRowOwned
+);
+
 fn count_deserialize_owned_bytes<R, D>(rdr: &mut Reader<R>) -> u64
 where
     R: io::Read,
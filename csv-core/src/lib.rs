@@ -4,6 +4,9 @@
 This crate will never use the standard library. `no_std` support is therefore
 enabled by default.
 
+The `alloc` feature enables a few conveniences that require an allocator (but
+not the standard library), such as `Reader::read_record_into_record`.
+
 If you're looking for more ergonomic CSV parsing routines, please use the
 [`csv`](https://docs.rs/csv) crate.
 
@@ -99,9 +102,12 @@ foo,\"bar,baz\"
 #![deny(missing_docs)]
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub use crate::reader::{
-    ReadFieldNoCopyResult, ReadFieldResult, ReadRecordNoCopyResult,
-    ReadRecordResult, Reader, ReaderBuilder,
+    FieldMeta, ReadCommentResult, ReadFieldNoCopyResult, ReadFieldResult,
+    ReadRecordNoCopyResult, ReadRecordResult, Reader, ReaderBuilder,
 };
 pub use crate::writer::{
     is_non_numeric, quote, WriteResult, Writer, WriterBuilder,
@@ -171,6 +177,13 @@ pub enum QuoteStyle {
     /// writing a field that does not parse as a valid float or integer, then
     /// quotes will be used even if they aren't strictly necessary.
     NonNumeric,
+    /// This puts quotes around all fields that are numeric. Namely, when
+    /// writing a field that parses as a valid float or integer, then quotes
+    /// will be used even if they aren't strictly necessary. Other fields are
+    /// quoted only when necessary.
+    ///
+    /// This is useful for forcing consumers to treat numbers as text.
+    Numeric,
     /// This *never* writes quotes, even if it would produce invalid CSV data.
     Never,
     /// Hints that destructuring should not be exhaustive.
@@ -1,5 +1,8 @@
 use core::fmt;
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use crate::Terminator;
 
 // BE ADVISED
@@ -91,7 +94,9 @@ use crate::Terminator;
 /// * CRLF, LF and CR are each treated as a single record terminator by
 ///   default.
 /// * Records are permitted to be of varying length.
-/// * Empty lines (that do not include other whitespace) are ignored.
+/// * Empty lines (that do not include other whitespace) are ignored, unless
+///   they separate groups of records. See
+///   [`ReaderBuilder::group_separator_blank_lines`](struct.ReaderBuilder.html#method.group_separator_blank_lines).
 #[derive(Clone, Debug)]
 pub struct Reader {
     /// A table-based DFA for parsing CSV.
@@ -112,6 +117,20 @@ pub struct Reader {
     double_quote: bool,
     /// If enabled, lines beginning with this byte are ignored.
     comment: Option<u8>,
+    /// If enabled, a line ending with this byte (outside of quotes) is
+    /// joined with the line that follows it.
+    continuation: Option<u8>,
+    /// Whether a quote inside a quoted field that isn't followed by a quote,
+    /// delimiter or terminator is treated as a literal quote.
+    relaxed_quotes: bool,
+    /// The number of consecutive blank lines that separate groups of
+    /// records. When zero, group separators are not recognized.
+    group_blank_lines: usize,
+    /// The number of consecutive blank lines seen since the last record.
+    blank_lines: usize,
+    /// Whether the last blank line seen ended with a `\r` that may be
+    /// followed by a `\n`.
+    blank_cr: bool,
     /// If enabled (the default), then quotes are respected. When disabled,
     /// quotes are not treated specially.
     quoting: bool,
@@ -126,6 +145,21 @@ pub struct Reader {
     has_read: bool,
     /// The current position in the output buffer when reading a record.
     output_pos: usize,
+    /// Whether to strip a `\r` immediately preceding a `\n` terminator from
+    /// the end of an unquoted field. This is only ever enabled when the
+    /// terminator is `Terminator::Any(b'\n')`.
+    strip_cr: bool,
+    /// Whether the last byte consumed by the previous call was a `\r`. This
+    /// is used to strip a trailing `\r` when a `\n` terminator is split from
+    /// it across calls to `read_record`.
+    prev_cr: bool,
+    /// Whether a trailing `\r` was withheld from the output of the previous
+    /// call to `read_field`. It is written to the output on the next call,
+    /// unless the next byte is a `\n` terminator.
+    pending_cr: bool,
+    /// The metadata of the field currently being parsed. This is only
+    /// tracked by `read_record_meta`.
+    field_meta: FieldMeta,
 }
 
 impl Default for Reader {
@@ -140,11 +174,20 @@ impl Default for Reader {
             escape: None,
             double_quote: true,
             comment: None,
+            continuation: None,
+            relaxed_quotes: false,
+            group_blank_lines: 0,
+            blank_lines: 0,
+            blank_cr: false,
             quoting: true,
             use_nfa: false,
             line: 1,
             has_read: false,
             output_pos: 0,
+            strip_cr: false,
+            prev_cr: false,
+            pending_cr: false,
+            field_meta: FieldMeta::default(),
         }
     }
 }
@@ -154,7 +197,7 @@ impl Default for Reader {
 /// This builder can be used to tweak the field delimiter, record terminator
 /// and more for parsing CSV. Once a CSV `Reader` is built, its configuration
 /// cannot be changed.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ReaderBuilder {
     rdr: Reader,
 }
@@ -168,6 +211,17 @@ impl ReaderBuilder {
     /// Build a CSV parser from this configuration.
     pub fn build(&self) -> Reader {
         let mut rdr = self.rdr.clone();
+        // Line continuations and relaxed quotes both need to look at the
+        // byte after the one that would otherwise end a field before
+        // deciding what to do with it, which the DFA can't do. Counting
+        // blank lines can't be done by the DFA either. Rather than slowing
+        // down the DFA for everyone, these use the NFA instead.
+        rdr.use_nfa = rdr.use_nfa
+            || rdr.continuation.is_some()
+            || rdr.relaxed_quotes
+            || rdr.group_blank_lines > 0;
+        rdr.strip_cr =
+            rdr.strip_cr && matches!(rdr.term, Terminator::Any(b'\n'));
         rdr.build_dfa();
         rdr
     }
@@ -190,6 +244,24 @@ impl ReaderBuilder {
         self
     }
 
+    /// Strip a `\r` that immediately precedes a `\n` record terminator.
+    ///
+    /// When the record terminator is set to `Terminator::Any(b'\n')`, data
+    /// using `\r\n` line endings results in a trailing `\r` at the end of
+    /// the last field of every record. When this is enabled, a single `\r`
+    /// immediately preceding the terminator is removed from the last field,
+    /// unless it is inside quotes. A `\r` anywhere else is left untouched.
+    ///
+    /// This has no effect unless the terminator is `Terminator::Any(b'\n')`.
+    /// (The default terminator, `Terminator::CRLF`, already treats `\r\n` as
+    /// a single terminator.)
+    ///
+    /// This is disabled by default.
+    pub fn strip_trailing_cr(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.rdr.strip_cr = yes;
+        self
+    }
+
     /// The quote character to use when parsing CSV.
     ///
     /// The default is `b'"'`.
@@ -230,7 +302,11 @@ impl ReaderBuilder {
     /// The comment character to use when parsing CSV.
     ///
     /// If the start of a record begins with the byte given here, then that
-    /// line is ignored by the CSV parser.
+    /// line is ignored by the CSV parser. Only the first byte of a record is
+    /// checked, so a field that begins with the comment byte inside quotes
+    /// (e.g., `"#foo",bar`) is never treated as a comment. Similarly, when
+    /// quoting is enabled, a record that starts with a quote is never a
+    /// comment, even if the comment byte is the same as the quote byte.
     ///
     /// This is disabled by default.
     pub fn comment(&mut self, comment: Option<u8>) -> &mut ReaderBuilder {
@@ -238,6 +314,96 @@ impl ReaderBuilder {
         self
     }
 
+    /// The line continuation character to use when parsing CSV.
+    ///
+    /// When a line ends with the byte given here, the byte and the record
+    /// terminator that follows it are dropped and the next line is parsed as
+    /// part of the same record. For example, with a continuation byte of
+    /// `\`, the lines `a,b\` and `c,d` are parsed as the single record
+    /// `["a", "bc", "d"]`.
+    ///
+    /// The continuation byte is only recognized outside of quoted fields and
+    /// only when it immediately precedes a record terminator. Anywhere else,
+    /// it is treated like any other byte. The delimiter, quote and terminator
+    /// take precedence over the continuation byte if they are the same byte.
+    ///
+    /// Enabling this makes parsing slower, since it requires a slower
+    /// parsing strategy.
+    ///
+    /// This is disabled by default.
+    pub fn line_continuation(
+        &mut self,
+        continuation: Option<u8>,
+    ) -> &mut ReaderBuilder {
+        self.rdr.continuation = continuation;
+        self
+    }
+
+    /// Enable relaxed handling of quotes.
+    ///
+    /// Quotes are only ever special at the start of a field. A quote that
+    /// appears anywhere else in an unquoted field (e.g., the inch mark in
+    /// `3" pipe`) is always treated as literal content. When this is
+    /// enabled, the same is true inside of quoted fields: a quote that isn't
+    /// followed by another quote, a delimiter or a record terminator is
+    /// treated as a literal quote instead of closing the field. For example,
+    /// `"3" pipe",42` is parsed as the record `["3\" pipe", "42"]` instead
+    /// of `["3 pipe\"", "42"]`.
+    ///
+    /// Enabling this makes parsing slower, since it requires a slower
+    /// parsing strategy.
+    ///
+    /// This is disabled by default.
+    pub fn relaxed_quotes(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.rdr.relaxed_quotes = yes;
+        self
+    }
+
+    /// Treat a run of `n` consecutive blank lines as a separator between
+    /// groups of records.
+    ///
+    /// Normally, blank lines are skipped. When this is set to a non-zero
+    /// value, the parser stops skipping blank lines once it has seen `n` of
+    /// them in a row and reports a group boundary instead. A boundary is
+    /// reported by `read_record` (and its variants) as
+    /// `ReadRecordResult::Record` with no fields, which can't happen
+    /// otherwise. Any further blank lines in the same run are skipped.
+    ///
+    /// A blank line is a line consisting only of a record terminator.
+    /// Boundaries are reported wherever they occur, including before the
+    /// first record and after the last one. `read_field` never reports them.
+    ///
+    /// Enabling this makes parsing slower, since it requires a slower
+    /// parsing strategy.
+    ///
+    /// This is disabled (set to `0`) by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv_core::{ReadRecordResult, ReaderBuilder};
+    ///
+    /// let mut rdr = ReaderBuilder::new().group_separator_blank_lines(2).build();
+    /// let mut input = &b"a\n\n\nb\n"[..];
+    /// let (mut output, mut ends) = ([0; 1024], [0; 10]);
+    ///
+    /// let (res, nin, _, nend) = rdr.read_record(input, &mut output, &mut ends);
+    /// assert_eq!((res, nend), (ReadRecordResult::Record, 1));
+    /// input = &input[nin..];
+    /// let (res, nin, _, nend) = rdr.read_record(input, &mut output, &mut ends);
+    /// assert_eq!((res, nend), (ReadRecordResult::Record, 0));
+    /// input = &input[nin..];
+    /// let (res, _, _, nend) = rdr.read_record(input, &mut output, &mut ends);
+    /// assert_eq!((res, nend), (ReadRecordResult::Record, 1));
+    /// ```
+    pub fn group_separator_blank_lines(
+        &mut self,
+        n: usize,
+    ) -> &mut ReaderBuilder {
+        self.rdr.group_blank_lines = n;
+        self
+    }
+
     /// A convenience method for specifying a configuration to read ASCII
     /// delimited text.
     ///
@@ -258,6 +424,32 @@ impl ReaderBuilder {
     }
 }
 
+/// Metadata describing how a single field appeared in the CSV data.
+///
+/// This is written by
+/// [`Reader::read_record_meta`](struct.Reader.html#method.read_record_meta)
+/// for each field it parses.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FieldMeta {
+    quoted: bool,
+    had_escapes: bool,
+}
+
+impl FieldMeta {
+    /// Returns true if and only if the field began with a quote.
+    #[inline]
+    pub fn quoted(&self) -> bool {
+        self.quoted
+    }
+
+    /// Returns true if and only if the field contained at least one escaped
+    /// quote, either as a doubled quote or via the escape byte.
+    #[inline]
+    pub fn had_escapes(&self) -> bool {
+        self.had_escapes
+    }
+}
+
 /// The result of parsing at most one field from CSV data.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ReadFieldResult {
@@ -395,6 +587,21 @@ pub enum ReadRecordNoCopyResult {
     End,
 }
 
+/// The result of reading at most one comment line from CSV data.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReadCommentResult {
+    /// The caller provided input was exhausted before it was known whether
+    /// the next row is a comment, or before the end of the comment was found.
+    InputEmpty,
+    /// The caller provided output buffer was filled before the entire comment
+    /// could be written to it.
+    OutputFull,
+    /// The end of a comment line was found.
+    Comment,
+    /// The next row isn't a comment.
+    NotComment,
+}
+
 /// What should be done with input bytes during an NFA transition
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum NfaInputAction {
@@ -420,6 +627,19 @@ enum NfaState {
     EndFieldTerm = 200,
     InRecordTerm = 201,
     End = 202,
+    // The states below are only used when line continuations are enabled,
+    // which always uses the NFA.
+    //
+    // A continuation byte was seen inside an unquoted field (or after the
+    // closing quote of a quoted field). The byte is withheld from the output
+    // until we know whether a record terminator follows it.
+    InContinuation = 203,
+    // Like InContinuation, but the continuation byte started a field.
+    StartContinuation = 204,
+    // A continuation byte followed by a `\r` was seen when the terminator
+    // is CRLF. This permits `\r\n` to follow a continuation byte.
+    InContinuationCR = 205,
+    StartContinuationCR = 206,
 
     // All states below are DFA states.
     StartRecord = 0,
@@ -471,6 +691,12 @@ impl NfaState {
             _ => false,
         }
     }
+
+    /// Returns true if this state is withholding a continuation byte from
+    /// the output.
+    fn is_continuation(&self) -> bool {
+        matches!(*self, NfaState::InContinuation | NfaState::StartContinuation)
+    }
 }
 
 impl Reader {
@@ -487,6 +713,124 @@ impl Reader {
         self.nfa_state = NfaState::StartRecord;
         self.line = 1;
         self.has_read = false;
+        self.prev_cr = false;
+        self.pending_cr = false;
+        self.blank_lines = 0;
+        self.blank_cr = false;
+        self.field_meta = FieldMeta::default();
+    }
+
+    /// Reset the parser such that it behaves as if it had never been used,
+    /// except that the current line number is set to `line`.
+    ///
+    /// This is equivalent to calling `reset` followed by `set_line`. It is
+    /// useful when resuming parsing at a known location in the CSV data,
+    /// such that line numbers remain accurate.
+    pub fn reset_to(&mut self, line: u64) {
+        self.reset();
+        self.set_line(line);
+    }
+
+    /// Return the delimiter used for this reader.
+    #[inline]
+    pub fn get_delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// Return the terminator used for this reader.
+    #[inline]
+    pub fn get_terminator(&self) -> Terminator {
+        self.term
+    }
+
+    /// Return the quote character used for this reader.
+    #[inline]
+    pub fn get_quote(&self) -> u8 {
+        self.quote
+    }
+
+    /// Return the escape character used for this reader, if any.
+    #[inline]
+    pub fn get_escape(&self) -> Option<u8> {
+        self.escape
+    }
+
+    /// Return whether two adjacent quotes in a quoted field are interpreted
+    /// as a single quote by this reader.
+    #[inline]
+    pub fn get_double_quote(&self) -> bool {
+        self.double_quote
+    }
+
+    /// Return whether quoting is enabled for this reader.
+    #[inline]
+    pub fn get_quoting(&self) -> bool {
+        self.quoting
+    }
+
+    /// Return the comment character used for this reader, if any.
+    #[inline]
+    pub fn get_comment(&self) -> Option<u8> {
+        self.comment
+    }
+
+    /// Return the number of consecutive blank lines that separate groups of
+    /// records, or `0` if group separators aren't recognized.
+    #[inline]
+    pub fn get_group_separator_blank_lines(&self) -> usize {
+        self.group_blank_lines
+    }
+
+    /// Return the line continuation character used for this reader, if any.
+    #[inline]
+    pub fn get_line_continuation(&self) -> Option<u8> {
+        self.continuation
+    }
+
+    /// Returns true if and only if this parser is inside a quoted field.
+    ///
+    /// That is, an opening quote has been read, but its closing quote has
+    /// not. If the input ends at this point, then the quoted field (and the
+    /// record containing it) is unterminated, which usually indicates that
+    /// the input was truncated.
+    pub fn is_in_quoted_field(&self) -> bool {
+        if self.use_nfa {
+            matches!(
+                self.nfa_state,
+                NfaState::InQuotedField | NfaState::InEscapedQuote
+            )
+        } else {
+            self.dfa_state == self.dfa.in_quoted
+                || self.dfa_state
+                    == self.dfa.new_state(NfaState::InEscapedQuote)
+        }
+    }
+
+    /// Set the comment character used for this reader.
+    ///
+    /// This has the same meaning as `ReaderBuilder::comment`, but may be
+    /// called at any time, including in the middle of parsing a record. The
+    /// state of the parser is otherwise unchanged.
+    ///
+    /// If the comment character differs from the current one, then this
+    /// rebuilds the parser's DFA transition table, which costs about as much
+    /// as building a new parser. So this shouldn't be called for every
+    /// record.
+    pub fn set_comment(&mut self, comment: Option<u8>) {
+        if self.comment == comment {
+            return;
+        }
+        // A DFA state is the NFA state scaled by the number of equivalence
+        // classes, which may change, so remember the NFA state.
+        let nclasses = self.dfa.classes.num_classes() as u8;
+        let nfa_index = self.dfa_state.0 / nclasses;
+
+        self.comment = comment;
+        self.dfa = Dfa::new();
+        self.build_dfa();
+
+        let nclasses = self.dfa.classes.num_classes() as u8;
+        self.dfa_state = DfaState(nfa_index * nclasses);
     }
 
     /// Return the current line number as measured by the number of occurrences
@@ -598,14 +942,258 @@ impl Reader {
     ) -> (ReadRecordResult, usize, usize, usize) {
         let (input, bom_nin) = self.strip_utf8_bom(input);
         let (res, nin, nout, nend) = if self.use_nfa {
-            self.read_record_nfa(input, output, ends)
+            self.read_record_nfa(input, output, ends, None)
+        } else {
+            self.read_record_dfa(input, output, ends, None)
+        };
+        self.has_read = true;
+        (res, nin + bom_nin, nout, nend)
+    }
+
+    /// Like `read_record`, but also writes metadata about each field to
+    /// `metas`.
+    ///
+    /// Whenever the end position of a field is written to `ends[i]`, the
+    /// metadata for that field is written to `metas[i]`. It reports whether
+    /// the field was quoted and whether it contained any escaped quotes.
+    /// If `metas` is shorter than `ends`, then only as many field end
+    /// positions as fit in `metas` are written before
+    /// `ReadRecordResult::OutputEndsFull` is returned.
+    ///
+    /// Tracking this metadata makes parsing a bit slower, which is why it
+    /// isn't done by `read_record`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv_core::{FieldMeta, Reader, ReadRecordResult};
+    ///
+    /// let mut rdr = Reader::new();
+    /// let mut output = [0; 1024];
+    /// let mut ends = [0; 10];
+    /// let mut metas = [FieldMeta::default(); 10];
+    ///
+    /// let (res, _, _, nend) = rdr.read_record_meta(
+    ///     b"a,\"b\",\"c\"\"d\"\n",
+    ///     &mut output,
+    ///     &mut ends,
+    ///     &mut metas,
+    /// );
+    /// assert_eq!((res, nend), (ReadRecordResult::Record, 3));
+    /// assert!(!metas[0].quoted());
+    /// assert!(metas[1].quoted() && !metas[1].had_escapes());
+    /// assert!(metas[2].quoted() && metas[2].had_escapes());
+    /// ```
+    pub fn read_record_meta(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        ends: &mut [usize],
+        metas: &mut [FieldMeta],
+    ) -> (ReadRecordResult, usize, usize, usize) {
+        let (input, bom_nin) = self.strip_utf8_bom(input);
+        let n = core::cmp::min(ends.len(), metas.len());
+        let ends = &mut ends[..n];
+        let (res, nin, nout, nend) = if self.use_nfa {
+            self.read_record_nfa(input, output, ends, Some(metas))
         } else {
-            self.read_record_dfa(input, output, ends)
+            self.read_record_dfa(input, output, ends, Some(metas))
         };
         self.has_read = true;
         (res, nin + bom_nin, nout, nend)
     }
 
+    /// Parse CSV data into growable buffers, reading at most one record.
+    ///
+    /// This is a convenience wrapper around `read_record` for callers that
+    /// have an allocator. Instead of fixed size buffers, unescaped field data
+    /// is appended to `output` and field end positions are appended to
+    /// `ends`. Both are grown as needed, so `ReadRecordResult::OutputFull`
+    /// and `ReadRecordResult::OutputEndsFull` are never returned.
+    ///
+    /// `output` and `ends` should both be empty at the start of each record.
+    /// That is, callers should clear them after `ReadRecordResult::Record`
+    /// is returned. End positions in `ends` are offsets into `output`.
+    ///
+    /// This returns the result of parsing, the number of bytes read from
+    /// `input` and the number of complete fields read so far in the current
+    /// record (which is always equal to `ends.len()`).
+    ///
+    /// This method is only available when the `alloc` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv_core::{Reader, ReadRecordResult};
+    ///
+    /// let mut rdr = Reader::new();
+    /// let (mut output, mut ends) = (vec![], vec![]);
+    ///
+    /// let (res, nin, nfields) =
+    ///     rdr.read_record_into_record(b"foo,bar", &mut output, &mut ends);
+    /// assert_eq!((res, nin, nfields), (ReadRecordResult::InputEmpty, 7, 1));
+    ///
+    /// let (res, _, nfields) =
+    ///     rdr.read_record_into_record(b"", &mut output, &mut ends);
+    /// assert_eq!((res, nfields), (ReadRecordResult::Record, 2));
+    /// assert_eq!(output, b"foobar");
+    /// assert_eq!(ends, vec![3, 6]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_record_into_record(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        ends: &mut Vec<usize>,
+    ) -> (ReadRecordResult, usize, usize) {
+        use core::cmp;
+
+        // Only zero as much room as the parser might need. The room grows
+        // whenever it fills up, so that long records are still read with few
+        // calls to the parser, but short reads don't pay to zero the entire
+        // capacity of the buffers.
+        let (mut oroom, mut eroom) = (64, 8);
+        let mut nin = 0;
+        loop {
+            let (olen, elen) = (output.len(), ends.len());
+            output.resize(olen + oroom, 0);
+            ends.resize(elen + eroom, 0);
+            let (res, i, o, e) = self.read_record(
+                &input[nin..],
+                &mut output[olen..],
+                &mut ends[elen..],
+            );
+            nin += i;
+            output.truncate(olen + o);
+            ends.truncate(elen + e);
+            match res {
+                ReadRecordResult::OutputFull => {
+                    oroom = cmp::max(oroom, olen + o);
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    eroom = cmp::max(eroom, elen + e);
+                }
+                res => return (res, nin, ends.len()),
+            }
+            // An empty input indicates the end of the data, so don't
+            // continue parsing with an empty input unless we were given one.
+            if nin == input.len() && !input.is_empty() {
+                return (ReadRecordResult::InputEmpty, nin, ends.len());
+            }
+        }
+    }
+
+    /// Parse a comment line in `input` and copy its contents to `output`, if
+    /// the next row is a comment.
+    ///
+    /// This must only be called at the start of a record, or after a previous
+    /// call returned `ReadCommentResult::InputEmpty` or
+    /// `ReadCommentResult::OutputFull`. Any blank lines before the comment
+    /// are skipped, just like `read_record` would skip them. The comment byte
+    /// and the `\n` that ends the comment aren't copied to `output`, but a
+    /// `\r` before that `\n` is.
+    ///
+    /// This returns the result of parsing, the number of bytes read from
+    /// `input` and the number of bytes written to `output`. If the next row
+    /// isn't a comment, then `ReadCommentResult::NotComment` is returned and
+    /// none of that row is read, so it can then be read as usual. Like
+    /// `read_record`, an empty `input` indicates the end of the data.
+    ///
+    /// This is useful for callers that want to see comments instead of having
+    /// them skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv_core::{ReadCommentResult, ReaderBuilder};
+    ///
+    /// let mut rdr = ReaderBuilder::new().comment(Some(b'#')).build();
+    /// let mut output = [0; 1024];
+    ///
+    /// let input = b"\n# schema: v2\na,b\n";
+    /// let (res, nin, nout) = rdr.read_comment(input, &mut output);
+    /// assert_eq!(res, ReadCommentResult::Comment);
+    /// assert_eq!(&output[..nout], b" schema: v2");
+    ///
+    /// let (res, n, _) = rdr.read_comment(&input[nin..], &mut output);
+    /// assert_eq!((res, n), (ReadCommentResult::NotComment, 0));
+    /// ```
+    pub fn read_comment(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> (ReadCommentResult, usize, usize) {
+        use self::NfaState::{InComment, StartRecord};
+
+        let (input, bom_nin) = self.strip_utf8_bom(input);
+        let mut state =
+            if self.is_in_comment() { InComment } else { StartRecord };
+        if input.is_empty() {
+            self.set_record_start_state(StartRecord);
+            return if state == InComment {
+                (ReadCommentResult::Comment, bom_nin, 0)
+            } else {
+                (ReadCommentResult::NotComment, bom_nin, 0)
+            };
+        }
+        let (mut nin, mut nout) = (0, 0);
+        let res = loop {
+            if nin >= input.len() {
+                break ReadCommentResult::InputEmpty;
+            }
+            let b = input[nin];
+            // Let the parser decide where comments start and end, so that
+            // this always agrees with `read_record`.
+            let next = self.transition_nfa(state, b).0;
+            match (state, next) {
+                (StartRecord, StartRecord) | (StartRecord, InComment) => {}
+                (InComment, InComment) => {
+                    if nout >= output.len() {
+                        break ReadCommentResult::OutputFull;
+                    }
+                    output[nout] = b;
+                    nout += 1;
+                }
+                (InComment, _) => {}
+                _ => break ReadCommentResult::NotComment,
+            }
+            self.line += (b == b'\n') as u64;
+            nin += 1;
+            let ended = state == InComment && next != InComment;
+            state = next;
+            if ended {
+                break ReadCommentResult::Comment;
+            }
+        };
+        self.set_record_start_state(state);
+        if nin + bom_nin > 0 {
+            self.has_read = true;
+        }
+        if self.strip_cr && nin > 0 {
+            self.prev_cr = input[nin - 1] == b'\r';
+        }
+        (res, nin + bom_nin, nout)
+    }
+
+    /// Returns true if the parser is inside a comment line.
+    fn is_in_comment(&self) -> bool {
+        if self.use_nfa {
+            self.nfa_state == NfaState::InComment
+        } else {
+            self.dfa_state == self.dfa.new_state(NfaState::InComment)
+        }
+    }
+
+    /// Put the parser in the given state, which must be either the start of
+    /// a record or inside a comment line.
+    fn set_record_start_state(&mut self, state: NfaState) {
+        if self.use_nfa {
+            self.nfa_state = state;
+        } else {
+            self.dfa_state = self.dfa.new_state(state);
+        }
+    }
+
     /// Strip off a possible UTF-8 BOM at the start of a file. Quick note that
     /// this method will fail to strip off the BOM if only part of the BOM is
     /// buffered. Hopefully that won't happen very often.
@@ -628,6 +1216,26 @@ impl Reader {
         input: &[u8],
         output: &mut [u8],
         ends: &mut [usize],
+        metas: Option<&mut [FieldMeta]>,
+    ) -> (ReadRecordResult, usize, usize, usize) {
+        // Keeping track of the previous state costs a little on every byte,
+        // so only do it when something needs it.
+        if self.strip_cr || metas.is_some() {
+            self.read_record_dfa_impl::<true>(input, output, ends, metas)
+        } else {
+            self.read_record_dfa_impl::<false>(input, output, ends, metas)
+        }
+    }
+
+    /// Parse a record with the DFA. When `TRACK` is false, then neither
+    /// field metadata nor trailing `\r` bytes are handled.
+    #[inline(always)]
+    fn read_record_dfa_impl<const TRACK: bool>(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        ends: &mut [usize],
+        mut metas: Option<&mut [FieldMeta]>,
     ) -> (ReadRecordResult, usize, usize, usize) {
         if input.is_empty() {
             let s = self.transition_final_dfa(self.dfa_state);
@@ -647,6 +1255,7 @@ impl Reader {
                     }
                     self.dfa_state = s;
                     ends[0] = self.output_pos;
+                    self.write_field_meta(&mut metas, 0);
                     self.output_pos = 0;
                     (res, 0, 0, 1)
                 }
@@ -665,6 +1274,7 @@ impl Reader {
         let (mut nin, mut nout, mut nend) = (0, 0, 0);
         let mut state = self.dfa_state;
         while nin < input.len() && nout < output.len() && nend < ends.len() {
+            let prev = state;
             let (s, has_out) = self.dfa.get_output(state, input[nin]);
             self.line += (input[nin] == b'\n') as u64;
             state = s;
@@ -673,10 +1283,21 @@ impl Reader {
                 nout += 1;
             }
             nin += 1;
+            if TRACK && metas.is_some() {
+                self.observe_meta_dfa(prev, state);
+            }
             if state >= self.dfa.final_field {
                 ends[nend] = self.output_pos + nout;
+                self.write_field_meta(&mut metas, nend);
                 nend += 1;
                 if state > self.dfa.final_field {
+                    if TRACK
+                        && self.strip_cr
+                        && prev == self.dfa.in_field
+                        && self.is_cr_before(input, nin)
+                    {
+                        ends[nend - 1] -= 1;
+                    }
                     break;
                 }
             }
@@ -699,6 +1320,9 @@ impl Reader {
         } else {
             self.output_pos += nout;
         }
+        if TRACK && self.strip_cr && nin > 0 {
+            self.prev_cr = input[nin - 1] == b'\r';
+        }
         (res, nin, nout, nend)
     }
 
@@ -707,8 +1331,27 @@ impl Reader {
         &mut self,
         input: &[u8],
         output: &mut [u8],
+    ) -> (ReadFieldResult, usize, usize) {
+        if self.strip_cr {
+            self.read_field_dfa_impl::<true>(input, output)
+        } else {
+            self.read_field_dfa_impl::<false>(input, output)
+        }
+    }
+
+    /// Parse a field with the DFA. When `STRIP_CR` is false, then trailing
+    /// `\r` bytes are never stripped.
+    #[inline(always)]
+    fn read_field_dfa_impl<const STRIP_CR: bool>(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
     ) -> (ReadFieldResult, usize, usize) {
         if input.is_empty() {
+            let nout = match self.write_pending_cr(output) {
+                None => return (ReadFieldResult::OutputFull, 0, 0),
+                Some(nout) => nout,
+            };
             self.dfa_state = self.transition_final_dfa(self.dfa_state);
             let res = self.dfa.new_read_field_result(
                 self.dfa_state,
@@ -716,16 +1359,28 @@ impl Reader {
                 false,
                 false,
             );
-            return (res, 0, 0);
+            return (res, 0, nout);
         }
         if output.is_empty() {
             return (ReadFieldResult::OutputFull, 0, 0);
         }
-        let (mut nin, mut nout) = (0, 0);
+        let mut nout = 0;
+        if STRIP_CR && self.pending_cr {
+            if input[0] == b'\n' {
+                self.pending_cr = false;
+            } else {
+                nout = self.write_pending_cr(output).unwrap();
+            }
+        }
+        let mut nin = 0;
         let mut state = self.dfa_state;
+        let mut prev = state;
         while nin < input.len() && nout < output.len() {
             let b = input[nin];
             self.line += (b == b'\n') as u64;
+            if STRIP_CR {
+                prev = state;
+            }
             let (s, has_out) = self.dfa.get_output(state, b);
             state = s;
             if has_out {
@@ -737,16 +1392,175 @@ impl Reader {
                 break;
             }
         }
+        let outdone = nout >= output.len();
+        if STRIP_CR {
+            if state > self.dfa.final_field {
+                if prev == self.dfa.in_field
+                    && nin >= 2
+                    && input[nin - 2] == b'\r'
+                {
+                    nout -= 1;
+                }
+            } else if state == self.dfa.in_field
+                && nin > 0
+                && input[nin - 1] == b'\r'
+            {
+                // We can't know whether this `\r` precedes a terminator
+                // until we see the next byte, so withhold it for now.
+                nout -= 1;
+                self.pending_cr = true;
+            }
+        }
         let res = self.dfa.new_read_field_result(
             state,
             false,
             nin >= input.len(),
-            nout >= output.len(),
+            outdone,
         );
         self.dfa_state = state;
         (res, nin, nout)
     }
 
+    /// Returns true if the byte immediately before `input[end - 1]` is a `\r`.
+    /// When `end` is `1`, this consults the last byte consumed by the
+    /// previous call.
+    #[inline(always)]
+    fn is_cr_before(&self, input: &[u8], end: usize) -> bool {
+        if end >= 2 {
+            input[end - 2] == b'\r'
+        } else {
+            self.prev_cr
+        }
+    }
+
+    /// Write the metadata of the field that was just parsed to `metas[i]`,
+    /// if metadata is being tracked, and start tracking the next field.
+    #[inline(always)]
+    fn write_field_meta(
+        &mut self,
+        metas: &mut Option<&mut [FieldMeta]>,
+        i: usize,
+    ) {
+        if let Some(ref mut metas) = *metas {
+            metas[i] = core::mem::take(&mut self.field_meta);
+        }
+    }
+
+    /// Update the metadata of the current field given a DFA transition from
+    /// `prev` to `state`.
+    #[inline(always)]
+    fn observe_meta_dfa(&mut self, prev: DfaState, state: DfaState) {
+        if state == self.dfa.in_quoted {
+            if prev == self.dfa.in_double_escaped {
+                self.field_meta.had_escapes = true;
+            } else if prev != self.dfa.in_quoted && prev != self.dfa.in_escaped
+            {
+                self.field_meta.quoted = true;
+            }
+        } else if state == self.dfa.in_escaped {
+            self.field_meta.had_escapes = true;
+        }
+    }
+
+    /// Update the metadata of the current field given an NFA transition from
+    /// `prev` to `state`.
+    #[inline(always)]
+    fn observe_meta_nfa(&mut self, prev: NfaState, state: NfaState) {
+        use self::NfaState::*;
+        match (prev, state) {
+            (StartField, InQuotedField) => self.field_meta.quoted = true,
+            (InDoubleEscapedQuote, InQuotedField) | (_, InEscapedQuote) => {
+                self.field_meta.had_escapes = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Write a `\r` withheld by a previous call to `read_field` to `output`.
+    ///
+    /// This returns the number of bytes written, or `None` if there was a
+    /// withheld `\r` but no room in `output` for it.
+    fn write_pending_cr(&mut self, output: &mut [u8]) -> Option<usize> {
+        if !self.pending_cr {
+            return Some(0);
+        }
+        if output.is_empty() {
+            return None;
+        }
+        output[0] = b'\r';
+        self.pending_cr = false;
+        Some(1)
+    }
+
+    /// If the given NFA state withholds a byte from the output that turns out
+    /// to be part of the field once `b` is seen, then return that byte and
+    /// the state to continue from. `b` should be processed from that state.
+    ///
+    /// This happens for a continuation byte that isn't followed by a record
+    /// terminator, and for a quote inside a quoted field that isn't followed
+    /// by a quote, delimiter or terminator when quotes are relaxed.
+    #[inline(always)]
+    fn withheld_nfa(&self, state: NfaState, b: u8) -> Option<(u8, NfaState)> {
+        if state.is_continuation() && !self.term.equals(b) {
+            Some((self.continuation.unwrap(), NfaState::InField))
+        } else if self.relaxed_quotes
+            && state == NfaState::InDoubleEscapedQuote
+            && self.is_literal_quote_before(b)
+        {
+            Some((self.quote, NfaState::InQuotedField))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if a quote inside a quoted field that is followed by `b`
+    /// doesn't close the field when quotes are relaxed.
+    ///
+    /// A quote followed by `\r` always closes the field, even when `\r` isn't
+    /// a terminator, since it most likely precedes a `\n` terminator.
+    #[inline(always)]
+    fn is_literal_quote_before(&self, b: u8) -> bool {
+        b != self.quote
+            && b != self.delimiter
+            && b != b'\r'
+            && !self.term.equals(b)
+    }
+
+    /// Write a continuation byte withheld by a previous call to `output`,
+    /// if `pending` is true.
+    ///
+    /// This returns the number of bytes written, or `None` if there was a
+    /// withheld continuation byte but no room in `output` for it.
+    fn write_continuation(
+        &self,
+        pending: bool,
+        output: &mut [u8],
+    ) -> Option<usize> {
+        if !pending {
+            return Some(0);
+        }
+        if output.is_empty() {
+            return None;
+        }
+        output[0] = self.continuation.unwrap();
+        Some(1)
+    }
+
+    /// Write whichever byte is withheld from the output of `read_field`,
+    /// either a `\r` or a continuation byte. At most one byte is ever
+    /// withheld at a time.
+    fn write_pending(
+        &mut self,
+        continuation: bool,
+        output: &mut [u8],
+    ) -> Option<usize> {
+        if continuation {
+            self.write_continuation(true, output)
+        } else {
+            self.write_pending_cr(output)
+        }
+    }
+
     /// Perform the final state transition, i.e., when the caller indicates
     /// that the input has been exhausted.
     fn transition_final_dfa(&self, state: DfaState) -> DfaState {
@@ -795,6 +1609,9 @@ impl Reader {
         //   6. The comment byte.
         //   7. Everything else.
         //
+        // (Line continuations and relaxed quotes aren't handled by the DFA at
+        // all. Readers that use them always use the NFA.)
+        //
         // We add those equivalence classes here. If more configuration knobs
         // are added to the parser with more discriminating bytes, then this
         // logic will need to be adjusted further.
@@ -821,8 +1638,12 @@ impl Reader {
             _ => unreachable!(),
         }
         // Build the DFA transition table by computing the DFA state for all
-        // possible combinations of state and input byte.
-        for &state in NFA_STATES {
+        // possible combinations of state and input byte. Line continuations
+        // need states that the DFA doesn't have, but the DFA is never used
+        // with them anyway.
+        let states =
+            if self.continuation.is_some() { &[] } else { NFA_STATES };
+        for &state in states {
             for c in (0..256).map(|c| c as u8) {
                 let mut nfa_result = (state, NfaInputAction::Epsilon);
                 // Consume NFA states until we hit a non-epsilon transition.
@@ -856,8 +1677,10 @@ impl Reader {
         input: &[u8],
         output: &mut [u8],
         ends: &mut [usize],
+        mut metas: Option<&mut [FieldMeta]>,
     ) -> (ReadRecordResult, usize, usize, usize) {
         if input.is_empty() {
+            let pending = self.nfa_state.is_continuation();
             let s = self.transition_final_nfa(self.nfa_state);
             let res = ReadRecordResult::from_nfa(s, false, false, false);
             return match res {
@@ -865,10 +1688,17 @@ impl Reader {
                     if ends.is_empty() {
                         return (ReadRecordResult::OutputEndsFull, 0, 0, 0);
                     }
+                    let nout = match self.write_continuation(pending, output) {
+                        None => {
+                            return (ReadRecordResult::OutputFull, 0, 0, 0)
+                        }
+                        Some(nout) => nout,
+                    };
                     self.nfa_state = s;
-                    ends[0] = self.output_pos;
+                    ends[0] = self.output_pos + nout;
+                    self.write_field_meta(&mut metas, 0);
                     self.output_pos = 0;
-                    (res, 0, 0, 1)
+                    (res, 0, nout, 1)
                 }
                 _ => {
                     self.nfa_state = s;
@@ -884,8 +1714,33 @@ impl Reader {
         }
         let (mut nin, mut nout, mut nend) = (0, self.output_pos, 0);
         let mut state = self.nfa_state;
+        let mut strip = false;
+        let mut boundary = false;
         while nin < input.len() && nout < output.len() && nend < ends.len() {
-            let (s, io) = self.transition_nfa(state, input[nin]);
+            if state == NfaState::StartRecord
+                && self.group_blank_lines > 0
+                && self.observe_blank_line(input[nin])
+            {
+                self.line += (input[nin] == b'\n') as u64;
+                nin += 1;
+                boundary = true;
+                break;
+            }
+            if let Some((b, s)) = self.withheld_nfa(state, input[nin]) {
+                output[nout] = b;
+                nout += 1;
+                state = s;
+                continue;
+            }
+            let (s, io) = self.transition_nfa(state, input[nin]);
+            if io != NfaInputAction::Epsilon {
+                self.line += (input[nin] == b'\n') as u64;
+            }
+            if s == NfaState::EndFieldTerm {
+                strip = self.strip_cr
+                    && state == NfaState::InField
+                    && self.is_cr_before(input, nin + 1);
+            }
             match io {
                 NfaInputAction::CopyToOutput => {
                     output[nout] = input[nin];
@@ -897,23 +1752,37 @@ impl Reader {
                 }
                 NfaInputAction::Epsilon => {}
             }
+            if metas.is_some() {
+                self.observe_meta_nfa(state, s);
+            }
             state = s;
             if state.is_field_final() {
                 ends[nend] = nout;
+                self.write_field_meta(&mut metas, nend);
                 nend += 1;
                 if state != NfaState::EndFieldDelim {
+                    if strip {
+                        ends[nend - 1] -= 1;
+                    }
                     break;
                 }
             }
         }
-        let res = ReadRecordResult::from_nfa(
-            state,
-            nin >= input.len(),
-            nout >= output.len(),
-            nend >= ends.len(),
-        );
+        let res = if boundary {
+            ReadRecordResult::Record
+        } else {
+            ReadRecordResult::from_nfa(
+                state,
+                nin >= input.len(),
+                nout >= output.len(),
+                nend >= ends.len(),
+            )
+        };
         self.nfa_state = state;
         self.output_pos = if res.is_record() { 0 } else { nout };
+        if self.strip_cr && nin > 0 {
+            self.prev_cr = input[nin - 1] == b'\r';
+        }
         (res, nin, nout, nend)
     }
 
@@ -924,19 +1793,48 @@ impl Reader {
         output: &mut [u8],
     ) -> (ReadFieldResult, usize, usize) {
         if input.is_empty() {
+            let pending = self.nfa_state.is_continuation();
+            let nout = match self.write_pending(pending, output) {
+                None => return (ReadFieldResult::OutputFull, 0, 0),
+                Some(nout) => nout,
+            };
             self.nfa_state = self.transition_final_nfa(self.nfa_state);
             let res = ReadFieldResult::from_nfa(self.nfa_state, false, false);
-            return (res, 0, 0);
+            return (res, 0, nout);
         }
         if output.is_empty() {
             // If the output buffer is empty, then we can never make progress,
             // so just quit now.
             return (ReadFieldResult::OutputFull, 0, 0);
         }
-        let (mut nin, mut nout) = (0, 0);
+        let mut nout = 0;
+        if self.pending_cr {
+            if input[0] == b'\n' {
+                self.pending_cr = false;
+            } else {
+                nout = self.write_pending_cr(output).unwrap();
+            }
+        }
+        let mut nin = 0;
         let mut state = self.nfa_state;
+        let mut strip = false;
         while nin < input.len() && nout < output.len() {
+            if let Some((b, s)) = self.withheld_nfa(state, input[nin]) {
+                output[nout] = b;
+                nout += 1;
+                state = s;
+                continue;
+            }
             let (s, io) = self.transition_nfa(state, input[nin]);
+            if io != NfaInputAction::Epsilon {
+                self.line += (input[nin] == b'\n') as u64;
+            }
+            if s == NfaState::EndFieldTerm {
+                strip = self.strip_cr
+                    && state == NfaState::InField
+                    && nin >= 1
+                    && input[nin - 1] == b'\r';
+            }
             match io {
                 NfaInputAction::CopyToOutput => {
                     output[nout] = input[nin];
@@ -953,15 +1851,47 @@ impl Reader {
                 break;
             }
         }
-        let res = ReadFieldResult::from_nfa(
-            state,
-            nin >= input.len(),
-            nout >= output.len(),
-        );
+        let outdone = nout >= output.len();
+        if strip && state.is_field_final() {
+            nout -= 1;
+        } else if self.strip_cr
+            && state == NfaState::InField
+            && nin > 0
+            && input[nin - 1] == b'\r'
+        {
+            // We can't know whether this `\r` precedes a terminator until we
+            // see the next byte, so withhold it for now.
+            nout -= 1;
+            self.pending_cr = true;
+        }
+        let res =
+            ReadFieldResult::from_nfa(state, nin >= input.len(), outdone);
         self.nfa_state = state;
         (res, nin, nout)
     }
 
+    /// Count the blank line ended by `c`, if any, at the start of a record.
+    /// Returns true when it completes a group separator.
+    ///
+    /// When the terminator is CRLF, a `\n` immediately following a `\r` is
+    /// part of the same blank line.
+    #[inline(always)]
+    fn observe_blank_line(&mut self, c: u8) -> bool {
+        let after_cr = core::mem::replace(&mut self.blank_cr, false);
+        if !self.term.equals(c) {
+            self.blank_lines = 0;
+            return false;
+        }
+        if self.term.is_crlf() {
+            if after_cr && c == b'\n' {
+                return false;
+            }
+            self.blank_cr = c == b'\r';
+        }
+        self.blank_lines += 1;
+        self.blank_lines == self.group_blank_lines
+    }
+
     /// Compute the final NFA transition after all caller-provided input has
     /// been exhausted.
     #[inline(always)]
@@ -971,7 +1901,8 @@ impl Reader {
             End | StartRecord | EndRecord | InComment | CRLF => End,
             StartField | EndFieldDelim | EndFieldTerm | InField
             | InQuotedField | InEscapedQuote | InDoubleEscapedQuote
-            | InRecordTerm => EndRecord,
+            | InContinuation | StartContinuation | InContinuationCR
+            | StartContinuationCR | InRecordTerm => EndRecord,
         }
     }
 
@@ -993,7 +1924,11 @@ impl Reader {
             StartRecord => {
                 if self.term.equals(c) {
                     (StartRecord, NfaInputAction::Discard)
-                } else if self.comment == Some(c) {
+                } else if self.comment == Some(c)
+                    && !(self.quoting && self.quote == c)
+                {
+                    // A record that starts with a quote is never a comment,
+                    // even if the comment and quote bytes are the same.
                     (InComment, NfaInputAction::Discard)
                 } else {
                     (StartField, NfaInputAction::Epsilon)
@@ -1007,6 +1942,8 @@ impl Reader {
                     (EndFieldDelim, NfaInputAction::Discard)
                 } else if self.term.equals(c) {
                     (EndFieldTerm, NfaInputAction::Epsilon)
+                } else if self.continuation == Some(c) {
+                    (StartContinuation, NfaInputAction::Discard)
                 } else {
                     (InField, NfaInputAction::CopyToOutput)
                 }
@@ -1018,6 +1955,8 @@ impl Reader {
                     (EndFieldDelim, NfaInputAction::Discard)
                 } else if self.term.equals(c) {
                     (EndFieldTerm, NfaInputAction::Epsilon)
+                } else if self.continuation == Some(c) {
+                    (InContinuation, NfaInputAction::Discard)
                 } else {
                     (InField, NfaInputAction::CopyToOutput)
                 }
@@ -1039,10 +1978,42 @@ impl Reader {
                     (EndFieldDelim, NfaInputAction::Discard)
                 } else if self.term.equals(c) {
                     (EndFieldTerm, NfaInputAction::Epsilon)
+                } else if self.continuation == Some(c) {
+                    (InContinuation, NfaInputAction::Discard)
                 } else {
                     (InField, NfaInputAction::CopyToOutput)
                 }
             }
+            // A withheld continuation byte that isn't followed by a record
+            // terminator is written to the output by the caller before
+            // moving back to InField. (See `read_record_nfa`.)
+            InContinuation | StartContinuation if !self.term.equals(c) => {
+                (InField, NfaInputAction::Epsilon)
+            }
+            InContinuation | StartContinuation => {
+                let next = if state == InContinuation {
+                    (InContinuationCR, InField)
+                } else {
+                    (StartContinuationCR, StartField)
+                };
+                if self.term.is_crlf() && b'\r' == c {
+                    (next.0, NfaInputAction::Discard)
+                } else {
+                    (next.1, NfaInputAction::Discard)
+                }
+            }
+            InContinuationCR | StartContinuationCR => {
+                let next = if state == InContinuationCR {
+                    InField
+                } else {
+                    StartField
+                };
+                if b'\n' == c {
+                    (next, NfaInputAction::Discard)
+                } else {
+                    (next, NfaInputAction::Epsilon)
+                }
+            }
             InComment => {
                 if b'\n' == c {
                     (StartRecord, NfaInputAction::Discard)
@@ -1082,7 +2053,7 @@ impl Reader {
 /// effectively be treated as identical. This reduces storage space
 /// substantially.
 ///
-/// The total number of NFA states (13) is greater than the total number of
+/// The total number of NFA states (17) is greater than the total number of
 /// NFA states that are in the DFA. In particular, any NFA state that can only
 /// be reached by epsilon transitions will never have explicit usage in the
 /// DFA.
@@ -1119,6 +2090,13 @@ struct Dfa {
     in_field: DfaState,
     /// The DFA state corresponding to being inside an quoted field.
     in_quoted: DfaState,
+    /// The DFA state corresponding to having just seen an escape byte inside
+    /// a quoted field.
+    in_escaped: DfaState,
+    /// The DFA state corresponding to having just seen a quote inside a
+    /// quoted field, which is either the closing quote or the first half of
+    /// a doubled quote.
+    in_double_escaped: DfaState,
     /// The minimum DFA state that indicates a field has been parsed. All DFA
     /// states greater than this are also final-field states.
     final_field: DfaState,
@@ -1135,6 +2113,8 @@ impl Dfa {
             classes: DfaClasses::new(),
             in_field: DfaState(0),
             in_quoted: DfaState(0),
+            in_escaped: DfaState(0),
+            in_double_escaped: DfaState(0),
             final_field: DfaState(0),
             final_record: DfaState(0),
         }
@@ -1170,6 +2150,9 @@ impl Dfa {
     fn finish(&mut self) {
         self.in_field = self.new_state(NfaState::InField);
         self.in_quoted = self.new_state(NfaState::InQuotedField);
+        self.in_escaped = self.new_state(NfaState::InEscapedQuote);
+        self.in_double_escaped =
+            self.new_state(NfaState::InDoubleEscapedQuote);
         self.final_field = self.new_state(NfaState::EndFieldDelim);
         self.final_record = self.new_state(NfaState::EndRecord);
     }
@@ -1587,6 +2570,55 @@ mod tests {
         }
     );
 
+    parses_to!(
+        strip_trailing_cr,
+        "a,b\r\nc,d\r\n",
+        csv![["a", "b"], ["c", "d"]],
+        |b: &mut ReaderBuilder| {
+            b.terminator(Terminator::Any(b'\n')).strip_trailing_cr(true);
+        }
+    );
+    parses_to!(
+        strip_trailing_cr_disabled,
+        "a,b\r\nc,d\r\n",
+        csv![["a", "b\r"], ["c", "d\r"]],
+        |b: &mut ReaderBuilder| {
+            b.terminator(Terminator::Any(b'\n'));
+        }
+    );
+    parses_to!(
+        strip_trailing_cr_only_one,
+        "a\r\r\nb\rc\r,d\r\n\r\n",
+        csv![["a\r"], ["b\rc\r", "d"], [""]],
+        |b: &mut ReaderBuilder| {
+            b.terminator(Terminator::Any(b'\n')).strip_trailing_cr(true);
+        }
+    );
+    parses_to!(
+        strip_trailing_cr_quoted,
+        "\"a\r\"\n\"b\"\r\n",
+        csv![["a\r"], ["b"]],
+        |b: &mut ReaderBuilder| {
+            b.terminator(Terminator::Any(b'\n')).strip_trailing_cr(true);
+        }
+    );
+    parses_to!(
+        strip_trailing_cr_no_lf,
+        "a\r",
+        csv![["a\r"]],
+        |b: &mut ReaderBuilder| {
+            b.terminator(Terminator::Any(b'\n')).strip_trailing_cr(true);
+        }
+    );
+    parses_to!(
+        strip_trailing_cr_crlf_term,
+        "a\r\nb",
+        csv![["a"], ["b"]],
+        |b: &mut ReaderBuilder| {
+            b.strip_trailing_cr(true);
+        }
+    );
+
     parses_to!(
         ascii_delimited,
         "a\x1fb\x1ec\x1fd",
@@ -1725,6 +2757,164 @@ mod tests {
             b.comment(Some(b'#'));
         }
     );
+    parses_to!(
+        comment_quoted,
+        "\"#foo\",bar\n#baz\n\"#\"",
+        csv![["#foo", "bar"], ["#"]],
+        |b: &mut ReaderBuilder| {
+            b.comment(Some(b'#'));
+        }
+    );
+    parses_to!(
+        comment_quoted_multiline,
+        "\"foo\n#bar\",baz\n#quux\n",
+        csv![["foo\n#bar", "baz"]],
+        |b: &mut ReaderBuilder| {
+            b.comment(Some(b'#'));
+        }
+    );
+    parses_to!(
+        comment_is_quote,
+        "\"foo\",bar\n\"baz\"",
+        csv![["foo", "bar"], ["baz"]],
+        |b: &mut ReaderBuilder| {
+            b.comment(Some(b'"'));
+        }
+    );
+    parses_to!(
+        comment_is_quote_no_quoting,
+        "\"foo,bar\nbaz",
+        csv![["baz"]],
+        |b: &mut ReaderBuilder| {
+            b.comment(Some(b'"')).quoting(false);
+        }
+    );
+
+    parses_to!(
+        continuation_none,
+        "a,b\\\nc,d",
+        csv![["a", "b\\"], ["c", "d"]]
+    );
+
+    parses_to!(
+        continuation_joins_lines,
+        "a,b\\\nc,d\n",
+        csv![["a", "bc", "d"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_crlf,
+        "a,b\\\r\nc\\\rd\n",
+        csv![["a", "bcd"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_at_field_start,
+        "a,\\\n\"b,c\"",
+        csv![["a", "b,c"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_after_quoted,
+        "\"a\"\\\nb,c",
+        csv![["ab", "c"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_not_at_line_end,
+        "a\\b,\\c\n\\",
+        csv![["a\\b", "\\c"], ["\\"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_doubled,
+        "a\\\\\nb",
+        csv![["a\\b"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_in_quotes,
+        "\"a\\\nb\",c\n",
+        csv![["a\\\nb", "c"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        continuation_no_terminator,
+        "a,b\\",
+        csv![["a", "b\\"]],
+        |b: &mut ReaderBuilder| {
+            b.line_continuation(Some(b'\\'));
+        }
+    );
+
+    parses_to!(
+        quote_interior_unquoted,
+        "3\" pipe,42",
+        csv![["3\" pipe", "42"]]
+    );
+
+    parses_to!(
+        quote_interior_quoted,
+        "\"3\" pipe\",42",
+        csv![["3 pipe\"", "42"]]
+    );
+
+    parses_to!(
+        relaxed_quotes_interior_unquoted,
+        "3\" pipe,42",
+        csv![["3\" pipe", "42"]],
+        |b: &mut ReaderBuilder| {
+            b.relaxed_quotes(true);
+        }
+    );
+
+    parses_to!(
+        relaxed_quotes_interior_quoted,
+        "\"3\" pipe\",42",
+        csv![["3\" pipe", "42"]],
+        |b: &mut ReaderBuilder| {
+            b.relaxed_quotes(true);
+        }
+    );
+
+    parses_to!(
+        relaxed_quotes_at_start,
+        "\"a,b\",\"\"\n\"c\"\r\n\"d\"",
+        csv![["a,b", ""], ["c"], ["d"]],
+        |b: &mut ReaderBuilder| {
+            b.relaxed_quotes(true);
+        }
+    );
+
+    parses_to!(
+        relaxed_quotes_doubled,
+        "\"a\"\"b\",\"c\"d\"\"\"",
+        csv![["a\"b", "c\"d\""]],
+        |b: &mut ReaderBuilder| {
+            b.relaxed_quotes(true);
+        }
+    );
 
     macro_rules! assert_read {
         (
@@ -1826,6 +3016,233 @@ mod tests {
         assert_read!(rdr, &[], out, 0, 0, End);
     }
 
+    // Test that a trailing `\r` is stripped even when it is split from the
+    // `\n` terminator across input buffers.
+    #[test]
+    fn stream_strip_trailing_cr() {
+        use crate::ReadFieldResult::*;
+
+        let out = &mut [0; 10];
+        let mut rdr = ReaderBuilder::new()
+            .terminator(Terminator::Any(b'\n'))
+            .strip_trailing_cr(true)
+            .build();
+
+        assert_read!(rdr, b("ab\r"), out, 3, 2, InputEmpty);
+        assert_eq!(&out[..2], b("ab"));
+        assert_read!(
+            rdr,
+            b("\nc\r"),
+            &mut out[2..],
+            1,
+            0,
+            Field { record_end: true }
+        );
+        assert_eq!(&out[..2], b("ab"));
+
+        assert_read!(rdr, b("c\r"), out, 2, 1, InputEmpty);
+        assert_read!(rdr, b("d"), &mut out[1..], 1, 2, InputEmpty);
+        assert_eq!(&out[..3], b("c\rd"));
+        assert_read!(rdr, b("\r"), &mut out[3..], 1, 0, InputEmpty);
+        assert_read!(
+            rdr,
+            &[],
+            &mut out[3..],
+            0,
+            1,
+            Field { record_end: true }
+        );
+        assert_eq!(&out[..4], b("c\rd\r"));
+        assert_read!(rdr, &[], out, 0, 0, End);
+    }
+
+    // Test that a continuation byte is withheld across input buffers until
+    // we know whether a record terminator follows it.
+    #[test]
+    fn stream_line_continuation() {
+        use crate::ReadFieldResult::*;
+
+        let out = &mut [0; 10];
+        let mut rdr =
+            ReaderBuilder::new().line_continuation(Some(b'\\')).build();
+
+        assert_read!(rdr, b("a\\"), out, 2, 1, InputEmpty);
+        assert_read!(rdr, b("\nb\\"), &mut out[1..], 3, 1, InputEmpty);
+        assert_read!(rdr, b("c"), &mut out[2..], 1, 2, InputEmpty);
+        assert_eq!(&out[..4], b("ab\\c"));
+        assert_read!(rdr, b("\\"), &mut out[4..], 1, 0, InputEmpty);
+        assert_read!(
+            rdr,
+            &[],
+            &mut out[4..],
+            0,
+            1,
+            Field { record_end: true }
+        );
+        assert_eq!(&out[..5], b("ab\\c\\"));
+        assert_read!(rdr, &[], out, 0, 0, End);
+    }
+
+    // Test that a trailing `\r` is stripped by `read_record` even when it is
+    // split from the `\n` terminator across input buffers.
+    #[test]
+    fn stream_strip_trailing_cr_record() {
+        use crate::ReadRecordResult::*;
+
+        for &nfa in &[false, true] {
+            let (out, ends) = (&mut [0; 10], &mut [0; 10]);
+            let mut rdr = ReaderBuilder::new()
+                .terminator(Terminator::Any(b'\n'))
+                .strip_trailing_cr(true)
+                .nfa(nfa)
+                .build();
+
+            let (res, nin, nout, nend) =
+                rdr.read_record(b("a,b\r"), out, ends);
+            assert_eq!((InputEmpty, 4, 3, 1), (res, nin, nout, nend));
+            let (res, nin, _, nend) =
+                rdr.read_record(b("\n"), &mut out[3..], &mut ends[1..]);
+            assert_eq!((Record, 1, 1), (res, nin, nend));
+            assert_eq!(&[1, 2], &ends[..2]);
+            assert_eq!(b("ab"), &out[..2]);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn read_record_into_record() {
+        use alloc::vec::Vec;
+
+        use crate::ReadRecordResult::*;
+
+        let data = "foo,\"bar\"\"baz\",quux\n\"a\",,b\n";
+        let mut rdr = Reader::new();
+        let (mut output, mut ends) = (Vec::new(), Vec::new());
+        let mut records = Csv::new();
+        // Feed the input in small chunks and with tiny initial buffers to
+        // force both buffers to grow.
+        output.reserve_exact(1);
+        for chunk in data.as_bytes().chunks(3).chain(Some(&b""[..])) {
+            let mut chunk = chunk;
+            loop {
+                let (res, nin, nfields) =
+                    rdr.read_record_into_record(chunk, &mut output, &mut ends);
+                chunk = &chunk[nin..];
+                assert_eq!(nfields, ends.len());
+                match res {
+                    InputEmpty => break,
+                    Record => {
+                        let mut start = 0;
+                        let mut row = Row::new();
+                        for &end in &ends {
+                            let s = str::from_utf8(&output[start..end]);
+                            row.push(Field::from(s.unwrap()).unwrap());
+                            start = end;
+                        }
+                        records.push(row);
+                        output.clear();
+                        ends.clear();
+                    }
+                    End => break,
+                    res => panic!("unexpected result: {:?}", res),
+                }
+            }
+        }
+        assert_eq!(records, csv![["foo", "bar\"baz", "quux"], ["a", "", "b"]]);
+
+        // A record large enough to force both buffers to grow.
+        let data = [b'a'; 200];
+        let mut rdr = Reader::new();
+        let (mut output, mut ends) = (Vec::new(), Vec::new());
+        for _ in 0..20 {
+            let (res, nin, _) =
+                rdr.read_record_into_record(&data, &mut output, &mut ends);
+            assert_eq!((res, nin), (InputEmpty, 200));
+            let (res, nin, _) =
+                rdr.read_record_into_record(b",", &mut output, &mut ends);
+            assert_eq!((res, nin), (InputEmpty, 1));
+        }
+        let (res, _, nfields) =
+            rdr.read_record_into_record(b"", &mut output, &mut ends);
+        assert_eq!((res, nfields), (Record, 21));
+        assert_eq!(output.len(), 4000);
+        assert_eq!(ends[0], 200);
+        assert_eq!(ends[20], 4000);
+    }
+
+    #[test]
+    fn read_record_meta() {
+        use crate::{FieldMeta, ReadRecordResult::*};
+
+        type Metas = ArrayVec<[(bool, bool); 10]>;
+
+        fn metas(nfa: bool, escape: Option<u8>, data: &str) -> Metas {
+            let mut rdr = ReaderBuilder::new().nfa(nfa).escape(escape).build();
+            let mut output = [0; 1024];
+            let mut ends = [0; 10];
+            let mut metas = [FieldMeta::default(); 10];
+            let mut got = Metas::new();
+            // Feed the input one byte at a time, so that fields span calls.
+            let (mut outpos, mut endpos) = (0, 0);
+            for chunk in data.as_bytes().chunks(1).chain(Some(&b""[..])) {
+                let (res, nin, nout, nend) = rdr.read_record_meta(
+                    chunk,
+                    &mut output[outpos..],
+                    &mut ends[endpos..],
+                    &mut metas[endpos..],
+                );
+                assert_eq!(nin, chunk.len());
+                outpos += nout;
+                endpos += nend;
+                match res {
+                    InputEmpty | End => {}
+                    Record => {
+                        for m in &metas[..endpos] {
+                            got.push((m.quoted(), m.had_escapes()));
+                        }
+                        outpos = 0;
+                        endpos = 0;
+                    }
+                    res => panic!("unexpected result: {:?}", res),
+                }
+            }
+            got
+        }
+
+        for &nfa in &[false, true] {
+            let got =
+                metas(nfa, None, "a,\"b\",\"c\"\"d\"\n\"\",e\"f\",\"g\"\"\"");
+            assert_eq!(
+                &got[..],
+                &[
+                    (false, false),
+                    (true, false),
+                    (true, true),
+                    (true, false),
+                    (false, false),
+                    (true, true),
+                ],
+                "nfa: {}",
+                nfa,
+            );
+            let got = metas(nfa, Some(b'\\'), "\"a\\\"b\",c\r\n\"d\"");
+            assert_eq!(
+                &got[..],
+                &[(true, true), (false, false), (true, false)],
+                "nfa: {}",
+                nfa,
+            );
+        }
+
+        // Field end positions are limited by the room in `metas`.
+        let mut rdr = Reader::new();
+        let (mut output, mut ends) = ([0; 10], [0; 10]);
+        let mut metas = [FieldMeta::default(); 1];
+        let (res, nin, _, nend) =
+            rdr.read_record_meta(b"a,b,c", &mut output, &mut ends, &mut metas);
+        assert_eq!((res, nin, nend), (OutputEndsFull, 2, 1));
+    }
+
     // Test we can read doubled quotes correctly in a stream.
     #[test]
     fn stream_doubled_quotes() {
@@ -1915,6 +3332,78 @@ mod tests {
         assert_eq!(&out[..4], b("bar\""));
     }
 
+    #[test]
+    fn is_in_quoted_field() {
+        use crate::ReadFieldResult::*;
+
+        for &use_nfa in &[false, true] {
+            let out = &mut [0; 10];
+            let mut rdr =
+                ReaderBuilder::new().escape(Some(b'\\')).nfa(use_nfa).build();
+            assert!(!rdr.is_in_quoted_field());
+
+            assert_read!(
+                rdr,
+                b("a,\"b"),
+                out,
+                2,
+                1,
+                Field { record_end: false }
+            );
+            assert!(!rdr.is_in_quoted_field());
+            assert_read!(rdr, b("\"b"), out, 2, 1, InputEmpty);
+            assert!(rdr.is_in_quoted_field());
+            assert_read!(rdr, b("\\"), out, 1, 0, InputEmpty);
+            assert!(rdr.is_in_quoted_field());
+            assert_read!(rdr, b("\"\""), out, 2, 1, InputEmpty);
+            assert!(!rdr.is_in_quoted_field());
+            assert_read!(rdr, b("\n"), out, 1, 0, Field { record_end: true });
+            assert!(!rdr.is_in_quoted_field());
+        }
+    }
+
+    #[test]
+    fn set_comment_works() {
+        use crate::ReadFieldResult::*;
+
+        let out = &mut [0; 10];
+        let mut rdr = Reader::new();
+
+        assert_read!(rdr, b("#a,b\n"), out, 3, 2, Field { record_end: false });
+        assert_eq!(&out[..2], b("#a"));
+        assert_read!(rdr, b("b\n"), out, 2, 1, Field { record_end: true });
+
+        // Change the comment byte in the middle of a quoted field.
+        assert_read!(rdr, b("\"c"), out, 2, 1, InputEmpty);
+        rdr.set_comment(Some(b'#'));
+        assert_eq!(rdr.get_comment(), Some(b'#'));
+        assert_read!(rdr, b("#d\"\n"), out, 4, 2, Field { record_end: true });
+        assert_eq!(&out[..2], b("#d"));
+        assert_read!(rdr, b("#e\nf\n"), out, 5, 1, Field { record_end: true });
+        assert_eq!(&out[..1], b("f"));
+
+        rdr.set_comment(None);
+        assert_read!(rdr, b("#g\n"), out, 3, 2, Field { record_end: true });
+        assert_eq!(&out[..2], b("#g"));
+    }
+
+    #[test]
+    fn reset_to_works() {
+        use crate::ReadFieldResult::*;
+
+        let out = &mut [0; 10];
+        let mut rdr = Reader::new();
+
+        assert_read!(rdr, b("\"a\nb"), out, 4, 3, InputEmpty);
+        assert_eq!(rdr.line(), 2);
+
+        rdr.reset_to(10);
+        assert_eq!(rdr.line(), 10);
+        assert_read!(rdr, b("c\nd"), out, 2, 1, Field { record_end: true });
+        assert_eq!(&out[..1], b("c"));
+        assert_eq!(rdr.line(), 11);
+    }
+
     // Test the line number reporting is correct.
     #[test]
     fn line_numbers() {
@@ -1938,6 +3427,38 @@ mod tests {
         assert_eq!(6, rdr.line());
     }
 
+    // Test that newlines inside quoted fields are counted.
+    #[test]
+    fn line_numbers_quoted() {
+        use crate::ReadFieldResult::*;
+
+        let out = &mut [0; 10];
+        let mut rdr = Reader::new();
+
+        assert_read!(
+            rdr,
+            b("\"a\nb\","),
+            out,
+            6,
+            3,
+            Field { record_end: false }
+        );
+        assert_eq!(2, rdr.line());
+
+        assert_read!(
+            rdr,
+            b("\"\r\n\n\"\n"),
+            out,
+            6,
+            3,
+            Field { record_end: true }
+        );
+        assert_eq!(5, rdr.line());
+
+        assert_read!(rdr, b("c\n"), out, 2, 1, Field { record_end: true });
+        assert_eq!(6, rdr.line());
+    }
+
     macro_rules! assert_read_record {
         (
             $rdr:expr, $input:expr, $output:expr, $ends:expr,
@@ -2002,4 +3523,139 @@ mod tests {
 
         assert_read_record!(rdr, &inp, out, ends, 0, 0, 0, End);
     }
+
+    // Test that a group boundary is reported at the blank line completing
+    // a group separator, even when it is split across input buffers.
+    #[test]
+    fn stream_record_group_separator() {
+        use crate::ReadRecordResult::*;
+
+        let out = &mut [0; 1024];
+        let ends = &mut [0; 10];
+        let mut rdr =
+            ReaderBuilder::new().group_separator_blank_lines(2).build();
+
+        assert_read_record!(rdr, b("a\r"), out, ends, 2, 1, 1, Record);
+        assert_read_record!(rdr, b("\n\r"), out, ends, 2, 0, 0, InputEmpty);
+        assert_read_record!(rdr, b("\r"), out, ends, 1, 0, 0, Record);
+        assert_read_record!(
+            rdr,
+            b("\n\r\n\n"),
+            out,
+            ends,
+            4,
+            0,
+            0,
+            InputEmpty
+        );
+        assert_read_record!(rdr, b("b\n"), out, ends, 2, 1, 1, Record);
+        assert_read_record!(rdr, b("\n\n"), out, ends, 2, 0, 0, Record);
+        assert_read_record!(rdr, &[], out, ends, 0, 0, 0, End);
+        assert_eq!(rdr.line(), 8);
+    }
+
+    // Test that a comment line breaks up a run of blank lines.
+    #[test]
+    fn group_separator_comment() {
+        use crate::ReadRecordResult::*;
+
+        let out = &mut [0; 1024];
+        let ends = &mut [0; 10];
+        let mut rdr = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .group_separator_blank_lines(2)
+            .build();
+
+        assert_read_record!(
+            rdr,
+            b("a\n\n#\n\nb\n"),
+            out,
+            ends,
+            2,
+            1,
+            1,
+            Record
+        );
+        assert_read_record!(rdr, b("\n#\n\nb\n"), out, ends, 6, 1, 1, Record);
+        assert_read_record!(rdr, &[], out, ends, 0, 0, 0, End);
+    }
+
+    #[test]
+    fn dialect_getters() {
+        let rdr = Reader::new();
+        assert_eq!(rdr.get_delimiter(), b',');
+        assert_eq!(rdr.get_quote(), b'"');
+        assert_eq!(rdr.get_escape(), None);
+        assert!(rdr.get_double_quote());
+        assert!(rdr.get_quoting());
+        assert!(matches!(rdr.get_terminator(), Terminator::CRLF));
+
+        let rdr = ReaderBuilder::new()
+            .delimiter(b';')
+            .quote(b'\'')
+            .escape(Some(b'\\'))
+            .double_quote(false)
+            .terminator(Terminator::Any(b'\n'))
+            .build();
+        assert_eq!(rdr.get_delimiter(), b';');
+        assert_eq!(rdr.get_quote(), b'\'');
+        assert_eq!(rdr.get_escape(), Some(b'\\'));
+        assert!(!rdr.get_double_quote());
+        assert!(matches!(rdr.get_terminator(), Terminator::Any(b'\n')));
+    }
+
+    #[test]
+    fn read_comment() {
+        use super::ReadCommentResult;
+
+        // Read comments from `input` `chunk` bytes at a time, with an output
+        // buffer that holds only a single byte, until a row that isn't a
+        // comment is found. Returns the comments and the unread input.
+        fn comments(
+            input: &[u8],
+            chunk: usize,
+            nfa: bool,
+        ) -> (Row, &[u8], u64) {
+            let mut rdr =
+                ReaderBuilder::new().comment(Some(b'#')).nfa(nfa).build();
+            let (mut comments, mut comment) =
+                (ArrayVec::new(), ArrayString::new());
+            let (mut nin, mut output) = (0, [0; 1]);
+            loop {
+                let end = core::cmp::min(nin + chunk, input.len());
+                let (res, n, nout) =
+                    rdr.read_comment(&input[nin..end], &mut output);
+                nin += n;
+                comment.push_str(str::from_utf8(&output[..nout]).unwrap());
+                match res {
+                    ReadCommentResult::InputEmpty
+                    | ReadCommentResult::OutputFull => {}
+                    ReadCommentResult::Comment => {
+                        comments.push(core::mem::take(&mut comment));
+                    }
+                    ReadCommentResult::NotComment => break,
+                }
+            }
+            (comments, &input[nin..], rdr.line())
+        }
+
+        for nfa in [false, true] {
+            let input = b"\xef\xbb\xbf\r\n#ab\r\n#\n\nx,#y\n";
+            let (got, rest, line) = comments(input, input.len(), nfa);
+            assert_eq!(got.len(), 2);
+            assert_eq!((got[0].as_str(), got[1].as_str()), ("ab\r", ""));
+            assert_eq!((rest, line), (&b"x,#y\n"[..], 5));
+
+            let input = b"\n#ab\n#cd";
+            let (got, rest, line) = comments(input, 1, nfa);
+            assert_eq!(got.len(), 2);
+            assert_eq!((got[0].as_str(), got[1].as_str()), ("ab", "cd"));
+            assert_eq!((rest, line), (&b""[..], 3));
+
+            // A row that starts with a quote is never a comment.
+            let (got, rest, _) = comments(b"\"#\"\n", 1, nfa);
+            assert!(got.is_empty());
+            assert_eq!(rest, b"\"#\"\n");
+        }
+    }
 }
@@ -5,6 +5,10 @@ use memchr::memchr;
 
 use crate::{QuoteStyle, Terminator};
 
+/// The maximum number of bytes in a record terminator set with
+/// `WriterBuilder::record_terminator`.
+const MAX_TERMINATOR_LEN: usize = 8;
+
 /// A builder for configuring a CSV writer.
 ///
 /// This builder permits specifying the CSV delimiter, terminator, quoting
@@ -22,6 +26,8 @@ impl WriterBuilder {
             requires_quotes: [false; 256],
             delimiter: b',',
             term: Terminator::Any(b'\n'),
+            term_bytes: [0; MAX_TERMINATOR_LEN],
+            term_len: 0,
             style: QuoteStyle::default(),
             quote: b'"',
             escape: b'\\',
@@ -43,7 +49,32 @@ impl WriterBuilder {
             // character is used for escaping quotes.
             wtr.requires_quotes[self.wtr.escape as usize] = true;
         }
+        if self.wtr.term_len > 0 {
+            // A custom terminator was given, so every byte in it must be
+            // quoted. If it contains a line break, then we quote both '\r'
+            // and '\n' for the same reason as below.
+            for &b in &self.wtr.term_bytes[..self.wtr.term_len] {
+                wtr.requires_quotes[b as usize] = true;
+                if b == b'\r' || b == b'\n' {
+                    wtr.requires_quotes[b'\r' as usize] = true;
+                    wtr.requires_quotes[b'\n' as usize] = true;
+                }
+            }
+        } else {
+            match self.wtr.term {
+                CRLF => {
+                    wtr.term_bytes[..2].copy_from_slice(b"\r\n");
+                    wtr.term_len = 2;
+                }
+                Any(b) => {
+                    wtr.term_bytes[0] = b;
+                    wtr.term_len = 1;
+                }
+                _ => unreachable!(),
+            }
+        }
         match self.wtr.term {
+            _ if self.wtr.term_len > 0 => {}
             CRLF | Any(b'\n') | Any(b'\r') => {
                 // This is a bit hokey. By default, the record terminator
                 // is '\n', but we still need to quote '\r' (even if our
@@ -81,8 +112,37 @@ impl WriterBuilder {
     ///
     /// Note that RFC 4180 specifies that record terminators should be `\r\n`.
     /// To use `\r\n`, use the special `Terminator::CRLF` value.
+    ///
+    /// This overrides any terminator previously set with
+    /// `record_terminator`.
     pub fn terminator(&mut self, term: Terminator) -> &mut WriterBuilder {
         self.wtr.term = term;
+        self.wtr.term_len = 0;
+        self
+    }
+
+    /// Use the given sequence of bytes as the record terminator when writing
+    /// CSV.
+    ///
+    /// This permits terminators that can't be expressed with a
+    /// `Terminator`, such as `|\n`. The bytes given are written verbatim
+    /// after every record. If the quoting style is `QuoteStyle::Necessary`,
+    /// then fields containing any of these bytes are quoted.
+    ///
+    /// This overrides any terminator previously set with `terminator`, and
+    /// vice versa.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `term` is empty or longer than 8 bytes.
+    pub fn record_terminator(&mut self, term: &[u8]) -> &mut WriterBuilder {
+        assert!(
+            !term.is_empty() && term.len() <= MAX_TERMINATOR_LEN,
+            "record terminator must be between 1 and {} bytes long",
+            MAX_TERMINATOR_LEN,
+        );
+        self.wtr.term_bytes[..term.len()].copy_from_slice(term);
+        self.wtr.term_len = term.len();
         self
     }
 
@@ -181,6 +241,11 @@ pub struct Writer {
     requires_quotes: [bool; 256],
     delimiter: u8,
     term: Terminator,
+    /// The bytes actually written for each record terminator. After
+    /// building, this always corresponds either to `term` or to a custom
+    /// terminator.
+    term_bytes: [u8; MAX_TERMINATOR_LEN],
+    term_len: usize,
     style: QuoteStyle,
     quote: u8,
     escape: u8,
@@ -199,6 +264,8 @@ impl Clone for Writer {
             requires_quotes: requires_quotes,
             delimiter: self.delimiter,
             term: self.term,
+            term_bytes: self.term_bytes,
+            term_len: self.term_len,
             style: self.style,
             quote: self.quote,
             escape: self.escape,
@@ -214,6 +281,7 @@ impl fmt::Debug for Writer {
             .field("state", &self.state)
             .field("delimiter", &self.delimiter)
             .field("term", &self.term)
+            .field("term_bytes", &&self.term_bytes[..self.term_len])
             .field("style", &self.style)
             .field("quote", &self.quote)
             .field("escape", &self.escape)
@@ -245,6 +313,16 @@ impl Writer {
         Writer::default()
     }
 
+    /// Reset the writer such that it behaves as if it had never been used.
+    ///
+    /// Any record or field in progress is abandoned, without writing any
+    /// closing quotes. This may be useful when reusing a single writer to
+    /// write many independent fragments of CSV data. The configuration of
+    /// the writer is unchanged.
+    pub fn reset(&mut self) {
+        self.state = WriterState::default();
+    }
+
     /// Finish writing CSV data to `output`.
     ///
     /// This must be called when one is done writing CSV data to `output`.
@@ -298,14 +376,43 @@ impl Writer {
     /// should contain the *entire* field. Otherwise, whether the field needs
     /// to be quoted or not cannot be determined.
     pub fn field(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> (WriteResult, usize, usize) {
+        self.field_impl(input, output, false)
+    }
+
+    /// Write a single CSV field from `input` to `output`, always enclosing it
+    /// in quotes.
+    ///
+    /// This behaves exactly like `field`, except the field is quoted (and any
+    /// quotes within it are escaped) regardless of this writer's quoting
+    /// style, including `QuoteStyle::Never`.
+    ///
+    /// Multiple successive calls to `field_quoted` (or `field`) will write
+    /// more data to the same field. Whether the field is quoted is determined
+    /// by the first call made for that field.
+    pub fn field_quoted(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> (WriteResult, usize, usize) {
+        self.field_impl(input, output, true)
+    }
+
+    /// Implementation of `field` and `field_quoted`.
+    #[inline(always)]
+    fn field_impl(
         &mut self,
         input: &[u8],
         mut output: &mut [u8],
+        force_quote: bool,
     ) -> (WriteResult, usize, usize) {
         let (mut nin, mut nout) = (0, 0);
 
         if !self.state.in_field {
-            self.state.quoting = self.should_quote(input);
+            self.state.quoting = force_quote || self.should_quote(input);
             if self.state.quoting {
                 let (res, o) = self.write(&[self.quote], output);
                 if o == 0 {
@@ -367,7 +474,8 @@ impl Writer {
     /// terminator, then no part of the terminator is written and
     /// `WriteResult::OutputFull` is returned. Otherwise,
     /// `WriteResult::InputEmpty` is returned along with the number of bytes
-    /// written to `output` (which is always `1` or `2`).
+    /// written to `output` (which is always `1` or `2`, unless a custom
+    /// terminator was set with `WriterBuilder::record_terminator`).
     pub fn terminator(
         &mut self,
         mut output: &mut [u8],
@@ -393,11 +501,8 @@ impl Writer {
             self.state.record_bytes += o as u64;
             self.state.quoting = false;
         }
-        let (res, o) = match self.term {
-            Terminator::CRLF => write_pessimistic(&[b'\r', b'\n'], output),
-            Terminator::Any(b) => write_pessimistic(&[b], output),
-            _ => unreachable!(),
-        };
+        let (res, o) =
+            write_pessimistic(&self.term_bytes[..self.term_len], output);
         if o == 0 {
             return (res, nout);
         }
@@ -427,6 +532,19 @@ impl Writer {
         needs || input.iter().any(|&b| self.is_special_byte(b))
     }
 
+    /// Returns true if and only if this writer's quoting style is
+    /// `QuoteStyle::Never` and the given field *requires* quotes to preserve
+    /// its integrity. That is, `field` would write it without quotes, which
+    /// would corrupt the CSV data.
+    ///
+    /// Nothing is written. Callers may use this to reject such a field
+    /// before writing any part of it. As with `QuoteStyle::Necessary`,
+    /// `input` should contain the *entire* field.
+    #[inline]
+    pub fn is_unquotable(&self, input: &[u8]) -> bool {
+        matches!(self.style, QuoteStyle::Never) && self.needs_quotes(input)
+    }
+
     /// Returns true if and only if the given byte corresponds to a special
     /// byte in this CSV writer's configuration.
     ///
@@ -445,6 +563,9 @@ impl Writer {
             QuoteStyle::Always => true,
             QuoteStyle::Never => false,
             QuoteStyle::NonNumeric => is_non_numeric(input),
+            QuoteStyle::Numeric => {
+                !is_non_numeric(input) || self.needs_quotes(input)
+            }
             QuoteStyle::Necessary => self.needs_quotes(input),
             _ => unreachable!(),
         }
@@ -462,6 +583,16 @@ impl Writer {
         self.term
     }
 
+    /// Return the bytes written as the record terminator by this writer.
+    ///
+    /// This corresponds to either the `Terminator` returned by
+    /// `get_terminator` or the bytes given to
+    /// `WriterBuilder::record_terminator`.
+    #[inline]
+    pub fn get_terminator_bytes(&self) -> &[u8] {
+        &self.term_bytes[..self.term_len]
+    }
+
     /// Return the quoting style used for this writer.
     #[inline]
     pub fn get_quote_style(&self) -> QuoteStyle {
@@ -487,6 +618,26 @@ impl Writer {
         self.double_quote
     }
 
+    /// Return whether the field currently being written is enclosed in
+    /// quotes.
+    ///
+    /// This returns `None` if whether to quote the current field hasn't been
+    /// decided yet, i.e., if `field` or `field_quoted` hasn't been called
+    /// since the last delimiter or terminator, or if it was called but had
+    /// no room to write the opening quote.
+    ///
+    /// When this returns `Some(false)`, every byte passed to `field` is
+    /// copied to the output unchanged. When this returns `Some(true)`, every
+    /// byte other than the quote character is copied to the output unchanged.
+    #[inline]
+    pub fn field_quoting(&self) -> Option<bool> {
+        if self.state.in_field {
+            Some(self.state.quoting)
+        } else {
+            None
+        }
+    }
+
     fn write(&self, data: &[u8], output: &mut [u8]) -> (WriteResult, usize) {
         if data.len() > output.len() {
             (WriteResult::OutputFull, 0)
@@ -642,6 +793,7 @@ fn moving<T>(x: T) -> T {
 mod tests {
     use crate::writer::WriteResult::*;
     use crate::writer::{quote, QuoteStyle, Writer, WriterBuilder};
+    use crate::Terminator;
 
     // OMG I HATE BYTE STRING LITERALS SO MUCH.
     fn b(s: &str) -> &[u8] {
@@ -839,6 +991,29 @@ mod tests {
         assert_eq!("\"abc\",5.2,98", s(&out[..n]));
     }
 
+    #[test]
+    fn writer_two_fields_numeric() {
+        let mut wtr =
+            WriterBuilder::new().quote_style(QuoteStyle::Numeric).build();
+        let out = &mut [0; 1024];
+        let mut n = 0;
+
+        assert_field!(wtr, b("abc"), &mut out[n..], 3, 3, InputEmpty, "abc");
+        n += 3;
+        assert_write!(wtr, delimiter, &mut out[n..], 1, InputEmpty, ",");
+        n += 1;
+        assert_field!(wtr, b("5.2"), &mut out[n..], 3, 4, InputEmpty, "\"5.2");
+        n += 4;
+        assert_write!(wtr, delimiter, &mut out[n..], 2, InputEmpty, "\",");
+        n += 2;
+        assert_field!(wtr, b("a,b"), &mut out[n..], 3, 4, InputEmpty, "\"a,b");
+        n += 4;
+        assert_write!(wtr, finish, &mut out[n..], 1, InputEmpty, "\"");
+        n += 1;
+
+        assert_eq!("abc,\"5.2\",\"a,b\"", s(&out[..n]));
+    }
+
     #[test]
     fn writer_two_fields_quote() {
         let mut wtr = Writer::new();
@@ -943,6 +1118,33 @@ mod tests {
         assert_eq!("\"a,bc\",\"\nz\"\n\"f\"\"oo\",\"quux,\"", s(&out[..n]));
     }
 
+    #[test]
+    fn writer_reset() {
+        let mut wtr = Writer::new();
+        let out = &mut [0; 1024];
+
+        // Abandon a quoted field halfway through.
+        assert_field!(wtr, b("a,b"), &mut out[..], 3, 4, InputEmpty, "\"a,b");
+        wtr.reset();
+
+        let mut n = 0;
+        assert_field!(wtr, b(""), &mut out[n..], 0, 0, InputEmpty, "");
+        assert_write!(wtr, terminator, &mut out[n..], 3, InputEmpty, "\"\"\n");
+        n += 3;
+        assert_eq!("\"\"\n", s(&out[..n]));
+        wtr.reset();
+
+        let mut n = 0;
+        assert_field!(wtr, b("x"), &mut out[n..], 1, 1, InputEmpty, "x");
+        n += 1;
+        assert_write!(wtr, delimiter, &mut out[n..], 1, InputEmpty, ",");
+        n += 1;
+        assert_field!(wtr, b("y"), &mut out[n..], 1, 1, InputEmpty, "y");
+        n += 1;
+        assert_write!(wtr, finish, &mut out[n..], 0, InputEmpty, "");
+        assert_eq!("x,y", s(&out[..n]));
+    }
+
     macro_rules! assert_quote {
         (
             $inp:expr, $out:expr,
@@ -1082,4 +1284,95 @@ mod tests {
         );
         assert_write!(wtr, finish, &mut out[..], 1, InputEmpty, "\"");
     }
+
+    #[test]
+    fn record_terminator() {
+        let mut wtr = WriterBuilder::new().record_terminator(b"|\n").build();
+        assert_eq!(wtr.get_terminator_bytes(), b"|\n");
+        assert!(wtr.should_quote(b("a|b")));
+        assert!(wtr.should_quote(b("a\rb")));
+        let out = &mut [0; 1024];
+
+        assert_field!(wtr, b("abc"), &mut out[..], 3, 3, InputEmpty, "abc");
+        assert_write!(wtr, terminator, &mut out[..1], 0, OutputFull, "");
+        assert_write!(wtr, terminator, &mut out[..], 2, InputEmpty, "|\n");
+        assert_field!(wtr, b("x|y"), &mut out[..], 3, 4, InputEmpty, "\"x|y");
+        assert_write!(wtr, terminator, &mut out[..], 3, InputEmpty, "\"|\n");
+
+        let wtr = WriterBuilder::new()
+            .record_terminator(b"|\n")
+            .terminator(Terminator::CRLF)
+            .build();
+        assert_eq!(wtr.get_terminator_bytes(), b"\r\n");
+        assert!(!wtr.should_quote(b("a|b")));
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_terminator_too_long() {
+        WriterBuilder::new().record_terminator(b"123456789");
+    }
+
+    #[test]
+    fn field_quoted() {
+        let mut wtr =
+            WriterBuilder::new().quote_style(QuoteStyle::Never).build();
+        let out = &mut [0; 1024];
+        let mut n = 0;
+
+        let (res, i, o) = wtr.field_quoted(b("a\"b"), &mut out[n..]);
+        assert_eq!((InputEmpty, 3, 5), (res, i, o));
+        n += o;
+        assert_write!(wtr, delimiter, &mut out[n..], 2, InputEmpty, "\",");
+        n += 2;
+        assert_field!(wtr, b("c"), &mut out[n..], 1, 1, InputEmpty, "c");
+        n += 1;
+        assert_write!(wtr, delimiter, &mut out[n..], 1, InputEmpty, ",");
+        n += 1;
+        let (res, i, o) = wtr.field_quoted(b(""), &mut out[n..]);
+        assert_eq!((InputEmpty, 0, 1), (res, i, o));
+        n += o;
+        assert_write!(wtr, terminator, &mut out[n..], 2, InputEmpty, "\"\n");
+        n += 2;
+        assert_eq!("\"a\"\"b\",c,\"\"\n", s(&out[..n]));
+    }
+
+    #[test]
+    fn field_quoting() {
+        let mut wtr = Writer::new();
+        let out = &mut [0; 1024];
+        assert_eq!(wtr.field_quoting(), None);
+
+        // No room for the opening quote, so nothing is decided yet.
+        let (res, i, o) = wtr.field(b("a,b"), &mut out[..0]);
+        assert_eq!((OutputFull, 0, 0), (res, i, o));
+        assert_eq!(wtr.field_quoting(), None);
+
+        let (res, i, o) = wtr.field(b("a,b"), &mut out[..2]);
+        assert_eq!((OutputFull, 1, 2), (res, i, o));
+        assert_eq!(wtr.field_quoting(), Some(true));
+        let (_, o) = wtr.delimiter(&mut out[..]);
+        assert_eq!(o, 2);
+        assert_eq!(wtr.field_quoting(), None);
+
+        let (res, i, o) = wtr.field(b("ab"), &mut out[..0]);
+        assert_eq!((OutputFull, 0, 0), (res, i, o));
+        assert_eq!(wtr.field_quoting(), Some(false));
+        wtr.terminator(&mut out[..]);
+        assert_eq!(wtr.field_quoting(), None);
+    }
+
+    #[test]
+    fn is_unquotable() {
+        let wtr = WriterBuilder::new().quote_style(QuoteStyle::Never).build();
+        assert!(!wtr.is_unquotable(b("abc")));
+        assert!(wtr.is_unquotable(b("b,c")));
+        assert!(wtr.is_unquotable(b("b\"c")));
+        assert!(wtr.is_unquotable(b("b\nc")));
+        assert!(wtr.is_unquotable(b("0123456789,")));
+
+        // Other quoting styles quote such fields instead.
+        let wtr = WriterBuilder::new().build();
+        assert!(!wtr.is_unquotable(b("b,c")));
+    }
 }
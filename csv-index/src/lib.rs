@@ -42,17 +42,16 @@ fn example() -> Result<(), Box<dyn Error>> {
     wtr.flush()?;
 
     // Open the index we just created, get the position of the last
-    // record and seek the CSV reader to the last record.
+    // record and read the CSV data from that record onwards.
     let mut idx = RandomAccessSimple::open(File::open("data.csv.idx")?)?;
     if idx.is_empty() {
         return Err(From::from("expected a non-empty CSV index"));
     }
     let last = idx.len() - 1;
     let pos = idx.get(last)?;
-    rdr.seek(pos)?;
 
     // Read the next record.
-    if let Some(result) = rdr.records().next() {
+    if let Some(result) = rdr.records_from(&pos)?.next() {
         let record = result?;
         println!("{:?}", record);
         Ok(())
@@ -194,13 +194,12 @@ impl<R: io::Read + io::Seek> RandomAccessSimple<R> {
     ///     RandomAccessSimple::create(&mut rdr, &mut wtr)?;
     ///
     ///     // Open the index we just created, get the position of the last
-    ///     // record and seek the CSV reader.
+    ///     // record and read the CSV data from that record onwards.
     ///     let mut idx = RandomAccessSimple::open(wtr)?;
     ///     let pos = idx.get(2)?;
-    ///     rdr.seek(pos)?;
     ///
     ///     // Read the next record.
-    ///     if let Some(result) = rdr.records().next() {
+    ///     if let Some(result) = rdr.records_from(&pos)?.next() {
     ///         let record = result?;
     ///         assert_eq!(record, vec!["Concord", "United States", "42695"]);
     ///         Ok(())
@@ -0,0 +1,977 @@
+use std::{io, str, sync::Arc};
+
+use arrow_array::{
+    builder::{
+        BooleanBuilder, GenericBinaryBuilder, GenericStringBuilder,
+        PrimitiveBuilder,
+    },
+    cast::AsArray,
+    types::*,
+    Array, ArrayRef, ArrowPrimitiveType, GenericBinaryArray,
+    GenericStringArray, OffsetSizeTrait, RecordBatch, RecordBatchOptions,
+};
+use arrow_buffer::NullBuffer;
+use arrow_cast::{
+    cast::cast,
+    display::{ArrayFormatter, FormatOptions},
+    parse::{parse_decimal, Parser},
+};
+use arrow_schema::{ArrowError, DataType, Field, SchemaRef, TimeUnit};
+
+use crate::{
+    byte_record::ByteRecord,
+    deserializer::{DeserializeError, DeserializeErrorKind as DEK},
+    error::{Error, ErrorKind, Result},
+    reader::Reader,
+    writer::Writer,
+};
+
+/// An owned iterator over batches of records as Apache Arrow
+/// `RecordBatch`es.
+///
+/// This is created by
+/// [`Reader::into_arrow_batches`](struct.Reader.html#method.into_arrow_batches).
+pub struct ArrowBatchesIntoIter<R> {
+    rdr: Reader<R>,
+    batches: BatchBuilder,
+}
+
+impl<R: io::Read> ArrowBatchesIntoIter<R> {
+    pub(crate) fn new(
+        rdr: Reader<R>,
+        schema: SchemaRef,
+        batch_size: usize,
+    ) -> ArrowBatchesIntoIter<R> {
+        ArrowBatchesIntoIter {
+            rdr,
+            batches: BatchBuilder::new(schema, batch_size),
+        }
+    }
+
+    /// Return the schema of the batches yielded by this iterator.
+    pub fn schema(&self) -> SchemaRef {
+        self.batches.schema.clone()
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Drop this iterator and return the underlying CSV reader.
+    ///
+    /// Any records read into a batch that hasn't been yielded yet are lost.
+    pub fn into_reader(self) -> Reader<R> {
+        self.rdr
+    }
+}
+
+impl<R: io::Read> Iterator for ArrowBatchesIntoIter<R> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Result<RecordBatch>> {
+        self.batches.next_batch(&mut self.rdr)
+    }
+}
+
+/// A borrowed iterator over batches of records as Apache Arrow
+/// `RecordBatch`es.
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+///
+/// This is created by
+/// [`Reader::arrow_batches`](struct.Reader.html#method.arrow_batches).
+pub struct ArrowBatchesIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    batches: BatchBuilder,
+}
+
+impl<'r, R: io::Read> ArrowBatchesIter<'r, R> {
+    pub(crate) fn new(
+        rdr: &'r mut Reader<R>,
+        schema: SchemaRef,
+        batch_size: usize,
+    ) -> ArrowBatchesIter<'r, R> {
+        ArrowBatchesIter {
+            rdr,
+            batches: BatchBuilder::new(schema, batch_size),
+        }
+    }
+
+    /// Return the schema of the batches yielded by this iterator.
+    pub fn schema(&self) -> SchemaRef {
+        self.batches.schema.clone()
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+}
+
+impl<'r, R: io::Read> Iterator for ArrowBatchesIter<'r, R> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Result<RecordBatch>> {
+        self.batches.next_batch(self.rdr)
+    }
+}
+
+/// The state shared by the borrowed and owned batch iterators.
+struct BatchBuilder {
+    schema: SchemaRef,
+    batch_size: usize,
+    /// One builder per field in the schema.
+    columns: Vec<Box<dyn Column>>,
+    /// The error to report if the schema contains a type we can't build.
+    err: Option<Error>,
+    /// The number of rows appended to the builders since the last batch.
+    rows: usize,
+    rec: ByteRecord,
+    done: bool,
+}
+
+impl BatchBuilder {
+    fn new(schema: SchemaRef, batch_size: usize) -> BatchBuilder {
+        assert!(batch_size > 0, "Arrow batch size must be greater than 0");
+        let (columns, err) =
+            match schema.fields().iter().map(|f| new_column(f)).collect() {
+                Ok(columns) => (columns, None),
+                Err(err) => (vec![], Some(err)),
+            };
+        BatchBuilder {
+            schema,
+            batch_size,
+            columns,
+            err,
+            rows: 0,
+            rec: ByteRecord::new(),
+            done: false,
+        }
+    }
+
+    fn next_batch<R: io::Read>(
+        &mut self,
+        rdr: &mut Reader<R>,
+    ) -> Option<Result<RecordBatch>> {
+        if let Some(err) = self.err.take() {
+            self.done = true;
+            return Some(Err(err));
+        }
+        if self.done {
+            return None;
+        }
+        let columns = &mut self.columns;
+        while self.rows < self.batch_size {
+            match rdr.read_byte_record(&mut self.rec) {
+                Err(err) => return Some(Err(err)),
+                Ok(false) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(true) => {}
+            }
+            // Every field is parsed before any is appended, so that a bad
+            // field doesn't leave the columns with different lengths.
+            for (i, column) in columns.iter_mut().enumerate() {
+                let field = self.rec.get(i).unwrap_or(b"");
+                if let Err(kind) = column.parse(field) {
+                    let name = self.schema.field(i).name().clone();
+                    let err = DeserializeError::for_field(
+                        i as u64,
+                        Some(name),
+                        kind,
+                    );
+                    return Some(Err(Error::new(ErrorKind::Deserialize {
+                        pos: self.rec.position().cloned(),
+                        err,
+                    })));
+                }
+            }
+            for (i, column) in columns.iter_mut().enumerate() {
+                column.commit(self.rec.get(i).unwrap_or(b""));
+            }
+            self.rows += 1;
+        }
+        if self.rows == 0 {
+            return None;
+        }
+        let arrays = columns.iter_mut().map(|c| c.finish()).collect();
+        let options =
+            RecordBatchOptions::new().with_row_count(Some(self.rows));
+        self.rows = 0;
+        // Each builder produces exactly its field's type and only produces
+        // nulls for nullable fields, so the batch always matches the schema.
+        let batch = RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            arrays,
+            &options,
+        )
+        .expect("Arrow columns match the schema");
+        Some(Ok(batch))
+    }
+}
+
+/// A builder for a single Arrow column.
+///
+/// Appending a field is split in two steps: `parse` checks the field and
+/// holds on to its value, and `commit` appends the value last parsed.
+trait Column: Send {
+    fn parse(&mut self, field: &[u8]) -> std::result::Result<(), DEK>;
+    fn commit(&mut self, field: &[u8]);
+    fn finish(&mut self) -> ArrayRef;
+}
+
+/// Return a column builder for the given schema field, or an error if its
+/// type isn't supported.
+fn new_column(field: &Field) -> Result<Box<dyn Column>> {
+    use arrow_schema::DataType::*;
+
+    let column: Box<dyn Column> = match *field.data_type() {
+        Boolean => Box::new(BooleanColumn::new(field)),
+        Int8 => primitive::<Int8Type>(field),
+        Int16 => primitive::<Int16Type>(field),
+        Int32 => primitive::<Int32Type>(field),
+        Int64 => primitive::<Int64Type>(field),
+        UInt8 => primitive::<UInt8Type>(field),
+        UInt16 => primitive::<UInt16Type>(field),
+        UInt32 => primitive::<UInt32Type>(field),
+        UInt64 => primitive::<UInt64Type>(field),
+        Float16 => primitive::<Float16Type>(field),
+        Float32 => primitive::<Float32Type>(field),
+        Float64 => primitive::<Float64Type>(field),
+        Date32 => primitive::<Date32Type>(field),
+        Date64 => primitive::<Date64Type>(field),
+        Timestamp(TimeUnit::Second, _) => {
+            primitive::<TimestampSecondType>(field)
+        }
+        Timestamp(TimeUnit::Millisecond, _) => {
+            primitive::<TimestampMillisecondType>(field)
+        }
+        Timestamp(TimeUnit::Microsecond, _) => {
+            primitive::<TimestampMicrosecondType>(field)
+        }
+        Timestamp(TimeUnit::Nanosecond, _) => {
+            primitive::<TimestampNanosecondType>(field)
+        }
+        Time32(TimeUnit::Second) => primitive::<Time32SecondType>(field),
+        Time32(TimeUnit::Millisecond) => {
+            primitive::<Time32MillisecondType>(field)
+        }
+        Time64(TimeUnit::Microsecond) => {
+            primitive::<Time64MicrosecondType>(field)
+        }
+        Time64(TimeUnit::Nanosecond) => {
+            primitive::<Time64NanosecondType>(field)
+        }
+        Duration(TimeUnit::Second) => primitive::<DurationSecondType>(field),
+        Duration(TimeUnit::Millisecond) => {
+            primitive::<DurationMillisecondType>(field)
+        }
+        Duration(TimeUnit::Microsecond) => {
+            primitive::<DurationMicrosecondType>(field)
+        }
+        Duration(TimeUnit::Nanosecond) => {
+            primitive::<DurationNanosecondType>(field)
+        }
+        Decimal128(precision, scale) => {
+            decimal::<Decimal128Type>(field, precision, scale)
+        }
+        Decimal256(precision, scale) => {
+            decimal::<Decimal256Type>(field, precision, scale)
+        }
+        Utf8 => Box::new(StringColumn::<i32>::new(field)),
+        LargeUtf8 => Box::new(StringColumn::<i64>::new(field)),
+        Binary => Box::new(BinaryColumn::<i32>::new(field)),
+        LargeBinary => Box::new(BinaryColumn::<i64>::new(field)),
+        ref dt => {
+            return Err(Error::new(ErrorKind::InvalidConfig(format!(
+                "unsupported Arrow data type {} for field '{}'",
+                dt,
+                field.name(),
+            ))))
+        }
+    };
+    Ok(column)
+}
+
+fn primitive<T: Parser>(field: &Field) -> Box<dyn Column> {
+    Box::new(PrimitiveColumn::<T>::new(field, Box::new(T::parse)))
+}
+
+fn decimal<T: DecimalType>(
+    field: &Field,
+    precision: u8,
+    scale: i8,
+) -> Box<dyn Column> {
+    let parse = move |s: &str| parse_decimal::<T>(s, precision, scale).ok();
+    Box::new(PrimitiveColumn::<T>::new(field, Box::new(parse)))
+}
+
+/// Return the error for an empty field in a column that can't hold nulls.
+fn empty_error() -> DEK {
+    DEK::Message("empty field in non-nullable column".to_string())
+}
+
+/// Return the field as a string, or an error if it isn't valid UTF-8.
+fn field_str(field: &[u8]) -> std::result::Result<&str, DEK> {
+    str::from_utf8(field).map_err(DEK::InvalidUtf8)
+}
+
+/// A function parsing a field into a primitive value.
+type ParseFn<T> =
+    Box<dyn Fn(&str) -> Option<<T as ArrowPrimitiveType>::Native> + Send>;
+
+struct PrimitiveColumn<T: ArrowPrimitiveType> {
+    builder: PrimitiveBuilder<T>,
+    parse: ParseFn<T>,
+    data_type: DataType,
+    nullable: bool,
+    value: Option<T::Native>,
+}
+
+impl<T: ArrowPrimitiveType> PrimitiveColumn<T> {
+    fn new(field: &Field, parse: ParseFn<T>) -> PrimitiveColumn<T> {
+        // Setting the data type carries over parameters such as a
+        // timestamp's time zone or a decimal's precision and scale.
+        let builder = PrimitiveBuilder::<T>::new()
+            .with_data_type(field.data_type().clone());
+        PrimitiveColumn {
+            builder,
+            parse,
+            data_type: field.data_type().clone(),
+            nullable: field.is_nullable(),
+            value: None,
+        }
+    }
+}
+
+impl<T: ArrowPrimitiveType> Column for PrimitiveColumn<T> {
+    fn parse(&mut self, field: &[u8]) -> std::result::Result<(), DEK> {
+        if field.is_empty() {
+            if !self.nullable {
+                return Err(empty_error());
+            }
+            self.value = None;
+            return Ok(());
+        }
+        let s = field_str(field)?;
+        match (self.parse)(s) {
+            Some(value) => {
+                self.value = Some(value);
+                Ok(())
+            }
+            None => Err(DEK::Message(format!(
+                "invalid {} value '{}'",
+                self.data_type, s
+            ))),
+        }
+    }
+
+    fn commit(&mut self, _: &[u8]) {
+        self.builder.append_option(self.value.take());
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct BooleanColumn {
+    builder: BooleanBuilder,
+    nullable: bool,
+    value: Option<bool>,
+}
+
+impl BooleanColumn {
+    fn new(field: &Field) -> BooleanColumn {
+        BooleanColumn {
+            builder: BooleanBuilder::new(),
+            nullable: field.is_nullable(),
+            value: None,
+        }
+    }
+}
+
+impl Column for BooleanColumn {
+    fn parse(&mut self, field: &[u8]) -> std::result::Result<(), DEK> {
+        if field.is_empty() {
+            if !self.nullable {
+                return Err(empty_error());
+            }
+            self.value = None;
+            return Ok(());
+        }
+        let value = field_str(field)?.parse().map_err(DEK::ParseBool)?;
+        self.value = Some(value);
+        Ok(())
+    }
+
+    fn commit(&mut self, _: &[u8]) {
+        self.builder.append_option(self.value.take());
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct StringColumn<O: OffsetSizeTrait> {
+    builder: GenericStringBuilder<O>,
+    nullable: bool,
+}
+
+impl<O: OffsetSizeTrait> StringColumn<O> {
+    fn new(field: &Field) -> StringColumn<O> {
+        StringColumn {
+            builder: GenericStringBuilder::new(),
+            nullable: field.is_nullable(),
+        }
+    }
+}
+
+impl<O: OffsetSizeTrait> Column for StringColumn<O> {
+    fn parse(&mut self, field: &[u8]) -> std::result::Result<(), DEK> {
+        field_str(field).map(|_| ())
+    }
+
+    fn commit(&mut self, field: &[u8]) {
+        if field.is_empty() && self.nullable {
+            self.builder.append_null();
+        } else {
+            // `parse` already checked that this is valid UTF-8.
+            self.builder.append_value(str::from_utf8(field).unwrap());
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+struct BinaryColumn<O: OffsetSizeTrait> {
+    builder: GenericBinaryBuilder<O>,
+    nullable: bool,
+}
+
+impl<O: OffsetSizeTrait> BinaryColumn<O> {
+    fn new(field: &Field) -> BinaryColumn<O> {
+        BinaryColumn {
+            builder: GenericBinaryBuilder::new(),
+            nullable: field.is_nullable(),
+        }
+    }
+}
+
+impl<O: OffsetSizeTrait> Column for BinaryColumn<O> {
+    fn parse(&mut self, _: &[u8]) -> std::result::Result<(), DEK> {
+        Ok(())
+    }
+
+    fn commit(&mut self, field: &[u8]) {
+        if field.is_empty() && self.nullable {
+            self.builder.append_null();
+        } else {
+            self.builder.append_value(field);
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        Arc::new(self.builder.finish())
+    }
+}
+
+/// Write every row of a record batch to the given writer.
+///
+/// This doesn't write a header row. That's up to the caller.
+pub(crate) fn write_batch<W: io::Write>(
+    wtr: &mut Writer<W>,
+    batch: &RecordBatch,
+) -> Result<()> {
+    // Durations are written as an integer count of their unit, since that's
+    // how `arrow_batches` reads them.
+    let arrays = batch
+        .columns()
+        .iter()
+        .map(|array| match *array.data_type() {
+            DataType::Duration(_) => cast(array, &DataType::Int64),
+            _ => Ok(Arc::clone(array)),
+        })
+        .collect::<std::result::Result<Vec<ArrayRef>, ArrowError>>()
+        .map_err(arrow_error)?;
+    let options = FormatOptions::new();
+    let formatters = arrays
+        .iter()
+        .map(|array| FieldFormatter::new(array.as_ref(), &options))
+        .collect::<Result<Vec<_>>>()?;
+    let mut bufs = vec![String::new(); formatters.len()];
+    for row in 0..batch.num_rows() {
+        // Format every value in the row before writing any of it, so that a
+        // value that can't be formatted doesn't leave a partial record
+        // behind.
+        for (formatter, buf) in formatters.iter().zip(&mut bufs) {
+            formatter.format(row, buf)?;
+        }
+        for (formatter, buf) in formatters.iter().zip(&bufs) {
+            formatter.write(wtr, row, buf)?;
+        }
+        wtr.write_record(None::<&[u8]>)?;
+    }
+    Ok(())
+}
+
+/// Convert an Arrow error to a CSV write error.
+fn arrow_error(err: ArrowError) -> Error {
+    Error::new(ErrorKind::Serialize(err.to_string()))
+}
+
+/// Writes the values of a single Arrow column as CSV fields.
+struct FieldFormatter<'a> {
+    nulls: Option<NullBuffer>,
+    kind: FormatterKind<'a>,
+}
+
+enum FormatterKind<'a> {
+    Utf8(&'a GenericStringArray<i32>),
+    LargeUtf8(&'a GenericStringArray<i64>),
+    Binary(&'a GenericBinaryArray<i32>),
+    LargeBinary(&'a GenericBinaryArray<i64>),
+    /// Every other type is written the way Arrow displays it.
+    Display(ArrayFormatter<'a>),
+}
+
+impl<'a> FieldFormatter<'a> {
+    fn new(
+        array: &'a dyn Array,
+        options: &FormatOptions<'a>,
+    ) -> Result<FieldFormatter<'a>> {
+        let kind = match *array.data_type() {
+            DataType::Utf8 => FormatterKind::Utf8(array.as_string()),
+            DataType::LargeUtf8 => FormatterKind::LargeUtf8(array.as_string()),
+            DataType::Binary => FormatterKind::Binary(array.as_binary()),
+            DataType::LargeBinary => {
+                FormatterKind::LargeBinary(array.as_binary())
+            }
+            _ => FormatterKind::Display(
+                ArrayFormatter::try_new(array, options)
+                    .map_err(arrow_error)?,
+            ),
+        };
+        Ok(FieldFormatter { nulls: array.logical_nulls(), kind })
+    }
+
+    /// Format the value in the given row into `buf`, if this column's values
+    /// need formatting. This is the only part of writing a value that can
+    /// fail, other than I/O.
+    fn format(&self, row: usize, buf: &mut String) -> Result<()> {
+        if let FormatterKind::Display(ref formatter) = self.kind {
+            buf.clear();
+            if !self.nulls.as_ref().is_some_and(|nulls| nulls.is_null(row)) {
+                formatter.value(row).write(buf).map_err(arrow_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the value in the given row as a field. For values that need
+    /// formatting, `buf` must contain what `format` wrote for this row.
+    fn write<W: io::Write>(
+        &self,
+        wtr: &mut Writer<W>,
+        row: usize,
+        buf: &str,
+    ) -> Result<()> {
+        if self.nulls.as_ref().is_some_and(|nulls| nulls.is_null(row)) {
+            return wtr.write_field(b"");
+        }
+        match self.kind {
+            FormatterKind::Utf8(array) => wtr.write_field(array.value(row)),
+            FormatterKind::LargeUtf8(array) => {
+                wtr.write_field(array.value(row))
+            }
+            FormatterKind::Binary(array) => wtr.write_field(array.value(row)),
+            FormatterKind::LargeBinary(array) => {
+                wtr.write_field(array.value(row))
+            }
+            FormatterKind::Display(_) => wtr.write_field(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{cast::AsArray, types::*, Array, *};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+
+    use crate::{
+        error::ErrorKind, reader::ReaderBuilder, writer::WriterBuilder,
+        DeserializeErrorKind,
+    };
+
+    fn schema(fields: Vec<Field>) -> SchemaRef {
+        Arc::new(Schema::new(fields))
+    }
+
+    #[test]
+    fn types() {
+        let data = "\
+b,i,u,f,s,d,ts,dec
+true,-5,7,1.5,foo,2024-02-29,2024-02-29T01:02:03Z,12.34
+false,42,0,-0.25,bar,1970-01-01,1970-01-01T00:00:00Z,-0.5
+";
+        let schema = schema(vec![
+            Field::new("b", DataType::Boolean, false),
+            Field::new("i", DataType::Int32, false),
+            Field::new("u", DataType::UInt8, false),
+            Field::new("f", DataType::Float64, false),
+            Field::new("s", DataType::Utf8, false),
+            Field::new("d", DataType::Date32, false),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                false,
+            ),
+            Field::new("dec", DataType::Decimal128(10, 2), false),
+        ]);
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let batches: Vec<_> = rdr
+            .arrow_batches(schema.clone(), 10)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema(), schema);
+        assert_eq!(batch.num_rows(), 2);
+
+        let b = batch.column(0).as_boolean();
+        assert!(b.value(0));
+        assert!(!b.value(1));
+        let i = batch.column(1).as_primitive::<Int32Type>();
+        assert_eq!(i.values(), &[-5, 42]);
+        let u = batch.column(2).as_primitive::<UInt8Type>();
+        assert_eq!(u.values(), &[7, 0]);
+        let f = batch.column(3).as_primitive::<Float64Type>();
+        assert_eq!(f.values(), &[1.5, -0.25]);
+        let s = batch.column(4).as_string::<i32>();
+        assert_eq!(s.value(0), "foo");
+        assert_eq!(s.value(1), "bar");
+        let d = batch.column(5).as_primitive::<Date32Type>();
+        assert_eq!(d.values(), &[19782, 0]);
+        let ts = batch.column(6).as_primitive::<TimestampSecondType>();
+        assert_eq!(ts.values(), &[1709168523, 0]);
+        assert_eq!(ts.timezone(), Some("UTC"));
+        let dec = batch.column(7).as_primitive::<Decimal128Type>();
+        assert_eq!(dec.values(), &[1234, -50]);
+        assert_eq!((dec.precision(), dec.scale()), (10, 2));
+    }
+
+    #[test]
+    fn nulls() {
+        let data = "\
+a,b,c
+1,,x
+,true,
+2
+";
+        let schema = schema(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Boolean, true),
+            Field::new("c", DataType::Utf8, false),
+        ]);
+        let mut rdr =
+            ReaderBuilder::new().flexible(true).from_reader(data.as_bytes());
+        let batch = rdr.arrow_batches(schema, 10).next().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+
+        let a = batch.column(0).as_primitive::<Int64Type>();
+        assert!(a.is_valid(0) && a.is_null(1) && a.is_valid(2));
+        let b = batch.column(1).as_boolean();
+        assert!(b.is_null(0) && b.is_valid(1) && b.is_null(2));
+        // A non-nullable string column reads empty fields as empty strings.
+        let c = batch.column(2).as_string::<i32>();
+        assert_eq!(c.null_count(), 0);
+        assert_eq!(c.value(0), "x");
+        assert_eq!(c.value(1), "");
+        assert_eq!(c.value(2), "");
+    }
+
+    #[test]
+    fn batch_size() {
+        let data = "n\n1\n2\n3\n4\n5\n";
+        let schema = schema(vec![Field::new("n", DataType::UInt16, false)]);
+        let rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut it = rdr.into_arrow_batches(schema, 2);
+        let mut got = vec![];
+        for result in &mut it {
+            let batch = result.unwrap();
+            let n = batch.column(0).as_primitive::<UInt16Type>();
+            got.push(n.values().to_vec());
+        }
+        assert_eq!(got, vec![vec![1, 2], vec![3, 4], vec![5]]);
+        assert!(it.next().is_none());
+        assert!(it.reader().is_done());
+    }
+
+    #[test]
+    fn parse_error() {
+        let data = "\
+a,b
+1,2
+3,x
+5,
+7,8
+";
+        let schema = schema(vec![
+            Field::new("a", DataType::Int8, false),
+            Field::new("b", DataType::Int8, false),
+        ]);
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut it = rdr.arrow_batches(schema, 10);
+
+        let err = it.next().unwrap().unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref pos, ref err } => {
+                let pos = pos.as_ref().unwrap();
+                assert_eq!(pos.record(), 2);
+                assert_eq!(pos.line(), 3);
+                assert_eq!(err.field(), Some(1));
+                assert_eq!(err.column(), Some("b"));
+                assert_eq!(
+                    *err.kind(),
+                    DeserializeErrorKind::Message(
+                        "invalid Int8 value 'x'".to_string()
+                    )
+                );
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = it.next().unwrap().unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref pos, ref err } => {
+                assert_eq!(pos.as_ref().unwrap().line(), 4);
+                assert_eq!(err.field(), Some(1));
+                assert_eq!(
+                    err.to_string(),
+                    "field 1 (column 'b'): empty field in non-nullable column"
+                );
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        // The rows read before the errors are kept, and the bad rows are
+        // skipped.
+        let batch = it.next().unwrap().unwrap();
+        let a = batch.column(0).as_primitive::<Int8Type>();
+        let b = batch.column(1).as_primitive::<Int8Type>();
+        assert_eq!(a.values(), &[1, 7]);
+        assert_eq!(b.values(), &[2, 8]);
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let data = "a\n1\n";
+        let schema = schema(vec![Field::new(
+            "a",
+            DataType::List(Arc::new(Field::new("item", DataType::Int8, true))),
+            true,
+        )]);
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut it = rdr.arrow_batches(schema, 10);
+        match *it.next().unwrap().unwrap_err().kind() {
+            ErrorKind::InvalidConfig(ref msg) => {
+                assert!(msg.contains("field 'a'"), "{}", msg);
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let data = b"a\n\xFF\n";
+        let schema = schema(vec![Field::new("a", DataType::Utf8, true)]);
+        let mut rdr = ReaderBuilder::new().from_reader(&data[..]);
+        let err = rdr.arrow_batches(schema, 10).next().unwrap().unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref err, .. } => match *err.kind() {
+                DeserializeErrorKind::InvalidUtf8(_) => {}
+                ref kind => panic!("unexpected error: {:?}", kind),
+            },
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    fn write(batches: &[RecordBatch], has_headers: bool) -> String {
+        let mut wtr =
+            WriterBuilder::new().has_headers(has_headers).from_writer(vec![]);
+        for batch in batches {
+            wtr.write_arrow_batch(batch).unwrap();
+        }
+        String::from_utf8(wtr.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn write_types() {
+        let schema = schema(vec![
+            Field::new("b", DataType::Boolean, true),
+            Field::new("i", DataType::Int32, true),
+            Field::new("f", DataType::Float64, true),
+            Field::new("s", DataType::Utf8, true),
+            Field::new("bin", DataType::Binary, true),
+            Field::new("d", DataType::Date32, true),
+            Field::new("t", DataType::Time64(TimeUnit::Microsecond), true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new(
+                "tz",
+                DataType::Timestamp(TimeUnit::Second, Some("+01:00".into())),
+                true,
+            ),
+            Field::new("dec", DataType::Decimal128(6, 2), true),
+            Field::new("dur", DataType::Duration(TimeUnit::Second), true),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(BooleanArray::from(vec![Some(true), None])),
+            Arc::new(Int32Array::from(vec![Some(-5), None])),
+            Arc::new(Float64Array::from(vec![Some(1.0), Some(1e20)])),
+            Arc::new(StringArray::from(vec![Some("a,b"), None])),
+            Arc::new(BinaryArray::from(vec![Some(&b"\xFFx"[..]), None])),
+            Arc::new(Date32Array::from(vec![Some(19782), None])),
+            Arc::new(Time64MicrosecondArray::from(vec![
+                Some(3_723_500_000),
+                None,
+            ])),
+            Arc::new(TimestampMillisecondArray::from(vec![
+                Some(1_709_168_523_250),
+                None,
+            ])),
+            Arc::new(
+                TimestampSecondArray::from(vec![Some(1_709_168_523), None])
+                    .with_timezone("+01:00"),
+            ),
+            Arc::new(
+                Decimal128Array::from(vec![Some(1230), None])
+                    .with_precision_and_scale(6, 2)
+                    .unwrap(),
+            ),
+            Arc::new(DurationSecondArray::from(vec![Some(90), None])),
+        ];
+        let batch = RecordBatch::try_new(schema, columns).unwrap();
+        let expected = b"\
+b,i,f,s,bin,d,t,ts,tz,dec,dur
+true,-5,1.0,\"a,b\",\xFFx,2024-02-29,01:02:03.500,\
+2024-02-29T01:02:03.250,2024-02-29T02:02:03+01:00,12.30,90
+,,1e20,,,,,,,,
+"
+        .to_vec();
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        wtr.write_arrow_batch(&batch).unwrap();
+        assert_eq!(
+            bstr::BString::from(wtr.into_inner().unwrap()),
+            bstr::BString::from(expected)
+        );
+    }
+
+    #[test]
+    fn write_headers() {
+        let schema = schema(vec![Field::new("n", DataType::Int8, false)]);
+        let empty = RecordBatch::new_empty(schema.clone());
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int8Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+
+        assert_eq!(write(&[empty.clone()], true), "n\n");
+        assert_eq!(
+            write(&[empty, batch.clone(), batch.clone()], true),
+            "n\n1\n2\n1\n2\n"
+        );
+        assert_eq!(write(&[batch], false), "1\n2\n");
+    }
+
+    #[test]
+    fn write_error_mid_row() {
+        let schema = schema(vec![
+            Field::new("n", DataType::Int32, false),
+            Field::new("d", DataType::Date64, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Date64Array::from(vec![0, i64::MAX])),
+            ],
+        )
+        .unwrap();
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let err = wtr.write_arrow_batch(&batch).unwrap_err();
+        assert!(matches!(*err.kind(), ErrorKind::Serialize(_)));
+        // The row with the bad value isn't written at all, so the next
+        // record starts on its own line.
+        wtr.write_record(["3", "x"]).unwrap();
+        assert_eq!(
+            String::from_utf8(wtr.into_inner().unwrap()).unwrap(),
+            "n,d\n1,1970-01-01T00:00:00\n3,x\n"
+        );
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let data = "\
+i,f,s,d,ts,dec,dur
+1,0.5,foo,2024-02-29,2024-02-29T01:02:03.250,-12.30,90
+,,,,,,
+";
+        let schema = schema(vec![
+            Field::new("i", DataType::UInt64, true),
+            Field::new("f", DataType::Float32, true),
+            Field::new("s", DataType::Utf8, true),
+            Field::new("d", DataType::Date64, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new("dec", DataType::Decimal256(20, 2), true),
+            Field::new("dur", DataType::Duration(TimeUnit::Second), true),
+        ]);
+        let batches: Vec<_> = ReaderBuilder::new()
+            .from_reader(data.as_bytes())
+            .into_arrow_batches(schema.clone(), 1)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(batches.len(), 2);
+        let written = write(&batches, true);
+        // Date64 values are written with a time of day.
+        assert_eq!(
+            written,
+            data.replace("2024-02-29,", "2024-02-29T00:00:00,")
+        );
+
+        let again: Vec<_> = ReaderBuilder::new()
+            .from_reader(written.as_bytes())
+            .into_arrow_batches(schema, 1)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(again, batches);
+    }
+}
@@ -1,16 +1,23 @@
 use std::{
-    cmp, fmt,
+    cmp, fmt, io,
     iter::FromIterator,
+    mem,
     ops::{self, Range},
     result,
 };
 
-use serde::de::Deserialize;
+use {csv_core::FieldMeta, memchr::memmem};
+
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
 
 use crate::{
     deserializer::deserialize_byte_record,
     error::{new_utf8_error, Result, Utf8Error},
     string_record::StringRecord,
+    writer::{write_record_to, RecordWriteOptions},
 };
 
 /// A single CSV record stored as raw bytes.
@@ -90,9 +97,14 @@ struct ByteRecordInner {
     /// The position of this byte record.
     pos: Option<Position>,
     /// All fields in this record, stored contiguously.
-    fields: Vec<u8>,
+    fields: SmallBuf<u8, INLINE_BYTES>,
     /// The number of and location of each field in this record.
     bounds: Bounds,
+    /// The metadata of each field in this record, if it was read with
+    /// `ReaderBuilder::field_meta` enabled. Field `i` has metadata only if
+    /// `i` is less than both the number of fields and the length of this
+    /// vector.
+    meta: Vec<FieldMeta>,
 }
 
 impl Default for ByteRecord {
@@ -139,8 +151,9 @@ impl ByteRecord {
     pub fn with_capacity(buffer: usize, fields: usize) -> ByteRecord {
         ByteRecord(Box::new(ByteRecordInner {
             pos: None,
-            fields: vec![0; buffer],
+            fields: SmallBuf::with_len(buffer),
             bounds: Bounds::with_capacity(fields),
+            meta: vec![],
         }))
     }
 
@@ -236,6 +249,22 @@ impl ByteRecord {
         deserialize_byte_record(self, headers)
     }
 
+    /// Convert this `ByteRecord` into a vector of its fields.
+    ///
+    /// This is a shortcut for `record.iter().map(|f| f.to_vec()).collect()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["a", "", "c"]);
+    /// assert_eq!(record.into_vec(), vec![b"a".to_vec(), vec![], b"c".to_vec()]);
+    /// ```
+    pub fn into_vec(self) -> Vec<Vec<u8>> {
+        self.iter().map(|field| field.to_vec()).collect()
+    }
+
     /// Returns an iterator over all fields in this record.
     ///
     /// # Example
@@ -273,6 +302,185 @@ impl ByteRecord {
         self.0.bounds.get(i).map(|range| &self.0.fields[range])
     }
 
+    /// Return the field in the column named `name`, using `headers` to find
+    /// the column's index.
+    ///
+    /// If `name` appears more than once in `headers`, then the field
+    /// corresponding to its first occurrence is returned. If `name` isn't
+    /// in `headers`, or if this record has no field at that index, then this
+    /// returns `None`.
+    ///
+    /// This searches `headers` on every call. For repeated lookups, consider
+    /// building a [`HeaderMap`](struct.HeaderMap.html) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let headers = ByteRecord::from(vec!["city", "pop"]);
+    /// let record = ByteRecord::from(vec!["Boston", "4628910"]);
+    /// assert_eq!(record.get_by_name(&headers, b"pop"), Some(&b"4628910"[..]));
+    /// assert_eq!(record.get_by_name(&headers, b"country"), None);
+    /// ```
+    #[inline]
+    pub fn get_by_name(
+        &self,
+        headers: &ByteRecord,
+        name: &[u8],
+    ) -> Option<&[u8]> {
+        headers.iter().position(|h| h == name).and_then(|i| self.get(i))
+    }
+
+    /// Returns true if and only if some field in this record is exactly
+    /// equal to `needle`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["Boston", "United States"]);
+    /// assert!(record.contains_field(b"Boston"));
+    /// assert!(!record.contains_field(b"Bost"));
+    /// ```
+    #[inline]
+    pub fn contains_field(&self, needle: &[u8]) -> bool {
+        self.position_of_field(needle).is_some()
+    }
+
+    /// Returns the index of the first field in this record that is exactly
+    /// equal to `needle`, or `None` if no such field exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["a", "b", "b"]);
+    /// assert_eq!(record.position_of_field(b"b"), Some(1));
+    /// assert_eq!(record.position_of_field(b"c"), None);
+    /// ```
+    #[inline]
+    pub fn position_of_field(&self, needle: &[u8]) -> Option<usize> {
+        self.iter().position(|field| field == needle)
+    }
+
+    /// Returns true if and only if some field in this record contains
+    /// `needle` as a substring.
+    ///
+    /// A match never spans more than one field. An empty needle is
+    /// contained in every field, so it matches if and only if this record
+    /// has at least one field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["Boston", "United States"]);
+    /// assert!(record.contains_substring(b"ted St"));
+    /// assert!(!record.contains_substring(b"nUnited"));
+    /// ```
+    pub fn contains_substring(&self, needle: &[u8]) -> bool {
+        let finder = memmem::Finder::new(needle);
+        self.iter().any(|field| finder.find(field).is_some())
+    }
+
+    /// Return a key built from the fields at the given column indices, in
+    /// the order given.
+    ///
+    /// The key is suitable for hashing, equality and ordering. Two records
+    /// have equal keys if and only if their fields in the given columns are
+    /// equal, and comparing keys orders records by those fields, comparing
+    /// the first column first. Fields that don't exist in this record are
+    /// treated as empty.
+    ///
+    /// The key is encoded as follows, so that no two distinct sequences of
+    /// fields produce the same key: each field is followed by a `\x00`
+    /// byte, and any `\x00` or `\x01` bytes within a field are escaped as
+    /// `\x01\x01` and `\x01\x02`, respectively. All other bytes are copied
+    /// as is.
+    ///
+    /// To have a `Reader` remember the key columns, see
+    /// [`ReaderBuilder::key_columns`](struct.ReaderBuilder.html#method.key_columns).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record1 = ByteRecord::from(vec!["Boston", "MA", "4628910"]);
+    /// let record2 = ByteRecord::from(vec!["Boston", "MA", "4628911"]);
+    /// let record3 = ByteRecord::from(vec!["Boston", "GA", "4628910"]);
+    /// assert_eq!(record1.key(&[0, 1]), record2.key(&[0, 1]));
+    /// assert!(record3.key(&[0, 1]) < record1.key(&[0, 1]));
+    /// assert_eq!(record1.key(&[1]), b"MA\x00");
+    /// ```
+    pub fn key(&self, columns: &[usize]) -> Vec<u8> {
+        let mut key = vec![];
+        for &i in columns {
+            for &b in self.get(i).unwrap_or(b"") {
+                match b {
+                    b'\x00' => key.extend_from_slice(b"\x01\x01"),
+                    b'\x01' => key.extend_from_slice(b"\x01\x02"),
+                    b => key.push(b),
+                }
+            }
+            key.push(b'\x00');
+        }
+        key
+    }
+
+    /// Return a 64-bit checksum of the fields in this record.
+    ///
+    /// The checksum is computed with the 64-bit FNV-1a hash over the number
+    /// of fields, followed by the length and contents of each field. Since
+    /// field boundaries are included, records like `["ab", "c"]` and
+    /// `["a", "bc"]` get different checksums. The record's position is
+    /// ignored.
+    ///
+    /// The checksum doesn't depend on the platform or on how the record was
+    /// quoted or escaped in the CSV data, which makes it suitable for
+    /// detecting changed records between runs. It is not a cryptographic
+    /// hash.
+    ///
+    /// To have a `Reader` maintain a checksum over all of the records it
+    /// reads, see
+    /// [`ReaderBuilder::record_checksum`](struct.ReaderBuilder.html#method.record_checksum).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record1 = ByteRecord::from(vec!["ab", "c"]);
+    /// let record2 = ByteRecord::from(vec!["a", "bc"]);
+    /// assert_ne!(record1.checksum(), record2.checksum());
+    /// assert_eq!(record1.checksum(), record1.clone().checksum());
+    /// ```
+    pub fn checksum(&self) -> u64 {
+        self.update_checksum(CHECKSUM_INIT)
+    }
+
+    /// Continue the FNV-1a hash `sum` with the fields of this record.
+    pub(crate) fn update_checksum(&self, sum: u64) -> u64 {
+        fn fnv(mut sum: u64, bytes: &[u8]) -> u64 {
+            for &b in bytes {
+                sum ^= u64::from(b);
+                sum = sum.wrapping_mul(0x100000001b3);
+            }
+            sum
+        }
+
+        let mut sum = fnv(sum, &(self.len() as u64).to_le_bytes());
+        for field in self.iter() {
+            sum = fnv(sum, &(field.len() as u64).to_le_bytes());
+            sum = fnv(sum, field);
+        }
+        sum
+    }
+
     /// Returns true if and only if this record is empty.
     ///
     /// # Example
@@ -322,9 +530,94 @@ impl ByteRecord {
     pub fn truncate(&mut self, n: usize) {
         if n <= self.len() {
             self.0.bounds.len = n;
+            self.0.meta.truncate(n);
         }
     }
 
+    /// Split this record into two at the field index given.
+    ///
+    /// This returns a new record containing the fields `[at, len)`, while
+    /// this record retains the fields `[0, at)`. The new record has the
+    /// same position as this record.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `at` is greater than the number of fields in this
+    /// record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let mut record = ByteRecord::from(vec!["a", "b", "c"]);
+    /// let rest = record.split_off_fields(1);
+    /// assert_eq!(record, vec!["a"]);
+    /// assert_eq!(rest, vec!["b", "c"]);
+    /// ```
+    pub fn split_off_fields(&mut self, at: usize) -> ByteRecord {
+        assert!(
+            at <= self.len(),
+            "field index (is {}) should be <= len (is {})",
+            at,
+            self.len()
+        );
+        let start = if at == 0 { 0 } else { self.0.bounds.ends()[at - 1] };
+        let end = self.0.bounds.end();
+        let mut other =
+            ByteRecord::with_capacity(end - start, self.len() - at);
+        other.0.fields[..end - start]
+            .copy_from_slice(&self.0.fields[start..end]);
+        for &field_end in &self.0.bounds.ends()[at..] {
+            other.0.bounds.add(field_end - start);
+        }
+        other.0.pos = self.0.pos.clone();
+        self.0.meta.truncate(self.len());
+        if self.0.meta.len() > at {
+            other.0.meta = self.0.meta.split_off(at);
+        }
+        self.truncate(at);
+        other
+    }
+
+    /// Retain only the fields for which the predicate given returns `true`.
+    ///
+    /// The fields that are kept remain in their original order, along with
+    /// their metadata. This operates in place and visits each field exactly
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let mut record = ByteRecord::from(vec!["a", "", "b", ""]);
+    /// record.retain(|field| !field.is_empty());
+    /// assert_eq!(record, vec!["a", "b"]);
+    /// ```
+    pub fn retain<F: FnMut(&[u8]) -> bool>(&mut self, mut f: F) {
+        let inner = &mut *self.0;
+        let len = inner.bounds.len;
+        inner.meta.truncate(len);
+        let (mut kept, mut kept_meta, mut write, mut start) = (0, 0, 0, 0);
+        for i in 0..len {
+            let end = inner.bounds.ends[i];
+            if f(&inner.fields[start..end]) {
+                inner.fields.copy_within(start..end, write);
+                write += end - start;
+                inner.bounds.ends[kept] = write;
+                if i < inner.meta.len() {
+                    inner.meta[kept_meta] = inner.meta[i];
+                    kept_meta += 1;
+                }
+                kept += 1;
+            }
+            start = end;
+        }
+        inner.bounds.len = kept;
+        inner.meta.truncate(kept_meta);
+    }
+
     /// Clear this record so that it has zero fields.
     ///
     /// This is equivalent to calling `truncate(0)`.
@@ -376,6 +669,7 @@ impl ByteRecord {
         for field in self.iter() {
             trimmed.push_field(trim_ascii(field));
         }
+        trimmed.take_meta(self);
         *self = trimmed;
     }
 
@@ -392,6 +686,7 @@ impl ByteRecord {
     /// ```
     #[inline]
     pub fn push_field(&mut self, field: &[u8]) {
+        self.0.meta.truncate(self.len());
         let (s, e) = (self.0.bounds.end(), self.0.bounds.end() + field.len());
         while e > self.0.fields.len() {
             self.expand_fields();
@@ -400,6 +695,95 @@ impl ByteRecord {
         self.0.bounds.add(e);
     }
 
+    /// Replace all fields in this record with the fields given.
+    ///
+    /// This is equivalent to calling `clear` followed by `push_field` for
+    /// each field given. The record's existing allocation is reused, which
+    /// makes this useful for filling a single record from many sources in a
+    /// loop without allocating.
+    ///
+    /// The position of this record is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let mut record = ByteRecord::from(vec!["a", "b", "c"]);
+    /// record.replace_fields(&["x", "y"]);
+    /// assert_eq!(record, vec!["x", "y"]);
+    /// ```
+    pub fn replace_fields<I, T>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.clear();
+        for field in fields {
+            self.push_field(field.as_ref());
+        }
+    }
+
+    /// Add all fields from `other` to the end of this record.
+    ///
+    /// This is equivalent to calling `push_field` for each field in `other`,
+    /// but copies all of the field data at once.
+    ///
+    /// The position of this record is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let mut record = ByteRecord::from(vec!["a", "b"]);
+    /// record.extend_from_record(&ByteRecord::from(vec!["c", "d"]));
+    /// assert_eq!(record, vec!["a", "b", "c", "d"]);
+    /// ```
+    #[inline]
+    pub fn extend_from_record(&mut self, other: &ByteRecord) {
+        let inner = &mut *self.0;
+        inner.meta.truncate(inner.bounds.len());
+        if inner.meta.len() == inner.bounds.len() {
+            let n = cmp::min(other.0.meta.len(), other.len());
+            inner.meta.extend_from_slice(&other.0.meta[..n]);
+        }
+        let data = other.as_slice();
+        let offset = self.0.bounds.end();
+        let (s, e) = (offset, offset + data.len());
+        while e > self.0.fields.len() {
+            self.expand_fields();
+        }
+        self.0.fields[s..e].copy_from_slice(data);
+        for &end in other.0.bounds.ends() {
+            self.0.bounds.add(offset + end);
+        }
+    }
+
+    /// Copy the contents of this record into `dst`, reusing its allocation.
+    ///
+    /// This clears `dst` and then copies this record's fields, field
+    /// metadata and position into it. Unlike `clone`, no new record is
+    /// allocated, and `dst` only allocates if its existing capacity is too
+    /// small. This is useful for passing records between a producer and a
+    /// consumer in a loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["a", "b", "c"]);
+    /// let mut dst = ByteRecord::from(vec!["x", "y"]);
+    /// record.clone_into(&mut dst);
+    /// assert_eq!(dst, vec!["a", "b", "c"]);
+    /// ```
+    pub fn clone_into(&self, dst: &mut ByteRecord) {
+        dst.clear();
+        dst.extend_from_record(self);
+        dst.0.pos = self.0.pos.clone();
+    }
+
     /// Return the position of this record, if available.
     ///
     /// # Example
@@ -463,6 +847,31 @@ impl ByteRecord {
         self.0.pos = pos;
     }
 
+    /// Return a mutable reference to the position of this record, if it
+    /// has one.
+    ///
+    /// This permits adjusting a record's position in place, e.g., when
+    /// renumbering records that are being written to a new file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::{ByteRecord, Position};
+    ///
+    /// let mut record = ByteRecord::from(vec!["a", "b", "c"]);
+    /// assert!(record.position_mut().is_none());
+    ///
+    /// record.set_position(Some(Position::new()));
+    /// if let Some(pos) = record.position_mut() {
+    ///     pos.set_record(5);
+    /// }
+    /// assert_eq!(record.position().unwrap().record(), 5);
+    /// ```
+    #[inline]
+    pub fn position_mut(&mut self) -> Option<&mut Position> {
+        self.0.pos.as_mut()
+    }
+
     /// Return the start and end position of a field in this record.
     ///
     /// If no such field exists at the given index, then return `None`.
@@ -483,6 +892,48 @@ impl ByteRecord {
         self.0.bounds.get(i)
     }
 
+    /// Return metadata describing how the field at index `i` appeared in
+    /// the CSV data, such as whether it was quoted.
+    ///
+    /// This is only available for fields read by a reader with
+    /// [`ReaderBuilder::field_meta`](struct.ReaderBuilder.html#method.field_meta)
+    /// enabled. If no such field exists at the given index, or if it wasn't
+    /// read with field metadata enabled, then this returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ByteRecord, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "a,\"b\",\"say \"\"hi\"\"\"\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .field_meta(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut record = ByteRecord::new();
+    ///     assert!(rdr.read_byte_record(&mut record)?);
+    ///
+    ///     let meta = record.field_meta(0).unwrap();
+    ///     assert!(!meta.quoted());
+    ///     let meta = record.field_meta(1).unwrap();
+    ///     assert!(meta.quoted() && !meta.had_escapes());
+    ///     let meta = record.field_meta(2).unwrap();
+    ///     assert!(meta.quoted() && meta.had_escapes());
+    ///     assert_eq!(record.field_meta(3), None);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    pub fn field_meta(&self, i: usize) -> Option<FieldMeta> {
+        if i >= self.len() {
+            return None;
+        }
+        self.0.meta.get(i).copied()
+    }
+
     /// Return the entire row as a single byte slice. The slice returned stores
     /// all fields contiguously. The boundaries of each field can be determined
     /// via the `range` method.
@@ -500,6 +951,79 @@ impl ByteRecord {
         &self.0.fields[..self.0.bounds.end()]
     }
 
+    /// Return the internal representation of this record: the contiguous
+    /// bytes of all fields and the ending offset of each field.
+    ///
+    /// The first slice is the same as the one returned by `as_slice`. The
+    /// second slice has one element per field, where `ends[i]` is the end
+    /// offset (exclusive) of field `i` in the first slice. Field `i` starts
+    /// at `ends[i - 1]`, or at `0` for the first field. Consequently, the
+    /// offsets are non-decreasing and the last one is equal to the length of
+    /// the first slice.
+    ///
+    /// This is useful for consumers that want to process fields without
+    /// going through an iterator, or to hand records to columnar formats
+    /// that use the same representation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ByteRecord;
+    ///
+    /// let record = ByteRecord::from(vec!["foo", "", "quux"]);
+    /// let (fields, ends) = record.as_raw_parts();
+    /// assert_eq!(fields, &b"fooquux"[..]);
+    /// assert_eq!(ends, &[3, 3, 7][..]);
+    /// ```
+    #[inline]
+    pub fn as_raw_parts(&self) -> (&[u8], &[usize]) {
+        (self.as_slice(), self.0.bounds.ends())
+    }
+
+    /// Write this record as a single row of CSV data to `wtr`, with fields
+    /// separated by the given delimiter.
+    ///
+    /// Fields are quoted only when necessary and the record is terminated by
+    /// `\n`. Use `write_to_with` to change the quote character, quoting
+    /// style or terminator.
+    ///
+    /// This is convenient for writing a record now and then, e.g., in tests.
+    /// For writing many records, a [`Writer`](struct.Writer.html) is more
+    /// efficient, since it buffers its output and doesn't need to be
+    /// configured again for every record. Note that `wtr` isn't flushed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ByteRecord;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let record = ByteRecord::from(vec!["a", "b;c", "d"]);
+    ///     let mut buf = vec![];
+    ///     record.write_to(&mut buf, b';')?;
+    ///     assert_eq!(buf, b"a;\"b;c\";d\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_to<W: io::Write>(&self, wtr: W, delimiter: u8) -> Result<()> {
+        self.write_to_with(wtr, RecordWriteOptions::new().delimiter(delimiter))
+    }
+
+    /// Write this record as a single row of CSV data to `wtr`, using the
+    /// given options.
+    ///
+    /// See [`RecordWriteOptions`](struct.RecordWriteOptions.html) for an
+    /// example.
+    pub fn write_to_with<W: io::Write>(
+        &self,
+        wtr: W,
+        options: &RecordWriteOptions,
+    ) -> Result<()> {
+        write_record_to(wtr, self, options)
+    }
+
     /// Clone this record, but only copy `fields` up to the end of bounds. This
     /// is useful when one wants to copy a record, but not necessarily any
     /// excess capacity in that record.
@@ -508,28 +1032,48 @@ impl ByteRecord {
         let mut br = ByteRecord::new();
         br.0.pos = self.0.pos.clone();
         br.0.bounds = self.0.bounds.clone();
-        br.0.fields = self.0.fields[..self.0.bounds.end()].to_vec();
+        br.0.fields =
+            SmallBuf::from_slice(&self.0.fields[..self.0.bounds.end()]);
+        br.0.meta = self.0.meta.clone();
         br
     }
 
     /// Retrieve the underlying parts of a byte record.
     #[inline]
-    pub(crate) fn as_parts(&mut self) -> (&mut Vec<u8>, &mut Vec<usize>) {
+    pub(crate) fn as_parts(&mut self) -> (&mut [u8], &mut [usize]) {
         let inner = &mut *self.0;
         (&mut inner.fields, &mut inner.bounds.ends)
     }
 
+    /// Retrieve the underlying parts of a byte record, including the field
+    /// metadata.
+    #[inline]
+    pub(crate) fn as_parts_meta(
+        &mut self,
+    ) -> (&mut [u8], &mut [usize], &mut Vec<FieldMeta>) {
+        let inner = &mut *self.0;
+        (&mut inner.fields, &mut inner.bounds.ends, &mut inner.meta)
+    }
+
+    /// Move the field metadata of `other` into this record. This is useful
+    /// when a record is rebuilt field by field.
+    #[inline]
+    pub(crate) fn take_meta(&mut self, other: &mut ByteRecord) {
+        self.0.meta = mem::take(&mut other.0.meta);
+    }
+
     /// Set the number of fields in the given record record.
     #[inline]
     pub(crate) fn set_len(&mut self, len: usize) {
         self.0.bounds.len = len;
+        self.0.meta.truncate(len);
     }
 
     /// Expand the capacity for storing fields.
     #[inline]
     pub(crate) fn expand_fields(&mut self) {
         let new_len = self.0.fields.len().checked_mul(2).unwrap();
-        self.0.fields.resize(cmp::max(4, new_len), 0);
+        self.0.fields.grow(cmp::max(4, new_len));
     }
 
     /// Expand the capacity for storing field ending positions.
@@ -587,6 +1131,12 @@ impl ByteRecord {
 /// Byte offsets and record indices start at `0`. Line numbers start at `1`.
 ///
 /// A CSV reader will automatically assign the position of each record.
+///
+/// Positions implement Serde's `Serialize` and `Deserialize` as a struct
+/// with `byte`, `line` and `record` fields. For a compact binary encoding
+/// that is stable across versions, use
+/// [`to_bytes`](struct.Position.html#method.to_bytes) and
+/// [`from_bytes`](struct.Position.html#method.from_bytes).
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Position {
     byte: u64,
@@ -640,13 +1190,212 @@ impl Position {
         self.record = record;
         self
     }
+
+    /// Encode this position as 24 bytes.
+    ///
+    /// The byte offset, line number and record index are written, in that
+    /// order, as big-endian 64-bit integers. This is the same encoding that
+    /// `csv-index` uses for the offsets it stores, and it is guaranteed not
+    /// to change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::Position;
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_byte(3).set_line(2).set_record(1);
+    /// let bytes = pos.to_bytes();
+    /// assert_eq!(&bytes[..8], &[0, 0, 0, 0, 0, 0, 0, 3]);
+    /// assert_eq!(Position::from_bytes(bytes), pos);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 24] {
+        let mut bytes = [0; 24];
+        bytes[..8].copy_from_slice(&self.byte.to_be_bytes());
+        bytes[8..16].copy_from_slice(&self.line.to_be_bytes());
+        bytes[16..].copy_from_slice(&self.record.to_be_bytes());
+        bytes
+    }
+
+    /// Decode a position previously encoded with
+    /// [`to_bytes`](struct.Position.html#method.to_bytes).
+    pub fn from_bytes(bytes: [u8; 24]) -> Position {
+        let read = |i: usize| {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(&bytes[i..i + 8]);
+            u64::from_be_bytes(buf)
+        };
+        Position { byte: read(0), line: read(8), record: read(16) }
+    }
+}
+
+const POSITION_FIELDS: &[&str] = &["byte", "line", "record"];
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> result::Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Position", 3)?;
+        s.serialize_field("byte", &self.byte)?;
+        s.serialize_field("line", &self.line)?;
+        s.serialize_field("record", &self.record)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> result::Result<Position, D::Error> {
+        deserializer.deserialize_struct(
+            "Position",
+            POSITION_FIELDS,
+            PositionVisitor,
+        )
+    }
+}
+
+/// A visitor that accepts a position as either a sequence or a map.
+struct PositionVisitor;
+
+impl<'de> Visitor<'de> for PositionVisitor {
+    type Value = Position;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a CSV position")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> result::Result<Position, A::Error> {
+        let mut next = |i: usize| -> result::Result<u64, A::Error> {
+            seq.next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))
+        };
+        Ok(Position { byte: next(0)?, line: next(1)?, record: next(2)? })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> result::Result<Position, A::Error> {
+        let mut fields = [None; 3];
+        while let Some(key) = map.next_key::<String>()? {
+            let i = match POSITION_FIELDS.iter().position(|&f| f == key) {
+                Some(i) => i,
+                None => {
+                    return Err(de::Error::unknown_field(
+                        &key,
+                        POSITION_FIELDS,
+                    ))
+                }
+            };
+            if fields[i].is_some() {
+                return Err(de::Error::duplicate_field(POSITION_FIELDS[i]));
+            }
+            fields[i] = Some(map.next_value()?);
+        }
+        let mut get = |i: usize| {
+            fields[i]
+                .take()
+                .ok_or_else(|| de::Error::missing_field(POSITION_FIELDS[i]))
+        };
+        Ok(Position { byte: get(0)?, line: get(1)?, record: get(2)? })
+    }
+}
+
+/// The initial value of an FNV-1a checksum.
+pub(crate) const CHECKSUM_INIT: u64 = 0xcbf29ce484222325;
+
+/// The number of bytes of field data a record stores without allocating.
+const INLINE_BYTES: usize = 32;
+
+/// The number of fields a record stores the bounds of without allocating.
+const INLINE_FIELDS: usize = 8;
+
+/// A zero initialized buffer that stores up to `N` elements inline and only
+/// allocates once it needs to grow beyond that.
+///
+/// Records with a handful of short fields are very common, and storing them
+/// inline means that creating or cloning such a record needs one allocation
+/// instead of three. The length of the buffer is its capacity, just like the
+/// `Vec`s it replaces, so an inline buffer always has length `N`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum SmallBuf<T, const N: usize> {
+    Inline([T; N]),
+    Heap(Vec<T>),
+}
+
+impl<T: Copy + Default, const N: usize> SmallBuf<T, N> {
+    /// Create a buffer with room for at least `len` elements.
+    #[inline]
+    fn with_len(len: usize) -> SmallBuf<T, N> {
+        if len <= N {
+            SmallBuf::Inline([T::default(); N])
+        } else {
+            SmallBuf::Heap(vec![T::default(); len])
+        }
+    }
+
+    /// Create a buffer whose first elements are a copy of `slice`.
+    #[inline]
+    fn from_slice(slice: &[T]) -> SmallBuf<T, N> {
+        if slice.len() <= N {
+            let mut buf = [T::default(); N];
+            buf[..slice.len()].copy_from_slice(slice);
+            SmallBuf::Inline(buf)
+        } else {
+            SmallBuf::Heap(slice.to_vec())
+        }
+    }
+
+    /// Grow this buffer to `len` elements, moving it to the heap if
+    /// necessary. This is a no-op if the buffer is already long enough.
+    #[inline]
+    fn grow(&mut self, len: usize) {
+        if len <= self.len() {
+            return;
+        }
+        match *self {
+            SmallBuf::Inline(ref buf) => {
+                let mut heap = vec![T::default(); len];
+                heap[..N].copy_from_slice(buf);
+                *self = SmallBuf::Heap(heap);
+            }
+            SmallBuf::Heap(ref mut heap) => heap.resize(len, T::default()),
+        }
+    }
+}
+
+impl<T, const N: usize> ops::Deref for SmallBuf<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match *self {
+            SmallBuf::Inline(ref buf) => buf,
+            SmallBuf::Heap(ref heap) => heap,
+        }
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for SmallBuf<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match *self {
+            SmallBuf::Inline(ref mut buf) => buf,
+            SmallBuf::Heap(ref mut heap) => heap,
+        }
+    }
 }
 
 /// The bounds of fields in a single record.
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct Bounds {
     /// The ending index of each field.
-    ends: Vec<usize>,
+    ends: SmallBuf<usize, INLINE_FIELDS>,
     /// The number of fields in this record.
     ///
     /// Technically, we could drop this field and maintain an invariant that
@@ -667,7 +1416,7 @@ impl Bounds {
     /// ends of fields.
     #[inline]
     fn with_capacity(capacity: usize) -> Bounds {
-        Bounds { ends: vec![0; capacity], len: 0 }
+        Bounds { ends: SmallBuf::with_len(capacity), len: 0 }
     }
 
     /// Returns the bounds of field `i`.
@@ -711,7 +1460,7 @@ impl Bounds {
     #[inline]
     fn expand(&mut self) {
         let new_len = self.ends.len().checked_mul(2).unwrap();
-        self.ends.resize(cmp::max(4, new_len), 0);
+        self.ends.grow(cmp::max(4, new_len));
     }
 
     /// Add a new field with the given ending position.
@@ -855,7 +1604,7 @@ impl<'r> DoubleEndedIterator for ByteRecordIter<'r> {
     }
 }
 
-fn trim_ascii(bytes: &[u8]) -> &[u8] {
+pub(crate) fn trim_ascii(bytes: &[u8]) -> &[u8] {
     trim_ascii_start(trim_ascii_end(bytes))
 }
 
@@ -885,12 +1634,38 @@ fn trim_ascii_end(mut bytes: &[u8]) -> &[u8] {
 mod tests {
     use crate::string_record::StringRecord;
 
-    use super::ByteRecord;
+    use super::{ByteRecord, Position, SmallBuf, INLINE_BYTES, INLINE_FIELDS};
 
     fn b(s: &str) -> &[u8] {
         s.as_bytes()
     }
 
+    #[test]
+    fn search_fields() {
+        let rec = ByteRecord::from(vec!["foo", "", "bar", "foo"]);
+        assert!(rec.contains_field(b"foo"));
+        assert!(rec.contains_field(b""));
+        assert!(!rec.contains_field(b"fo"));
+        assert!(!rec.contains_field(b"foobar"));
+        assert_eq!(rec.position_of_field(b"foo"), Some(0));
+        assert_eq!(rec.position_of_field(b""), Some(1));
+        assert_eq!(rec.position_of_field(b"bar"), Some(2));
+        assert_eq!(rec.position_of_field(b"baz"), None);
+
+        assert!(rec.contains_substring(b"oo"));
+        assert!(rec.contains_substring(b"bar"));
+        assert!(rec.contains_substring(b""));
+        // Matches don't span fields, even though the fields are stored
+        // contiguously.
+        assert!(!rec.contains_substring(b"barfoo"));
+        assert!(!rec.contains_substring(b"oob"));
+
+        let empty = ByteRecord::new();
+        assert!(!empty.contains_field(b""));
+        assert!(!empty.contains_substring(b""));
+        assert_eq!(empty.position_of_field(b""), None);
+    }
+
     #[test]
     fn record_1() {
         let mut rec = ByteRecord::new();
@@ -915,6 +1690,83 @@ mod tests {
         assert_eq!(rec.get(3), None);
     }
 
+    #[test]
+    fn split_off_fields() {
+        let mut rec = ByteRecord::from(vec!["foo", "", "quux", "z"]);
+        let mut pos = Position::new();
+        pos.set_byte(5).set_line(2).set_record(1);
+        rec.set_position(Some(pos.clone()));
+
+        let rest = rec.split_off_fields(2);
+        assert_eq!(rec, vec!["foo", ""]);
+        assert_eq!(rest, vec!["quux", "z"]);
+        assert_eq!(rec.range(1), Some(3..3));
+        assert_eq!(rec.range(2), None);
+        assert_eq!(rest.range(0), Some(0..4));
+        assert_eq!(rest.range(1), Some(4..5));
+        assert_eq!(rest.as_slice(), b("quuxz"));
+        assert_eq!(rest.position(), Some(&pos));
+
+        // The retained half can still grow.
+        rec.push_field(b"bar");
+        assert_eq!(rec, vec!["foo", "", "bar"]);
+    }
+
+    #[test]
+    fn split_off_fields_ends() {
+        let mut rec = ByteRecord::from(vec!["a", "b"]);
+        let rest = rec.split_off_fields(2);
+        assert_eq!(rec, vec!["a", "b"]);
+        assert!(rest.is_empty());
+
+        let rest = rec.split_off_fields(0);
+        assert!(rec.is_empty());
+        assert_eq!(rest, vec!["a", "b"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_fields_out_of_bounds() {
+        ByteRecord::from(vec!["a"]).split_off_fields(2);
+    }
+
+    #[test]
+    fn retain() {
+        let retain = |fields: Vec<&str>, drop: &[&str]| {
+            let mut rec = ByteRecord::from(fields);
+            let mut pos = Position::new();
+            pos.set_byte(5).set_line(2).set_record(1);
+            rec.set_position(Some(pos.clone()));
+            rec.retain(|field| !drop.iter().any(|d| d.as_bytes() == field));
+            assert_eq!(rec.position(), Some(&pos));
+            rec
+        };
+
+        let rec = retain(vec!["foo", "bar", "quux"], &["foo"]);
+        assert_eq!(rec, vec!["bar", "quux"]);
+        assert_eq!(rec.as_slice(), b("barquux"));
+        assert_eq!(rec.range(1), Some(3..7));
+
+        let rec = retain(vec!["foo", "bar", "quux"], &["bar"]);
+        assert_eq!(rec, vec!["foo", "quux"]);
+        assert_eq!(rec.as_slice(), b("fooquux"));
+
+        let rec = retain(vec!["foo", "bar", "quux"], &["quux"]);
+        assert_eq!(rec, vec!["foo", "bar"]);
+        assert_eq!(rec.as_slice(), b("foobar"));
+
+        let rec = retain(vec!["foo", "bar", "quux"], &["foo", "bar", "quux"]);
+        assert!(rec.is_empty());
+        assert_eq!(rec.as_slice(), b(""));
+
+        let mut rec = retain(vec!["", "a", "", "", "b", ""], &[""]);
+        assert_eq!(rec, vec!["a", "b"]);
+
+        // The record can still grow.
+        rec.push_field(b"c");
+        assert_eq!(rec, vec!["a", "b", "c"]);
+    }
+
     #[test]
     fn empty_record() {
         let rec = ByteRecord::new();
@@ -1188,4 +2040,203 @@ mod tests {
         let test2 = ByteRecord::from(vec!["12", "34"]);
         assert_ne!(test1, test2);
     }
+
+    #[test]
+    fn extend_from_record() {
+        let mut rec = ByteRecord::from(vec!["foo", "", "bar"]);
+        rec.extend_from_record(&ByteRecord::from(vec!["baz", "", "quux"]));
+
+        assert_eq!(rec.len(), 6);
+        let fields: Vec<&[u8]> = rec.iter().collect();
+        assert_eq!(
+            fields,
+            vec![b("foo"), b(""), b("bar"), b("baz"), b(""), b("quux")]
+        );
+        assert_eq!(rec.as_slice(), b("foobarbazquux"));
+    }
+
+    #[test]
+    fn replace_fields() {
+        let mut rec = ByteRecord::from(vec!["foo", "bar", "baz", "quux"]);
+        let (nfields, nends) = (rec.0.fields.len(), rec.0.bounds.ends.len());
+
+        rec.replace_fields(vec!["a", "", "b"]);
+        assert_eq!(rec, vec!["a", "", "b"]);
+        assert_eq!(rec.0.fields.len(), nfields);
+        assert_eq!(rec.0.bounds.ends.len(), nends);
+
+        rec.replace_fields(Vec::<&[u8]>::new());
+        assert!(rec.is_empty());
+        assert_eq!(rec.0.fields.len(), nfields);
+    }
+
+    #[test]
+    fn small_records_spill_to_heap() {
+        let mut rec = ByteRecord::from(vec!["a", "b", "c"]);
+        assert!(matches!(rec.0.fields, SmallBuf::Inline(_)));
+        assert!(matches!(rec.0.bounds.ends, SmallBuf::Inline(_)));
+
+        let long = "x".repeat(INLINE_BYTES * 3);
+        for _ in 0..INLINE_FIELDS {
+            rec.push_field(long.as_bytes());
+        }
+        assert!(matches!(rec.0.fields, SmallBuf::Heap(_)));
+        assert!(matches!(rec.0.bounds.ends, SmallBuf::Heap(_)));
+        assert_eq!(rec.len(), 3 + INLINE_FIELDS);
+        let fields: Vec<&[u8]> = rec.iter().collect();
+        assert_eq!(&fields[..3], &[b("a"), b("b"), b("c")][..]);
+        assert!(fields[3..].iter().all(|&f| f == long.as_bytes()));
+
+        let cloned = rec.clone_truncated();
+        assert_eq!(cloned, rec);
+        rec.truncate(2);
+        let cloned = rec.clone_truncated();
+        assert!(matches!(cloned.0.fields, SmallBuf::Inline(_)));
+        assert_eq!(cloned, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn clone_into() {
+        let mut rec = ByteRecord::from(vec!["foo", "", "bar"]);
+        let mut pos = Position::new();
+        pos.set_byte(5).set_line(2).set_record(1);
+        rec.set_position(Some(pos.clone()));
+
+        let mut dst = ByteRecord::with_capacity(64, 8);
+        dst.push_field(b"quux");
+        dst.push_field(b"baz");
+        let (fields_ptr, ends_ptr) =
+            (dst.0.fields.as_ptr(), dst.0.bounds.ends.as_ptr());
+        rec.clone_into(&mut dst);
+        assert_eq!(dst, rec);
+        assert_eq!(dst.position(), Some(&pos));
+        assert_eq!(dst.0.fields.as_ptr(), fields_ptr);
+        assert_eq!(dst.0.bounds.ends.as_ptr(), ends_ptr);
+
+        ByteRecord::new().clone_into(&mut dst);
+        assert!(dst.is_empty());
+        assert_eq!(dst.position(), None);
+    }
+
+    #[test]
+    fn extend_from_record_empty() {
+        let mut rec = ByteRecord::new();
+        rec.extend_from_record(&ByteRecord::from(vec!["a", "b"]));
+        assert_eq!(rec, vec!["a", "b"]);
+
+        rec.extend_from_record(&ByteRecord::new());
+        assert_eq!(rec, vec!["a", "b"]);
+
+        let mut rec = ByteRecord::from(vec!["a"]);
+        rec.extend_from_record(&ByteRecord::from(vec![""]));
+        assert_eq!(rec, vec!["a", ""]);
+    }
+
+    #[test]
+    fn as_raw_parts() {
+        let mut rec = ByteRecord::with_capacity(64, 8);
+        rec.push_field(b"ab");
+        rec.push_field(b"");
+        rec.push_field(b"cde");
+        assert_eq!(rec.as_raw_parts(), (&b"abcde"[..], &[2, 2, 5][..]));
+
+        rec.truncate(1);
+        assert_eq!(rec.as_raw_parts(), (&b"ab"[..], &[2][..]));
+
+        rec.clear();
+        assert_eq!(rec.as_raw_parts(), (&b""[..], &[][..]));
+    }
+
+    #[test]
+    fn key_no_collisions() {
+        let key = |fields: Vec<&[u8]>| {
+            let rec = ByteRecord::from(fields);
+            rec.key(&[0, 1])
+        };
+        let keys = [
+            key(vec![b"a\x00", b"b"]),
+            key(vec![b"a", b"\x00b"]),
+            key(vec![b"a\x01", b"b"]),
+            key(vec![b"a", b"\x01b"]),
+            key(vec![b"ab", b""]),
+            key(vec![b"a", b"b"]),
+        ];
+        for (i, k1) in keys.iter().enumerate() {
+            for k2 in &keys[i + 1..] {
+                assert_ne!(k1, k2);
+            }
+        }
+        // Missing fields are treated as empty.
+        assert_eq!(key(vec![b"a"]), key(vec![b"a", b""]));
+    }
+
+    #[test]
+    fn key_ordering() {
+        let key = |fields: Vec<&[u8]>| {
+            let rec = ByteRecord::from(fields);
+            rec.key(&[1, 0])
+        };
+        assert!(key(vec![b"z", b"a"]) < key(vec![b"a", b"b"]));
+        assert!(key(vec![b"a", b"b"]) < key(vec![b"a", b"b\x00"]));
+        assert!(key(vec![b"a", b"b\x00"]) < key(vec![b"a", b"b\x01"]));
+        assert!(key(vec![b"a", b"b\x01"]) < key(vec![b"a", b"b\x02"]));
+        assert!(key(vec![b"b", b""]) < key(vec![b"a", b"\x00"]));
+    }
+
+    #[test]
+    fn checksum() {
+        // The checksum must be stable across runs and versions.
+        let rec = ByteRecord::from(vec!["a", "b"]);
+        assert_eq!(rec.checksum(), 0xfa5ee0e55f1e4e92);
+        let sum = |fields: Vec<&[u8]>| ByteRecord::from(fields).checksum();
+        assert_eq!(sum(vec![b"a", b"b"]), sum(vec![b"a", b"b"]));
+        assert_ne!(sum(vec![b"a", b"b"]), sum(vec![b"b", b"a"]));
+        assert_ne!(sum(vec![b"ab", b""]), sum(vec![b"a", b"b"]));
+        assert_ne!(sum(vec![b""]), sum(vec![]));
+        assert_ne!(sum(vec![b"", b""]), sum(vec![b""]));
+    }
+
+    #[test]
+    fn position_bytes_roundtrip() {
+        let mut pos = Position::new();
+        pos.set_byte(u64::MAX).set_line(1 << 40).set_record(7);
+        let bytes = pos.to_bytes();
+        assert_eq!(&bytes[..8], &[0xFF; 8]);
+        assert_eq!(&bytes[8..16], &[0, 0, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(&bytes[16..], &[0, 0, 0, 0, 0, 0, 0, 7]);
+        assert_eq!(Position::from_bytes(bytes), pos);
+        assert_eq!(
+            Position::from_bytes(Position::new().to_bytes()),
+            Position::new()
+        );
+    }
+
+    #[test]
+    fn position_serde_roundtrip() {
+        let mut pos = Position::new();
+        pos.set_byte(10).set_line(3).set_record(2);
+
+        let mut wtr = crate::Writer::from_writer(vec![]);
+        wtr.serialize(&pos).unwrap();
+        let data = wtr.into_inner().unwrap();
+        assert_eq!(data, b"byte,line,record\n10,3,2\n");
+
+        let mut rdr = crate::Reader::from_reader(&*data);
+        let got: Position = rdr.deserialize().next().unwrap().unwrap();
+        assert_eq!(got, pos);
+
+        // Without headers, positions are deserialized from a sequence.
+        let mut rdr = crate::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(&b"10,3,2\n"[..]);
+        let got: Position = rdr.deserialize().next().unwrap().unwrap();
+        assert_eq!(got, pos);
+    }
+
+    #[test]
+    fn position_serde_missing_field() {
+        let mut rdr = crate::Reader::from_reader(&b"byte,line\n10,3\n"[..]);
+        let err = rdr.deserialize::<Position>().next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("missing field `record`"), "{}", err);
+    }
 }
@@ -1,7 +1,7 @@
-use std::{error::Error as StdError, fmt, iter, num, str};
+use std::{borrow::Cow, error::Error as StdError, fmt, iter, num, str};
 
 use serde::{
-    de::value::BorrowedBytesDeserializer,
+    de::value::{BorrowedBytesDeserializer, BorrowedStrDeserializer},
     de::{
         Deserialize, DeserializeSeed, Deserializer, EnumAccess,
         Error as SerdeError, IntoDeserializer, MapAccess, SeqAccess,
@@ -14,20 +14,67 @@ use crate::{
     byte_record::{ByteRecord, ByteRecordIter},
     error::{Error, ErrorKind},
     string_record::{StringRecord, StringRecordIter},
+    ColumnType, Normalization,
 };
 
 use self::DeserializeErrorKind as DEK;
 
+/// Options that control how records are deserialized.
+#[derive(Clone, Copy, Debug)]
+pub struct DeserializeOptions {
+    /// Whether the types of fields are inferred at all when the target type
+    /// doesn't say what it wants. When disabled, such fields are always
+    /// strings (or bytes, if they aren't valid UTF-8).
+    pub infer_types: bool,
+    /// Whether fields may be inferred as floating point numbers when the
+    /// target type doesn't say what it wants (e.g., via `deserialize_any`).
+    pub infer_floats: bool,
+    /// The normalization applied to header names before they are matched
+    /// to struct fields or used as map keys.
+    pub header_normalization: Normalization,
+    /// Whether integers with a `0x`, `0o` or `0b` prefix are parsed in the
+    /// corresponding radix. (A lowercase `0x` prefix is always recognized.)
+    pub integer_radix_detection: bool,
+    /// Whether a record with fewer fields than the header row is
+    /// deserialized as if its missing trailing fields were empty.
+    pub missing_fields_as_empty: bool,
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> DeserializeOptions {
+        DeserializeOptions {
+            infer_types: true,
+            infer_floats: true,
+            header_normalization: Normalization::None,
+            integer_radix_detection: false,
+            missing_fields_as_empty: false,
+        }
+    }
+}
+
 pub fn deserialize_string_record<'de, D: Deserialize<'de>>(
     record: &'de StringRecord,
     headers: Option<&'de StringRecord>,
+) -> Result<D, Error> {
+    deserialize_string_record_with(record, headers, Default::default())
+}
+
+/// Like `deserialize_string_record`, but with the given options.
+pub fn deserialize_string_record_with<'de, D: Deserialize<'de>>(
+    record: &'de StringRecord,
+    headers: Option<&'de StringRecord>,
+    options: DeserializeOptions,
 ) -> Result<D, Error> {
     let mut deser = DeRecordWrap(DeStringRecord {
         it: record.iter().peekable(),
         headers: headers.map(|r| r.iter()),
         field: 0,
+        options,
     });
-    D::deserialize(&mut deser).map_err(|err| {
+    D::deserialize(&mut deser).map_err(|mut err| {
+        if let (Some(field), Some(headers)) = (err.field, headers) {
+            err.column = headers.get(field as usize).map(String::from);
+        }
         Error::new(ErrorKind::Deserialize {
             pos: record.position().map(Clone::clone),
             err,
@@ -43,8 +90,15 @@ pub fn deserialize_byte_record<'de, D: Deserialize<'de>>(
         it: record.iter().peekable(),
         headers: headers.map(|r| r.iter()),
         field: 0,
+        options: Default::default(),
     });
-    D::deserialize(&mut deser).map_err(|err| {
+    D::deserialize(&mut deser).map_err(|mut err| {
+        if let (Some(field), Some(headers)) = (err.field, headers) {
+            err.column = headers
+                .get(field as usize)
+                .and_then(|name| str::from_utf8(name).ok())
+                .map(String::from);
+        }
         Error::new(ErrorKind::Deserialize {
             pos: record.position().map(Clone::clone),
             err,
@@ -72,6 +126,12 @@ trait DeRecord<'r> {
     /// Returns true if and only if this deserialize has access to headers.
     fn has_headers(&self) -> bool;
 
+    /// Returns the normalization to apply to header names.
+    fn header_normalization(&self) -> Normalization;
+
+    /// Returns true if integers may use a radix prefix like `0b`.
+    fn integer_radix_detection(&self) -> bool;
+
     /// Extracts the next string header value from the underlying record.
     fn next_header(&mut self) -> Result<Option<&'r str>, DeserializeError>;
 
@@ -80,6 +140,14 @@ trait DeRecord<'r> {
         &mut self,
     ) -> Result<Option<&'r [u8]>, DeserializeError>;
 
+    /// Extracts the next header value from the underlying record as a string
+    /// if it is valid UTF-8, or as raw bytes otherwise.
+    ///
+    /// Unlike `next_header`, invalid UTF-8 is not an error.
+    fn next_header_str_or_bytes(
+        &mut self,
+    ) -> Result<Option<Result<&'r str, &'r [u8]>>, DeserializeError>;
+
     /// Extracts the next string field from the underlying record.
     fn next_field(&mut self) -> Result<&'r str, DeserializeError>;
 
@@ -107,6 +175,16 @@ impl<'r, T: DeRecord<'r>> DeRecord<'r> for DeRecordWrap<T> {
         self.0.has_headers()
     }
 
+    #[inline]
+    fn header_normalization(&self) -> Normalization {
+        self.0.header_normalization()
+    }
+
+    #[inline]
+    fn integer_radix_detection(&self) -> bool {
+        self.0.integer_radix_detection()
+    }
+
     #[inline]
     fn next_header(&mut self) -> Result<Option<&'r str>, DeserializeError> {
         self.0.next_header()
@@ -119,6 +197,13 @@ impl<'r, T: DeRecord<'r>> DeRecord<'r> for DeRecordWrap<T> {
         self.0.next_header_bytes()
     }
 
+    #[inline]
+    fn next_header_str_or_bytes(
+        &mut self,
+    ) -> Result<Option<Result<&'r str, &'r [u8]>>, DeserializeError> {
+        self.0.next_header_str_or_bytes()
+    }
+
     #[inline]
     fn next_field(&mut self) -> Result<&'r str, DeserializeError> {
         self.0.next_field()
@@ -152,6 +237,15 @@ struct DeStringRecord<'r> {
     it: iter::Peekable<StringRecordIter<'r>>,
     headers: Option<StringRecordIter<'r>>,
     field: u64,
+    options: DeserializeOptions,
+}
+
+impl<'r> DeStringRecord<'r> {
+    /// Whether a field past the end of the record is read as empty.
+    #[inline]
+    fn pad_missing_fields(&self) -> bool {
+        self.options.missing_fields_as_empty && self.headers.is_some()
+    }
 }
 
 impl<'r> DeRecord<'r> for DeStringRecord<'r> {
@@ -160,6 +254,16 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
         self.headers.is_some()
     }
 
+    #[inline]
+    fn header_normalization(&self) -> Normalization {
+        self.options.header_normalization
+    }
+
+    #[inline]
+    fn integer_radix_detection(&self) -> bool {
+        self.options.integer_radix_detection
+    }
+
     #[inline]
     fn next_header(&mut self) -> Result<Option<&'r str>, DeserializeError> {
         Ok(self.headers.as_mut().and_then(|it| it.next()))
@@ -172,6 +276,14 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
         Ok(self.next_header()?.map(|s| s.as_bytes()))
     }
 
+    #[inline]
+    fn next_header_str_or_bytes(
+        &mut self,
+    ) -> Result<Option<Result<&'r str, &'r [u8]>>, DeserializeError> {
+        // String headers are already known to be valid UTF-8.
+        Ok(self.next_header()?.map(Ok))
+    }
+
     #[inline]
     fn next_field(&mut self) -> Result<&'r str, DeserializeError> {
         match self.it.next() {
@@ -179,8 +291,13 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
                 self.field += 1;
                 Ok(field)
             }
+            None if self.pad_missing_fields() => {
+                self.field += 1;
+                Ok("")
+            }
             None => Err(DeserializeError {
                 field: None,
+                column: None,
                 kind: DEK::UnexpectedEndOfRow,
             }),
         }
@@ -197,7 +314,11 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
     }
 
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
-        DeserializeError { field: Some(self.field.saturating_sub(1)), kind }
+        DeserializeError {
+            field: Some(self.field.saturating_sub(1)),
+            column: None,
+            kind,
+        }
     }
 
     fn infer_deserialize<'de, V: Visitor<'de>>(
@@ -205,6 +326,9 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
         visitor: V,
     ) -> Result<V::Value, DeserializeError> {
         let x = self.next_field()?;
+        if !self.options.infer_types {
+            return visitor.visit_str(x);
+        }
         if x == "true" {
             return visitor.visit_bool(true);
         } else if x == "false" {
@@ -214,14 +338,14 @@ impl<'r> DeRecord<'r> for DeStringRecord<'r> {
         } else if let Some(n) = try_negative_integer64(x) {
             return visitor.visit_i64(n);
         }
-        serde_if_integer128! {
-            if let Some(n) = try_positive_integer128(x) {
-                return visitor.visit_u128(n);
-            } else if let Some(n) = try_negative_integer128(x) {
-                return visitor.visit_i128(n);
-            }
-        }
-        if let Some(n) = try_float(x) {
+        // An integer too big for a 64-bit integer is kept as a string rather
+        // than being rounded to the nearest float. It isn't inferred as a
+        // 128-bit integer either, since Serde can't buffer those for untagged
+        // enums and flattened fields, which would make them fail to
+        // deserialize even into a string.
+        if let Some(n) = try_float(x)
+            .filter(|_| self.options.infer_floats && !is_integer(x.as_bytes()))
+        {
             visitor.visit_f64(n)
         } else {
             visitor.visit_str(x)
@@ -233,6 +357,15 @@ struct DeByteRecord<'r> {
     it: iter::Peekable<ByteRecordIter<'r>>,
     headers: Option<ByteRecordIter<'r>>,
     field: u64,
+    options: DeserializeOptions,
+}
+
+impl<'r> DeByteRecord<'r> {
+    /// Whether a field past the end of the record is read as empty.
+    #[inline]
+    fn pad_missing_fields(&self) -> bool {
+        self.options.missing_fields_as_empty && self.headers.is_some()
+    }
 }
 
 impl<'r> DeRecord<'r> for DeByteRecord<'r> {
@@ -241,6 +374,16 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
         self.headers.is_some()
     }
 
+    #[inline]
+    fn header_normalization(&self) -> Normalization {
+        self.options.header_normalization
+    }
+
+    #[inline]
+    fn integer_radix_detection(&self) -> bool {
+        self.options.integer_radix_detection
+    }
+
     #[inline]
     fn next_header(&mut self) -> Result<Option<&'r str>, DeserializeError> {
         match self.next_header_bytes() {
@@ -260,6 +403,15 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
         Ok(self.headers.as_mut().and_then(|it| it.next()))
     }
 
+    #[inline]
+    fn next_header_str_or_bytes(
+        &mut self,
+    ) -> Result<Option<Result<&'r str, &'r [u8]>>, DeserializeError> {
+        Ok(self
+            .next_header_bytes()?
+            .map(|field| str::from_utf8(field).map_err(|_| field)))
+    }
+
     #[inline]
     fn next_field(&mut self) -> Result<&'r str, DeserializeError> {
         self.next_field_bytes().and_then(|field| {
@@ -275,8 +427,13 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
                 self.field += 1;
                 Ok(field)
             }
+            None if self.pad_missing_fields() => {
+                self.field += 1;
+                Ok(b"")
+            }
             None => Err(DeserializeError {
                 field: None,
+                column: None,
                 kind: DEK::UnexpectedEndOfRow,
             }),
         }
@@ -288,7 +445,11 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
     }
 
     fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
-        DeserializeError { field: Some(self.field.saturating_sub(1)), kind }
+        DeserializeError {
+            field: Some(self.field.saturating_sub(1)),
+            column: None,
+            kind,
+        }
     }
 
     fn infer_deserialize<'de, V: Visitor<'de>>(
@@ -296,6 +457,12 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
         visitor: V,
     ) -> Result<V::Value, DeserializeError> {
         let x = self.next_field_bytes()?;
+        if !self.options.infer_types {
+            return match str::from_utf8(x) {
+                Ok(s) => visitor.visit_str(s),
+                Err(_) => visitor.visit_bytes(x),
+            };
+        }
         if x == b"true" {
             return visitor.visit_bool(true);
         } else if x == b"false" {
@@ -305,14 +472,10 @@ impl<'r> DeRecord<'r> for DeByteRecord<'r> {
         } else if let Some(n) = try_negative_integer64_bytes(x) {
             return visitor.visit_i64(n);
         }
-        serde_if_integer128! {
-            if let Some(n) = try_positive_integer128_bytes(x) {
-                return visitor.visit_u128(n);
-            } else if let Some(n) = try_negative_integer128_bytes(x) {
-                return visitor.visit_i128(n);
-            }
-        }
-        if let Some(n) = try_float_bytes(x) {
+        // See the comment in `DeStringRecord::infer_deserialize`.
+        if let Some(n) = try_float_bytes(x)
+            .filter(|_| self.options.infer_floats && !is_integer(x))
+        {
             visitor.visit_f64(n)
         } else if let Ok(s) = str::from_utf8(x) {
             visitor.visit_str(s)
@@ -329,10 +492,19 @@ macro_rules! deserialize_int {
             visitor: V,
         ) -> Result<V::Value, Self::Error> {
             let field = self.next_field()?;
-            let num = if field.starts_with("0x") {
-                <$inttype>::from_str_radix(&field[2..], 16)
+            let radix = if self.integer_radix_detection() {
+                split_radix(field)
             } else {
-                field.parse()
+                None
+            };
+            let num = match radix {
+                Some((digits, radix)) => {
+                    <$inttype>::from_str_radix(&digits, radix)
+                }
+                None if field.starts_with("0x") => {
+                    <$inttype>::from_str_radix(&field[2..], 16)
+                }
+                None => field.parse(),
             };
             visitor.$visit(num.map_err(|err| self.error(DEK::ParseInt(err)))?)
         }
@@ -489,19 +661,19 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> Deserializer<'de>
 
     fn deserialize_tuple<V: Visitor<'de>>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        visitor.visit_seq(TupleAccess { de: self, len })
     }
 
     fn deserialize_tuple_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(self)
+        visitor.visit_seq(TupleAccess { de: self, len })
     }
 
     fn deserialize_map<V: Visitor<'de>>(
@@ -518,11 +690,11 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> Deserializer<'de>
     fn deserialize_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
         if !self.has_headers() {
-            visitor.visit_seq(self)
+            visitor.visit_seq(TupleAccess { de: self, len: fields.len() })
         } else {
             visitor.visit_map(self)
         }
@@ -582,10 +754,15 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> VariantAccess<'de>
 
     fn newtype_variant_seed<U: DeserializeSeed<'de>>(
         self,
-        _seed: U,
+        seed: U,
     ) -> Result<U::Value, Self::Error> {
-        let unexp = Unexpected::UnitVariant;
-        Err(DeserializeError::invalid_type(unexp, &"newtype variant"))
+        // The payload is in the field following the variant name. Its
+        // column has a header of its own, which is skipped so that headers
+        // and fields stay aligned when deserializing by name.
+        if self.has_headers() {
+            self.next_header_bytes()?;
+        }
+        seed.deserialize(self)
     }
 
     fn tuple_variant<V: Visitor<'de>>(
@@ -624,6 +801,46 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> SeqAccess<'de>
     }
 }
 
+/// Sequence access for types with a fixed number of elements, such as tuples
+/// and structs deserialized positionally.
+///
+/// Unlike a plain sequence, running out of fields before all elements have
+/// been deserialized isn't necessarily the end of the sequence. Each missing
+/// element is still given a chance to deserialize from the end of the row,
+/// which succeeds for types like `Option<T>`. Otherwise, the element is
+/// reported as absent, so that Serde can fall back to a default value (e.g.,
+/// for fields marked `#[serde(default)]`) or report an error.
+struct TupleAccess<'a, T> {
+    de: &'a mut DeRecordWrap<T>,
+    len: usize,
+}
+
+impl<'a, 'de: 'a, T: DeRecord<'de>> SeqAccess<'de> for TupleAccess<'a, T> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<U: DeserializeSeed<'de>>(
+        &mut self,
+        seed: U,
+    ) -> Result<Option<U::Value>, Self::Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        if self.de.peek_field().is_some() {
+            return seed.deserialize(&mut *self.de).map(Some);
+        }
+        match seed.deserialize(&mut *self.de) {
+            Ok(value) => Ok(Some(value)),
+            Err(ref err) if *err.kind() == DEK::UnexpectedEndOfRow => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
 impl<'a, 'de: 'a, T: DeRecord<'de>> MapAccess<'de>
     for &'a mut DeRecordWrap<T>
 {
@@ -634,11 +851,32 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> MapAccess<'de>
         seed: K,
     ) -> Result<Option<K::Value>, Self::Error> {
         assert!(self.has_headers());
-        let field = match self.next_header_bytes()? {
+        let normalization = self.header_normalization();
+        if normalization == Normalization::None {
+            let field = match self.next_header_bytes()? {
+                None => return Ok(None),
+                Some(field) => field,
+            };
+            return seed
+                .deserialize(BorrowedBytesDeserializer::new(field))
+                .map(Some);
+        }
+        let name = match self.next_header_str_or_bytes()? {
             None => return Ok(None),
-            Some(field) => field,
+            Some(Ok(name)) => name,
+            Some(Err(field)) => {
+                return seed
+                    .deserialize(BorrowedBytesDeserializer::new(field))
+                    .map(Some)
+            }
         };
-        seed.deserialize(BorrowedBytesDeserializer::new(field)).map(Some)
+        match normalization.apply(name) {
+            Cow::Borrowed(name) => {
+                seed.deserialize(BorrowedStrDeserializer::new(name))
+            }
+            Cow::Owned(name) => seed.deserialize(name.into_deserializer()),
+        }
+        .map(Some)
     }
 
     fn next_value_seed<K: DeserializeSeed<'de>>(
@@ -653,6 +891,7 @@ impl<'a, 'de: 'a, T: DeRecord<'de>> MapAccess<'de>
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DeserializeError {
     field: Option<u64>,
+    column: Option<String>,
     kind: DeserializeErrorKind,
 }
 
@@ -673,6 +912,9 @@ pub enum DeserializeErrorKind {
     /// This error occurs when a boolean value fails to parse.
     ParseBool(str::ParseBoolError),
     /// This error occurs when an integer value fails to parse.
+    ///
+    /// Use [`int_error_kind`](enum.DeserializeErrorKind.html#method.int_error_kind)
+    /// to tell an out of range value apart from one that isn't a number.
     ParseInt(num::ParseIntError),
     /// This error occurs when a float value fails to parse.
     ParseFloat(num::ParseFloatError),
@@ -680,7 +922,11 @@ pub enum DeserializeErrorKind {
 
 impl SerdeError for DeserializeError {
     fn custom<T: fmt::Display>(msg: T) -> DeserializeError {
-        DeserializeError { field: None, kind: DEK::Message(msg.to_string()) }
+        DeserializeError {
+            field: None,
+            column: None,
+            kind: DEK::Message(msg.to_string()),
+        }
     }
 }
 
@@ -692,7 +938,9 @@ impl StdError for DeserializeError {
 
 impl fmt::Display for DeserializeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let Some(field) = self.field {
+        if let (Some(field), Some(column)) = (self.field, &self.column) {
+            write!(f, "field {} (column '{}'): {}", field, column, self.kind)
+        } else if let Some(field) = self.field {
             write!(f, "field {}: {}", field, self.kind)
         } else {
             write!(f, "{}", self.kind)
@@ -724,13 +972,81 @@ impl DeserializeError {
         self.field
     }
 
+    /// Return the name of the column corresponding to this error's field, if
+    /// available.
+    ///
+    /// This is only available when the record being deserialized has a
+    /// corresponding header row and the failing field has an entry in it.
+    /// For raw byte records, the header name must also be valid UTF-8.
+    pub fn column(&self) -> Option<&str> {
+        self.column.as_deref()
+    }
+
     /// Return the underlying error kind.
     pub fn kind(&self) -> &DeserializeErrorKind {
         &self.kind
     }
+
+    /// Create an error for the field at the given index, optionally naming
+    /// the column it belongs to.
+    #[cfg(feature = "arrow")]
+    pub(crate) fn for_field(
+        field: u64,
+        column: Option<String>,
+        kind: DeserializeErrorKind,
+    ) -> DeserializeError {
+        DeserializeError { field: Some(field), column, kind }
+    }
 }
 
 impl DeserializeErrorKind {
+    /// Return the reason an integer value failed to parse, if this is a
+    /// `ParseInt` error.
+    ///
+    /// This distinguishes values that are out of range for the target type
+    /// (`IntErrorKind::PosOverflow` and `IntErrorKind::NegOverflow`) from
+    /// values that aren't integers at all (`IntErrorKind::InvalidDigit`),
+    /// and from empty fields (`IntErrorKind::Empty`). Note that, as with
+    /// `str::parse`, a negative value for an unsigned type is reported as
+    /// `InvalidDigit`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, num::IntErrorKind};
+    /// use csv::{ErrorKind, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "300\nabc\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(data.as_bytes());
+    ///
+    ///     let mut kinds = vec![];
+    ///     for result in rdr.deserialize::<(u8,)>() {
+    ///         let err = result.unwrap_err();
+    ///         match *err.kind() {
+    ///             ErrorKind::Deserialize { ref err, .. } => {
+    ///                 kinds.push(err.kind().int_error_kind().cloned());
+    ///             }
+    ///             ref wrong => panic!("unexpected error: {:?}", wrong),
+    ///         }
+    ///     }
+    ///     assert_eq!(kinds, vec![
+    ///         Some(IntErrorKind::PosOverflow),
+    ///         Some(IntErrorKind::InvalidDigit),
+    ///     ]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn int_error_kind(&self) -> Option<&num::IntErrorKind> {
+        match *self {
+            DeserializeErrorKind::ParseInt(ref err) => Some(err.kind()),
+            _ => None,
+        }
+    }
+
     #[allow(deprecated)]
     fn description(&self) -> &str {
         use self::DeserializeErrorKind::*;
@@ -747,13 +1063,19 @@ impl DeserializeErrorKind {
     }
 }
 
-serde_if_integer128! {
-    fn try_positive_integer128(s: &str) -> Option<u128> {
-        s.parse().ok()
-    }
-
-    fn try_negative_integer128(s: &str) -> Option<i128> {
-        s.parse().ok()
+/// Infer the type of the given field, in the same way as `infer_deserialize`
+/// does with type inference enabled.
+pub(crate) fn infer_column_type(s: &str) -> ColumnType {
+    if s == "true" || s == "false" {
+        ColumnType::Bool
+    } else if try_positive_integer64(s).is_some()
+        || try_negative_integer64(s).is_some()
+    {
+        ColumnType::Integer
+    } else if try_float(s).is_some() && !is_integer(s.as_bytes()) {
+        ColumnType::Float
+    } else {
+        ColumnType::String
     }
 }
 
@@ -777,18 +1099,44 @@ fn try_negative_integer64_bytes(s: &[u8]) -> Option<i64> {
     str::from_utf8(s).ok().and_then(|s| s.parse().ok())
 }
 
-serde_if_integer128! {
-    fn try_positive_integer128_bytes(s: &[u8]) -> Option<u128> {
-        str::from_utf8(s).ok().and_then(|s| s.parse().ok())
-    }
+fn try_float_bytes(s: &[u8]) -> Option<f64> {
+    str::from_utf8(s).ok().and_then(|s| s.parse().ok())
+}
 
-    fn try_negative_integer128_bytes(s: &[u8]) -> Option<i128> {
-        str::from_utf8(s).ok().and_then(|s| s.parse().ok())
+/// If the given field has a `0x`, `0o` or `0b` prefix (in either case),
+/// optionally preceded by a sign, then return its digits and radix. A
+/// leading `-` is kept with the digits so that they can be parsed with
+/// `from_str_radix`.
+fn split_radix(field: &str) -> Option<(Cow<'_, str>, u32)> {
+    let (sign, rest) = match field.as_bytes().first() {
+        Some(b'+') | Some(b'-') => field.split_at(1),
+        _ => ("", field),
+    };
+    let radix = match rest.get(..2)? {
+        "0x" | "0X" => 16,
+        "0o" | "0O" => 8,
+        "0b" | "0B" => 2,
+        _ => return None,
+    };
+    let digits = &rest[2..];
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    if sign == "-" {
+        Some((Cow::Owned(format!("-{}", digits)), radix))
+    } else {
+        Some((Cow::Borrowed(digits), radix))
     }
 }
 
-fn try_float_bytes(s: &[u8]) -> Option<f64> {
-    str::from_utf8(s).ok().and_then(|s| s.parse().ok())
+/// Returns true if the given field consists of ASCII digits with an optional
+/// leading sign.
+fn is_integer(s: &[u8]) -> bool {
+    let digits = match s.first() {
+        Some(b'+') | Some(b'-') => &s[1..],
+        _ => s,
+    };
+    !digits.is_empty() && digits.iter().all(|b| b.is_ascii_digit())
 }
 
 #[cfg(test)]
@@ -801,10 +1149,17 @@ mod tests {
     };
 
     use crate::{
-        byte_record::ByteRecord, error::Error, string_record::StringRecord,
+        byte_record::ByteRecord,
+        error::{Error, ErrorKind},
+        string_record::StringRecord,
+        Normalization,
     };
 
-    use super::{deserialize_byte_record, deserialize_string_record};
+    use super::{
+        deserialize_byte_record, deserialize_string_record,
+        deserialize_string_record_with, DeserializeError,
+        DeserializeErrorKind, DeserializeOptions,
+    };
 
     fn de<D: DeserializeOwned>(fields: &[&str]) -> Result<D, Error> {
         let record = StringRecord::from(fields);
@@ -999,6 +1354,18 @@ mod tests {
         assert_eq!(got, vec![1, 5, 10]);
     }
 
+    #[test]
+    fn string_seq() {
+        let got: Vec<String> = de(&["a", "", "1.5"]).unwrap();
+        assert_eq!(got, vec!["a", "", "1.5"]);
+
+        let got: (Vec<String>,) = de(&["a", "", "1.5"]).unwrap();
+        assert_eq!(got.0, vec!["a", "", "1.5"]);
+
+        let got: Vec<String> = de(&[]).unwrap();
+        assert!(got.is_empty());
+    }
+
     #[test]
     fn simple_hex_seq() {
         let got: Vec<i32> = de(&["0x7F", "0xA9", "0x10"]).unwrap();
@@ -1085,6 +1452,36 @@ mod tests {
         assert_eq!(got, Row { label: Label::Bar, x: 5.0 });
     }
 
+    #[test]
+    fn enum_newtype_variant() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            value: Value,
+            x: f64,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        enum Value {
+            Price(f64),
+            Qty(u64),
+            Unknown,
+        }
+
+        let got: Row =
+            de_headers(&["value", "value_value", "x"], &["Price", "1.5", "5"])
+                .unwrap();
+        assert_eq!(got, Row { value: Value::Price(1.5), x: 5.0 });
+        let got: Row =
+            de_headers(&["x", "value", "value_value"], &["5", "Qty", "3"])
+                .unwrap();
+        assert_eq!(got, Row { value: Value::Qty(3), x: 5.0 });
+
+        let got: (Value, Value, Value) =
+            de(&["Qty", "3", "Unknown", "Price", "2"]).unwrap();
+        assert_eq!(got, (Value::Qty(3), Value::Unknown, Value::Price(2.0)));
+        assert!(de::<(Value,)>(&["Qty"]).is_err());
+    }
+
     #[test]
     fn enum_untagged() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -1115,6 +1512,179 @@ mod tests {
         );
     }
 
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Inferred {
+        Bool(bool),
+        Integer(i64),
+        Float(f64),
+        String(String),
+    }
+
+    #[test]
+    fn infer_huge_integer_as_string() {
+        let huge = "123456789012345678901234567890123456789012345";
+        let got: (Inferred, Inferred) = de(&[huge, "1.5"]).unwrap();
+        assert_eq!(got.0, Inferred::String(huge.into()));
+        assert_eq!(got.1, Inferred::Float(1.5));
+
+        let record = ByteRecord::from(vec![huge, "-1e3"]);
+        let got: (Inferred, Inferred) =
+            deserialize_byte_record(&record, None).unwrap();
+        assert_eq!(got.0, Inferred::String(huge.into()));
+        assert_eq!(got.1, Inferred::Float(-1000.0));
+
+        // Integers that only fit in 128 bits are strings too.
+        let big = "-99999999999999999999";
+        let got: (Inferred,) = de(&[big]).unwrap();
+        assert_eq!(got.0, Inferred::String(big.into()));
+    }
+
+    #[test]
+    fn infer_floats_disabled() {
+        let no_floats =
+            DeserializeOptions { infer_floats: false, ..Default::default() };
+        let record = StringRecord::from(vec!["true", "-5", "1.10", "NaN"]);
+        let got: (Inferred, Inferred, Inferred, Inferred) =
+            deserialize_string_record_with(&record, None, no_floats).unwrap();
+        assert_eq!(got.0, Inferred::Bool(true));
+        assert_eq!(got.1, Inferred::Integer(-5));
+        assert_eq!(got.2, Inferred::String("1.10".into()));
+        assert_eq!(got.3, Inferred::String("NaN".into()));
+
+        // Typed floats are unaffected.
+        let got: (String, String, f32, f64) =
+            deserialize_string_record_with(&record, None, no_floats).unwrap();
+        assert_eq!(got.2, 1.10);
+        assert!(got.3.is_nan());
+    }
+
+    #[test]
+    fn infer_types_disabled() {
+        let no_infer =
+            DeserializeOptions { infer_types: false, ..Default::default() };
+        let record = StringRecord::from(vec!["true", "-5", "1.10"]);
+        let got: (Inferred, Inferred, Inferred) =
+            deserialize_string_record_with(&record, None, no_infer).unwrap();
+        assert_eq!(got.0, Inferred::String("true".into()));
+        assert_eq!(got.1, Inferred::String("-5".into()));
+        assert_eq!(got.2, Inferred::String("1.10".into()));
+
+        // Typed fields are unaffected.
+        let got: (bool, i32, f64) =
+            deserialize_string_record_with(&record, None, no_infer).unwrap();
+        assert_eq!(got, (true, -5, 1.10));
+    }
+
+    #[test]
+    fn integer_radix_detection() {
+        let radix = DeserializeOptions {
+            integer_radix_detection: true,
+            ..Default::default()
+        };
+        let record =
+            StringRecord::from(vec!["0x1F", "0X1f", "0o17", "0b1010"]);
+        let got: (u8, i32, u16, i64) =
+            deserialize_string_record_with(&record, None, radix).unwrap();
+        assert_eq!(got, (0x1F, 0x1F, 0o17, 0b1010));
+
+        let record = StringRecord::from(vec!["-0x1F", "+0b11", "-0o7", "42"]);
+        let got: (i8, u32, i64, u64) =
+            deserialize_string_record_with(&record, None, radix).unwrap();
+        assert_eq!(got, (-0x1F, 0b11, -0o7, 42));
+
+        // Without detection, only a lowercase `0x` prefix is recognized.
+        let record = StringRecord::from(vec!["0x1F", "0b1010"]);
+        let got: Result<(u8, u8), _> =
+            deserialize_string_record_with(&record, None, Default::default());
+        assert!(got.is_err());
+    }
+
+    #[test]
+    fn integer_radix_detection_invalid() {
+        let radix = DeserializeOptions {
+            integer_radix_detection: true,
+            ..Default::default()
+        };
+        for field in ["-0x1F", "0b102", "0x", "0x-1", "0o+1", "0xFFF"] {
+            let record = StringRecord::from(vec![field]);
+            let got: Result<(u8,), _> =
+                deserialize_string_record_with(&record, None, radix);
+            assert!(got.is_err(), "{:?} should not parse", field);
+        }
+    }
+
+    fn de_headers_normalized<D: DeserializeOwned>(
+        normalization: Normalization,
+        headers: &[&str],
+        fields: &[&str],
+    ) -> Result<D, Error> {
+        let headers = StringRecord::from(headers);
+        let record = StringRecord::from(fields);
+        let options = DeserializeOptions {
+            header_normalization: normalization,
+            ..Default::default()
+        };
+        deserialize_string_record_with(&record, Some(&headers), options)
+    }
+
+    #[test]
+    fn header_normalization() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            city: String,
+            population: u64,
+        }
+
+        let headers = &["City", "Population "];
+        let fields = &["Boston", "4628910"];
+        let expected = Row { city: "Boston".into(), population: 4628910 };
+
+        assert!(de_headers::<Row>(headers, fields).is_err());
+        let got: Row =
+            de_headers_normalized(Normalization::All, headers, fields)
+                .unwrap();
+        assert_eq!(got, expected);
+
+        // Trailing whitespace only.
+        let headers = &["city", " population\t"];
+        assert!(de_headers_normalized::<Row>(
+            Normalization::Lowercase,
+            headers,
+            fields
+        )
+        .is_err());
+        let got: Row =
+            de_headers_normalized(Normalization::Trim, headers, fields)
+                .unwrap();
+        assert_eq!(got, expected);
+
+        // Mixed case only.
+        let headers = &["CiTy", "POPULATION"];
+        assert!(de_headers_normalized::<Row>(
+            Normalization::Trim,
+            headers,
+            fields
+        )
+        .is_err());
+        let got: Row =
+            de_headers_normalized(Normalization::Lowercase, headers, fields)
+                .unwrap();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn header_normalization_map_keys() {
+        let got: HashMap<String, String> = de_headers_normalized(
+            Normalization::All,
+            &[" Ünïcode ", "abc"],
+            &["x", "y"],
+        )
+        .unwrap();
+        assert_eq!(got["ünïcode"], "x");
+        assert_eq!(got["abc"], "y");
+    }
+
     #[test]
     fn option_empty_field() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -1146,6 +1716,226 @@ mod tests {
         assert_eq!(got, Foo { a: None, b: None, c: Some(5) });
     }
 
+    #[test]
+    fn split_field() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            #[serde(deserialize_with = "crate::split_pipe")]
+            a: Vec<i32>,
+            #[serde(deserialize_with = "crate::split_semicolon")]
+            b: Vec<String>,
+            #[serde(deserialize_with = "crate::split_pipe")]
+            c: Vec<f64>,
+        }
+
+        let got: Foo =
+            de_headers(&["a", "b", "c"], &["1|2|3", "x;;y z", ""]).unwrap();
+        assert_eq!(
+            got,
+            Foo {
+                a: vec![1, 2, 3],
+                b: vec!["x".into(), "".into(), "y z".into()],
+                c: vec![],
+            }
+        );
+
+        let got: Foo = de(&["-1", "x", "1.5|2"]).unwrap();
+        assert_eq!(
+            got,
+            Foo { a: vec![-1], b: vec!["x".into()], c: vec![1.5, 2.0] }
+        );
+    }
+
+    #[test]
+    fn split_field_invalid() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            #[serde(deserialize_with = "crate::split_pipe")]
+            a: Vec<i32>,
+        }
+
+        let err = de_headers::<Foo>(&["a"], &["1|x|3"]).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("failed to parse list element \"x\""), "{}", msg);
+        assert!(de_headers::<Foo>(&["a"], &["1||3"]).is_err());
+    }
+
+    #[test]
+    fn fixed_point() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            #[serde(deserialize_with = "crate::fixed_point_4")]
+            a: i64,
+        }
+
+        let fp = |field: &str| de::<Foo>(&[field]).map(|foo| foo.a);
+        assert_eq!(fp("1.2345").unwrap(), 12345);
+        assert_eq!(fp("7").unwrap(), 70000);
+        assert_eq!(fp("-1.5").unwrap(), -15000);
+        assert_eq!(fp("+0.0001").unwrap(), 1);
+        assert_eq!(fp("-.25").unwrap(), -2500);
+        assert_eq!(fp("3.").unwrap(), 30000);
+        assert_eq!(fp("-0").unwrap(), 0);
+        assert_eq!(fp("1.230000").unwrap(), 12300);
+        assert_eq!(fp("-2.50000").unwrap(), -25000);
+        assert_eq!(fp("-922337203685477.5808").unwrap(), i64::MIN);
+        assert_eq!(fp("922337203685477.5807").unwrap(), i64::MAX);
+
+        let err = fp("1.23456").unwrap_err().to_string();
+        assert!(err.contains("too many fractional digits"), "{}", err);
+        let err = fp("922337203685477.5808").unwrap_err().to_string();
+        assert!(err.contains("number out of range"), "{}", err);
+        for invalid in &["", "-", ".", "1.2.3", "1e3", " 1", "--1", "1,5"] {
+            let err = fp(invalid).unwrap_err().to_string();
+            assert!(err.contains("invalid decimal number"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn positional_missing_trailing_fields() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            a: i32,
+            b: Option<i32>,
+            #[serde(default)]
+            c: String,
+            #[serde(default = "seven")]
+            d: u8,
+        }
+        fn seven() -> u8 {
+            7
+        }
+
+        let got: Foo = de(&["1"]).unwrap();
+        assert_eq!(got, Foo { a: 1, b: None, c: "".into(), d: 7 });
+        let got: Foo = de(&["1", "2", "x"]).unwrap();
+        assert_eq!(got, Foo { a: 1, b: Some(2), c: "x".into(), d: 7 });
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Bar(i32, Option<String>, #[serde(default)] Vec<i32>);
+        let got: Bar = de(&["1"]).unwrap();
+        assert_eq!(got, Bar(1, None, vec![]));
+
+        let got: (i32, Option<i32>, Option<String>) = de(&["1"]).unwrap();
+        assert_eq!(got, (1, None, None));
+
+        // Missing fields without a default are still an error.
+        assert!(de::<(i32, i32)>(&["1"]).is_err());
+        assert!(de::<Foo>(&[]).is_err());
+    }
+
+    #[test]
+    fn missing_fields_as_empty() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            a: i32,
+            b: Option<i32>,
+            c: String,
+            #[serde(default)]
+            d: Option<String>,
+        }
+
+        let padded = DeserializeOptions {
+            missing_fields_as_empty: true,
+            ..Default::default()
+        };
+        let headers = StringRecord::from(vec!["a", "b", "c", "d"]);
+        let record = StringRecord::from(vec!["1"]);
+        let got: Foo =
+            deserialize_string_record_with(&record, Some(&headers), padded)
+                .unwrap();
+        assert_eq!(got, Foo { a: 1, b: None, c: "".into(), d: None });
+
+        let got: HashMap<String, String> =
+            deserialize_string_record_with(&record, Some(&headers), padded)
+                .unwrap();
+        assert_eq!(got.len(), 4);
+        assert_eq!(got["a"], "1");
+        assert_eq!(got["d"], "");
+
+        // Missing fields that can't be empty report their column.
+        let err = deserialize_string_record_with::<(i32, i32)>(
+            &record,
+            Some(&headers),
+            padded,
+        )
+        .unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref err, .. } => {
+                assert_eq!(err.field(), Some(1));
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        // Disabled by default.
+        let err = deserialize_string_record::<Foo>(&record, Some(&headers))
+            .unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref err, .. } => {
+                assert_eq!(
+                    *err.kind(),
+                    DeserializeErrorKind::UnexpectedEndOfRow
+                );
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    // Containers that forward to their inner type must hand string types the
+    // raw field, without any type inference.
+    #[test]
+    fn option_string_is_raw() {
+        let got: Option<String> = de(&["42"]).unwrap();
+        assert_eq!(got, Some("42".to_string()));
+
+        let got: (Option<String>, Option<String>) =
+            de(&["0x10", " 1.5e3 "]).unwrap();
+        assert_eq!(got, (Some("0x10".into()), Some(" 1.5e3 ".into())));
+    }
+
+    #[test]
+    fn newtype_string_is_raw() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Wrap(String);
+
+        let got: Wrap = de(&["42"]).unwrap();
+        assert_eq!(got, Wrap("42".into()));
+
+        let got: Option<Wrap> = de(&["42"]).unwrap();
+        assert_eq!(got, Some(Wrap("42".into())));
+
+        let got: (Wrap, Option<Wrap>) = de(&["true", "-7"]).unwrap();
+        assert_eq!(got, (Wrap("true".into()), Some(Wrap("-7".into()))));
+    }
+
+    #[test]
+    fn struct_string_fields_are_raw() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Wrap(String);
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            a: Option<String>,
+            b: Wrap,
+            c: Option<Wrap>,
+        }
+
+        let expected = Foo {
+            a: Some("42".into()),
+            b: Wrap("42".into()),
+            c: Some(Wrap("42".into())),
+        };
+        let got: Foo =
+            de_headers(&["a", "b", "c"], &["42", "42", "42"]).unwrap();
+        assert_eq!(got, expected);
+
+        let headers = ByteRecord::from(vec!["a", "b", "c"]);
+        let record = ByteRecord::from(vec!["42", "42", "42"]);
+        let got: Foo =
+            deserialize_byte_record(&record, Some(&headers)).unwrap();
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn borrowed() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -1224,6 +2014,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flatten_extra_columns() {
+        use std::collections::HashMap;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            name: String,
+            pop: u64,
+            #[serde(flatten)]
+            extra: HashMap<String, String>,
+        }
+
+        // Named fields are deserialized with their own types, so only the
+        // unknown columns need type inference to be disabled.
+        let no_infer =
+            DeserializeOptions { infer_types: false, ..Default::default() };
+        let header = StringRecord::from(vec!["country", "name", "lat", "pop"]);
+        let record =
+            StringRecord::from(vec!["United States", "Boston", "42.3", "7"]);
+        let got: Row =
+            deserialize_string_record_with(&record, Some(&header), no_infer)
+                .unwrap();
+        let mut extra = HashMap::new();
+        extra.insert("country".to_string(), "United States".to_string());
+        extra.insert("lat".to_string(), "42.3".to_string());
+        assert_eq!(got, Row { name: "Boston".to_string(), pop: 7, extra });
+
+        // With type inference, unknown columns that aren't strings can't be
+        // collected into a map of strings.
+        let err = deserialize_string_record_with::<Row>(
+            &record,
+            Some(&header),
+            Default::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid type"), "{}", err);
+
+        // Without any unknown columns, the map is empty.
+        let header = StringRecord::from(vec!["pop", "name"]);
+        let record = StringRecord::from(vec!["7", "Boston"]);
+        let got: Row =
+            deserialize_string_record_with(&record, Some(&header), no_infer)
+                .unwrap();
+        assert_eq!(
+            got,
+            Row { name: "Boston".to_string(), pop: 7, extra: HashMap::new() }
+        );
+    }
+
+    #[test]
+    fn str_types_get_raw_field() {
+        use std::{
+            fmt,
+            net::{IpAddr, Ipv4Addr, SocketAddr},
+        };
+
+        use serde::de::{Deserializer, Error, Unexpected, Visitor};
+
+        // A type that, like `uuid::Uuid`, asks for a string and parses it
+        // itself.
+        #[derive(Debug, PartialEq)]
+        struct HexId(u128);
+
+        impl<'de> Deserialize<'de> for HexId {
+            fn deserialize<D: Deserializer<'de>>(
+                d: D,
+            ) -> Result<HexId, D::Error> {
+                struct HexIdVisitor;
+
+                impl<'de> Visitor<'de> for HexIdVisitor {
+                    type Value = HexId;
+
+                    fn expecting(
+                        &self,
+                        f: &mut fmt::Formatter,
+                    ) -> fmt::Result {
+                        f.write_str("32 hex digits")
+                    }
+
+                    fn visit_str<E: Error>(self, s: &str) -> Result<HexId, E> {
+                        if s.len() != 32 {
+                            return Err(E::invalid_length(s.len(), &self));
+                        }
+                        u128::from_str_radix(s, 16).map(HexId).map_err(|_| {
+                            E::invalid_value(Unexpected::Str(s), &self)
+                        })
+                    }
+                }
+
+                d.deserialize_str(HexIdVisitor)
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Row {
+            ip: IpAddr,
+            addr: SocketAddr,
+            id: HexId,
+        }
+
+        let digits = "12345678901234567890123456789012";
+        let expected = Row {
+            ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            addr: "[::1]:8080".parse().unwrap(),
+            id: HexId(u128::from_str_radix(digits, 16).unwrap()),
+        };
+        let fields = &["10.0.0.1", "[::1]:8080", digits];
+        assert_eq!(de::<Row>(fields).unwrap(), expected);
+        assert_eq!(
+            de_headers::<Row>(
+                &["addr", "id", "ip"],
+                &[fields[1], fields[2], fields[0]]
+            )
+            .unwrap(),
+            expected
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Flat {
+            #[serde(flatten)]
+            row: Row,
+        }
+        assert_eq!(
+            de_headers::<Flat>(&["ip", "addr", "id"], fields).unwrap(),
+            Flat { row: expected }
+        );
+    }
+
     #[test]
     fn partially_invalid_utf8() {
         #[derive(Debug, Deserialize, PartialEq)]
@@ -1247,4 +2165,86 @@ mod tests {
             }
         );
     }
+
+    fn de_err(err: Error) -> DeserializeError {
+        match err.into_kind() {
+            ErrorKind::Deserialize { err, .. } => err,
+            kind => panic!("expected deserialize error, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn error_int_kind() {
+        use std::num::IntErrorKind;
+
+        let kind = |field: &str| {
+            let err = de_err(de::<(u8,)>(&[field]).unwrap_err());
+            err.kind().int_error_kind().cloned()
+        };
+        assert_eq!(kind("256"), Some(IntErrorKind::PosOverflow));
+        assert_eq!(kind("abc"), Some(IntErrorKind::InvalidDigit));
+        assert_eq!(kind("1a"), Some(IntErrorKind::InvalidDigit));
+        assert_eq!(kind("-1"), Some(IntErrorKind::InvalidDigit));
+        assert_eq!(kind(""), Some(IntErrorKind::Empty));
+        assert_eq!(kind("0x100"), Some(IntErrorKind::PosOverflow));
+
+        let err = de_err(de::<(i8,)>(&["-129"]).unwrap_err());
+        assert_eq!(
+            err.kind().int_error_kind(),
+            Some(&IntErrorKind::NegOverflow)
+        );
+
+        let err = de_err(de::<(f64,)>(&["abc"]).unwrap_err());
+        assert_eq!(err.kind().int_error_kind(), None);
+    }
+
+    #[test]
+    fn error_column() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            x: i32,
+            y: f64,
+        }
+
+        let err = de_err(
+            de_headers::<Foo>(&["y", "x"], &["1.5", "nope"]).unwrap_err(),
+        );
+        assert_eq!(err.field(), Some(1));
+        assert_eq!(err.column(), Some("x"));
+        assert_eq!(
+            err.to_string(),
+            "field 1 (column 'x'): invalid digit found in string"
+        );
+
+        let err = de_err(de::<(f64, i32)>(&["1.5", "nope"]).unwrap_err());
+        assert_eq!(err.field(), Some(1));
+        assert_eq!(err.column(), None);
+        assert_eq!(err.to_string(), "field 1: invalid digit found in string");
+    }
+
+    #[test]
+    fn error_column_bytes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Foo {
+            x: i32,
+            y: i32,
+        }
+
+        let headers = ByteRecord::from(vec!["x", "y"]);
+        let record = ByteRecord::from(vec!["1", "nope"]);
+        let err = de_err(
+            deserialize_byte_record::<Foo>(&record, Some(&headers))
+                .unwrap_err(),
+        );
+        assert_eq!(err.column(), Some("y"));
+
+        let headers = ByteRecord::from(vec![b(b"x"), b(b"\xFF")]);
+        let record = ByteRecord::from(vec!["1", "nope"]);
+        let err = de_err(
+            deserialize_byte_record::<(i32, i32)>(&record, Some(&headers))
+                .unwrap_err(),
+        );
+        assert_eq!(err.field(), Some(1));
+        assert_eq!(err.column(), None);
+    }
 }
@@ -3,6 +3,8 @@ use std::{error::Error as StdError, fmt, io, result};
 use crate::{
     byte_record::{ByteRecord, Position},
     deserializer::DeserializeError,
+    writer::Writer,
+    ColumnType, DetectedTerminator,
 };
 
 /// A type alias for `Result<T, csv::Error>`.
@@ -16,23 +18,36 @@ pub type Result<T> = result::Result<T, Error>;
 /// For example, if a CSV reader is used on an in-memory buffer with the
 /// `flexible` option enabled and one is reading records as raw byte strings,
 /// then no error can occur.
-#[derive(Debug)]
-pub struct Error(Box<ErrorKind>);
+pub struct Error(Box<ErrorInner>);
+
+struct ErrorInner {
+    kind: ErrorKind,
+    /// Whether only the record number of this error's position is
+    /// meaningful. This is the case for errors that occur while writing,
+    /// since writers don't track lines or byte offsets.
+    record_only: bool,
+}
 
 impl Error {
     /// A crate private constructor for `Error`.
     pub(crate) fn new(kind: ErrorKind) -> Error {
-        Error(Box::new(kind))
+        Error(Box::new(ErrorInner { kind, record_only: false }))
+    }
+
+    /// Like `new`, but only the record number of the error's position is
+    /// meaningful, so it is the only part of the position that is displayed.
+    pub(crate) fn record_only(kind: ErrorKind) -> Error {
+        Error(Box::new(ErrorInner { kind, record_only: true }))
     }
 
     /// Return the specific type of this error.
     pub fn kind(&self) -> &ErrorKind {
-        &self.0
+        &self.0.kind
     }
 
     /// Unwrap this error into its underlying type.
     pub fn into_kind(self) -> ErrorKind {
-        *self.0
+        self.0.kind
     }
 
     /// Returns true if this is an I/O error.
@@ -40,7 +55,7 @@ impl Error {
     /// If this is true, the underlying `ErrorKind` is guaranteed to be
     /// `ErrorKind::Io`.
     pub fn is_io_error(&self) -> bool {
-        match *self.0 {
+        match self.0.kind {
             ErrorKind::Io(_) => true,
             _ => false,
         }
@@ -51,7 +66,7 @@ impl Error {
     /// This is a convenience function that permits callers to easily access
     /// the position on an error without doing case analysis on `ErrorKind`.
     pub fn position(&self) -> Option<&Position> {
-        self.0.position()
+        self.0.kind.position()
     }
 }
 
@@ -75,6 +90,10 @@ pub enum ErrorKind {
     UnequalLengths {
         /// The position of the first record with an unequal number of fields
         /// to the previous record, if available.
+        ///
+        /// When writing, only the record number of this position is
+        /// meaningful. It is the number of records (including any header
+        /// row) written before the bad record.
         pos: Option<Position>,
         /// The expected number of fields in a record. This is the number of
         /// fields in the record read prior to the record indicated by
@@ -87,8 +106,61 @@ pub enum ErrorKind {
     /// are called on a CSV reader that was asked to `seek` before it parsed
     /// the first record.
     Seek,
-    /// An error of this kind occurs only when using the Serde serializer.
+    /// An error of this kind occurs only when using the Serde serializer, or
+    /// when writing an Arrow record batch whose values can't be formatted.
     Serialize(String),
+    /// This error occurs when a CSV writer configured to never quote fields
+    /// is asked to write a field that cannot be written without quotes. This
+    /// error only occurs when the `reject_unquotable` option in a CSV writer
+    /// is enabled.
+    UnquotableField {
+        /// The index (starting at 0) of the offending field in its record.
+        field: u64,
+    },
+    /// This error occurs when selecting columns by name from a CSV reader
+    /// and one of the names is not present in the header row.
+    UnknownColumn {
+        /// The name of the column that could not be found.
+        name: String,
+    },
+    /// This error occurs when a CSV reader or writer is built with a
+    /// configuration that cannot work, e.g., when the delimiter and the quote
+    /// character are the same byte.
+    InvalidConfig(String),
+    /// This error occurs when the CSV data ends inside a quoted field, which
+    /// usually means that it was truncated. This error only occurs when the
+    /// `strict_eof` option in a CSV reader is enabled.
+    UnexpectedEof {
+        /// The position of the record containing the unterminated quoted
+        /// field, if available.
+        pos: Option<Position>,
+    },
+    /// This error occurs when records are terminated by more than one kind
+    /// of line ending. This error only occurs when the
+    /// `require_consistent_terminator` option in a CSV reader is enabled.
+    InconsistentTerminator {
+        /// The position of the first record with a new kind of line ending,
+        /// if available.
+        pos: Option<Position>,
+        /// The line ending of the records read before the bad record.
+        expected: DetectedTerminator,
+        /// The line ending of the bad record.
+        found: DetectedTerminator,
+    },
+    /// This error occurs when a field doesn't have the type expected of its
+    /// column. This error only occurs when validating records with
+    /// `Reader::validate_schema`.
+    SchemaMismatch {
+        /// The position of the record containing the bad field, if
+        /// available.
+        pos: Option<Position>,
+        /// The index (starting at 0) of the bad field in its record.
+        field: u64,
+        /// The type expected of the field's column.
+        expected: ColumnType,
+        /// The type inferred from the bad field.
+        found: ColumnType,
+    },
     /// An error of this kind occurs only when performing automatic
     /// deserialization with serde.
     Deserialize {
@@ -115,6 +187,9 @@ impl ErrorKind {
         match *self {
             ErrorKind::Utf8 { ref pos, .. } => pos.as_ref(),
             ErrorKind::UnequalLengths { ref pos, .. } => pos.as_ref(),
+            ErrorKind::UnexpectedEof { ref pos } => pos.as_ref(),
+            ErrorKind::InconsistentTerminator { ref pos, .. } => pos.as_ref(),
+            ErrorKind::SchemaMismatch { ref pos, .. } => pos.as_ref(),
             ErrorKind::Deserialize { ref pos, .. } => pos.as_ref(),
             _ => None,
         }
@@ -135,9 +210,15 @@ impl From<Error> for io::Error {
 
 impl StdError for Error {}
 
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Error").field(&self.0.kind).finish()
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self.0 {
+        match self.0.kind {
             ErrorKind::Io(ref err) => err.fmt(f),
             ErrorKind::Utf8 { pos: None, ref err } => {
                 write!(f, "CSV parse error: field {}: {}", err.field(), err)
@@ -161,6 +242,19 @@ impl fmt::Display for Error {
                     len, expected_len
                 )
             }
+            ErrorKind::UnequalLengths {
+                pos: Some(ref pos),
+                expected_len,
+                len,
+            } if self.0.record_only => write!(
+                f,
+                "CSV error: record {}: \
+                 found record with {} fields, but the previous record \
+                 has {} fields",
+                pos.record(),
+                len,
+                expected_len
+            ),
             ErrorKind::UnequalLengths {
                 pos: Some(ref pos),
                 expected_len,
@@ -185,6 +279,89 @@ impl fmt::Display for Error {
             ErrorKind::Serialize(ref err) => {
                 write!(f, "CSV write error: {}", err)
             }
+            ErrorKind::UnquotableField { field } => write!(
+                f,
+                "CSV write error: field {} contains a delimiter, quote or \
+                 terminator but quoting is disabled",
+                field
+            ),
+            ErrorKind::UnknownColumn { ref name } => write!(
+                f,
+                "CSV error: no column named '{}' in the header row",
+                name
+            ),
+            ErrorKind::InvalidConfig(ref msg) => {
+                write!(f, "CSV config error: {}", msg)
+            }
+            ErrorKind::UnexpectedEof { pos: None } => write!(
+                f,
+                "CSV parse error: unexpected end of input inside a quoted \
+                 field"
+            ),
+            ErrorKind::UnexpectedEof { pos: Some(ref pos) } => write!(
+                f,
+                "CSV parse error: record {} (line: {}, byte: {}): \
+                 unexpected end of input inside a quoted field",
+                pos.record(),
+                pos.line(),
+                pos.byte()
+            ),
+            ErrorKind::InconsistentTerminator {
+                pos: None,
+                expected,
+                found,
+            } => write!(
+                f,
+                "CSV parse error: found a record terminated by {}, but \
+                 previous records were terminated by {}",
+                terminator_name(found),
+                terminator_name(expected)
+            ),
+            ErrorKind::InconsistentTerminator {
+                pos: Some(ref pos),
+                expected,
+                found,
+            } => write!(
+                f,
+                "CSV parse error: record {} (line: {}, byte: {}): \
+                 found a record terminated by {}, but previous records \
+                 were terminated by {}",
+                pos.record(),
+                pos.line(),
+                pos.byte(),
+                terminator_name(found),
+                terminator_name(expected)
+            ),
+            ErrorKind::SchemaMismatch {
+                pos: None,
+                field,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "CSV schema error: field {}: expected {} but found {}",
+                    field,
+                    column_type_name(expected),
+                    column_type_name(found)
+                )
+            }
+            ErrorKind::SchemaMismatch {
+                pos: Some(ref pos),
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "CSV schema error: record {} (line: {}, field: {}, byte: {}): \
+                 expected {} but found {}",
+                pos.record(),
+                pos.line(),
+                field,
+                pos.byte(),
+                column_type_name(expected),
+                column_type_name(found)
+            ),
             ErrorKind::Deserialize { pos: None, ref err } => {
                 write!(f, "CSV deserialize error: {}", err)
             }
@@ -202,6 +379,26 @@ impl fmt::Display for Error {
     }
 }
 
+/// Describe a line ending for use in error messages.
+fn terminator_name(term: DetectedTerminator) -> &'static str {
+    match term {
+        DetectedTerminator::LF => "\\n",
+        DetectedTerminator::CRLF => "\\r\\n",
+        DetectedTerminator::CR => "\\r",
+        DetectedTerminator::Mixed => "mixed line endings",
+    }
+}
+
+/// Describe a column type for use in error messages.
+fn column_type_name(ty: ColumnType) -> &'static str {
+    match ty {
+        ColumnType::String => "a string",
+        ColumnType::Integer => "an integer",
+        ColumnType::Float => "a float",
+        ColumnType::Bool => "a boolean",
+    }
+}
+
 /// A UTF-8 validation error during record conversion.
 ///
 /// This occurs when attempting to convert a `ByteRecord` into a
@@ -330,6 +527,57 @@ impl<W> IntoInnerError<W> {
     }
 }
 
+impl<W: io::Write> IntoInnerError<Writer<W>> {
+    /// Returns the CSV data that was buffered by the writer but could not
+    /// be written to the underlying writer.
+    ///
+    /// This makes it possible to recover from a failed write, e.g., by
+    /// writing the data to a different sink. Note that if the underlying
+    /// writer failed part way through, then a prefix of this data may have
+    /// been written to it already.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::{self, Write};
+    /// use csv::Writer;
+    ///
+    /// #[derive(Debug)]
+    /// struct Broken;
+    ///
+    /// impl Write for Broken {
+    ///     fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+    ///         Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut wtr = Writer::from_writer(Broken);
+    /// wtr.write_record(&["a", "b", "c"]).unwrap();
+    /// let err = wtr.into_inner().unwrap_err();
+    /// assert_eq!(err.unflushed(), b"a,b,c\n");
+    ///
+    /// let mut fallback = vec![];
+    /// fallback.extend_from_slice(&err.into_unflushed());
+    /// assert_eq!(fallback, b"a,b,c\n");
+    /// ```
+    pub fn unflushed(&self) -> &[u8] {
+        self.wtr.buffered()
+    }
+
+    /// Consumes this error and returns the CSV data that was buffered by
+    /// the writer but could not be written to the underlying writer.
+    ///
+    /// The writer is dropped without attempting to write this data again.
+    /// See [`IntoInnerError::unflushed`] for more details.
+    pub fn into_unflushed(mut self) -> Vec<u8> {
+        self.wtr.take_buffered()
+    }
+}
+
 impl<W: std::any::Any> StdError for IntoInnerError<W> {}
 
 impl<W> fmt::Display for IntoInnerError<W> {
@@ -135,10 +135,16 @@ $ cargo run --example cookbook-read-serde < examples/data/smallpop.csv
 
 #![deny(missing_docs)]
 
-use std::result;
+use std::{borrow::Cow, fmt, result, str::FromStr};
 
-use serde::{Deserialize, Deserializer};
+use serde::{
+    de::Error as _, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+pub use csv_core::FieldMeta;
 
+#[cfg(feature = "arrow")]
+pub use crate::arrow::{ArrowBatchesIntoIter, ArrowBatchesIter};
 pub use crate::{
     byte_record::{ByteRecord, ByteRecordIter, Position},
     deserializer::{DeserializeError, DeserializeErrorKind},
@@ -146,14 +152,20 @@ pub use crate::{
         Error, ErrorKind, FromUtf8Error, IntoInnerError, Result, Utf8Error,
     },
     reader::{
-        ByteRecordsIntoIter, ByteRecordsIter, DeserializeRecordsIntoIter,
-        DeserializeRecordsIter, Reader, ReaderBuilder, StringRecordsIntoIter,
-        StringRecordsIter,
+        ByteRecordsIntoIter, ByteRecordsIter, ColumnReader,
+        ContinueOnDataErrors, DeserializeRecordsIntoIter,
+        DeserializeRecordsIter, EnumerateRecordsIter, LenientRecordsIter,
+        Reader, ReaderBuilder, RecordOrComment, RecordsWithCommentsIter,
+        SchemaValidator, StringRecordsIntoIter, StringRecordsIter,
+        TakeRecordsIter,
     },
-    string_record::{StringRecord, StringRecordIter},
-    writer::{Writer, WriterBuilder},
+    sniff::sniff_delimiter,
+    string_record::{HeaderMap, StringRecord, StringRecordIter},
+    writer::{RecordSink, RecordWriteOptions, Writer, WriterBuilder},
 };
 
+#[cfg(feature = "arrow")]
+mod arrow;
 mod byte_record;
 pub mod cookbook;
 mod debug;
@@ -161,6 +173,7 @@ mod deserializer;
 mod error;
 mod reader;
 mod serializer;
+mod sniff;
 mod string_record;
 pub mod tutorial;
 mod writer;
@@ -182,6 +195,13 @@ pub enum QuoteStyle {
     /// writing a field that does not parse as a valid float or integer, then
     /// quotes will be used even if they aren't strictly necessary.
     NonNumeric,
+    /// This puts quotes around all fields that are numeric. Namely, when
+    /// writing a field that parses as a valid float or integer, then quotes
+    /// will be used even if they aren't strictly necessary. Other fields are
+    /// quoted only when necessary.
+    ///
+    /// This is useful for forcing consumers to treat numbers as text.
+    Numeric,
     /// This *never* writes quotes, even if it would produce invalid CSV data.
     Never,
     /// Hints that destructuring should not be exhaustive.
@@ -199,6 +219,7 @@ impl QuoteStyle {
             QuoteStyle::Always => csv_core::QuoteStyle::Always,
             QuoteStyle::Necessary => csv_core::QuoteStyle::Necessary,
             QuoteStyle::NonNumeric => csv_core::QuoteStyle::NonNumeric,
+            QuoteStyle::Numeric => csv_core::QuoteStyle::Numeric,
             QuoteStyle::Never => csv_core::QuoteStyle::Never,
             _ => unreachable!(),
         }
@@ -239,6 +260,15 @@ impl Terminator {
             _ => unreachable!(),
         }
     }
+
+    /// Convert the csv_core type of the same name to this type.
+    fn from_core(term: csv_core::Terminator) -> Terminator {
+        match term {
+            csv_core::Terminator::CRLF => Terminator::CRLF,
+            csv_core::Terminator::Any(b) => Terminator::Any(b),
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl Default for Terminator {
@@ -247,6 +277,80 @@ impl Default for Terminator {
     }
 }
 
+/// The line ending observed by a reader.
+///
+/// This is returned by
+/// [`Reader::detected_terminator`](struct.Reader.html#method.detected_terminator).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DetectedTerminator {
+    /// Every record was terminated by `\n`.
+    LF,
+    /// Every record was terminated by `\r\n`.
+    CRLF,
+    /// Every record was terminated by `\r`.
+    CR,
+    /// Records were terminated by more than one kind of line ending.
+    Mixed,
+}
+
+/// The type of a column, as inferred from its fields.
+///
+/// This is used by
+/// [`Reader::validate_schema`](struct.Reader.html#method.validate_schema)
+/// to describe the expected type of each column. Types are inferred in the
+/// same way as when deserializing into a type that accepts anything, such as
+/// an untagged enum.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ColumnType {
+    /// Any field, including an empty one.
+    String,
+    /// A field that is a 64-bit signed or unsigned integer, such as `-5`.
+    Integer,
+    /// A field that is a floating point number, such as `1.5` or `NaN`.
+    Float,
+    /// A field that is either `true` or `false`.
+    Bool,
+}
+
+/// Return an error if any of the special bytes used by a reader or writer
+/// collide in a way that makes it impossible to read or write CSV correctly.
+///
+/// `quote` and `escape` should be `None` when they are not used by the
+/// configuration, e.g., when quoting is disabled. `term` contains every byte
+/// that may terminate a record.
+fn check_special_bytes(
+    delimiter: u8,
+    quote: Option<u8>,
+    escape: Option<u8>,
+    term: &[u8],
+) -> Result<()> {
+    let is_term = |b: u8| term.contains(&b);
+    let collide = |first: &str, second: &str, byte: u8| {
+        Err(Error::new(ErrorKind::InvalidConfig(format!(
+            "the {} and {} are both {:?}",
+            first, second, byte as char
+        ))))
+    };
+    if quote == Some(delimiter) {
+        return collide("delimiter", "quote", delimiter);
+    }
+    if escape == Some(delimiter) {
+        return collide("delimiter", "escape", delimiter);
+    }
+    if is_term(delimiter) {
+        return collide("delimiter", "terminator", delimiter);
+    }
+    if let Some(quote) = quote.filter(|&q| is_term(q)) {
+        return collide("quote", "terminator", quote);
+    }
+    if let Some(escape) = escape.filter(|&e| is_term(e)) {
+        return collide("escape", "terminator", escape);
+    }
+    Ok(())
+}
+
 /// The whitespace preservation behaviour when reading CSV data.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Trim {
@@ -283,6 +387,79 @@ impl Default for Trim {
     }
 }
 
+/// The normalization applied to header names before they are matched to
+/// struct fields when deserializing.
+///
+/// See
+/// [`ReaderBuilder::header_normalization`](struct.ReaderBuilder.html#method.header_normalization)
+/// for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub enum Normalization {
+    /// Header names are used as is. This is the default.
+    #[default]
+    None,
+    /// Trim whitespace from header names.
+    Trim,
+    /// Convert header names to lowercase.
+    Lowercase,
+    /// Trim whitespace from header names and convert them to lowercase.
+    All,
+}
+
+impl Normalization {
+    /// Apply this normalization to the given header name.
+    fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        let name = match *self {
+            Normalization::Trim | Normalization::All => name.trim(),
+            _ => name,
+        };
+        let lowercase =
+            *self == Normalization::Lowercase || *self == Normalization::All;
+        if lowercase
+            && name.chars().any(|c| c.is_alphabetic() && !c.is_lowercase())
+        {
+            Cow::Owned(name.to_lowercase())
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+}
+
+/// Where the header row is located in CSV data.
+///
+/// See
+/// [`ReaderBuilder::headers_position`](struct.ReaderBuilder.html#method.headers_position)
+/// for details.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum HeaderPosition {
+    /// The header row is the first row. This is the default.
+    #[default]
+    First,
+    /// The header row is the last row.
+    Last,
+}
+
+/// What a CSV reader does about a recoverable error.
+///
+/// This is returned by the handler set with
+/// [`ReaderBuilder::error_handler`](struct.ReaderBuilder.html#method.error_handler).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorAction {
+    /// Skip the offending record and read the next one.
+    Skip,
+    /// Return the error, as if no handler was set.
+    Abort,
+    /// Ignore the error and return the offending record anyway.
+    ///
+    /// A record whose length is unexpected is returned with the fields it
+    /// has. In a `StringRecord` that isn't valid UTF-8, each invalid
+    /// sequence is replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    Continue,
+}
+
 /// A custom Serde deserializer for possibly invalid `Option<T>` fields.
 ///
 /// When deserializing CSV data, it is sometimes desirable to simply ignore
@@ -343,3 +520,373 @@ where
 {
     Option::<T>::deserialize(de).or_else(|_| Ok(None))
 }
+
+/// A custom Serde serializer for writing `None` as a specific string.
+///
+/// By default, `None` is written as an empty field and `Some(value)` is
+/// written as `value`. This function writes `Some(value)` in the same way,
+/// but writes `None` as the string `none` instead.
+///
+/// Since Serde's `serialize_with` attribute cannot pass extra arguments, this
+/// is typically used via a small wrapper function that chooses the string to
+/// write.
+///
+/// # Example
+///
+/// This example shows how to write missing values as `NULL`.
+///
+/// ```
+/// use std::error::Error;
+///
+/// use serde::Serializer;
+///
+/// #[derive(serde::Serialize)]
+/// struct Row {
+///     #[serde(serialize_with = "null")]
+///     a: Option<i32>,
+///     #[serde(serialize_with = "null")]
+///     b: Option<i32>,
+///     c: Option<i32>,
+/// }
+///
+/// fn null<S: Serializer>(
+///     value: &Option<i32>,
+///     ser: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     csv::serialize_none_as(value, "NULL", ser)
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let mut wtr = csv::Writer::from_writer(vec![]);
+///     wtr.serialize(Row { a: Some(5), b: None, c: None })?;
+///
+///     let data = String::from_utf8(wtr.into_inner()?)?;
+///     assert_eq!(data, "a,b,c\n5,NULL,\n");
+///     Ok(())
+/// }
+/// ```
+pub fn serialize_none_as<T, S>(
+    value: &Option<T>,
+    none: &str,
+    ser: S,
+) -> result::Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match *value {
+        None => ser.serialize_str(none),
+        Some(ref value) => value.serialize(ser),
+    }
+}
+
+/// A custom Serde deserializer for fields containing a list of values.
+///
+/// This splits a single field on `sep` and parses each piece with
+/// `FromStr`. An empty field results in an empty list. Pieces are not
+/// trimmed, and if any piece fails to parse, then an error is returned.
+///
+/// Since Serde's `deserialize_with` attribute cannot pass extra arguments,
+/// this is typically used via a small wrapper function that chooses the
+/// separator. For the most common separators, [`split_pipe`](fn.split_pipe.html)
+/// and [`split_semicolon`](fn.split_semicolon.html) are provided.
+///
+/// # Example
+///
+/// This example shows how to read a field like `a/b/c` into a `Vec<String>`.
+///
+/// ```
+/// use std::error::Error;
+///
+/// use serde::Deserializer;
+///
+/// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+/// struct Row {
+///     name: String,
+///     #[serde(deserialize_with = "split_slash")]
+///     path: Vec<String>,
+/// }
+///
+/// fn split_slash<'de, D: Deserializer<'de>>(
+///     de: D,
+/// ) -> Result<Vec<String>, D::Error> {
+///     csv::split_field(de, '/')
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// name,path
+/// home,usr/local/bin
+/// ";
+///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+///     let row: Row = rdr.deserialize().next().unwrap()?;
+///     assert_eq!(row.path, vec!["usr", "local", "bin"]);
+///     Ok(())
+/// }
+/// ```
+pub fn split_field<'de, D, T>(
+    de: D,
+    sep: char,
+) -> result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let field = String::deserialize(de)?;
+    if field.is_empty() {
+        return Ok(vec![]);
+    }
+    field
+        .split(sep)
+        .map(|piece| {
+            piece.parse().map_err(|err| {
+                D::Error::custom(format!(
+                    "failed to parse list element {:?}: {}",
+                    piece, err
+                ))
+            })
+        })
+        .collect()
+}
+
+/// A custom Serde deserializer for fields containing a list of values
+/// separated by `|`.
+///
+/// This is equivalent to `split_field(de, '|')`. See
+/// [`split_field`](fn.split_field.html) for details.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+///
+/// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+/// struct Row {
+///     id: u32,
+///     #[serde(deserialize_with = "csv::split_pipe")]
+///     scores: Vec<i32>,
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// id,scores
+/// 1,1|2|3
+/// 2,
+/// ";
+///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+///     let rows = rdr.deserialize().collect::<Result<Vec<Row>, _>>()?;
+///     assert_eq!(rows[0], Row { id: 1, scores: vec![1, 2, 3] });
+///     assert_eq!(rows[1], Row { id: 2, scores: vec![] });
+///     Ok(())
+/// }
+/// ```
+pub fn split_pipe<'de, D, T>(de: D) -> result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    split_field(de, '|')
+}
+
+/// A custom Serde deserializer for fields containing a list of values
+/// separated by `;`.
+///
+/// This is equivalent to `split_field(de, ';')`. See
+/// [`split_field`](fn.split_field.html) for details.
+pub fn split_semicolon<'de, D, T>(de: D) -> result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    split_field(de, ';')
+}
+
+/// A custom Serde deserializer for decimal fields read as fixed-point
+/// integers.
+///
+/// This parses a decimal number like `-1.2345` and returns it multiplied by
+/// `10^scale`, as an `i64`. For example, with a scale of `4`, `1.2345` is
+/// read as `12345` and `7` is read as `70000`. This is useful for amounts
+/// of money, where floating point numbers would lose precision.
+///
+/// The number may have a leading `+` or `-` sign, and either its integer
+/// part or its fractional part (but not both) may be omitted, as in `.5` or
+/// `5.`. An error is returned if the field is empty, if it isn't a decimal
+/// number, if the result doesn't fit in an `i64` or if the number has more
+/// fractional digits than `scale`, since the value would otherwise have to
+/// be rounded. Trailing zeros beyond `scale` are permitted, since dropping
+/// them doesn't change the value.
+///
+/// Since Serde's `deserialize_with` attribute cannot pass extra arguments,
+/// this is typically used via a small wrapper function that chooses the
+/// scale. For the most common scales, [`fixed_point_2`](fn.fixed_point_2.html),
+/// [`fixed_point_4`](fn.fixed_point_4.html) and
+/// [`fixed_point_6`](fn.fixed_point_6.html) are provided.
+///
+/// # Panics
+///
+/// This panics if `scale` is greater than `18`, since `10^19` doesn't fit in
+/// an `i64`.
+///
+/// # Example
+///
+/// This example shows how to read prices in thousandths of a unit.
+///
+/// ```
+/// use std::error::Error;
+///
+/// use serde::Deserializer;
+///
+/// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+/// struct Row {
+///     item: String,
+///     #[serde(deserialize_with = "fixed_point_3")]
+///     price: i64,
+/// }
+///
+/// fn fixed_point_3<'de, D: Deserializer<'de>>(
+///     de: D,
+/// ) -> Result<i64, D::Error> {
+///     csv::fixed_point(de, 3)
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// item,price
+/// apple,1.25
+/// pear,-0.005
+/// ";
+///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+///     let rows = rdr.deserialize().collect::<Result<Vec<Row>, _>>()?;
+///     assert_eq!(rows[0].price, 1_250);
+///     assert_eq!(rows[1].price, -5);
+///     Ok(())
+/// }
+/// ```
+pub fn fixed_point<'de, D>(de: D, scale: u32) -> result::Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    assert!(scale <= 18, "fixed-point scale (is {}) should be <= 18", scale);
+    let field = String::deserialize(de)?;
+    parse_fixed_point(&field, scale).map_err(|err| {
+        D::Error::custom(format!(
+            "failed to parse fixed-point number {:?}: {}",
+            field, err
+        ))
+    })
+}
+
+/// A custom Serde deserializer for decimal fields read as fixed-point
+/// integers in hundredths.
+///
+/// This is equivalent to `fixed_point(de, 2)`. See
+/// [`fixed_point`](fn.fixed_point.html) for details.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+///
+/// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+/// struct Row {
+///     id: u32,
+///     #[serde(deserialize_with = "csv::fixed_point_2")]
+///     cents: i64,
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// id,cents
+/// 1,19.99
+/// 2,-3
+/// ";
+///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+///     let rows = rdr.deserialize().collect::<Result<Vec<Row>, _>>()?;
+///     assert_eq!(rows[0], Row { id: 1, cents: 1999 });
+///     assert_eq!(rows[1], Row { id: 2, cents: -300 });
+///     Ok(())
+/// }
+/// ```
+pub fn fixed_point_2<'de, D>(de: D) -> result::Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fixed_point(de, 2)
+}
+
+/// A custom Serde deserializer for decimal fields read as fixed-point
+/// integers in ten-thousandths.
+///
+/// This is equivalent to `fixed_point(de, 4)`. See
+/// [`fixed_point`](fn.fixed_point.html) for details.
+pub fn fixed_point_4<'de, D>(de: D) -> result::Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fixed_point(de, 4)
+}
+
+/// A custom Serde deserializer for decimal fields read as fixed-point
+/// integers in millionths.
+///
+/// This is equivalent to `fixed_point(de, 6)`. See
+/// [`fixed_point`](fn.fixed_point.html) for details.
+pub fn fixed_point_6<'de, D>(de: D) -> result::Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fixed_point(de, 6)
+}
+
+/// Parse a decimal number into an integer scaled by `10^scale`.
+fn parse_fixed_point(
+    field: &str,
+    scale: u32,
+) -> result::Result<i64, &'static str> {
+    let (negative, unsigned) = match field.as_bytes().first() {
+        Some(b'-') => (true, &field[1..]),
+        Some(b'+') => (false, &field[1..]),
+        _ => (false, field),
+    };
+    let (int, frac) = match unsigned.find('.') {
+        None => (unsigned, ""),
+        Some(i) => (&unsigned[..i], &unsigned[i + 1..]),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (int.is_empty() && frac.is_empty())
+        || !is_digits(int)
+        || !is_digits(frac)
+    {
+        return Err("invalid decimal number");
+    }
+    let frac = frac.trim_end_matches('0');
+    if frac.len() > scale as usize {
+        return Err("too many fractional digits");
+    }
+    let overflow = "number out of range";
+    // Accumulating in the negative direction permits reading i64::MIN.
+    let mut value: i64 = 0;
+    let digits = int.bytes().chain(frac.bytes());
+    let padding = scale as usize - frac.len();
+    for b in digits.chain(std::iter::repeat(b'0').take(padding)) {
+        let digit = i64::from(b - b'0');
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_sub(digit))
+            .ok_or(overflow)?;
+    }
+    if negative {
+        Ok(value)
+    } else {
+        value.checked_neg().ok_or(overflow)
+    }
+}
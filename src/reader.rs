@@ -1,21 +1,34 @@
 use std::{
+    cmp, fmt,
     fs::File,
-    io::{self, BufRead, Seek},
+    io,
     marker::PhantomData,
+    mem,
     path::Path,
-    result,
+    result, slice,
+    sync::{Arc, Mutex},
 };
 
 use {
-    csv_core::{Reader as CoreReader, ReaderBuilder as CoreReaderBuilder},
+    csv_core::{
+        FieldMeta, ReadCommentResult, Reader as CoreReader,
+        ReaderBuilder as CoreReaderBuilder, Terminator as CoreTerminator,
+    },
     serde::de::DeserializeOwned,
 };
 
 use crate::{
-    byte_record::{ByteRecord, Position},
-    error::{Error, ErrorKind, Result, Utf8Error},
+    byte_record::{ByteRecord, Position, CHECKSUM_INIT},
+    deserializer::{
+        deserialize_string_record_with, infer_column_type, DeserializeOptions,
+    },
+    error::{new_utf8_error, Error, ErrorKind, Result, Utf8Error},
+    sniff::{sniff_delimiter, sniff_has_headers},
     string_record::StringRecord,
-    {Terminator, Trim},
+    {
+        check_special_bytes, ColumnType, DetectedTerminator, ErrorAction,
+        HeaderPosition, Normalization, Terminator, Trim,
+    },
 };
 
 /// Builds a CSV reader with various configuration knobs.
@@ -28,7 +41,21 @@ pub struct ReaderBuilder {
     capacity: usize,
     flexible: bool,
     has_headers: bool,
+    headers_position: HeaderPosition,
     trim: Trim,
+    detect_delimiter: bool,
+    drop_trailing_empty_field: bool,
+    strict_eof: bool,
+    consistent_terminator: bool,
+    record_checksum: bool,
+    field_meta: bool,
+    infer_types: bool,
+    infer_floats: bool,
+    header_normalization: Normalization,
+    integer_radix_detection: bool,
+    missing_fields_as_empty: bool,
+    key_columns: Vec<usize>,
+    error_handler: Option<ErrorHandler>,
     /// The underlying CSV parser builder.
     ///
     /// We explicitly put this on the heap because CoreReaderBuilder embeds an
@@ -43,7 +70,21 @@ impl Default for ReaderBuilder {
             capacity: 8 * (1 << 10),
             flexible: false,
             has_headers: true,
+            headers_position: HeaderPosition::default(),
             trim: Trim::default(),
+            detect_delimiter: false,
+            drop_trailing_empty_field: false,
+            strict_eof: false,
+            consistent_terminator: false,
+            record_checksum: false,
+            field_meta: false,
+            infer_types: true,
+            infer_floats: true,
+            header_normalization: Normalization::None,
+            integer_radix_detection: false,
+            missing_fields_as_empty: false,
+            key_columns: vec![],
+            error_handler: None,
             builder: Box::new(CoreReaderBuilder::default()),
         }
     }
@@ -90,6 +131,9 @@ impl ReaderBuilder {
     /// If there was a problem opening the file at the given path, then this
     /// returns the corresponding error.
     ///
+    /// Like `try_from_reader`, this also returns an error if the
+    /// configuration is invalid.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -107,7 +151,9 @@ impl ReaderBuilder {
     /// }
     /// ```
     pub fn from_path<P: AsRef<Path>>(&self, path: P) -> Result<Reader<File>> {
-        Ok(Reader::new(self, File::open(path)?))
+        let rdr = Reader::new_seekable(self, self.capacity, File::open(path)?);
+        rdr.check_config()?;
+        Ok(rdr)
     }
 
     /// Build a CSV parser from this configuration that reads data from `rdr`.
@@ -115,6 +161,12 @@ impl ReaderBuilder {
     /// Note that the CSV reader is buffered automatically, so you should not
     /// wrap `rdr` in a buffered reader like `io::BufReader`.
     ///
+    /// This does not check the configuration. For example, if the delimiter
+    /// is the same as the quote character, then the reader is still built.
+    /// Use
+    /// [`try_from_reader`](struct.ReaderBuilder.html#method.try_from_reader)
+    /// to get an error for an invalid configuration instead.
+    ///
     /// # Example
     ///
     /// ```
@@ -137,7 +189,236 @@ impl ReaderBuilder {
     /// }
     /// ```
     pub fn from_reader<R: io::Read>(&self, rdr: R) -> Reader<R> {
-        Reader::new(self, rdr)
+        Reader::new(self, self.capacity, rdr)
+    }
+
+    /// Build a CSV parser from this configuration that reads data from `rdr`,
+    /// returning an error if the configuration is invalid.
+    ///
+    /// This is like `from_reader`, except it checks that the delimiter, quote,
+    /// escape and terminator bytes don't collide. Namely, an error is
+    /// returned if:
+    ///
+    /// * the delimiter is the same as the quote or escape character,
+    /// * or the delimiter, quote or escape character is also a record
+    ///   terminator.
+    ///
+    /// The quote and escape characters are ignored when quoting is disabled.
+    /// Note that `Terminator::CRLF` treats both `\r` and `\n` as
+    /// terminators.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ReaderBuilder;
+    ///
+    /// let result = ReaderBuilder::new()
+    ///     .delimiter(b'"')
+    ///     .try_from_reader("a,b,c".as_bytes());
+    /// assert!(result.is_err());
+    ///
+    /// let result = ReaderBuilder::new()
+    ///     .delimiter(b'"')
+    ///     .quoting(false)
+    ///     .try_from_reader("a,b,c".as_bytes());
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn try_from_reader<R: io::Read>(&self, rdr: R) -> Result<Reader<R>> {
+        let rdr = Reader::new(self, self.capacity, rdr);
+        rdr.check_config()?;
+        Ok(rdr)
+    }
+
+    /// Build a CSV parser from this configuration that reads data from a
+    /// seekable `rdr`.
+    ///
+    /// This is like `try_from_reader`, except that the reader returned
+    /// supports reading the header row from the end of the data, as
+    /// configured by
+    /// [`headers_position`](struct.ReaderBuilder.html#method.headers_position).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::{HeaderPosition, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "Boston,4628910\ncity,pop\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .headers_position(HeaderPosition::Last)
+    ///         .from_seekable_reader(io::Cursor::new(data))?;
+    ///     assert_eq!(rdr.headers()?, vec!["city", "pop"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_seekable_reader<R: io::Read + io::Seek>(
+        &self,
+        rdr: R,
+    ) -> Result<Reader<R>> {
+        let rdr = Reader::new_seekable(self, self.capacity, rdr);
+        rdr.check_config()?;
+        Ok(rdr)
+    }
+
+    /// Build a CSV parser from this configuration that reads data from `rdr`
+    /// using a buffer with the given capacity (in bytes).
+    ///
+    /// This is a shortcut for calling `buffer_capacity` followed by
+    /// `from_reader`, except that the capacity configured on this builder is
+    /// left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .from_reader_with_capacity(1 << 16, data.as_bytes());
+    ///     assert_eq!(rdr.buffer_capacity(), 1 << 16);
+    ///     for result in rdr.records() {
+    ///         let record = result?;
+    ///         println!("{:?}", record);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader_with_capacity<R: io::Read>(
+        &self,
+        capacity: usize,
+        rdr: R,
+    ) -> Reader<R> {
+        Reader::new(self, capacity, rdr)
+    }
+
+    /// Build a CSV parser from this configuration that reads data from an
+    /// already buffered reader.
+    ///
+    /// Unlike `from_reader`, `rdr` is not wrapped in another buffer. Instead,
+    /// the CSV parser reads directly out of `rdr`'s own buffer. This avoids
+    /// an extra buffer and copy when `rdr` is, for example, an
+    /// `io::BufReader` or a `&[u8]`.
+    ///
+    /// Since the reader has no buffer of its own, its
+    /// [`buffer_capacity`](struct.Reader.html#method.buffer_capacity) is
+    /// always `0`, and the `buffer_capacity` setting on this builder is
+    /// ignored.
+    ///
+    /// Like `from_reader`, this does not check the configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use std::io::BufReader;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let buffered = BufReader::new(data.as_bytes());
+    ///     let mut rdr = ReaderBuilder::new().from_buf_reader(buffered);
+    ///     assert_eq!(rdr.buffer_capacity(), 0);
+    ///     for result in rdr.records() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_buf_reader<R: io::BufRead>(&self, rdr: R) -> Reader<R> {
+        Reader::with_input(self, Input::buffered(rdr))
+    }
+
+    /// Build a CSV parser from this configuration that reads data from the
+    /// given string.
+    ///
+    /// This is a shortcut for `from_reader(data.as_bytes())`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .delimiter(b';')
+    ///         .from_string("city;pop\nBoston;4628910\n");
+    ///     let record = rdr.records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "4628910"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_string<'a>(&self, data: &'a str) -> Reader<&'a [u8]> {
+        self.from_bytes(data.as_bytes())
+    }
+
+    /// Build a CSV parser from this configuration that reads data from the
+    /// given bytes.
+    ///
+    /// This is a shortcut for `from_reader(data)`, which is useful when the
+    /// data isn't necessarily valid UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_bytes(b"Boston,\xFF\n");
+    ///     let record = rdr.byte_records().next().unwrap()?;
+    ///     assert_eq!(record, vec![&b"Boston"[..], &b"\xFF"[..]]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_bytes<'a>(&self, data: &'a [u8]) -> Reader<&'a [u8]> {
+        self.from_reader(data)
+    }
+
+    /// Build a CSV parser from this configuration that reads data from the
+    /// given owned bytes.
+    ///
+    /// This is a shortcut for `from_reader(io::Cursor::new(data))`. Since
+    /// the reader returned owns its data, it (and any iterator created from
+    /// it with `into_records`, `into_byte_records` or `into_deserialize`)
+    /// isn't tied to any borrow. See
+    /// [`Reader::from_vec`](struct.Reader.html#method.from_vec) for an
+    /// example of returning such an iterator from a function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = b"city;pop\nBoston;4628910\n".to_vec();
+    ///     let mut rdr = ReaderBuilder::new().delimiter(b';').from_vec(data);
+    ///     let record = rdr.records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "4628910"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_vec(&self, data: Vec<u8>) -> Reader<io::Cursor<Vec<u8>>> {
+        self.from_reader(io::Cursor::new(data))
     }
 
     /// The field delimiter to use when parsing CSV.
@@ -174,6 +455,53 @@ impl ReaderBuilder {
         self
     }
 
+    /// Whether to guess the field delimiter from the data.
+    ///
+    /// When enabled, a sample of the data is passed to
+    /// [`sniff_delimiter`](fn.sniff_delimiter.html) before the first record
+    /// is parsed. The sample is read until it contains at least one complete
+    /// line or 64 KiB of data, whichever comes first, or until the end of
+    /// the data. (When reading from a caller's buffered reader with
+    /// `from_buf_reader`, the sample is whatever that reader has buffered.)
+    /// If a delimiter is detected, then it replaces the delimiter set with
+    /// [`delimiter`](struct.ReaderBuilder.html#method.delimiter). Otherwise,
+    /// the configured delimiter is used.
+    ///
+    /// Detection is a heuristic, not a guarantee. See `sniff_delimiter` for
+    /// details on how candidates are chosen.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city;country;pop
+    /// Boston;United States;4628910
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .detect_delimiter(true)
+    ///         .from_reader(data.as_bytes());
+    ///
+    ///     if let Some(result) = rdr.records().next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn detect_delimiter(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.detect_delimiter = yes;
+        self
+    }
+
     /// Whether to treat the first row as a special header row.
     ///
     /// By default, the first row is treated as a special header row, which
@@ -229,6 +557,59 @@ impl ReaderBuilder {
         self
     }
 
+    /// Where the header row is located in the CSV data.
+    ///
+    /// By default, the header row is the first row. With
+    /// `HeaderPosition::Last`, it is the last row instead. Finding it
+    /// requires reading all of the data, so the first time the headers are
+    /// needed, the reader reads up to the end of the data and then seeks back
+    /// to where it started. Every row before the last one is then read as a
+    /// record, and the last row is never returned as a record.
+    ///
+    /// This requires a seekable reader, i.e., one built with `from_path` or
+    /// `from_seekable_reader`. Other readers return an error when the
+    /// headers are first needed.
+    ///
+    /// This has no effect when `has_headers` is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::{HeaderPosition, ReaderBuilder};
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     pop: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// Boston,4628910
+    /// Concord,42695
+    /// city,pop
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .headers_position(HeaderPosition::Last)
+    ///         .from_seekable_reader(io::Cursor::new(data))?;
+    ///     let rows = rdr.deserialize().collect::<Result<Vec<Row>, _>>()?;
+    ///     assert_eq!(rows, vec![
+    ///         Row { city: "Boston".to_string(), pop: 4628910 },
+    ///         Row { city: "Concord".to_string(), pop: 42695 },
+    ///     ]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn headers_position(
+        &mut self,
+        position: HeaderPosition,
+    ) -> &mut ReaderBuilder {
+        self.headers_position = position;
+        self
+    }
+
     /// Whether the number of fields in records is allowed to change or not.
     ///
     /// When disabled (which is the default), parsing CSV data will return an
@@ -328,6 +709,12 @@ impl ReaderBuilder {
     /// meeting the definition of ASCII whitespace are trimmed. ASCII
     /// whitespace characters correspond to the set `[\t\n\v\f\r ]`.
     ///
+    /// Trimming is applied to records before they are deserialized with
+    /// Serde. Since numeric types are parsed strictly, a field like ` 42 `
+    /// can only be deserialized into an integer when fields are trimmed,
+    /// e.g., with `Trim::Fields`. Note that this also trims the fields that
+    /// are deserialized into strings.
+    ///
     /// # Example
     ///
     /// This example shows what happens when all values are trimmed.
@@ -395,6 +782,49 @@ impl ReaderBuilder {
         self
     }
 
+    /// Strip a `\r` that immediately precedes a `\n` record terminator.
+    ///
+    /// This is useful when the record terminator is set to
+    /// `Terminator::Any(b'\n')` but the data uses `\r\n` line endings. In
+    /// that case, the last field of every record would otherwise end with a
+    /// `\r`. When enabled, a single `\r` immediately preceding the `\n` is
+    /// removed, unless it is inside a quoted field.
+    ///
+    /// This has no effect for any other record terminator. In particular,
+    /// the default terminator, `Terminator::CRLF`, already treats `\r\n` as
+    /// a single record terminator.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, Terminator};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "city,country,pop\r\nBoston,United States,4628910\r\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .terminator(Terminator::Any(b'\n'))
+    ///         .strip_trailing_cr(true)
+    ///         .from_reader(data.as_bytes());
+    ///
+    ///     assert_eq!(rdr.headers()?, vec!["city", "country", "pop"]);
+    ///     if let Some(result) = rdr.records().next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn strip_trailing_cr(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.builder.strip_trailing_cr(yes);
+        self
+    }
+
     /// The quote character to use when parsing CSV.
     ///
     /// The default is `b'"'`.
@@ -473,6 +903,17 @@ impl ReaderBuilder {
     /// This is enabled by default, but it may be disabled. When disabled,
     /// doubled quotes are not interpreted as escapes.
     ///
+    /// When disabled and no escape character is set with `escape`, there is
+    /// no way to represent a quote inside a quoted field. In that case, the
+    /// first quote after an opening quote always closes the quoted part of
+    /// the field. Any bytes following it, up to the next delimiter or record
+    /// terminator, are appended to the field verbatim, including any quotes.
+    /// For example, `"a"b",c` is parsed as the fields `ab"` and `c`, while
+    /// `"a"b,c"` is parsed as the fields `ab` and `c"`. This never results
+    /// in an error, so such data is silently read differently from how it
+    /// was likely intended. If the data may contain quotes inside quoted
+    /// fields, then either enable this option or set an escape character.
+    ///
     /// # Example
     ///
     /// ```
@@ -545,7 +986,11 @@ impl ReaderBuilder {
     /// The comment character to use when parsing CSV.
     ///
     /// If the start of a record begins with the byte given here, then that
-    /// line is ignored by the CSV parser.
+    /// line is ignored by the CSV parser. Only the first byte of a record is
+    /// checked, so a field that begins with the comment byte inside quotes
+    /// (e.g., `"#foo",bar`) is never treated as a comment. Similarly, when
+    /// quoting is enabled, a record that starts with a quote is never a
+    /// comment, even if the comment byte is the same as the quote byte.
     ///
     /// This is disabled by default.
     ///
@@ -580,11 +1025,15 @@ impl ReaderBuilder {
         self
     }
 
-    /// A convenience method for specifying a configuration to read ASCII
-    /// delimited text.
+    /// The line continuation character to use when parsing CSV.
     ///
-    /// This sets the delimiter and record terminator to the ASCII unit
-    /// separator (`\x1F`) and record separator (`\x1E`), respectively.
+    /// When a line ends with the byte given here, the byte and the line
+    /// terminator that follows it are removed, and the next line is parsed
+    /// as part of the same record. The continuation byte is only recognized
+    /// outside of quotes and only when it immediately precedes a line
+    /// terminator. Anywhere else, it is treated as a normal byte.
+    ///
+    /// This is disabled by default.
     ///
     /// # Example
     ///
@@ -595,2045 +1044,7790 @@ impl ReaderBuilder {
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city\x1Fcountry\x1Fpop\x1EBoston\x1FUnited States\x1F4628910";
+    /// city,note
+    /// Boston,a very \\
+    /// long note
+    /// ";
     ///     let mut rdr = ReaderBuilder::new()
-    ///         .ascii()
+    ///         .line_continuation(Some(b'\\'))
     ///         .from_reader(data.as_bytes());
     ///
     ///     if let Some(result) = rdr.records().next() {
     ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         assert_eq!(record, vec!["Boston", "a very long note"]);
     ///         Ok(())
     ///     } else {
     ///         Err(From::from("expected at least one record but got none"))
     ///     }
     /// }
     /// ```
-    pub fn ascii(&mut self) -> &mut ReaderBuilder {
-        self.builder.ascii();
-        self
-    }
-
-    /// Set the capacity (in bytes) of the buffer used in the CSV reader.
-    /// This defaults to a reasonable setting.
-    pub fn buffer_capacity(&mut self, capacity: usize) -> &mut ReaderBuilder {
-        self.capacity = capacity;
+    pub fn line_continuation(
+        &mut self,
+        continuation: Option<u8>,
+    ) -> &mut ReaderBuilder {
+        self.builder.line_continuation(continuation);
         self
     }
 
-    /// Enable or disable the NFA for parsing CSV.
+    /// Enable relaxed handling of quotes.
     ///
-    /// This is intended to be a debug option. The NFA is always slower than
-    /// the DFA.
-    #[doc(hidden)]
-    pub fn nfa(&mut self, yes: bool) -> &mut ReaderBuilder {
-        self.builder.nfa(yes);
-        self
-    }
-}
-
-/// A already configured CSV reader.
-///
-/// A CSV reader takes as input CSV data and transforms that into standard Rust
-/// values. The most flexible way to read CSV data is as a sequence of records,
-/// where a record is a sequence of fields and each field is a string. However,
-/// a reader can also deserialize CSV data into Rust types like `i64` or
-/// `(String, f64, f64, f64)` or even a custom struct automatically using
-/// Serde.
-///
-/// # Configuration
-///
-/// A CSV reader has a couple convenient constructor methods like `from_path`
-/// and `from_reader`. However, if you want to configure the CSV reader to use
-/// a different delimiter or quote character (among many other things), then
-/// you should use a [`ReaderBuilder`](struct.ReaderBuilder.html) to construct
-/// a `Reader`. For example, to change the field delimiter:
-///
-/// ```
-/// use std::error::Error;
-/// use csv::ReaderBuilder;
-///
-/// # fn main() { example().unwrap(); }
-/// fn example() -> Result<(), Box<dyn Error>> {
-///     let data = "\
-/// city;country;pop
-/// Boston;United States;4628910
-/// ";
-///     let mut rdr = ReaderBuilder::new()
-///         .delimiter(b';')
-///         .from_reader(data.as_bytes());
-///
-///     if let Some(result) = rdr.records().next() {
-///         let record = result?;
-///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-///         Ok(())
-///     } else {
-///         Err(From::from("expected at least one record but got none"))
-///     }
-/// }
-/// ```
-///
-/// # Error handling
-///
-/// In general, CSV *parsing* does not ever return an error. That is, there is
-/// no such thing as malformed CSV data. Instead, this reader will prioritize
-/// finding a parse over rejecting CSV data that it does not understand. This
-/// choice was inspired by other popular CSV parsers, but also because it is
-/// pragmatic. CSV data varies wildly, so even if the CSV data is malformed,
-/// it might still be possible to work with the data. In the land of CSV, there
-/// is no "right" or "wrong," only "right" and "less right."
-///
-/// With that said, a number of errors can occur while reading CSV data:
-///
-/// * By default, all records in CSV data must have the same number of fields.
-///   If a record is found with a different number of fields than a prior
-///   record, then an error is returned. This behavior can be disabled by
-///   enabling flexible parsing via the `flexible` method on
-///   [`ReaderBuilder`](struct.ReaderBuilder.html).
-/// * When reading CSV data from a resource (like a file), it is possible for
-///   reading from the underlying resource to fail. This will return an error.
-///   For subsequent calls to the `Reader` after encountering a such error
-///   (unless `seek` is used), it will behave as if end of file had been
-///   reached, in order to avoid running into infinite loops when still
-///   attempting to read the next record when one has errored.
-/// * When reading CSV data into `String` or `&str` fields (e.g., via a
-///   [`StringRecord`](struct.StringRecord.html)), UTF-8 is strictly
-///   enforced. If CSV data is invalid UTF-8, then an error is returned. If
-///   you want to read invalid UTF-8, then you should use the byte oriented
-///   APIs such as [`ByteRecord`](struct.ByteRecord.html). If you need explicit
-///   support for another encoding entirely, then you'll need to use another
-///   crate to transcode your CSV data to UTF-8 before parsing it.
-/// * When using Serde to deserialize CSV data into Rust types, it is possible
-///   for a number of additional errors to occur. For example, deserializing
-///   a field `xyz` into an `i32` field will result in an error.
-///
-/// For more details on the precise semantics of errors, see the
-/// [`Error`](enum.Error.html) type.
-#[derive(Debug)]
-pub struct Reader<R> {
-    /// The underlying CSV parser.
-    ///
-    /// We explicitly put this on the heap because CoreReader embeds an entire
-    /// DFA transition table, which along with other things, tallies up to
-    /// almost 500 bytes on the stack.
-    core: Box<CoreReader>,
-    /// The underlying reader.
-    rdr: io::BufReader<R>,
-    /// Various state tracking.
-    ///
-    /// There is more state embedded in the `CoreReader`.
-    state: ReaderState,
-}
-
-#[derive(Debug)]
-struct ReaderState {
-    /// When set, this contains the first row of any parsed CSV data.
+    /// A quote is only special at the very start of a field. A quote
+    /// anywhere else in an unquoted field, such as the inch mark in
+    /// `3" pipe,42`, is always read as part of the field. When this is
+    /// enabled, the same is true inside of quoted fields: a quote that isn't
+    /// followed by another quote, the delimiter or a record terminator is
+    /// read as a literal quote instead of closing the field. This matches
+    /// how some spreadsheet applications read CSV data.
     ///
-    /// This is always populated, regardless of whether `has_headers` is set.
-    headers: Option<Headers>,
-    /// When set, the first row of parsed CSV data is excluded from things
-    /// that read records, like iterators and `read_record`.
-    has_headers: bool,
-    /// When set, there is no restriction on the length of records. When not
-    /// set, every record must have the same number of fields, or else an error
-    /// is reported.
-    flexible: bool,
-    trim: Trim,
-    /// The number of fields in the first record parsed.
-    first_field_count: Option<u64>,
-    /// The current position of the parser.
+    /// This is disabled by default.
     ///
-    /// Note that this position is only observable by callers at the start
-    /// of a record. More granular positions are not supported.
-    cur_pos: Position,
-    /// Whether the first record has been read or not.
-    first: bool,
-    /// Whether the reader has been seeked or not.
-    seeked: bool,
-    /// Whether EOF of the underlying reader has been reached or not.
+    /// # Example
     ///
-    /// IO errors on the underlying reader will be considered as an EOF for
-    /// subsequent read attempts, as it would be incorrect to keep on trying
-    /// to read when the underlying reader has broken.
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
     ///
-    /// For clarity, having the best `Debug` impl and in case they need to be
-    /// treated differently at some point, we store whether the `EOF` is
-    /// considered because an actual EOF happened, or because we encoundered
-    /// an IO error.
-    /// This has no additional runtime cost.
-    eof: ReaderEofState,
-}
-
-/// Whether EOF of the underlying reader has been reached or not.
-///
-/// IO errors on the underlying reader will be considered as an EOF for
-/// subsequent read attempts, as it would be incorrect to keep on trying
-/// to read when the underlying reader has broken.
-///
-/// For clarity, having the best `Debug` impl and in case they need to be
-/// treated differently at some point, we store whether the `EOF` is
-/// considered because an actual EOF happened, or because we encoundered
-/// an IO error
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ReaderEofState {
-    NotEof,
-    Eof,
-    IOError,
-}
-
-/// Headers encapsulates any data associated with the headers of CSV data.
-///
-/// The headers always correspond to the first row.
-#[derive(Debug)]
-struct Headers {
-    /// The header, as raw bytes.
-    byte_record: ByteRecord,
-    /// The header, as valid UTF-8 (or a UTF-8 error).
-    string_record: result::Result<StringRecord, Utf8Error>,
-}
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// item,qty
+    /// 3\" pipe,42
+    /// \"1/2\" elbow, 90°\",7
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .relaxed_quotes(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records[0], vec!["3\" pipe", "42"]);
+    ///     assert_eq!(records[1], vec!["1/2\" elbow, 90°", "7"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn relaxed_quotes(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.builder.relaxed_quotes(yes);
+        self
+    }
 
-impl Reader<Reader<File>> {
-    /// Create a new CSV parser with a default configuration for the given
-    /// file path.
+    /// A convenience method for specifying a configuration to read ASCII
+    /// delimited text.
     ///
-    /// To customize CSV parsing, use a `ReaderBuilder`.
+    /// This sets the delimiter and record terminator to the ASCII unit
+    /// separator (`\x1F`) and record separator (`\x1E`), respectively.
     ///
     /// # Example
     ///
-    /// ```no_run
+    /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let mut rdr = Reader::from_path("foo.csv")?;
-    ///     for result in rdr.records() {
+    ///     let data = "\
+    /// city\x1Fcountry\x1Fpop\x1EBoston\x1FUnited States\x1F4628910";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .ascii()
+    ///         .from_reader(data.as_bytes());
+    ///
+    ///     if let Some(result) = rdr.records().next() {
     ///         let record = result?;
-    ///         println!("{:?}", record);
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
     ///     }
-    ///     Ok(())
     /// }
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<File>> {
-        ReaderBuilder::new().from_path(path)
-    }
-}
-
-impl<R: io::Read> Reader<R> {
-    /// Create a new CSV reader given a builder and a source of underlying
-    /// bytes.
-    fn new(builder: &ReaderBuilder, rdr: R) -> Reader<R> {
-        Reader {
-            core: Box::new(builder.builder.build()),
-            rdr: io::BufReader::with_capacity(builder.capacity, rdr),
-            state: ReaderState {
-                headers: None,
-                has_headers: builder.has_headers,
-                flexible: builder.flexible,
-                trim: builder.trim,
-                first_field_count: None,
-                cur_pos: Position::new(),
-                first: false,
-                seeked: false,
-                eof: ReaderEofState::NotEof,
-            },
-        }
+    pub fn ascii(&mut self) -> &mut ReaderBuilder {
+        self.builder.ascii();
+        self
     }
 
-    /// Create a new CSV parser with a default configuration for the given
-    /// reader.
+    /// A convenience method for specifying a configuration to read CSV data
+    /// produced by Microsoft Excel.
     ///
-    /// To customize CSV parsing, use a `ReaderBuilder`.
+    /// This sets the following knobs:
+    ///
+    /// * `delimiter(b',')`
+    /// * `terminator(Terminator::CRLF)`
+    /// * `quoting(true)`
+    /// * `quote(b'"')`
+    /// * `double_quote(true)`
+    /// * `escape(None)`
+    ///
+    /// Excel often writes a UTF-8 byte order mark at the start of the data.
+    /// This needs no configuration, since a leading UTF-8 BOM is always
+    /// stripped by the reader.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
-    /// Concord,United States,42695
-    /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///     for result in rdr.records() {
-    ///         let record = result?;
-    ///         println!("{:?}", record);
-    ///     }
+    ///     let data = "\u{FEFF}city,note\r\nBoston,\"say \"\"hi\"\"\"\r\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .excel()
+    ///         .from_reader(data.as_bytes());
+    ///
+    ///     assert_eq!(rdr.headers()?, vec!["city", "note"]);
+    ///     let record = rdr.records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "say \"hi\""]);
     ///     Ok(())
     /// }
     /// ```
-    pub fn from_reader(rdr: R) -> Reader<R> {
-        ReaderBuilder::new().from_reader(rdr)
+    pub fn excel(&mut self) -> &mut ReaderBuilder {
+        self.delimiter(b',')
+            .terminator(Terminator::CRLF)
+            .quoting(true)
+            .quote(b'"')
+            .double_quote(true)
+            .escape(None)
     }
 
-    /// Returns a borrowed iterator over deserialized records.
+    /// A convenience method for specifying a configuration to read CSV data
+    /// that strictly follows [RFC 4180](https://tools.ietf.org/html/rfc4180).
     ///
-    /// Each item yielded by this iterator is a `Result<D, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// This sets the same knobs as [`excel`](#method.excel), and in
+    /// addition:
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record. Additionally,
-    /// if `has_headers` is enabled, then deserializing into a struct will
-    /// automatically align the values in each row to the fields of a struct
-    /// based on the header row.
+    /// * `flexible(false)`
+    /// * `strict_eof(true)`
     ///
-    /// # Example
+    /// That is, every record must have the same number of fields, and a
+    /// quoted field that isn't closed before the end of the data is an
+    /// error. Note that `Terminator::CRLF` still accepts a lone `\r` or `\n`
+    /// as a record terminator, in addition to `\r\n`.
     ///
-    /// This shows how to deserialize CSV data into normal Rust structs. The
-    /// fields of the header row are used to match up the values in each row
-    /// to the fields of the struct.
+    /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    ///
-    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
-    /// struct Row {
-    ///     city: String,
-    ///     country: String,
-    ///     #[serde(rename = "popcount")]
-    ///     population: u64,
-    /// }
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,popcount
-    /// Boston,United States,4628910
-    /// ";
-    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.deserialize();
+    ///     let data = "city,pop\r\nBoston,\"4628910\r\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .rfc4180()
+    ///         .from_reader(data.as_bytes());
     ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record: Row = result?;
-    ///         assert_eq!(record, Row {
-    ///             city: "Boston".to_string(),
-    ///             country: "United States".to_string(),
-    ///             population: 4628910,
-    ///         });
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
+    ///     assert!(rdr.records().next().unwrap().is_err());
+    ///     Ok(())
     /// }
     /// ```
+    pub fn rfc4180(&mut self) -> &mut ReaderBuilder {
+        self.excel().flexible(false).strict_eof(true)
+    }
+
+    /// A convenience method for specifying a configuration to read CSV data
+    /// with Unix line endings.
     ///
-    /// # Rules
+    /// This sets the following knobs:
     ///
-    /// For the most part, any Rust type that maps straight-forwardly to a CSV
-    /// record is supported. This includes maps, structs, tuples and tuple
-    /// structs. Other Rust types, such as `Vec`s, arrays, and enums have
-    /// a more complicated story. In general, when working with CSV data, one
-    /// should avoid *nested sequences* as much as possible.
+    /// * `delimiter(b',')`
+    /// * `terminator(Terminator::Any(b'\n'))`
+    /// * `quoting(true)`
+    /// * `quote(b'"')`
+    /// * `double_quote(true)`
+    /// * `escape(None)`
     ///
-    /// Maps, structs, tuples and tuple structs map to CSV records in a simple
-    /// way. Tuples and tuple structs decode their fields in the order that
-    /// they are defined. Structs will do the same only if `has_headers` has
-    /// been disabled using [`ReaderBuilder`](struct.ReaderBuilder.html),
-    /// otherwise, structs and maps are deserialized based on the fields
-    /// defined in the header row. (If there is no header row, then
-    /// deserializing into a map will result in an error.)
+    /// Since only `\n` terminates records, a `\r` is always part of a field.
     ///
-    /// Nested sequences are supported in a limited capacity. Namely, they
-    /// are flattened. As a result, it's often useful to use a `Vec` to capture
-    /// a "tail" of fields in a record:
+    /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    ///
-    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
-    /// struct Row {
-    ///     label: String,
-    ///     values: Vec<i32>,
-    /// }
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "foo,1,2,3";
-    ///     let mut rdr = csv::ReaderBuilder::new()
-    ///         .has_headers(false)
+    ///     let data = "city,note\nBoston,a\rb\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .unix()
     ///         .from_reader(data.as_bytes());
-    ///     let mut iter = rdr.deserialize();
     ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record: Row = result?;
-    ///         assert_eq!(record, Row {
-    ///             label: "foo".to_string(),
-    ///             values: vec![1, 2, 3],
-    ///         });
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
+    ///     let record = rdr.records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "a\rb"]);
+    ///     Ok(())
     /// }
     /// ```
+    pub fn unix(&mut self) -> &mut ReaderBuilder {
+        self.delimiter(b',')
+            .terminator(Terminator::Any(b'\n'))
+            .quoting(true)
+            .quote(b'"')
+            .double_quote(true)
+            .escape(None)
+    }
+
+    /// Set the capacity (in bytes) of the buffer used in the CSV reader.
+    /// This defaults to a reasonable setting.
     ///
-    /// In the above example, adding another field to the `Row` struct after
-    /// the `values` field will result in a deserialization error. This is
-    /// because the deserializer doesn't know when to stop reading fields
-    /// into the `values` vector, so it will consume the rest of the fields in
-    /// the record leaving none left over for the additional field.
+    /// This is only the initial capacity. When a single record spans more
+    /// than two fills of the buffer, the buffer's capacity is doubled for
+    /// each additional fill, up to 1 MiB, so that very long records are read
+    /// with fewer, larger reads. The buffer never shrinks back afterwards.
+    pub fn buffer_capacity(&mut self, capacity: usize) -> &mut ReaderBuilder {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Treat a run of `n` or more consecutive blank lines as a separator
+    /// between groups of records.
     ///
-    /// Finally, simple enums in Rust can be deserialized as well. Namely,
-    /// enums must either be variants with no arguments or variants with a
-    /// single argument. Variants with no arguments are deserialized based on
-    /// which variant name the field matches. Variants with a single argument
-    /// are deserialized based on which variant can store the data. The latter
-    /// is only supported when using "untagged" enum deserialization. The
-    /// following example shows both forms in action:
+    /// Normally, blank lines in CSV data are skipped. When this is set to a
+    /// non-zero value, blank lines are still skipped, but if at least `n` of
+    /// them appear immediately before a record, then that record is
+    /// considered to start a new group. This can be observed via
+    /// [`Reader::is_group_start`](struct.Reader.html#method.is_group_start),
+    /// or groups can be read all at once with
+    /// [`Reader::read_group`](struct.Reader.html#method.read_group).
     ///
-    /// ```
-    /// use std::error::Error;
+    /// A blank line is a line consisting only of a record terminator. Blank
+    /// lines at the end of the data never separate groups.
     ///
-    /// #[derive(Debug, serde::Deserialize, PartialEq)]
-    /// struct Row {
-    ///     label: Label,
-    ///     value: Number,
-    /// }
+    /// Enabling this makes parsing slower, since it requires a slower
+    /// parsing strategy.
     ///
-    /// #[derive(Debug, serde::Deserialize, PartialEq)]
-    /// #[serde(rename_all = "lowercase")]
-    /// enum Label {
-    ///     Celsius,
-    ///     Fahrenheit,
-    /// }
+    /// This is disabled (set to `0`) by default.
     ///
-    /// #[derive(Debug, serde::Deserialize, PartialEq)]
-    /// #[serde(untagged)]
-    /// enum Number {
-    ///     Integer(i64),
-    ///     Float(f64),
-    /// }
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, StringRecord};
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// label,value
-    /// celsius,22.2222
-    /// fahrenheit,72
-    /// ";
-    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.deserialize();
+    /// city,pop
+    /// Boston,4628910
+    /// Concord,42695
     ///
-    ///     // Read the first record.
-    ///     if let Some(result) = iter.next() {
-    ///         let record: Row = result?;
-    ///         assert_eq!(record, Row {
-    ///             label: Label::Celsius,
-    ///             value: Number::Float(22.2222),
-    ///         });
-    ///     } else {
-    ///         return Err(From::from(
-    ///             "expected at least two records but got none"));
-    ///     }
     ///
-    ///     // Read the second record.
-    ///     if let Some(result) = iter.next() {
-    ///         let record: Row = result?;
-    ///         assert_eq!(record, Row {
-    ///             label: Label::Fahrenheit,
-    ///             value: Number::Integer(72),
-    ///         });
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from(
-    ///             "expected at least two records but got only one"))
-    ///     }
+    /// Paris,2161000
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .group_separator_blank_lines(2)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut group = vec![];
+    ///
+    ///     assert!(rdr.read_group(&mut group)?);
+    ///     assert_eq!(group, vec![
+    ///         StringRecord::from(vec!["Boston", "4628910"]),
+    ///         StringRecord::from(vec!["Concord", "42695"]),
+    ///     ]);
+    ///     assert!(rdr.read_group(&mut group)?);
+    ///     assert_eq!(group, vec![StringRecord::from(vec!["Paris", "2161000"])]);
+    ///     assert!(!rdr.read_group(&mut group)?);
+    ///     Ok(())
     /// }
     /// ```
-    pub fn deserialize<D>(&mut self) -> DeserializeRecordsIter<R, D>
-    where
-        D: DeserializeOwned,
-    {
-        DeserializeRecordsIter::new(self)
+    pub fn group_separator_blank_lines(
+        &mut self,
+        n: usize,
+    ) -> &mut ReaderBuilder {
+        self.builder.group_separator_blank_lines(n);
+        self
     }
 
-    /// Returns an owned iterator over deserialized records.
-    ///
-    /// Each item yielded by this iterator is a `Result<D, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// Whether to drop a single trailing empty field from each record.
     ///
-    /// This is mostly useful when you want to return a CSV iterator or store
-    /// it somewhere.
+    /// Some CSV exporters end some or all lines with a superfluous delimiter,
+    /// which results in a phantom empty field at the end of those records.
+    /// When enabled, if the last field of a record (including the header
+    /// row) is empty, then it is removed before the record's length is
+    /// checked. At most one field is removed, and a record consisting of a
+    /// single empty field is left untouched.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record. Additionally,
-    /// if `has_headers` is enabled, then deserializing into a struct will
-    /// automatically align the values in each row to the fields of a struct
-    /// based on the header row.
+    /// Note that this cannot distinguish a trailing delimiter from a
+    /// genuinely empty last field, including one written as `""`.
     ///
-    /// For more detailed deserialization rules, see the documentation on the
-    /// `deserialize` method.
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    ///
-    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
-    /// struct Row {
-    ///     city: String,
-    ///     country: String,
-    ///     #[serde(rename = "popcount")]
-    ///     population: u64,
-    /// }
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,popcount
-    /// Boston,United States,4628910
+    /// city,country,pop
+    /// Boston,United States,4628910,
+    /// Concord,United States,42695
     /// ";
-    ///     let rdr = csv::Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.into_deserialize();
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .drop_trailing_empty_field(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut iter = rdr.records();
     ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record: Row = result?;
-    ///         assert_eq!(record, Row {
-    ///             city: "Boston".to_string(),
-    ///             country: "United States".to_string(),
-    ///             population: 4628910,
-    ///         });
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
+    ///     let record = iter.next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     let record = iter.next().unwrap()?;
+    ///     assert_eq!(record, vec!["Concord", "United States", "42695"]);
+    ///     assert!(iter.next().is_none());
+    ///     Ok(())
     /// }
     /// ```
-    pub fn into_deserialize<D>(self) -> DeserializeRecordsIntoIter<R, D>
-    where
-        D: DeserializeOwned,
-    {
-        DeserializeRecordsIntoIter::new(self)
+    pub fn drop_trailing_empty_field(
+        &mut self,
+        yes: bool,
+    ) -> &mut ReaderBuilder {
+        self.drop_trailing_empty_field = yes;
+        self
     }
 
-    /// Returns a borrowed iterator over all records as strings.
+    /// Whether to report an error when the CSV data ends inside a quoted
+    /// field.
     ///
-    /// Each item yielded by this iterator is a `Result<StringRecord, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// By default, the parser always finds a parse, so if the data ends
+    /// before the closing quote of a field, then the field simply ends at
+    /// the end of the data. This makes it impossible to tell truncated data
+    /// (e.g., a file that is still being written) apart from complete data.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record.
+    /// When enabled, reaching the end of the data inside a quoted field
+    /// returns an `UnexpectedEof` error instead of the final record. The
+    /// error's position is the position of the truncated record.
+    ///
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::{ErrorKind, ReaderBuilder};
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// city,country
+    /// Boston,\"United States
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.records();
-    ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .strict_eof(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let err = rdr.records().next().unwrap().unwrap_err();
+    ///     match *err.kind() {
+    ///         ErrorKind::UnexpectedEof { ref pos } => {
+    ///             assert_eq!(pos.as_ref().unwrap().line(), 2);
+    ///         }
+    ///         ref wrong => panic!("unexpected error: {:?}", wrong),
     ///     }
+    ///     Ok(())
     /// }
     /// ```
-    pub fn records(&mut self) -> StringRecordsIter<R> {
-        StringRecordsIter::new(self)
+    pub fn strict_eof(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.strict_eof = yes;
+        self
     }
 
-    /// Returns an owned iterator over all records as strings.
+    /// Whether to report an error when records are terminated by more than
+    /// one kind of line ending.
     ///
-    /// Each item yielded by this iterator is a `Result<StringRecord, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// Data that mixes `\n` and `\r\n` line endings is usually the result of
+    /// concatenating files from different sources. When enabled, the first
+    /// record whose line ending differs from those of the records before it
+    /// returns an `InconsistentTerminator` error. The error's position is
+    /// the position of that record. Reading may continue after the error, in
+    /// which case it is reported again only for a third kind of line ending.
     ///
-    /// This is mostly useful when you want to return a CSV iterator or store
-    /// it somewhere.
+    /// Line endings are tracked in the same way as
+    /// [`Reader::detected_terminator`](struct.Reader.html#method.detected_terminator).
+    /// In particular, with the default `Terminator::CRLF`, whether a record
+    /// terminated by `\r` is followed by `\n` is only known once the next
+    /// record is read. So the error for such a record is returned by the
+    /// next read instead, and the read after that returns the next record.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record.
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::{DetectedTerminator, ErrorKind, ReaderBuilder};
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
-    /// ";
-    ///     let rdr = Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.into_records();
-    ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
+    ///     let data = "city,pop\r\nBoston,4628910\nConcord,42695\r\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .require_consistent_terminator(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let err = rdr.records().next().unwrap().unwrap_err();
+    ///     match *err.kind() {
+    ///         ErrorKind::InconsistentTerminator {
+    ///             ref pos, expected, found,
+    ///         } => {
+    ///             assert_eq!(pos.as_ref().unwrap().record(), 1);
+    ///             assert_eq!(expected, DetectedTerminator::CRLF);
+    ///             assert_eq!(found, DetectedTerminator::LF);
+    ///         }
+    ///         ref wrong => panic!("unexpected error: {:?}", wrong),
     ///     }
+    ///     Ok(())
     /// }
     /// ```
-    pub fn into_records(self) -> StringRecordsIntoIter<R> {
-        StringRecordsIntoIter::new(self)
+    pub fn require_consistent_terminator(
+        &mut self,
+        yes: bool,
+    ) -> &mut ReaderBuilder {
+        self.consistent_terminator = yes;
+        self
     }
 
-    /// Returns a borrowed iterator over all records as raw bytes.
+    /// Enable or disable a rolling checksum over the records read.
     ///
-    /// Each item yielded by this iterator is a `Result<ByteRecord, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// When enabled, the reader folds every record returned by
+    /// [`Reader::read_byte_record`](struct.Reader.html#method.read_byte_record)
+    /// (and the methods and iterators built on it, such as `read_record` and
+    /// `deserialize`) into a checksum, which is available via
+    /// [`Reader::checksum`](struct.Reader.html#method.checksum). The header
+    /// row is only included if it is returned as a record, i.e., when
+    /// `has_headers` is disabled. Records for which `read_byte_record`
+    /// returns an error are not included.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record.
+    /// The checksum is computed in the same way as
+    /// [`ByteRecord::checksum`](struct.ByteRecord.html#method.checksum),
+    /// except that it continues across records. Two streams of records have
+    /// the same checksum (with high probability) if and only if they have
+    /// the same records in the same order.
+    ///
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
-    /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.byte_records();
-    ///
-    ///     if let Some(result) = iter.next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
+    ///     let checksum = |data: &str| -> Result<u64, Box<dyn Error>> {
+    ///         let mut rdr = ReaderBuilder::new()
+    ///             .record_checksum(true)
+    ///             .from_reader(data.as_bytes());
+    ///         for result in rdr.records() {
+    ///             let _record = result?;
+    ///             // ... process the record ...
+    ///         }
+    ///         Ok(rdr.checksum().unwrap())
+    ///     };
+    ///     let before = checksum("city,pop\nBoston,4628910\n")?;
+    ///     // Quoting doesn't change the checksum, but data does.
+    ///     assert_eq!(before, checksum("city,pop\n\"Boston\",4628910\n")?);
+    ///     assert_ne!(before, checksum("city,pop\nBoston,4628911\n")?);
+    ///     Ok(())
     /// }
     /// ```
-    pub fn byte_records(&mut self) -> ByteRecordsIter<R> {
-        ByteRecordsIter::new(self)
+    pub fn record_checksum(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.record_checksum = yes;
+        self
     }
 
-    /// Returns an owned iterator over all records as raw bytes.
+    /// Enable or disable recording how each field appeared in the CSV data.
     ///
-    /// Each item yielded by this iterator is a `Result<ByteRecord, Error>`.
-    /// Therefore, in order to access the record, callers must handle the
-    /// possibility of error (typically with `try!` or `?`).
+    /// When enabled, every record read also stores a
+    /// [`FieldMeta`](struct.FieldMeta.html) for each of its fields, which
+    /// says whether the field was quoted and whether it contained any
+    /// escaped quotes. It is available via
+    /// [`ByteRecord::field_meta`](struct.ByteRecord.html#method.field_meta)
+    /// and
+    /// [`StringRecord::field_meta`](struct.StringRecord.html#method.field_meta).
+    /// This is useful for tools that rewrite CSV data and want to preserve
+    /// its original quoting.
     ///
-    /// This is mostly useful when you want to return a CSV iterator or store
-    /// it somewhere.
+    /// Tracking this metadata makes parsing a bit slower.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this does not include the first record.
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::Reader;
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
-    /// ";
-    ///     let rdr = Reader::from_reader(data.as_bytes());
-    ///     let mut iter = rdr.into_byte_records();
-    ///
-    ///     if let Some(result) = iter.next() {
+    ///     let data = "city,pop\n\"Boston\",4628910\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .field_meta(true)
+    ///         .from_reader(data.as_bytes());
+    ///     for result in rdr.records() {
     ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
+    ///         assert!(record.field_meta(0).unwrap().quoted());
+    ///         assert!(!record.field_meta(1).unwrap().quoted());
     ///     }
+    ///     Ok(())
     /// }
     /// ```
-    pub fn into_byte_records(self) -> ByteRecordsIntoIter<R> {
-        ByteRecordsIntoIter::new(self)
+    pub fn field_meta(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.field_meta = yes;
+        self
     }
 
-    /// Returns a reference to the first row read by this parser.
-    ///
-    /// If no row has been read yet, then this will force parsing of the first
-    /// row.
+    /// Whether the types of fields are inferred when deserializing.
     ///
-    /// If there was a problem parsing the row or if it wasn't valid UTF-8,
-    /// then this returns an error.
+    /// Type inference only occurs when the target type doesn't say what it
+    /// expects, which is the case for untagged enums and `#[serde(flatten)]`
+    /// fields. By default, such a field is inferred as a boolean, an integer
+    /// or a float if it parses as one, and as a string otherwise. When this
+    /// is disabled, such fields are always inferred as strings (or as bytes,
+    /// if they aren't valid UTF-8).
     ///
-    /// If the underlying reader emits EOF before any data, then this returns
-    /// an empty record.
+    /// This is useful for collecting the columns that don't match any named
+    /// field of a struct into a flattened map of strings. Serde buffers
+    /// those columns with their inferred types, so a column like `42.3`
+    /// can't otherwise be deserialized into a `String` in the map. Named
+    /// fields of the struct are unaffected, since they are deserialized
+    /// according to their own types. Conversely, numbers and booleans
+    /// nested inside an untagged enum or a flattened struct can no longer be
+    /// deserialized when this is disabled.
     ///
-    /// Note that this method may be used regardless of whether `has_headers`
-    /// was enabled (but it is enabled by default).
+    /// This is enabled by default.
     ///
     /// # Example
     ///
-    /// This example shows how to get the header row of CSV data. Notice that
-    /// the header row does not appear as a record in the iterator!
-    ///
     /// ```
-    /// use std::error::Error;
-    /// use csv::Reader;
+    /// use std::{collections::HashMap, error::Error};
+    ///
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Row {
+    ///     city: String,
+    ///     pop: u64,
+    ///     #[serde(flatten)]
+    ///     extra: HashMap<String, String>,
+    /// }
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// city,lat,pop,country
+    /// Boston,42.36,4628910,United States
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///
-    ///     // We can read the headers before iterating.
-    ///     {
-    ///         // `headers` borrows from the reader, so we put this in its
-    ///         // own scope. That way, the borrow ends before we try iterating
-    ///         // below. Alternatively, we could clone the headers.
-    ///         let headers = rdr.headers()?;
-    ///         assert_eq!(headers, vec!["city", "country", "pop"]);
-    ///     }
-    ///
-    ///     if let Some(result) = rdr.records().next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///     } else {
-    ///         return Err(From::from(
-    ///             "expected at least one record but got none"))
-    ///     }
+    ///     let mut rdr = csv::ReaderBuilder::new()
+    ///         .infer_types(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize();
     ///
-    ///     // We can also read the headers after iterating.
-    ///     let headers = rdr.headers()?;
-    ///     assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     let row: Row = iter.next().unwrap()?;
+    ///     assert_eq!(row.city, "Boston");
+    ///     assert_eq!(row.pop, 4628910);
+    ///     assert_eq!(row.extra.len(), 2);
+    ///     assert_eq!(row.extra["lat"], "42.36");
+    ///     assert_eq!(row.extra["country"], "United States");
     ///     Ok(())
     /// }
     /// ```
-    pub fn headers(&mut self) -> Result<&StringRecord> {
-        if self.state.headers.is_none() {
-            let mut record = ByteRecord::new();
-            self.read_byte_record_impl(&mut record)?;
-            self.set_headers_impl(Err(record));
-        }
-        let headers = self.state.headers.as_ref().unwrap();
-        match headers.string_record {
-            Ok(ref record) => Ok(record),
-            Err(ref err) => Err(Error::new(ErrorKind::Utf8 {
-                pos: headers.byte_record.position().map(Clone::clone),
-                err: err.clone(),
-            })),
-        }
+    pub fn infer_types(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.infer_types = yes;
+        self
     }
 
-    /// Returns a reference to the first row read by this parser as raw bytes.
+    /// Whether fields may be inferred as floating point numbers when
+    /// deserializing.
     ///
-    /// If no row has been read yet, then this will force parsing of the first
-    /// row.
+    /// Type inference only occurs when the target type doesn't say what it
+    /// expects, which is the case for untagged enums and `#[serde(flatten)]`
+    /// fields. By default, a field that isn't a boolean or an integer but
+    /// parses as an `f64` is inferred as a float. When this is disabled,
+    /// such fields are inferred as strings instead, which preserves their
+    /// exact contents. Booleans and integers are still inferred either way.
     ///
-    /// If there was a problem parsing the row then this returns an error.
+    /// Note that this has no effect on fields whose type asks for a float,
+    /// such as `f32` or `f64`. Those are always parsed directly from the
+    /// field's contents. Conversely, an `f64` nested inside an untagged enum
+    /// or a flattened field can no longer be deserialized when this is
+    /// disabled, since the field is presented to it as a string.
     ///
-    /// If the underlying reader emits EOF before any data, then this returns
-    /// an empty record.
+    /// Regardless of this setting, integers that are too big for a 64-bit
+    /// integer type are always inferred as strings, since converting them to
+    /// a float would silently lose precision.
     ///
-    /// Note that this method may be used regardless of whether `has_headers`
-    /// was enabled (but it is enabled by default).
+    /// This is enabled by default.
     ///
     /// # Example
     ///
-    /// This example shows how to get the header row of CSV data. Notice that
-    /// the header row does not appear as a record in the iterator!
-    ///
     /// ```
-    /// use std::error::Error;
-    /// use csv::Reader;
+    /// use std::{collections::HashMap, error::Error};
+    ///
+    /// #[derive(Debug, serde::Deserialize, PartialEq)]
+    /// #[serde(untagged)]
+    /// enum Value {
+    ///     Bool(bool),
+    ///     Integer(i64),
+    ///     Float(f64),
+    ///     String(String),
+    /// }
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// id,price,active
+    /// 42,1.10,true
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///
-    ///     // We can read the headers before iterating.
-    ///     {
-    ///         // `headers` borrows from the reader, so we put this in its
-    ///         // own scope. That way, the borrow ends before we try iterating
-    ///         // below. Alternatively, we could clone the headers.
-    ///         let headers = rdr.byte_headers()?;
-    ///         assert_eq!(headers, vec!["city", "country", "pop"]);
-    ///     }
-    ///
-    ///     if let Some(result) = rdr.byte_records().next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///     } else {
-    ///         return Err(From::from(
-    ///             "expected at least one record but got none"))
-    ///     }
+    ///     let mut rdr = csv::ReaderBuilder::new()
+    ///         .infer_floats(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize();
     ///
-    ///     // We can also read the headers after iterating.
-    ///     let headers = rdr.byte_headers()?;
-    ///     assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     let row: HashMap<String, Value> = iter.next().unwrap()?;
+    ///     assert_eq!(row["id"], Value::Integer(42));
+    ///     assert_eq!(row["price"], Value::String("1.10".to_string()));
+    ///     assert_eq!(row["active"], Value::Bool(true));
     ///     Ok(())
     /// }
     /// ```
-    pub fn byte_headers(&mut self) -> Result<&ByteRecord> {
-        if self.state.headers.is_none() {
-            let mut record = ByteRecord::new();
-            self.read_byte_record_impl(&mut record)?;
-            self.set_headers_impl(Err(record));
-        }
-        Ok(&self.state.headers.as_ref().unwrap().byte_record)
+    pub fn infer_floats(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.infer_floats = yes;
+        self
     }
 
-    /// Set the headers of this CSV parser manually.
+    /// The normalization to apply to header names when deserializing.
     ///
-    /// This overrides any other setting (including `set_byte_headers`). Any
-    /// automatic detection of headers is disabled. This may be called at any
-    /// time.
+    /// When deserializing records into a struct, the header names are
+    /// matched against the struct's field names (after any renaming done
+    /// by Serde attributes such as `rename_all`). By default, they must
+    /// match exactly, so a header like `Population ` doesn't match a field
+    /// named `population`. A normalization such as `Normalization::All`
+    /// trims surrounding whitespace from header names and converts them to
+    /// lowercase before matching, which makes such headers match. Note that
+    /// the struct's field names are never normalized, so they should be
+    /// written (or renamed) in the normalized form, e.g., in lowercase.
+    ///
+    /// Normalization also applies to the keys of maps, such as a
+    /// `HashMap<String, String>`, that records are deserialized into. It
+    /// has no effect on the header row itself, as returned by
+    /// [`Reader::headers`](struct.Reader.html#method.headers). Header names
+    /// that aren't valid UTF-8 are never normalized.
+    ///
+    /// This is `Normalization::None` by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::{Reader, StringRecord};
+    /// use csv::{Normalization, ReaderBuilder};
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     population: u64,
+    /// }
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// City,Population \n\
+    /// Boston,4628910
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///
-    ///     assert_eq!(rdr.headers()?, vec!["city", "country", "pop"]);
-    ///     rdr.set_headers(StringRecord::from(vec!["a", "b", "c"]));
-    ///     assert_eq!(rdr.headers()?, vec!["a", "b", "c"]);
-    ///
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .header_normalization(Normalization::All)
+    ///         .from_reader(data.as_bytes());
+    ///     let row: Row = rdr.deserialize().next().unwrap()?;
+    ///     assert_eq!(row, Row {
+    ///         city: "Boston".to_string(),
+    ///         population: 4628910,
+    ///     });
     ///     Ok(())
     /// }
     /// ```
-    pub fn set_headers(&mut self, headers: StringRecord) {
-        self.set_headers_impl(Ok(headers));
+    pub fn header_normalization(
+        &mut self,
+        normalization: Normalization,
+    ) -> &mut ReaderBuilder {
+        self.header_normalization = normalization;
+        self
     }
 
-    /// Set the headers of this CSV parser manually as raw bytes.
+    /// Whether to detect radix prefixes when deserializing integers.
     ///
-    /// This overrides any other setting (including `set_headers`). Any
-    /// automatic detection of headers is disabled. This may be called at any
-    /// time.
+    /// When enabled, a field deserialized into an integer type may use a
+    /// `0x` (hexadecimal), `0o` (octal) or `0b` (binary) prefix, in either
+    /// case. The prefix may be preceded by a sign, e.g., `-0x1F` is `-31`.
+    /// Fields without a prefix are parsed as decimal integers, as usual.
+    ///
+    /// This is disabled by default, in which case integers are parsed as
+    /// decimal, with the exception of a lowercase `0x` prefix without a
+    /// sign, which is always recognized.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::{Reader, ByteRecord};
+    /// use csv::ReaderBuilder;
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// register,value,flags
+    /// r1,-0x1F,0b1010
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///
-    ///     assert_eq!(rdr.byte_headers()?, vec!["city", "country", "pop"]);
-    ///     rdr.set_byte_headers(ByteRecord::from(vec!["a", "b", "c"]));
-    ///     assert_eq!(rdr.byte_headers()?, vec!["a", "b", "c"]);
-    ///
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .integer_radix_detection(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let row: (String, i32, u8) = rdr.deserialize().next().unwrap()?;
+    ///     assert_eq!(row, ("r1".to_string(), -31, 10));
     ///     Ok(())
     /// }
     /// ```
-    pub fn set_byte_headers(&mut self, headers: ByteRecord) {
-        self.set_headers_impl(Err(headers));
-    }
-
-    fn set_headers_impl(
+    pub fn integer_radix_detection(
         &mut self,
-        headers: result::Result<StringRecord, ByteRecord>,
-    ) {
-        // If we have string headers, then get byte headers. But if we have
-        // byte headers, then get the string headers (or a UTF-8 error).
-        let (mut str_headers, mut byte_headers) = match headers {
-            Ok(string) => {
-                let bytes = string.clone().into_byte_record();
-                (Ok(string), bytes)
-            }
-            Err(bytes) => {
-                match StringRecord::from_byte_record(bytes.clone()) {
-                    Ok(str_headers) => (Ok(str_headers), bytes),
-                    Err(err) => (Err(err.utf8_error().clone()), bytes),
-                }
-            }
-        };
-        if self.state.trim.should_trim_headers() {
-            if let Ok(ref mut str_headers) = str_headers.as_mut() {
-                str_headers.trim();
-            }
-            byte_headers.trim();
-        }
-        self.state.headers = Some(Headers {
-            byte_record: byte_headers,
-            string_record: str_headers,
-        });
+        yes: bool,
+    ) -> &mut ReaderBuilder {
+        self.integer_radix_detection = yes;
+        self
     }
 
-    /// Read a single row into the given record. Returns false when no more
-    /// records could be read.
+    /// Whether to deserialize missing trailing fields as empty fields.
     ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this will never read the first record.
+    /// When `flexible` is enabled, a record may have fewer fields than the
+    /// header row. By default, deserializing such a record by header name
+    /// fails for any column past the end of the record, unless the
+    /// corresponding value is an `Option`. When this is enabled, such a
+    /// record is deserialized as if it had one field per header, with every
+    /// missing field being empty. For example, missing columns deserialize
+    /// to `None` for `Option` values and to empty strings for `String`
+    /// values, and maps have an entry for every header.
     ///
-    /// This method is useful when you want to read records as fast as
-    /// as possible. It's less ergonomic than an iterator, but it permits the
-    /// caller to reuse the `StringRecord` allocation, which usually results
-    /// in higher throughput.
+    /// This only applies when deserializing with a header row. Without one,
+    /// fields missing from the end of a record are always treated as absent,
+    /// so that `Option` and `#[serde(default)]` values can fill them in.
     ///
-    /// Records read via this method are guaranteed to have a position set
-    /// on them, even if the reader is at EOF or if an error is returned.
+    /// This is disabled by default.
     ///
     /// # Example
     ///
     /// ```
     /// use std::error::Error;
-    /// use csv::{Reader, StringRecord};
+    /// use csv::ReaderBuilder;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     country: String,
+    ///     population: Option<u64>,
+    /// }
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// city,country,population
+    /// Boston
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
-    ///     let mut record = StringRecord::new();
-    ///
-    ///     if rdr.read_record(&mut record)? {
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .flexible(true)
+    ///         .missing_fields_as_empty(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let row: Row = rdr.deserialize().next().unwrap()?;
+    ///     assert_eq!(row, Row {
+    ///         city: "Boston".to_string(),
+    ///         country: "".to_string(),
+    ///         population: None,
+    ///     });
+    ///     Ok(())
     /// }
     /// ```
-    pub fn read_record(&mut self, record: &mut StringRecord) -> Result<bool> {
-        let result = record.read(self);
-        // We need to trim again because trimming string records includes
-        // Unicode whitespace. (ByteRecord trimming only includes ASCII
-        // whitespace.)
-        if self.state.trim.should_trim_fields() {
-            record.trim();
-        }
-        result
+    pub fn missing_fields_as_empty(
+        &mut self,
+        yes: bool,
+    ) -> &mut ReaderBuilder {
+        self.missing_fields_as_empty = yes;
+        self
     }
 
-    /// Read a single row into the given byte record. Returns false when no
-    /// more records could be read.
-    ///
-    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
-    /// default), then this will never read the first record.
+    /// Set the indices of the columns that make up the key of each record.
     ///
-    /// This method is useful when you want to read records as fast as
-    /// as possible. It's less ergonomic than an iterator, but it permits the
-    /// caller to reuse the `ByteRecord` allocation, which usually results
-    /// in higher throughput.
+    /// The key columns don't change how records are read. Instead, they are
+    /// remembered by the reader so that
+    /// [`Reader::record_key`](struct.Reader.html#method.record_key) can
+    /// build a key for any record read from it. This is useful for
+    /// deduplicating, sorting or looking up records by a subset of their
+    /// columns. See
+    /// [`ByteRecord::key`](struct.ByteRecord.html#method.key) for how keys
+    /// are encoded.
     ///
-    /// Records read via this method are guaranteed to have a position set
-    /// on them, even if the reader is at EOF or if an error is returned.
+    /// By default, there are no key columns, so every record has the same
+    /// (empty) key.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::error::Error;
-    /// use csv::{ByteRecord, Reader};
+    /// use std::{collections::HashSet, error::Error};
+    /// use csv::{ByteRecord, ReaderBuilder};
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,pop
-    /// Boston,United States,4628910
+    /// city,state,pop,year
+    /// Boston,MA,4628910,2020
+    /// Concord,NH,42695,2020
+    /// Boston,MA,4552402,2010
     /// ";
-    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .key_columns(&[0, 1])
+    ///         .from_reader(data.as_bytes());
     ///     let mut record = ByteRecord::new();
-    ///
-    ///     if rdr.read_byte_record(&mut record)? {
-    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
+    ///     let mut seen = HashSet::new();
+    ///     let mut pops = vec![];
+    ///     while rdr.read_byte_record(&mut record)? {
+    ///         if seen.insert(rdr.record_key(&record)) {
+    ///             pops.push(record[2].to_vec());
+    ///         }
     ///     }
+    ///     assert_eq!(pops, vec![b"4628910".to_vec(), b"42695".to_vec()]);
+    ///     Ok(())
     /// }
     /// ```
-    pub fn read_byte_record(
-        &mut self,
-        record: &mut ByteRecord,
-    ) -> Result<bool> {
-        if !self.state.seeked && !self.state.has_headers && !self.state.first {
-            // If the caller indicated "no headers" and we haven't yielded the
-            // first record yet, then we should yield our header row if we have
-            // one.
-            if let Some(ref headers) = self.state.headers {
-                self.state.first = true;
-                record.clone_from(&headers.byte_record);
-                if self.state.trim.should_trim_fields() {
-                    record.trim();
-                }
-                return Ok(!record.is_empty());
-            }
-        }
-        let ok = self.read_byte_record_impl(record)?;
-        self.state.first = true;
-        if !self.state.seeked && self.state.headers.is_none() {
-            self.set_headers_impl(Err(record.clone()));
-            // If the end user indicated that we have headers, then we should
-            // never return the first row. Instead, we should attempt to
-            // read and return the next one.
-            if self.state.has_headers {
-                let result = self.read_byte_record_impl(record);
-                if self.state.trim.should_trim_fields() {
-                    record.trim();
-                }
-                return result;
-            }
-        }
-        if self.state.trim.should_trim_fields() {
-            record.trim();
-        }
-        Ok(ok)
-    }
-
-    /// Read a byte record from the underlying CSV reader, without accounting
-    /// for headers.
-    #[inline(always)]
-    fn read_byte_record_impl(
-        &mut self,
-        record: &mut ByteRecord,
-    ) -> Result<bool> {
-        use csv_core::ReadRecordResult::*;
-
-        record.clear();
-        record.set_position(Some(self.state.cur_pos.clone()));
-        if self.state.eof != ReaderEofState::NotEof {
-            return Ok(false);
-        }
-        let (mut outlen, mut endlen) = (0, 0);
-        loop {
-            let (res, nin, nout, nend) = {
-                let input_res = self.rdr.fill_buf();
-                if input_res.is_err() {
-                    self.state.eof = ReaderEofState::IOError;
-                }
-                let input = input_res?;
-                let (fields, ends) = record.as_parts();
-                self.core.read_record(
-                    input,
-                    &mut fields[outlen..],
-                    &mut ends[endlen..],
-                )
-            };
-            self.rdr.consume(nin);
-            let byte = self.state.cur_pos.byte();
-            self.state
-                .cur_pos
-                .set_byte(byte + nin as u64)
-                .set_line(self.core.line());
-            outlen += nout;
-            endlen += nend;
-            match res {
-                InputEmpty => continue,
-                OutputFull => {
-                    record.expand_fields();
-                    continue;
-                }
-                OutputEndsFull => {
-                    record.expand_ends();
-                    continue;
-                }
-                Record => {
-                    record.set_len(endlen);
-                    self.state.add_record(record)?;
-                    return Ok(true);
-                }
-                End => {
-                    self.state.eof = ReaderEofState::Eof;
-                    return Ok(false);
-                }
-            }
-        }
+    pub fn key_columns(&mut self, columns: &[usize]) -> &mut ReaderBuilder {
+        self.key_columns = columns.to_vec();
+        self
     }
 
-    /// Return the current position of this CSV reader.
-    ///
-    /// The byte offset in the position returned can be used to `seek` this
-    /// reader. In particular, seeking to a position returned here on the same
-    /// data will result in parsing the same subsequent record.
-    ///
-    /// # Example: reading the position
+    /// Set a handler that decides what to do about recoverable errors.
     ///
-    /// ```
-    /// use std::{error::Error, io};
-    /// use csv::{Reader, Position};
+    /// The handler is called with each recoverable error as it occurs, and
+    /// the [`ErrorAction`](enum.ErrorAction.html) it returns decides whether
+    /// the offending record is skipped, the error is returned (which is what
+    /// happens when no handler is set) or the record is returned anyway. The
+    /// recoverable errors are:
     ///
-    /// # fn main() { example().unwrap(); }
-    /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,popcount
-    /// Boston,United States,4628910
-    /// Concord,United States,42695
-    /// ";
-    ///     let rdr = Reader::from_reader(io::Cursor::new(data));
-    ///     let mut iter = rdr.into_records();
-    ///     let mut pos = Position::new();
-    ///     loop {
-    ///         // Read the position immediately before each record.
-    ///         let next_pos = iter.reader().position().clone();
-    ///         if iter.next().is_none() {
-    ///             break;
-    ///         }
-    ///         pos = next_pos;
-    ///     }
+    /// * [`UnequalLengths`](enum.ErrorKind.html#variant.UnequalLengths),
+    ///   when `flexible` is disabled and a record's length differs from
+    ///   that of the first record.
+    /// * [`Utf8`](enum.ErrorKind.html#variant.Utf8), when a record read as a
+    ///   `StringRecord` isn't valid UTF-8.
     ///
-    ///     // `pos` should now be the position immediately before the last
-    ///     // record.
-    ///     assert_eq!(pos.byte(), 51);
-    ///     assert_eq!(pos.line(), 3);
-    ///     assert_eq!(pos.record(), 2);
-    ///     Ok(())
-    /// }
-    /// ```
-    pub fn position(&self) -> &Position {
-        &self.state.cur_pos
-    }
-
-    /// Returns true if and only if this reader has been exhausted.
+    /// Every other error, such as an I/O error, is always returned without
+    /// calling the handler. The handler is consulted by every method that
+    /// reads records, except for `read_raw_record`.
     ///
-    /// When this returns true, no more records can be read from this reader
-    /// (unless it has been seeked to another position).
+    /// The handler must be `Send`, so that readers remain `Send`. All of the
+    /// readers built from this builder share the same handler.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::{error::Error, io};
-    /// use csv::{Reader, Position};
+    /// use std::error::Error;
+    /// use csv::{ErrorAction, ReaderBuilder, StringRecord};
     ///
     /// # fn main() { example().unwrap(); }
     /// fn example() -> Result<(), Box<dyn Error>> {
     ///     let data = "\
-    /// city,country,popcount
+    /// city,country,pop
     /// Boston,United States,4628910
-    /// Concord,United States,42695
+    /// Concord,United States
+    /// Springfield,United States,30720
     /// ";
-    ///     let mut rdr = Reader::from_reader(io::Cursor::new(data));
-    ///     assert!(!rdr.is_done());
-    ///     for result in rdr.records() {
-    ///         let _ = result?;
-    ///     }
-    ///     assert!(rdr.is_done());
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .error_handler(Box::new(|err| {
+    ///             eprintln!("skipping record: {}", err);
+    ///             ErrorAction::Skip
+    ///         }))
+    ///         .from_reader(data.as_bytes());
+    ///     let records = rdr.records().collect::<Result<Vec<StringRecord>, _>>()?;
+    ///     assert_eq!(records.len(), 2);
+    ///     assert_eq!(&records[1][0], "Springfield");
     ///     Ok(())
     /// }
     /// ```
-    pub fn is_done(&self) -> bool {
-        self.state.eof != ReaderEofState::NotEof
-    }
-
-    /// Returns true if and only if this reader has been configured to
-    /// interpret the first record as a header record.
-    pub fn has_headers(&self) -> bool {
-        self.state.has_headers
-    }
-
-    /// Returns a reference to the underlying reader.
-    pub fn get_ref(&self) -> &R {
-        self.rdr.get_ref()
-    }
-
-    /// Returns a mutable reference to the underlying reader.
-    pub fn get_mut(&mut self) -> &mut R {
-        self.rdr.get_mut()
+    pub fn error_handler(
+        &mut self,
+        handler: Box<dyn FnMut(&Error) -> ErrorAction + Send>,
+    ) -> &mut ReaderBuilder {
+        self.error_handler = Some(ErrorHandler(Arc::new(Mutex::new(handler))));
+        self
     }
 
-    /// Unwraps this CSV reader, returning the underlying reader.
+    /// Enable or disable the NFA for parsing CSV.
     ///
-    /// Note that any leftover data inside this reader's internal buffer is
-    /// lost.
-    pub fn into_inner(self) -> R {
-        self.rdr.into_inner()
+    /// This is intended to be a debug option. The NFA is always slower than
+    /// the DFA.
+    #[doc(hidden)]
+    pub fn nfa(&mut self, yes: bool) -> &mut ReaderBuilder {
+        self.builder.nfa(yes);
+        self
     }
 }
 
-impl<R: io::Read + io::Seek> Reader<R> {
-    /// Seeks the underlying reader to the position given.
-    ///
-    /// This comes with a few caveats:
-    ///
-    /// * Any internal buffer associated with this reader is cleared.
-    /// * If the given position does not correspond to a position immediately
-    ///   before the start of a record, then the behavior of this reader is
-    ///   unspecified.
-    /// * Any special logic that skips the first record in the CSV reader
-    ///   when reading or iterating over records is disabled.
-    ///
-    /// If the given position has a byte offset equivalent to the current
-    /// position, then no seeking is performed.
+/// A already configured CSV reader.
+///
+/// A CSV reader takes as input CSV data and transforms that into standard Rust
+/// values. The most flexible way to read CSV data is as a sequence of records,
+/// where a record is a sequence of fields and each field is a string. However,
+/// a reader can also deserialize CSV data into Rust types like `i64` or
+/// `(String, f64, f64, f64)` or even a custom struct automatically using
+/// Serde.
+///
+/// # Configuration
+///
+/// A CSV reader has a couple convenient constructor methods like `from_path`
+/// and `from_reader`. However, if you want to configure the CSV reader to use
+/// a different delimiter or quote character (among many other things), then
+/// you should use a [`ReaderBuilder`](struct.ReaderBuilder.html) to construct
+/// a `Reader`. For example, to change the field delimiter:
+///
+/// ```
+/// use std::error::Error;
+/// use csv::ReaderBuilder;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// city;country;pop
+/// Boston;United States;4628910
+/// ";
+///     let mut rdr = ReaderBuilder::new()
+///         .delimiter(b';')
+///         .from_reader(data.as_bytes());
+///
+///     if let Some(result) = rdr.records().next() {
+///         let record = result?;
+///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+///         Ok(())
+///     } else {
+///         Err(From::from("expected at least one record but got none"))
+///     }
+/// }
+/// ```
+///
+/// # Error handling
+///
+/// In general, CSV *parsing* does not ever return an error. That is, there is
+/// no such thing as malformed CSV data. Instead, this reader will prioritize
+/// finding a parse over rejecting CSV data that it does not understand. This
+/// choice was inspired by other popular CSV parsers, but also because it is
+/// pragmatic. CSV data varies wildly, so even if the CSV data is malformed,
+/// it might still be possible to work with the data. In the land of CSV, there
+/// is no "right" or "wrong," only "right" and "less right."
+///
+/// With that said, a number of errors can occur while reading CSV data:
+///
+/// * By default, all records in CSV data must have the same number of fields.
+///   If a record is found with a different number of fields than a prior
+///   record, then an error is returned. This behavior can be disabled by
+///   enabling flexible parsing via the `flexible` method on
+///   [`ReaderBuilder`](struct.ReaderBuilder.html).
+/// * When reading CSV data from a resource (like a file), it is possible for
+///   reading from the underlying resource to fail. This will return an error.
+///   For subsequent calls to the `Reader` after encountering a such error
+///   (unless `seek` is used), it will behave as if end of file had been
+///   reached, in order to avoid running into infinite loops when still
+///   attempting to read the next record when one has errored.
+/// * When reading CSV data into `String` or `&str` fields (e.g., via a
+///   [`StringRecord`](struct.StringRecord.html)), UTF-8 is strictly
+///   enforced. If CSV data is invalid UTF-8, then an error is returned. If
+///   you want to read invalid UTF-8, then you should use the byte oriented
+///   APIs such as [`ByteRecord`](struct.ByteRecord.html). If you need explicit
+///   support for another encoding entirely, then you'll need to use another
+///   crate to transcode your CSV data to UTF-8 before parsing it.
+/// * When using Serde to deserialize CSV data into Rust types, it is possible
+///   for a number of additional errors to occur. For example, deserializing
+///   a field `xyz` into an `i32` field will result in an error.
+///
+/// For more details on the precise semantics of errors, see the
+/// [`Error`](enum.Error.html) type.
+#[derive(Debug)]
+pub struct Reader<R> {
+    /// The underlying CSV parser.
     ///
-    /// If the header row has not already been read, then this will attempt
-    /// to read the header row before seeking. Therefore, it is possible that
-    /// this returns an error associated with reading CSV data.
+    /// We explicitly put this on the heap because CoreReader embeds an entire
+    /// DFA transition table, which along with other things, tallies up to
+    /// almost 500 bytes on the stack.
+    core: Box<CoreReader>,
+    /// The underlying reader.
+    rdr: Input<R>,
+    /// Various state tracking.
     ///
-    /// Note that seeking is performed based only on the byte offset in the
-    /// given position. Namely, the record or line numbers in the position may
-    /// be incorrect, but this will cause any future position generated by
-    /// this CSV reader to be similarly incorrect.
+    /// There is more state embedded in the `CoreReader`.
+    state: ReaderState,
+    /// Reads the header row from the end of the data, if the underlying
+    /// reader is seekable.
+    read_footer: Option<ReadFooterFn<R>>,
+}
+
+#[derive(Debug)]
+struct ReaderState {
+    /// When set, this contains the first row of any parsed CSV data.
     ///
-    /// # Example: seek to parse a record twice
+    /// This is always populated, regardless of whether `has_headers` is set.
+    headers: Option<Headers>,
+    /// When set, the first row of parsed CSV data is excluded from things
+    /// that read records, like iterators and `read_record`.
+    has_headers: bool,
+    /// Where the header row is located, when `has_headers` is set.
+    headers_position: HeaderPosition,
+    /// When the header row was read from the end of the data, this is the
+    /// byte offset where it starts. Reading stops there.
+    footer: Option<u64>,
+    /// When set, there is no restriction on the length of records. When not
+    /// set, every record must have the same number of fields, or else an error
+    /// is reported.
+    flexible: bool,
+    trim: Trim,
+    /// The number of fields in the first record parsed.
+    first_field_count: Option<u64>,
+    /// The current position of the parser.
     ///
-    /// ```
-    /// use std::{error::Error, io};
-    /// use csv::{Reader, Position};
+    /// Note that this position is only observable by callers at the start
+    /// of a record. More granular positions are not supported.
+    cur_pos: Position,
+    /// Whether the first record has been read or not.
+    first: bool,
+    /// Whether the reader has been seeked or not.
+    seeked: bool,
+    /// Whether EOF of the underlying reader has been reached or not.
     ///
-    /// # fn main() { example().unwrap(); }
-    /// fn example() -> Result<(), Box<dyn Error>> {
-    ///     let data = "\
-    /// city,country,popcount
-    /// Boston,United States,4628910
-    /// Concord,United States,42695
-    /// ";
-    ///     let rdr = Reader::from_reader(io::Cursor::new(data));
-    ///     let mut iter = rdr.into_records();
-    ///     let mut pos = Position::new();
-    ///     loop {
-    ///         // Read the position immediately before each record.
-    ///         let next_pos = iter.reader().position().clone();
-    ///         if iter.next().is_none() {
-    ///             break;
-    ///         }
-    ///         pos = next_pos;
-    ///     }
+    /// IO errors on the underlying reader will be considered as an EOF for
+    /// subsequent read attempts, as it would be incorrect to keep on trying
+    /// to read when the underlying reader has broken.
     ///
-    ///     // Now seek the reader back to `pos`. This will let us read the
-    ///     // last record again.
-    ///     iter.reader_mut().seek(pos)?;
-    ///     let mut iter = iter.into_reader().into_records();
-    ///     if let Some(result) = iter.next() {
-    ///         let record = result?;
-    ///         assert_eq!(record, vec!["Concord", "United States", "42695"]);
-    ///         Ok(())
-    ///     } else {
-    ///         Err(From::from("expected at least one record but got none"))
-    ///     }
-    /// }
-    /// ```
-    pub fn seek(&mut self, pos: Position) -> Result<()> {
-        self.byte_headers()?;
-        self.state.seeked = true;
-        if pos.byte() == self.state.cur_pos.byte() {
-            return Ok(());
-        }
-        self.rdr.seek(io::SeekFrom::Start(pos.byte()))?;
-        self.core.reset();
-        self.core.set_line(pos.line());
-        self.state.cur_pos = pos;
-        self.state.eof = ReaderEofState::NotEof;
-        Ok(())
+    /// For clarity, having the best `Debug` impl and in case they need to be
+    /// treated differently at some point, we store whether the `EOF` is
+    /// considered because an actual EOF happened, or because we encoundered
+    /// an IO error.
+    /// This has no additional runtime cost.
+    eof: ReaderEofState,
+    /// Whether the most recently read record was preceded by a group
+    /// separator.
+    group_start: bool,
+    /// The first record of the next group, if `read_group` has read it.
+    group_next: Option<StringRecord>,
+    /// Whether to drop a trailing empty field from each record.
+    drop_trailing_empty_field: bool,
+    /// Whether reaching EOF inside a quoted field is an error.
+    strict_eof: bool,
+    /// Whether records terminated by more than one kind of line ending are
+    /// an error.
+    consistent_terminator: bool,
+    /// Options that control how records are deserialized.
+    deserialize: DeserializeOptions,
+    /// The indices of the columns that make up a record's key.
+    key_columns: Vec<usize>,
+    /// The handler deciding what to do about recoverable errors, if any.
+    error_handler: Option<ErrorHandler>,
+    /// When delimiter detection is enabled, this contains the parser
+    /// configuration to rebuild with the detected delimiter.
+    sniff: Option<Box<CoreReaderBuilder>>,
+    /// Whether delimiter detection has been attempted.
+    sniffed: bool,
+    /// The raw bytes of the first row, if it was read as the headers while
+    /// `has_headers` is disabled. It is taken by `read_raw_record`.
+    raw_first: Option<Vec<u8>>,
+    /// A record reused by `read_raw_record` for parsing.
+    raw_scratch: Option<ByteRecord>,
+    /// The total number of bytes consumed from the underlying reader. Unlike
+    /// `cur_pos`, this is never reset by seeking.
+    bytes_consumed: u64,
+    /// The rolling checksum of the records read so far, when enabled.
+    checksum: Option<u64>,
+    /// Whether to record the metadata of each field read.
+    field_meta: bool,
+    /// The kinds of line endings that terminated the records read so far,
+    /// as a set of `TERM_*` bits.
+    terms_seen: u8,
+    /// Set to the position of the last record read when it was terminated
+    /// by `\r`, but it isn't known yet whether a `\n` follows.
+    pending_cr: Option<Position>,
+    /// The byte that terminated the last record read, if any.
+    last_term: Option<u8>,
+    /// Whether the `\r` that terminated the last record read is known to be
+    /// followed by a `\n`.
+    last_crlf: bool,
+    /// The row that caused the most recent `UnequalLengths` error. Its
+    /// allocation is reused across errors.
+    unequal_record: Option<ByteRecord>,
+}
+
+/// A handler for recoverable errors, shared by all of the readers built from
+/// the same builder.
+#[derive(Clone)]
+struct ErrorHandler(Arc<Mutex<ErrorHandlerFn>>);
+
+type ErrorHandlerFn = Box<dyn FnMut(&Error) -> ErrorAction + Send>;
+
+/// A function that reads the header row from the end of the data.
+type ReadFooterFn<R> = fn(&mut Reader<R>) -> Result<()>;
+
+impl ErrorHandler {
+    fn call(&self, err: &Error) -> ErrorAction {
+        // If the handler panicked before, then it's up to the handler to
+        // cope with whatever state it was left in.
+        let mut handler = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        handler(err)
     }
+}
 
-    /// This is like `seek`, but provides direct control over how the seeking
-    /// operation is performed via `io::SeekFrom`.
-    ///
-    /// The `pos` position given *should* correspond the position indicated
-    /// by `seek_from`, but there is no requirement. If the `pos` position
-    /// given is incorrect, then the position information returned by this
-    /// reader will be similarly incorrect.
-    ///
-    /// If the header row has not already been read, then this will attempt
-    /// to read the header row before seeking. Therefore, it is possible that
-    /// this returns an error associated with reading CSV data.
-    ///
-    /// Unlike `seek`, this will always cause an actual seek to be performed.
-    pub fn seek_raw(
-        &mut self,
-        seek_from: io::SeekFrom,
-        pos: Position,
-    ) -> Result<()> {
-        self.byte_headers()?;
-        self.state.seeked = true;
-        self.rdr.seek(seek_from)?;
-        self.core.reset();
-        self.core.set_line(pos.line());
-        self.state.cur_pos = pos;
-        self.state.eof = ReaderEofState::NotEof;
-        Ok(())
+impl fmt::Debug for ErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorHandler(..)")
     }
 }
 
-impl ReaderState {
-    #[inline(always)]
-    fn add_record(&mut self, record: &ByteRecord) -> Result<()> {
-        let i = self.cur_pos.record();
-        self.cur_pos.set_record(i.checked_add(1).unwrap());
-        if !self.flexible {
-            match self.first_field_count {
-                None => self.first_field_count = Some(record.len() as u64),
-                Some(expected) => {
-                    if record.len() as u64 != expected {
-                        return Err(Error::new(ErrorKind::UnequalLengths {
-                            pos: record.position().map(Clone::clone),
-                            expected_len: expected,
-                            len: record.len() as u64,
-                        }));
-                    }
-                }
-            }
-        }
-        Ok(())
+const TERM_LF: u8 = 1 << 0;
+const TERM_CRLF: u8 = 1 << 1;
+const TERM_CR: u8 = 1 << 2;
+
+/// Convert a set of `TERM_*` bits to the line ending they describe.
+fn detected_terminator(terms: u8) -> Option<DetectedTerminator> {
+    match terms {
+        0 => None,
+        TERM_LF => Some(DetectedTerminator::LF),
+        TERM_CRLF => Some(DetectedTerminator::CRLF),
+        TERM_CR => Some(DetectedTerminator::CR),
+        _ => Some(DetectedTerminator::Mixed),
     }
 }
 
-/// An owned iterator over deserialized records.
+/// Whether EOF of the underlying reader has been reached or not.
 ///
-/// The type parameter `R` refers to the underlying `io::Read` type, and `D`
-/// refers to the type that this iterator will deserialize a record into.
-pub struct DeserializeRecordsIntoIter<R, D> {
-    rdr: Reader<R>,
-    rec: StringRecord,
-    headers: Option<StringRecord>,
-    _priv: PhantomData<D>,
+/// IO errors on the underlying reader will be considered as an EOF for
+/// subsequent read attempts, as it would be incorrect to keep on trying
+/// to read when the underlying reader has broken.
+///
+/// For clarity, having the best `Debug` impl and in case they need to be
+/// treated differently at some point, we store whether the `EOF` is
+/// considered because an actual EOF happened, or because we encoundered
+/// an IO error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReaderEofState {
+    NotEof,
+    Eof,
+    IOError,
 }
 
-impl<R: io::Read, D: DeserializeOwned> DeserializeRecordsIntoIter<R, D> {
-    fn new(mut rdr: Reader<R>) -> DeserializeRecordsIntoIter<R, D> {
-        let headers = if !rdr.state.has_headers {
-            None
-        } else {
-            rdr.headers().ok().map(Clone::clone)
-        };
-        DeserializeRecordsIntoIter {
-            rdr,
-            rec: StringRecord::new(),
-            headers,
-            _priv: PhantomData,
+/// The largest capacity that a reader's buffer grows to on its own.
+const MAX_GROWN_CAPACITY: usize = 1 << 20;
+
+/// The number of bytes after which the sample used to detect the delimiter
+/// is complete, even if it doesn't contain a complete line.
+const SNIFF_SAMPLE_LEN: usize = 1 << 16;
+
+/// The source of bytes for a CSV reader.
+///
+/// Normally, the underlying reader is wrapped in a `GrowableBufReader`. But
+/// if the caller already provided a buffered reader, then we read out of its
+/// buffer directly. Since `Reader<R>` only requires `R: io::Read`, we can't
+/// call `BufRead` methods on `R` generically, so the relevant methods are
+/// captured as function pointers when the reader is built.
+#[derive(Debug)]
+enum Input<R> {
+    Unbuffered(GrowableBufReader<R>),
+    Buffered {
+        rdr: R,
+        fill_buf: fn(&mut R) -> io::Result<&[u8]>,
+        consume: fn(&mut R, usize),
+    },
+}
+
+impl<R: io::Read> Input<R> {
+    fn unbuffered(capacity: usize, rdr: R) -> Input<R> {
+        Input::Unbuffered(GrowableBufReader::with_capacity(capacity, rdr))
+    }
+
+    fn buffered(rdr: R) -> Input<R>
+    where
+        R: io::BufRead,
+    {
+        Input::Buffered {
+            rdr,
+            fill_buf: <R as io::BufRead>::fill_buf,
+            consume: <R as io::BufRead>::consume,
+        }
+    }
+
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match *self {
+            Input::Unbuffered(ref mut rdr) => rdr.fill_buf(),
+            Input::Buffered { ref mut rdr, fill_buf, .. } => fill_buf(rdr),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match *self {
+            Input::Unbuffered(ref mut rdr) => rdr.consume(amt),
+            Input::Buffered { ref mut rdr, consume, .. } => consume(rdr, amt),
+        }
+    }
+
+    /// Fill the buffer until it contains a line terminator or at least `min`
+    /// bytes, or until EOF, and return its contents without consuming them.
+    ///
+    /// More data can't be read out of a caller's buffered reader without
+    /// consuming what it has buffered, so in that case, this is the same as
+    /// `fill_buf`.
+    fn fill_sample(&mut self, min: usize) -> io::Result<&[u8]> {
+        if let Input::Unbuffered(ref mut rdr) = *self {
+            loop {
+                let buf = rdr.fill_buf()?;
+                if buf.len() >= min
+                    || memchr::memchr2(b'\n', b'\r', buf).is_some()
+                    || !rdr.fill_more()?
+                {
+                    break;
+                }
+            }
+        }
+        self.fill_buf()
+    }
+
+    fn capacity(&self) -> usize {
+        match *self {
+            Input::Unbuffered(ref rdr) => rdr.capacity(),
+            Input::Buffered { .. } => 0,
+        }
+    }
+
+    /// Discard any data in this reader's own buffer.
+    fn discard_buffer(&mut self) {
+        if let Input::Unbuffered(ref mut rdr) = *self {
+            rdr.discard_buffer();
+        }
+    }
+
+    /// Double the capacity of this reader's own buffer, unless it has
+    /// already reached `MAX_GROWN_CAPACITY`.
+    fn grow(&mut self) {
+        if let Input::Unbuffered(ref mut rdr) = *self {
+            rdr.grow();
+        }
+    }
+
+    fn get_ref(&self) -> &R {
+        match *self {
+            Input::Unbuffered(ref rdr) => rdr.get_ref(),
+            Input::Buffered { ref rdr, .. } => rdr,
+        }
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        match *self {
+            Input::Unbuffered(ref mut rdr) => rdr.get_mut(),
+            Input::Buffered { ref mut rdr, .. } => rdr,
+        }
+    }
+
+    fn into_inner(self) -> R {
+        match self {
+            Input::Unbuffered(rdr) => rdr.into_inner(),
+            Input::Buffered { rdr, .. } => rdr,
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek> Input<R> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        match *self {
+            Input::Unbuffered(ref mut rdr) => rdr.seek(pos),
+            Input::Buffered { ref mut rdr, .. } => rdr.seek(pos),
+        }
+    }
+}
+
+/// A buffered reader like `io::BufReader`, except that its buffer can grow.
+struct GrowableBufReader<R> {
+    rdr: R,
+    buf: Vec<u8>,
+    /// The start of the unconsumed data in `buf`.
+    pos: usize,
+    /// The end of the data read into `buf`.
+    filled: usize,
+}
+
+impl<R: io::Read> GrowableBufReader<R> {
+    fn with_capacity(capacity: usize, rdr: R) -> GrowableBufReader<R> {
+        GrowableBufReader { rdr, buf: vec![0; capacity], pos: 0, filled: 0 }
+    }
+
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.filled {
+            self.filled = self.rdr.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.filled);
+    }
+
+    /// Read more data after the data already buffered, without consuming
+    /// any of it. The buffer is grown if it's full. Returns false if the
+    /// underlying reader is at EOF.
+    fn fill_more(&mut self) -> io::Result<bool> {
+        if self.pos > 0 {
+            self.buf.copy_within(self.pos..self.filled, 0);
+            self.filled -= self.pos;
+            self.pos = 0;
+        }
+        if self.filled == self.buf.len() {
+            let capacity = cmp::max(1, self.buf.len());
+            self.buf.resize(capacity * 2, 0);
+        }
+        let n = self.rdr.read(&mut self.buf[self.filled..])?;
+        self.filled += n;
+        Ok(n > 0)
+    }
+
+    fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+    }
+
+    fn grow(&mut self) {
+        let capacity = self.buf.len();
+        if capacity < MAX_GROWN_CAPACITY {
+            self.buf.resize(cmp::min(capacity * 2, MAX_GROWN_CAPACITY), 0);
+        }
+    }
+
+    fn get_ref(&self) -> &R {
+        &self.rdr
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        &mut self.rdr
+    }
+
+    fn into_inner(self) -> R {
+        self.rdr
+    }
+}
+
+impl<R: io::Read + io::Seek> GrowableBufReader<R> {
+    /// Seek the underlying reader and discard the buffer.
+    ///
+    /// As with `io::BufReader`, a relative seek is relative to the position
+    /// of the unconsumed data, not to the position of the underlying reader.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let result = if let io::SeekFrom::Current(n) = pos {
+            let remainder = (self.filled - self.pos) as i64;
+            match n.checked_sub(remainder) {
+                Some(n) => self.rdr.seek(io::SeekFrom::Current(n))?,
+                None => {
+                    self.rdr.seek(io::SeekFrom::Current(-remainder))?;
+                    self.discard_buffer();
+                    self.rdr.seek(io::SeekFrom::Current(n))?
+                }
+            }
+        } else {
+            self.rdr.seek(pos)?
+        };
+        self.discard_buffer();
+        Ok(result)
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for GrowableBufReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrowableBufReader")
+            .field("rdr", &self.rdr)
+            .field(
+                "buffer",
+                &format_args!("{}/{}", self.filled - self.pos, self.buf.len()),
+            )
+            .finish()
+    }
+}
+
+/// Headers encapsulates any data associated with the headers of CSV data.
+///
+/// The headers always correspond to the first row.
+#[derive(Debug)]
+struct Headers {
+    /// The header, as raw bytes.
+    byte_record: ByteRecord,
+    /// The header, as valid UTF-8 (or a UTF-8 error).
+    string_record: result::Result<StringRecord, Utf8Error>,
+}
+
+impl Reader<Reader<File>> {
+    /// Create a new CSV parser with a default configuration for the given
+    /// file path.
+    ///
+    /// To customize CSV parsing, use a `ReaderBuilder`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut rdr = Reader::from_path("foo.csv")?;
+    ///     for result in rdr.records() {
+    ///         let record = result?;
+    ///         println!("{:?}", record);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<File>> {
+        ReaderBuilder::new().from_path(path)
+    }
+}
+
+impl<'a> Reader<&'a [u8]> {
+    /// Create a new CSV parser with a default configuration for the given
+    /// string.
+    ///
+    /// To customize CSV parsing, use a `ReaderBuilder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut rdr = Reader::from_string("\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ");
+    ///     let record = rdr.records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_string(data: &'a str) -> Reader<&'a [u8]> {
+        ReaderBuilder::new().from_string(data)
+    }
+
+    /// Create a new CSV parser with a default configuration for the given
+    /// bytes.
+    ///
+    /// To customize CSV parsing, use a `ReaderBuilder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut rdr = Reader::from_bytes(b"city,pop\nBoston,4628910\n");
+    ///     let record = rdr.byte_records().next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "4628910"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_bytes(data: &'a [u8]) -> Reader<&'a [u8]> {
+        ReaderBuilder::new().from_bytes(data)
+    }
+}
+
+impl Reader<io::Cursor<Vec<u8>>> {
+    /// Create a new CSV parser with a default configuration for the given
+    /// owned bytes.
+    ///
+    /// To customize CSV parsing, use a `ReaderBuilder`.
+    ///
+    /// Since the reader returned owns its data, iterators created from it
+    /// with `into_records`, `into_byte_records` or `into_deserialize` own
+    /// their data too. This makes it easy to return such an iterator from a
+    /// function, which isn't possible with a reader that borrows its data
+    /// from a local variable (e.g., via `from_bytes`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{Reader, StringRecord};
+    ///
+    /// fn records(
+    ///     data: Vec<u8>,
+    /// ) -> impl Iterator<Item = csv::Result<StringRecord>> {
+    ///     Reader::from_vec(data).into_records()
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = b"city,pop\nBoston,4628910\n".to_vec();
+    ///     let mut iter = records(data);
+    ///     let record = iter.next().unwrap()?;
+    ///     assert_eq!(record, vec!["Boston", "4628910"]);
+    ///     assert!(iter.next().is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_vec(data: Vec<u8>) -> Reader<io::Cursor<Vec<u8>>> {
+        ReaderBuilder::new().from_vec(data)
+    }
+}
+
+impl<R: io::Read> Reader<R> {
+    /// Create a new CSV reader given a builder, a buffer capacity and a source
+    /// of underlying bytes.
+    fn new(builder: &ReaderBuilder, capacity: usize, rdr: R) -> Reader<R> {
+        Reader::with_input(builder, Input::unbuffered(capacity, rdr))
+    }
+
+    /// Create a new CSV reader given a builder and a source of bytes.
+    fn with_input(builder: &ReaderBuilder, rdr: Input<R>) -> Reader<R> {
+        Reader {
+            core: Box::new(builder.builder.build()),
+            rdr,
+            state: ReaderState {
+                headers: None,
+                has_headers: builder.has_headers,
+                headers_position: builder.headers_position,
+                footer: None,
+                flexible: builder.flexible,
+                trim: builder.trim,
+                first_field_count: None,
+                cur_pos: Position::new(),
+                first: false,
+                seeked: false,
+                eof: ReaderEofState::NotEof,
+                group_start: false,
+                group_next: None,
+                drop_trailing_empty_field: builder.drop_trailing_empty_field,
+                strict_eof: builder.strict_eof,
+                consistent_terminator: builder.consistent_terminator,
+                deserialize: DeserializeOptions {
+                    infer_types: builder.infer_types,
+                    infer_floats: builder.infer_floats,
+                    header_normalization: builder.header_normalization,
+                    integer_radix_detection: builder.integer_radix_detection,
+                    missing_fields_as_empty: builder.missing_fields_as_empty,
+                },
+                key_columns: builder.key_columns.clone(),
+                error_handler: builder.error_handler.clone(),
+                sniff: if builder.detect_delimiter {
+                    Some(builder.builder.clone())
+                } else {
+                    None
+                },
+                sniffed: false,
+                raw_first: None,
+                raw_scratch: None,
+                bytes_consumed: 0,
+                checksum: if builder.record_checksum {
+                    Some(CHECKSUM_INIT)
+                } else {
+                    None
+                },
+                field_meta: builder.field_meta,
+                terms_seen: 0,
+                pending_cr: None,
+                last_term: None,
+                last_crlf: false,
+                unequal_record: None,
+            },
+            read_footer: None,
+        }
+    }
+
+    /// Return an error if the special bytes in this reader's configuration
+    /// collide.
+    fn check_config(&self) -> Result<()> {
+        let core = &self.core;
+        let (quote, escape) = if core.get_quoting() {
+            (Some(core.get_quote()), core.get_escape())
+        } else {
+            (None, None)
+        };
+        let term = core.get_terminator();
+        let term = match term {
+            CoreTerminator::CRLF => &b"\r\n"[..],
+            CoreTerminator::Any(ref t) => slice::from_ref(t),
+            _ => unreachable!(),
+        };
+        check_special_bytes(core.get_delimiter(), quote, escape, term)
+    }
+
+    /// Create a new CSV parser with a default configuration for the given
+    /// reader.
+    ///
+    /// To customize CSV parsing, use a `ReaderBuilder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     for result in rdr.records() {
+    ///         let record = result?;
+    ///         println!("{:?}", record);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader(rdr: R) -> Reader<R> {
+        ReaderBuilder::new().from_reader(rdr)
+    }
+
+    /// Create a new CSV parser with a default configuration that reads
+    /// directly out of the given buffered reader.
+    ///
+    /// See
+    /// [`ReaderBuilder::from_buf_reader`](struct.ReaderBuilder.html#method.from_buf_reader)
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_buf_reader(data.as_bytes());
+    ///     for result in rdr.records() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_buf_reader(rdr: R) -> Reader<R>
+    where
+        R: io::BufRead,
+    {
+        ReaderBuilder::new().from_buf_reader(rdr)
+    }
+
+    /// Returns a borrowed iterator over deserialized records.
+    ///
+    /// Each item yielded by this iterator is a `Result<D, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record. Additionally,
+    /// if `has_headers` is enabled, then deserializing into a struct will
+    /// automatically align the values in each row to the fields of a struct
+    /// based on the header row.
+    ///
+    /// If a record fails to parse or deserialize, then the iterator yields an
+    /// error for that record, and calling `next` again resumes at the
+    /// following record. This makes it possible to skip bad records without
+    /// abandoning the iterator. The exception is an I/O error from the
+    /// underlying reader, after which no more records are yielded. To fall
+    /// back to reading raw records, use
+    /// [`DeserializeRecordsIter::into_records`](struct.DeserializeRecordsIter.html#method.into_records).
+    ///
+    /// # Example
+    ///
+    /// This shows how to deserialize CSV data into normal Rust structs. The
+    /// fields of the header row are used to match up the values in each row
+    /// to the fields of the struct.
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     country: String,
+    ///     #[serde(rename = "popcount")]
+    ///     population: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record: Row = result?;
+    ///         assert_eq!(record, Row {
+    ///             city: "Boston".to_string(),
+    ///             country: "United States".to_string(),
+    ///             population: 4628910,
+    ///         });
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Rules
+    ///
+    /// For the most part, any Rust type that maps straight-forwardly to a CSV
+    /// record is supported. This includes maps, structs, tuples and tuple
+    /// structs. Other Rust types, such as `Vec`s, arrays, and enums have
+    /// a more complicated story. In general, when working with CSV data, one
+    /// should avoid *nested sequences* as much as possible.
+    ///
+    /// Maps, structs, tuples and tuple structs map to CSV records in a simple
+    /// way. Tuples and tuple structs decode their fields in the order that
+    /// they are defined. Structs will do the same only if `has_headers` has
+    /// been disabled using [`ReaderBuilder`](struct.ReaderBuilder.html),
+    /// otherwise, structs and maps are deserialized based on the fields
+    /// defined in the header row. (If there is no header row, then
+    /// deserializing into a map will result in an error.)
+    ///
+    /// Nested sequences are supported in a limited capacity. Namely, they
+    /// are flattened. As a result, it's often useful to use a `Vec` to capture
+    /// a "tail" of fields in a record:
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     label: String,
+    ///     values: Vec<i32>,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "foo,1,2,3";
+    ///     let mut rdr = csv::ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record: Row = result?;
+    ///         assert_eq!(record, Row {
+    ///             label: "foo".to_string(),
+    ///             values: vec![1, 2, 3],
+    ///         });
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// In the above example, adding another field to the `Row` struct after
+    /// the `values` field will result in a deserialization error. This is
+    /// because the deserializer doesn't know when to stop reading fields
+    /// into the `values` vector, so it will consume the rest of the fields in
+    /// the record leaving none left over for the additional field.
+    ///
+    /// When tuples, tuple structs or structs are deserialized positionally
+    /// and a record has fewer fields than the type, the missing trailing
+    /// fields are not necessarily an error. Fields of type `Option<T>`
+    /// become `None`, and fields marked with `#[serde(default)]` get their
+    /// default value. Any other missing field results in an error.
+    ///
+    /// Numbers and booleans are parsed strictly. In particular, a field with
+    /// surrounding whitespace, like ` 42 `, is not a valid integer. Use
+    /// [`ReaderBuilder::trim`](struct.ReaderBuilder.html#method.trim) to
+    /// trim fields before they are deserialized.
+    ///
+    /// Fields whose type asks for a string, such as `String`, `Option<String>`
+    /// or a newtype wrapping a `String`, always receive the raw field
+    /// contents. No type inference is performed, so a field containing `42`
+    /// deserializes to the string `"42"`. Type inference only occurs when
+    /// the target type does not say what it expects, which is the case for
+    /// untagged enums and `#[serde(flatten)]` fields. Integers that are too
+    /// big for a 64-bit integer type are inferred as strings rather than
+    /// floats, and float inference can be disabled entirely with
+    /// [`ReaderBuilder::infer_floats`](struct.ReaderBuilder.html#method.infer_floats).
+    /// Disabling all type inference with
+    /// [`ReaderBuilder::infer_types`](struct.ReaderBuilder.html#method.infer_types)
+    /// makes it possible to collect unknown columns into a flattened
+    /// `HashMap<String, String>`.
+    ///
+    /// Finally, simple enums in Rust can be deserialized as well. Namely,
+    /// enums must either be variants with no arguments or variants with a
+    /// single argument. Variants with no arguments are deserialized based on
+    /// which variant name the field matches. Variants with a single argument
+    /// are deserialized based on which variant can store the data. The latter
+    /// is only supported when using "untagged" enum deserialization. The
+    /// following example shows both forms in action:
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, PartialEq)]
+    /// struct Row {
+    ///     label: Label,
+    ///     value: Number,
+    /// }
+    ///
+    /// #[derive(Debug, serde::Deserialize, PartialEq)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Label {
+    ///     Celsius,
+    ///     Fahrenheit,
+    /// }
+    ///
+    /// #[derive(Debug, serde::Deserialize, PartialEq)]
+    /// #[serde(untagged)]
+    /// enum Number {
+    ///     Integer(i64),
+    ///     Float(f64),
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// label,value
+    /// celsius,22.2222
+    /// fahrenheit,72
+    /// ";
+    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize();
+    ///
+    ///     // Read the first record.
+    ///     if let Some(result) = iter.next() {
+    ///         let record: Row = result?;
+    ///         assert_eq!(record, Row {
+    ///             label: Label::Celsius,
+    ///             value: Number::Float(22.2222),
+    ///         });
+    ///     } else {
+    ///         return Err(From::from(
+    ///             "expected at least two records but got none"));
+    ///     }
+    ///
+    ///     // Read the second record.
+    ///     if let Some(result) = iter.next() {
+    ///         let record: Row = result?;
+    ///         assert_eq!(record, Row {
+    ///             label: Label::Fahrenheit,
+    ///             value: Number::Integer(72),
+    ///         });
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from(
+    ///             "expected at least two records but got only one"))
+    ///     }
+    /// }
+    /// ```
+    pub fn deserialize<D>(&mut self) -> DeserializeRecordsIter<R, D>
+    where
+        D: DeserializeOwned,
+    {
+        DeserializeRecordsIter::new(self)
+    }
+
+    /// Returns an owned iterator over deserialized records.
+    ///
+    /// Each item yielded by this iterator is a `Result<D, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// This is mostly useful when you want to return a CSV iterator or store
+    /// it somewhere.
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record. Additionally,
+    /// if `has_headers` is enabled, then deserializing into a struct will
+    /// automatically align the values in each row to the fields of a struct
+    /// based on the header row.
+    ///
+    /// For more detailed deserialization rules, see the documentation on the
+    /// `deserialize` method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     country: String,
+    ///     #[serde(rename = "popcount")]
+    ///     population: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// ";
+    ///     let rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.into_deserialize();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record: Row = result?;
+    ///         assert_eq!(record, Row {
+    ///             city: "Boston".to_string(),
+    ///             country: "United States".to_string(),
+    ///             population: 4628910,
+    ///         });
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn into_deserialize<D>(self) -> DeserializeRecordsIntoIter<R, D>
+    where
+        D: DeserializeOwned,
+    {
+        DeserializeRecordsIntoIter::new(self)
+    }
+
+    /// Deserializes all remaining records into a `Vec`.
+    ///
+    /// This is a convenience for collecting the iterator returned by
+    /// [`deserialize`](struct.Reader.html#method.deserialize). Headers are
+    /// handled in exactly the same way, and a single record buffer is reused
+    /// while reading. If reading or deserializing any record fails, then the
+    /// first such error is returned.
+    ///
+    /// This is useful for loading a small CSV file into memory all at once.
+    /// For large inputs, prefer iterating over records with `deserialize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     population: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,population
+    /// Boston,4628910
+    /// Concord,42695
+    /// ";
+    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let rows: Vec<Row> = rdr.deserialize_all()?;
+    ///     assert_eq!(rows, vec![
+    ///         Row { city: "Boston".to_string(), population: 4628910 },
+    ///         Row { city: "Concord".to_string(), population: 42695 },
+    ///     ]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn deserialize_all<D>(&mut self) -> Result<Vec<D>>
+    where
+        D: DeserializeOwned,
+    {
+        self.deserialize().collect()
+    }
+
+    /// Deserializes all remaining records into a `Vec`, consuming this
+    /// reader.
+    ///
+    /// This is like
+    /// [`deserialize_all`](struct.Reader.html#method.deserialize_all), except
+    /// it takes ownership of the reader.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// #[derive(Debug, serde::Deserialize, Eq, PartialEq)]
+    /// struct Row {
+    ///     city: String,
+    ///     population: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,population
+    /// Boston,4628910
+    /// ";
+    ///     let rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let rows: Vec<Row> = rdr.into_deserialize_all()?;
+    ///     assert_eq!(rows, vec![
+    ///         Row { city: "Boston".to_string(), population: 4628910 },
+    ///     ]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_deserialize_all<D>(self) -> Result<Vec<D>>
+    where
+        D: DeserializeOwned,
+    {
+        self.into_deserialize().collect()
+    }
+
+    /// Returns a borrowed iterator over all records as strings.
+    ///
+    /// Each item yielded by this iterator is a `Result<StringRecord, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.records();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn records(&mut self) -> StringRecordsIter<R> {
+        StringRecordsIter::new(self)
+    }
+
+    /// Returns a borrowed iterator over all records as strings, along with
+    /// the index of each record.
+    ///
+    /// Each item yielded by this iterator is a pair of the record index and
+    /// a `Result<StringRecord, Error>`. The record index is the parser's own
+    /// record number, i.e., the value of
+    /// [`Position::record`](struct.Position.html#method.record) for the
+    /// record. This differs from `records().enumerate()`: when
+    /// `has_headers` is enabled, the header row is record `0`, so the first
+    /// record yielded has index `1`. After a `seek`, numbering continues from
+    /// the record number of the position seeked to.
+    ///
+    /// If the header row can't be read, then the error is yielded first.
+    ///
+    /// This is useful for reporting progress or errors on long running
+    /// imports.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut indices = vec![];
+    ///     for (i, result) in rdr.enumerate_records() {
+    ///         let record = result?;
+    ///         indices.push((i, record[0].to_string()));
+    ///     }
+    ///     assert_eq!(indices, vec![
+    ///         (1, "Boston".to_string()),
+    ///         (2, "Concord".to_string()),
+    ///     ]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn enumerate_records(&mut self) -> EnumerateRecordsIter<'_, R> {
+        EnumerateRecordsIter::new(self)
+    }
+
+    /// Returns a borrowed iterator over all records as strings that does not
+    /// stop at records with an unexpected number of fields.
+    ///
+    /// This is a middle ground between the default strict behavior and
+    /// [`ReaderBuilder::flexible`](struct.ReaderBuilder.html#method.flexible).
+    /// When a record has a different number of fields than the first record,
+    /// it is still yielded as `Ok`, but the corresponding `UnequalLengths`
+    /// error is recorded and can be inspected via
+    /// [`LenientRecordsIter::length_errors`](struct.LenientRecordsIter.html#method.length_errors).
+    /// All other errors are yielded as usual.
+    ///
+    /// If the reader is configured to be flexible, then no length errors are
+    /// ever recorded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ErrorKind, Reader};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// Concord,United States
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.records_lenient();
+    ///     let mut records = vec![];
+    ///     for result in &mut iter {
+    ///         records.push(result?);
+    ///     }
+    ///     assert_eq!(records.len(), 2);
+    ///     assert_eq!(records[1], vec!["Concord", "United States"]);
+    ///
+    ///     assert_eq!(iter.length_errors().len(), 1);
+    ///     match *iter.length_errors()[0].kind() {
+    ///         ErrorKind::UnequalLengths { expected_len, len, .. } => {
+    ///             assert_eq!((expected_len, len), (3, 2));
+    ///         }
+    ///         ref wrong => panic!("unexpected error: {:?}", wrong),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn records_lenient(&mut self) -> LenientRecordsIter<'_, R> {
+        LenientRecordsIter::new(self)
+    }
+
+    /// Returns a borrowed iterator over at most `n` records as strings.
+    ///
+    /// Once `n` records have been yielded, the iterator stops without asking
+    /// the reader for another record, which makes this convenient for
+    /// sampling or previewing data from an expensive source. Note though
+    /// that this does not guarantee that no data beyond the `n`th record is
+    /// read from the underlying reader, since the CSV reader buffers its
+    /// input.
+    ///
+    /// Errors count towards the limit like records do. After the iterator
+    /// is exhausted, the reader can continue to be used to read the
+    /// remaining records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// Paris,France,2161000
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut cities = vec![];
+    ///     for result in rdr.take_records(2) {
+    ///         let record = result?;
+    ///         cities.push(record[0].to_string());
+    ///     }
+    ///     assert_eq!(cities, vec!["Boston", "Concord"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn take_records(&mut self, n: usize) -> TakeRecordsIter<'_, R> {
+        TakeRecordsIter::new(self, n)
+    }
+
+    /// Returns a borrowed iterator over all records as strings, along with
+    /// any comment lines between them.
+    ///
+    /// Normally, comment lines (see
+    /// [`ReaderBuilder::comment`](struct.ReaderBuilder.html#method.comment))
+    /// are skipped silently. This iterator instead yields each of them as a
+    /// `RecordOrComment::Comment`, in the order in which it appears in the
+    /// data, which makes it possible to inspect metadata stored in
+    /// comments. A comment's contents exclude the comment byte and the line
+    /// terminator. Records are yielded as `RecordOrComment::Record`, just
+    /// like `records` would yield them. In particular, if `has_headers` is
+    /// enabled, then the header row is never yielded, although comments
+    /// preceding it are.
+    ///
+    /// If no comment byte is set, then this yields only records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{RecordOrComment, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// #schema: v2
+    /// city,pop
+    /// Boston,4628910
+    /// #source: census
+    /// Concord,42695
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .comment(Some(b'#'))
+    ///         .from_reader(data.as_bytes());
+    ///     let mut iter = rdr.records_with_comments();
+    ///
+    ///     assert_eq!(
+    ///         iter.next().unwrap()?,
+    ///         RecordOrComment::Comment("schema: v2".to_string()),
+    ///     );
+    ///     match iter.next().unwrap()? {
+    ///         RecordOrComment::Record(record) => {
+    ///             assert_eq!(record, vec!["Boston", "4628910"]);
+    ///         }
+    ///         item => panic!("expected a record, got {:?}", item),
+    ///     }
+    ///     assert_eq!(
+    ///         iter.next().unwrap()?,
+    ///         RecordOrComment::Comment("source: census".to_string()),
+    ///     );
+    ///     assert!(iter.next().unwrap()?.is_record());
+    ///     assert!(iter.next().is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn records_with_comments(&mut self) -> RecordsWithCommentsIter<'_, R> {
+        RecordsWithCommentsIter::new(self)
+    }
+
+    /// Returns an owned iterator over all records as strings.
+    ///
+    /// Each item yielded by this iterator is a `Result<StringRecord, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// This is mostly useful when you want to return a CSV iterator or store
+    /// it somewhere. Note that the iterator can only outlive the scope it
+    /// was created in if the underlying reader owns its data, e.g., a `File`
+    /// or a reader built with
+    /// [`Reader::from_vec`](struct.Reader.html#method.from_vec).
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.into_records();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn into_records(self) -> StringRecordsIntoIter<R> {
+        StringRecordsIntoIter::new(self)
+    }
+
+    /// Returns a borrowed iterator over all records as raw bytes.
+    ///
+    /// Each item yielded by this iterator is a `Result<ByteRecord, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.byte_records();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn byte_records(&mut self) -> ByteRecordsIter<R> {
+        ByteRecordsIter::new(self)
+    }
+
+    /// Returns an owned iterator over all records as raw bytes.
+    ///
+    /// Each item yielded by this iterator is a `Result<ByteRecord, Error>`.
+    /// Therefore, in order to access the record, callers must handle the
+    /// possibility of error (typically with `try!` or `?`).
+    ///
+    /// This is mostly useful when you want to return a CSV iterator or store
+    /// it somewhere.
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this does not include the first record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.into_byte_records();
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn into_byte_records(self) -> ByteRecordsIntoIter<R> {
+        ByteRecordsIntoIter::new(self)
+    }
+
+    /// Returns a borrowed iterator over batches of records as Apache Arrow
+    /// `RecordBatch`es with the given schema.
+    ///
+    /// Each item yielded by this iterator is a `Result<RecordBatch, Error>`.
+    /// Every batch has `batch_size` rows, except possibly the last one.
+    ///
+    /// The fields of the schema are matched to the columns of each record by
+    /// position, not by name. If `has_headers` was enabled via a
+    /// `ReaderBuilder` (which is the default), then the first record is not
+    /// included. A record with fewer fields than the schema is treated as if
+    /// the missing fields were empty, and fields beyond the end of the schema
+    /// are ignored.
+    ///
+    /// An empty field is read as a null if its schema field is nullable.
+    /// Otherwise, it is read as an empty value for string and binary types,
+    /// and is an error for every other type.
+    ///
+    /// The following types are supported: `Boolean` (`true` or `false`),
+    /// the integer and floating point types, `Utf8`, `LargeUtf8`, `Binary`,
+    /// `LargeBinary`, `Date32`, `Date64`, `Timestamp`, `Time32`, `Time64`,
+    /// `Duration`, `Decimal128` and `Decimal256`. Values other than booleans
+    /// are parsed the same way as Arrow's own CSV reader parses them. In
+    /// particular, timestamps without a UTC offset are taken to be in UTC,
+    /// whatever the time zone of the field. If the schema contains any other
+    /// type, then the first item yielded is an `InvalidConfig` error.
+    ///
+    /// When a field can't be parsed, an error of kind
+    /// [`ErrorKind::Deserialize`](enum.ErrorKind.html#variant.Deserialize)
+    /// is yielded in place of the batch being built. Its position identifies
+    /// the record, and its
+    /// [`DeserializeError`](struct.DeserializeError.html) gives the index and
+    /// schema name of the field. The bad record is skipped, and the records
+    /// read before it are kept: they are yielded in the next batch if
+    /// iteration continues.
+    ///
+    /// This method is only available when the `arrow` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `batch_size` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, sync::Arc};
+    ///
+    /// use arrow_array::{cast::AsArray, types::Int64Type, Array};
+    /// use arrow_schema::{DataType, Field, Schema};
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,pop
+    /// Boston,4628910
+    /// Concord,
+    /// ";
+    ///     let schema = Arc::new(Schema::new(vec![
+    ///         Field::new("city", DataType::Utf8, false),
+    ///         Field::new("pop", DataType::Int64, true),
+    ///     ]));
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.arrow_batches(schema, 1024);
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let batch = result?;
+    ///         assert_eq!(batch.num_rows(), 2);
+    ///         let pop = batch.column(1).as_primitive::<Int64Type>();
+    ///         assert_eq!(pop.value(0), 4628910);
+    ///         assert!(pop.is_null(1));
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one batch but got none"))
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub fn arrow_batches(
+        &mut self,
+        schema: arrow_schema::SchemaRef,
+        batch_size: usize,
+    ) -> crate::arrow::ArrowBatchesIter<R> {
+        crate::arrow::ArrowBatchesIter::new(self, schema, batch_size)
+    }
+
+    /// Returns an owned iterator over batches of records as Apache Arrow
+    /// `RecordBatch`es with the given schema.
+    ///
+    /// This is mostly useful when you want to return a CSV iterator or store
+    /// it somewhere. See
+    /// [`arrow_batches`](struct.Reader.html#method.arrow_batches) for how
+    /// records are converted.
+    ///
+    /// This method is only available when the `arrow` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `batch_size` is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, sync::Arc};
+    ///
+    /// use arrow_schema::{DataType, Field, Schema};
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,pop
+    /// Boston,4628910
+    /// Concord,42605
+    /// Salem,44480
+    /// ";
+    ///     let schema = Arc::new(Schema::new(vec![
+    ///         Field::new("city", DataType::Utf8, false),
+    ///         Field::new("pop", DataType::UInt32, false),
+    ///     ]));
+    ///     let rdr = Reader::from_reader(data.as_bytes());
+    ///     let rows = rdr
+    ///         .into_arrow_batches(schema, 2)
+    ///         .map(|result| result.map(|batch| batch.num_rows()))
+    ///         .collect::<csv::Result<Vec<usize>>>()?;
+    ///     assert_eq!(rows, vec![2, 1]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub fn into_arrow_batches(
+        self,
+        schema: arrow_schema::SchemaRef,
+        batch_size: usize,
+    ) -> crate::arrow::ArrowBatchesIntoIter<R> {
+        crate::arrow::ArrowBatchesIntoIter::new(self, schema, batch_size)
+    }
+
+    /// Returns a borrowed iterator over records containing only the columns
+    /// with the given header names, in the order given.
+    ///
+    /// The names are resolved against the header row (as returned by
+    /// `headers`) once, before any records are read. If any name is not
+    /// present in the header row, then an
+    /// [`ErrorKind::UnknownColumn`](enum.ErrorKind.html#variant.UnknownColumn)
+    /// error is returned. If a name occurs more than once in the header row,
+    /// then its first occurrence is used. A name may be given more than once,
+    /// in which case the corresponding column is repeated.
+    ///
+    /// If a record has fewer fields than the header row (which is only
+    /// possible when `flexible` is enabled), then any missing columns are
+    /// yielded as empty fields.
+    ///
+    /// Each item yielded by the iterator is a `Result<StringRecord, Error>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.select_columns(&["pop", "city"])?;
+    ///     assert_eq!(iter.headers(), vec!["pop", "city"]);
+    ///
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["4628910", "Boston"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn select_columns(
+        &mut self,
+        names: &[&str],
+    ) -> Result<ColumnReader<'_, R>> {
+        ColumnReader::new(self, names)
+    }
+
+    /// Returns a borrowed iterator over records that checks each record
+    /// against the column types given.
+    ///
+    /// The type of each field is inferred, and then compared with the type
+    /// expected of its column. A field matches a column of type `String`
+    /// regardless of its contents, and an integer field also matches a
+    /// column of type `Float`. Note that empty fields are strings, and that
+    /// integers that don't fit in 64 bits are strings too. See
+    /// [`ColumnType`](enum.ColumnType.html) for details.
+    ///
+    /// Each item yielded by the iterator is a `Result<StringRecord, Error>`.
+    /// If a record has a field that doesn't match the type of its column,
+    /// then an
+    /// [`ErrorKind::SchemaMismatch`](enum.ErrorKind.html#variant.SchemaMismatch)
+    /// error for the first such field is yielded instead of the record. If a
+    /// record doesn't have exactly one field per column, then an
+    /// [`ErrorKind::UnequalLengths`](enum.ErrorKind.html#variant.UnequalLengths)
+    /// error is yielded instead. Either way, iteration may continue with the
+    /// next record.
+    ///
+    /// This is cheaper than deserializing records with Serde when records
+    /// only need to be checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ColumnType, ErrorKind, Reader};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,pop,coastal
+    /// Boston,4628910,true
+    /// Concord,unknown,false
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let schema = [ColumnType::String, ColumnType::Integer, ColumnType::Bool];
+    ///     let mut iter = rdr.validate_schema(&schema);
+    ///
+    ///     assert_eq!(iter.next().unwrap()?, vec!["Boston", "4628910", "true"]);
+    ///     let err = iter.next().unwrap().unwrap_err();
+    ///     match *err.kind() {
+    ///         ErrorKind::SchemaMismatch { ref pos, field, .. } => {
+    ///             assert_eq!(pos.as_ref().unwrap().line(), 3);
+    ///             assert_eq!(field, 1);
+    ///         }
+    ///         ref kind => panic!("unexpected error: {:?}", kind),
+    ///     }
+    ///     assert!(iter.next().is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_schema(
+        &mut self,
+        types: &[ColumnType],
+    ) -> SchemaValidator<'_, R> {
+        SchemaValidator::new(self, types)
+    }
+
+    /// Returns a reference to the first row read by this parser.
+    ///
+    /// If no row has been read yet, then this will force parsing of the first
+    /// row.
+    ///
+    /// If there was a problem parsing the row or if it wasn't valid UTF-8,
+    /// then this returns an error.
+    ///
+    /// If the underlying reader emits EOF before any data, then this returns
+    /// an empty record.
+    ///
+    /// Note that this method may be used regardless of whether `has_headers`
+    /// was enabled (but it is enabled by default).
+    ///
+    /// # Example
+    ///
+    /// This example shows how to get the header row of CSV data. Notice that
+    /// the header row does not appear as a record in the iterator!
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///
+    ///     // We can read the headers before iterating.
+    ///     {
+    ///         // `headers` borrows from the reader, so we put this in its
+    ///         // own scope. That way, the borrow ends before we try iterating
+    ///         // below. Alternatively, we could clone the headers.
+    ///         let headers = rdr.headers()?;
+    ///         assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     }
+    ///
+    ///     if let Some(result) = rdr.records().next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     } else {
+    ///         return Err(From::from(
+    ///             "expected at least one record but got none"))
+    ///     }
+    ///
+    ///     // We can also read the headers after iterating.
+    ///     let headers = rdr.headers()?;
+    ///     assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn headers(&mut self) -> Result<&StringRecord> {
+        if self.state.headers.is_none() {
+            self.read_headers_impl()?;
+        }
+        let headers = self.state.headers.as_ref().unwrap();
+        match headers.string_record {
+            Ok(ref record) => Ok(record),
+            Err(ref err) => Err(Error::new(ErrorKind::Utf8 {
+                pos: headers.byte_record.position().map(Clone::clone),
+                err: err.clone(),
+            })),
+        }
+    }
+
+    /// Returns a reference to the first row read by this parser as raw bytes.
+    ///
+    /// If no row has been read yet, then this will force parsing of the first
+    /// row.
+    ///
+    /// If there was a problem parsing the row then this returns an error.
+    ///
+    /// If the underlying reader emits EOF before any data, then this returns
+    /// an empty record.
+    ///
+    /// Note that this method may be used regardless of whether `has_headers`
+    /// was enabled (but it is enabled by default).
+    ///
+    /// # Example
+    ///
+    /// This example shows how to get the header row of CSV data. Notice that
+    /// the header row does not appear as a record in the iterator!
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///
+    ///     // We can read the headers before iterating.
+    ///     {
+    ///         // `headers` borrows from the reader, so we put this in its
+    ///         // own scope. That way, the borrow ends before we try iterating
+    ///         // below. Alternatively, we could clone the headers.
+    ///         let headers = rdr.byte_headers()?;
+    ///         assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     }
+    ///
+    ///     if let Some(result) = rdr.byte_records().next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///     } else {
+    ///         return Err(From::from(
+    ///             "expected at least one record but got none"))
+    ///     }
+    ///
+    ///     // We can also read the headers after iterating.
+    ///     let headers = rdr.byte_headers()?;
+    ///     assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn byte_headers(&mut self) -> Result<&ByteRecord> {
+        if self.state.headers.is_none() {
+            self.read_headers_impl()?;
+        }
+        Ok(&self.state.headers.as_ref().unwrap().byte_record)
+    }
+
+    /// Read the header row and return it along with a reader for the
+    /// remaining rows.
+    ///
+    /// The reader returned behaves as if it were built with
+    /// `has_headers(false)` and positioned at the first data row. That is,
+    /// every row it reads is a record, and calling `headers` on it returns
+    /// (without consuming) the first data row rather than the header row.
+    /// This is useful when one component consumes the header (e.g., to build
+    /// a schema) and hands the data rows to another component, since the
+    /// header can never be mistaken for a record downstream. Unless the
+    /// reader is flexible, records must still have the same number of fields
+    /// as the header.
+    ///
+    /// If this reader was configured with `has_headers(false)`, then there
+    /// is no header row to consume. In that case, an empty record is
+    /// returned along with this reader, unchanged. Similarly, if the data is
+    /// empty, then an empty header is returned.
+    ///
+    /// If there was a problem reading the header row (including if it isn't
+    /// valid UTF-8), then an error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let rdr = Reader::from_reader(data.as_bytes());
+    ///     let (headers, mut rdr) = rdr.split_header()?;
+    ///     assert_eq!(headers, vec!["city", "country", "pop"]);
+    ///
+    ///     assert!(!rdr.has_headers());
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records, vec![vec!["Boston", "United States", "4628910"]]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split_header(mut self) -> Result<(StringRecord, Reader<R>)> {
+        if !self.state.has_headers {
+            return Ok((StringRecord::new(), self));
+        }
+        let headers = self.headers()?.clone();
+        self.state.has_headers = false;
+        self.state.headers = None;
+        Ok((headers, self))
+    }
+
+    /// Read the first row and use it as the headers.
+    ///
+    /// This reads the row in raw mode, so that its entire terminator is
+    /// consumed. When `has_headers` is disabled, the first row is also a
+    /// record, so its raw bytes are kept around for `read_raw_record`.
+    fn read_headers_impl(&mut self) -> Result<()> {
+        if self.state.has_footer_headers() {
+            return match self.read_footer {
+                Some(read_footer) => read_footer(self),
+                None => Err(Error::new(ErrorKind::InvalidConfig(
+                    "reading the header row from the end of the data \
+                     requires a seekable reader, such as one built with \
+                     ReaderBuilder::from_seekable_reader"
+                        .to_string(),
+                ))),
+            };
+        }
+        let mut record = ByteRecord::new();
+        let mut raw = vec![];
+        self.read_byte_record_raw_impl(&mut record, Some(&mut raw))?;
+        if !self.state.has_headers {
+            self.state.raw_first = Some(raw);
+        }
+        self.set_headers_impl(Err(record));
+        Ok(())
+    }
+
+    /// Set the headers of this CSV parser manually.
+    ///
+    /// This overrides any other setting (including `set_byte_headers`). Any
+    /// automatic detection of headers is disabled. This may be called at any
+    /// time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{Reader, StringRecord};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///
+    ///     assert_eq!(rdr.headers()?, vec!["city", "country", "pop"]);
+    ///     rdr.set_headers(StringRecord::from(vec!["a", "b", "c"]));
+    ///     assert_eq!(rdr.headers()?, vec!["a", "b", "c"]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_headers(&mut self, headers: StringRecord) {
+        self.set_headers_impl(Ok(headers));
+    }
+
+    /// Set the headers of this CSV parser manually as raw bytes.
+    ///
+    /// This overrides any other setting (including `set_headers`). Any
+    /// automatic detection of headers is disabled. This may be called at any
+    /// time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{Reader, ByteRecord};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///
+    ///     assert_eq!(rdr.byte_headers()?, vec!["city", "country", "pop"]);
+    ///     rdr.set_byte_headers(ByteRecord::from(vec!["a", "b", "c"]));
+    ///     assert_eq!(rdr.byte_headers()?, vec!["a", "b", "c"]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_byte_headers(&mut self, headers: ByteRecord) {
+        self.set_headers_impl(Err(headers));
+    }
+
+    fn set_headers_impl(
+        &mut self,
+        headers: result::Result<StringRecord, ByteRecord>,
+    ) {
+        // If we have string headers, then get byte headers. But if we have
+        // byte headers, then get the string headers (or a UTF-8 error).
+        let (mut str_headers, mut byte_headers) = match headers {
+            Ok(string) => {
+                let bytes = string.clone().into_byte_record();
+                (Ok(string), bytes)
+            }
+            Err(bytes) => {
+                match StringRecord::from_byte_record(bytes.clone()) {
+                    Ok(str_headers) => (Ok(str_headers), bytes),
+                    Err(err) => (Err(err.utf8_error().clone()), bytes),
+                }
+            }
+        };
+        if self.state.trim.should_trim_headers() {
+            if let Ok(ref mut str_headers) = str_headers.as_mut() {
+                str_headers.trim();
+            }
+            byte_headers.trim();
+        }
+        self.state.headers = Some(Headers {
+            byte_record: byte_headers,
+            string_record: str_headers,
+        });
+    }
+
+    /// Read a single row into the given record. Returns false when no more
+    /// records could be read.
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this will never read the first record.
+    ///
+    /// This method is useful when you want to read records as fast as
+    /// as possible. It's less ergonomic than an iterator, but it permits the
+    /// caller to reuse the `StringRecord` allocation, which usually results
+    /// in higher throughput.
+    ///
+    /// Records read via this method are guaranteed to have a position set
+    /// on them, even if the reader is at EOF or if an error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{Reader, StringRecord};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut record = StringRecord::new();
+    ///
+    ///     if rdr.read_record(&mut record)? {
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn read_record(&mut self, record: &mut StringRecord) -> Result<bool> {
+        let result = record.read(self);
+        // We need to trim again because trimming string records includes
+        // Unicode whitespace. (ByteRecord trimming only includes ASCII
+        // whitespace.)
+        if self.state.trim.should_trim_fields() {
+            record.trim();
+        }
+        result
+    }
+
+    /// Read a single row into the given byte record. Returns false when no
+    /// more records could be read.
+    ///
+    /// If `has_headers` was enabled via a `ReaderBuilder` (which is the
+    /// default), then this will never read the first record.
+    ///
+    /// This method is useful when you want to read records as fast as
+    /// as possible. It's less ergonomic than an iterator, but it permits the
+    /// caller to reuse the `ByteRecord` allocation, which usually results
+    /// in higher throughput.
+    ///
+    /// Records read via this method are guaranteed to have a position set
+    /// on them, even if the reader is at EOF or if an error is returned.
+    ///
+    /// If an `UnequalLengths` error is returned, then the given record still
+    /// contains the fields that were parsed for the offending row. (A copy
+    /// of it is also available from
+    /// [`unequal_record`](struct.Reader.html#method.unequal_record).) The
+    /// reader has moved past that row, so the next call reads the row after
+    /// it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ByteRecord, Reader};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut record = ByteRecord::new();
+    ///
+    ///     if rdr.read_byte_record(&mut record)? {
+    ///         assert_eq!(record, vec!["Boston", "United States", "4628910"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn read_byte_record(
+        &mut self,
+        record: &mut ByteRecord,
+    ) -> Result<bool> {
+        let ok = self.read_byte_record_unchecked(record)?;
+        if ok {
+            if let Some(ref mut sum) = self.state.checksum {
+                *sum = record.update_checksum(*sum);
+            }
+        }
+        Ok(ok)
+    }
+
+    /// Like `read_byte_record`, but doesn't update the rolling checksum.
+    fn read_byte_record_unchecked(
+        &mut self,
+        record: &mut ByteRecord,
+    ) -> Result<bool> {
+        if !self.state.seeked && !self.state.has_headers && !self.state.first {
+            // If the caller indicated "no headers" and we haven't yielded the
+            // first record yet, then we should yield our header row if we have
+            // one.
+            if let Some(ref headers) = self.state.headers {
+                self.state.first = true;
+                record.clone_from(&headers.byte_record);
+                if self.state.trim.should_trim_fields() {
+                    record.trim();
+                }
+                return Ok(!record.is_empty());
+            }
+        }
+        if !self.state.seeked
+            && self.state.headers.is_none()
+            && self.state.has_footer_headers()
+        {
+            self.read_headers_impl()?;
+        }
+        let ok = self.read_byte_record_impl(record)?;
+        self.state.first = true;
+        if !self.state.seeked && self.state.headers.is_none() {
+            self.set_headers_impl(Err(record.clone()));
+            // If the end user indicated that we have headers, then we should
+            // never return the first row. Instead, we should attempt to
+            // read and return the next one.
+            if self.state.has_headers {
+                let result = self.read_byte_record_impl(record);
+                if self.state.trim.should_trim_fields() {
+                    record.trim();
+                }
+                return result;
+            }
+        }
+        if self.state.trim.should_trim_fields() {
+            record.trim();
+        }
+        Ok(ok)
+    }
+
+    /// Read a single row and append its fields to the given byte record,
+    /// after any fields it already contains. Returns false when no more
+    /// records could be read, in which case the record is left unchanged.
+    ///
+    /// This is like `read_byte_record`, except the record is not cleared
+    /// first. This makes it possible to combine several rows into a single
+    /// record without copying fields from one record to another. If the
+    /// record is empty, then this behaves exactly like `read_byte_record`.
+    /// Otherwise, the record's position is left as is, i.e., it continues
+    /// to refer to the first row that was read into it.
+    ///
+    /// The length check enabled by default (see `ReaderBuilder::flexible`)
+    /// only considers the fields of the row that was just read, not the
+    /// total number of fields in the record. If an `UnequalLengths` error
+    /// is returned, then the record still contains the appended fields,
+    /// while the error itself contains only the fields of the offending row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ByteRecord, Reader};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country
+    /// Boston,United States
+    /// Concord,United States
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut record = ByteRecord::new();
+    ///
+    ///     assert!(rdr.read_byte_record_append(&mut record)?);
+    ///     assert!(rdr.read_byte_record_append(&mut record)?);
+    ///     assert_eq!(
+    ///         record,
+    ///         vec!["Boston", "United States", "Concord", "United States"],
+    ///     );
+    ///     assert!(!rdr.read_byte_record_append(&mut record)?);
+    ///     assert_eq!(record.len(), 4);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn read_byte_record_append(
+        &mut self,
+        record: &mut ByteRecord,
+    ) -> Result<bool> {
+        if record.is_empty() {
+            return self.read_byte_record(record);
+        }
+        if !self.state.seeked && !self.state.first {
+            if self.state.headers.is_none() {
+                self.read_headers_impl()?;
+            }
+            if !self.state.has_headers {
+                if let Some(ref headers) = self.state.headers {
+                    self.state.first = true;
+                    record.extend_from_record(&headers.byte_record);
+                    if self.state.trim.should_trim_fields() {
+                        record.trim();
+                    }
+                    return Ok(!headers.byte_record.is_empty());
+                }
+            }
+        }
+        let result = self.read_byte_record_fields(record, None);
+        self.state.first = true;
+        if self.state.trim.should_trim_fields() {
+            record.trim();
+        }
+        result
+    }
+
+    /// Read the raw bytes of the next record, exactly as they appear in the
+    /// underlying reader, and append them to `buf`. Returns false when no
+    /// more records could be read.
+    ///
+    /// The bytes appended include any quotes and escapes, the delimiters
+    /// and the record's terminator (if it has one). Any comment lines or
+    /// blank lines immediately preceding the record are included as well.
+    /// This makes it possible to forward records byte-for-byte, e.g., in a
+    /// proxy that doesn't need to interpret them.
+    ///
+    /// The record is still parsed, so the same rules apply as for
+    /// `read_byte_record`. In particular, if `has_headers` is enabled, then
+    /// the header row is never returned by this method, and records with an
+    /// unexpected number of fields result in an error (in which case the
+    /// raw bytes of the offending record are still appended to `buf`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,\"United States\",4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut buf = vec![];
+    ///
+    ///     assert!(rdr.read_raw_record(&mut buf)?);
+    ///     assert_eq!(buf, b"Boston,\"United States\",4628910\n");
+    ///     buf.clear();
+    ///     assert!(rdr.read_raw_record(&mut buf)?);
+    ///     assert_eq!(buf, b"Concord,United States,42695\n");
+    ///     assert!(!rdr.read_raw_record(&mut buf)?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn read_raw_record(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        if !self.state.seeked && !self.state.first {
+            if self.state.has_headers {
+                if self.state.headers.is_none() {
+                    self.read_headers_impl()?;
+                }
+            } else if let Some(raw) = self.state.raw_first.take() {
+                // The first row was already read as the headers.
+                self.state.first = true;
+                buf.extend_from_slice(&raw);
+                return Ok(!raw.is_empty());
+            }
+        }
+        let mut record = self.state.raw_scratch.take().unwrap_or_default();
+        let result = self.read_byte_record_raw_impl(&mut record, Some(buf));
+        self.state.first = true;
+        if result.is_ok() && !self.state.seeked && self.state.headers.is_none()
+        {
+            self.set_headers_impl(Err(record.clone()));
+        }
+        self.state.raw_scratch = Some(record);
+        result
+    }
+
+    /// Read a byte record from the underlying CSV reader, without accounting
+    /// for headers.
+    #[inline(always)]
+    fn read_byte_record_impl(
+        &mut self,
+        record: &mut ByteRecord,
+    ) -> Result<bool> {
+        loop {
+            return match self.read_byte_record_raw_impl(record, None) {
+                Err(err) => match self.error_action(&err) {
+                    ErrorAction::Skip => continue,
+                    ErrorAction::Abort => Err(err),
+                    ErrorAction::Continue => Ok(true),
+                },
+                result => result,
+            };
+        }
+    }
+
+    /// Decide what to do about the given error, by asking the error handler
+    /// if the error is recoverable and a handler is set.
+    pub(crate) fn error_action(&self, err: &Error) -> ErrorAction {
+        match (&self.state.error_handler, err.kind()) {
+            (
+                Some(handler),
+                ErrorKind::UnequalLengths { .. } | ErrorKind::Utf8 { .. },
+            ) => handler.call(err),
+            _ => ErrorAction::Abort,
+        }
+    }
+
+    /// Like `read_byte_record_impl`, but when `raw` is present, the raw bytes
+    /// consumed for the record are appended to it.
+    #[inline(always)]
+    fn read_byte_record_raw_impl(
+        &mut self,
+        record: &mut ByteRecord,
+        raw: Option<&mut Vec<u8>>,
+    ) -> Result<bool> {
+        record.clear();
+        let skipped = self.skip_pending_lf();
+        record.set_position(Some(self.state.cur_pos.clone()));
+        skipped?;
+        self.read_byte_record_fields(record, raw)
+    }
+
+    /// Read the fields of the next record and add them to the end of the
+    /// given record, after any fields it already contains. When `raw` is
+    /// present, the raw bytes consumed for the record are appended to it.
+    ///
+    /// Only the newly read fields are subject to the length check.
+    #[inline(always)]
+    fn read_byte_record_fields(
+        &mut self,
+        record: &mut ByteRecord,
+        mut raw: Option<&mut Vec<u8>>,
+    ) -> Result<bool> {
+        use csv_core::ReadRecordResult::*;
+
+        self.state.group_start = false;
+        if self.state.eof != ReaderEofState::NotEof {
+            return Ok(false);
+        }
+        self.skip_pending_lf()?;
+        self.state.last_term = None;
+        self.state.last_crlf = false;
+        if self
+            .state
+            .footer
+            .is_some_and(|end| self.state.cur_pos.byte() >= end)
+        {
+            self.state.eof = ReaderEofState::Eof;
+            return Ok(false);
+        }
+        let pos = self.state.cur_pos.clone();
+        if !self.state.sniffed {
+            self.state.sniffed = true;
+            if let Some(ref builder) = self.state.sniff {
+                let input_res = self.rdr.fill_sample(SNIFF_SAMPLE_LEN);
+                if input_res.is_err() {
+                    self.state.eof = ReaderEofState::IOError;
+                }
+                if let Some(delimiter) = sniff_delimiter(input_res?) {
+                    let mut builder = CoreReaderBuilder::clone(builder);
+                    builder.delimiter(delimiter);
+                    *self.core = builder.build();
+                }
+            }
+        }
+        let (start_out, start) = (record.as_slice().len(), record.len());
+        let (mut outlen, mut endlen) = (start_out, start);
+        // Metadata is only recorded if the fields already present have it,
+        // so that it stays aligned with the fields.
+        let track_meta = self.state.field_meta && {
+            let (_, _, metas) = record.as_parts_meta();
+            metas.truncate(start);
+            metas.len() == start
+        };
+        // The number of times the input ran out while reading this record.
+        let mut fills = 0;
+        loop {
+            let (res, nin, nout, nend, last) = {
+                let input_res = self.rdr.fill_buf();
+                if input_res.is_err() {
+                    self.state.eof = ReaderEofState::IOError;
+                }
+                let input = input_res?;
+                self.state.resolve_cr(input.first().copied())?;
+                if input.is_empty()
+                    && self.state.strict_eof
+                    && self.core.is_in_quoted_field()
+                {
+                    self.state.eof = ReaderEofState::Eof;
+                    return Err(Error::new(ErrorKind::UnexpectedEof {
+                        pos: Some(pos),
+                    }));
+                }
+                let (fields, ends, metas) = record.as_parts_meta();
+                let (res, nin, nout, nend) = if track_meta {
+                    metas.resize(ends.len(), FieldMeta::default());
+                    self.core.read_record_meta(
+                        input,
+                        &mut fields[outlen..],
+                        &mut ends[endlen..],
+                        &mut metas[endlen..],
+                    )
+                } else {
+                    self.core.read_record(
+                        input,
+                        &mut fields[outlen..],
+                        &mut ends[endlen..],
+                    )
+                };
+                if let Some(ref mut raw) = raw {
+                    raw.extend_from_slice(&input[..nin]);
+                }
+                (res, nin, nout, nend, input.get(nin.wrapping_sub(1)).copied())
+            };
+            self.consume(nin);
+            let byte = self.state.cur_pos.byte();
+            self.state
+                .cur_pos
+                .set_byte(byte + nin as u64)
+                .set_line(self.core.line());
+            outlen += nout;
+            endlen += nend;
+            match res {
+                InputEmpty => {
+                    // A record that spans many fills of the buffer is long
+                    // enough that reading it with larger reads pays off.
+                    fills += 1;
+                    if fills > 1 {
+                        self.rdr.grow();
+                    }
+                    continue;
+                }
+                OutputFull => {
+                    record.expand_fields();
+                    continue;
+                }
+                OutputEndsFull => {
+                    record.expand_ends();
+                    continue;
+                }
+                // The parser reports a group separator as a record without
+                // any fields.
+                Record if endlen == start => {
+                    self.state.group_start = true;
+                    continue;
+                }
+                Record => {
+                    let term = self.core.get_terminator();
+                    self.state.last_term =
+                        self.state.record_terminator(term, last);
+                    self.state.observe_terminator(term, last, &pos)?;
+                    if let Some(raw) = raw {
+                        if last == Some(b'\r')
+                            && matches!(term, CoreTerminator::CRLF)
+                        {
+                            self.read_lf(Some(raw))?;
+                        }
+                    }
+                    if start > 0 {
+                        // csv-core reports field ends relative to the start
+                        // of the record it is reading, so shift them past
+                        // the fields that were already present.
+                        let (_, ends) = record.as_parts();
+                        for end in &mut ends[start..endlen] {
+                            *end += start_out;
+                        }
+                    }
+                    record.set_len(endlen);
+                    if self.state.drop_trailing_empty_field
+                        && endlen - start > 1
+                        && record.get(endlen - 1) == Some(&b""[..])
+                    {
+                        record.set_len(endlen - 1);
+                    }
+                    self.state.add_record(record, start, pos)?;
+                    return Ok(true);
+                }
+                End => {
+                    self.state.eof = ReaderEofState::Eof;
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    /// Mark `n` bytes of the buffered input as consumed.
+    #[inline(always)]
+    fn consume(&mut self, n: usize) {
+        self.rdr.consume(n);
+        self.state.bytes_consumed += n as u64;
+    }
+
+    /// If the previous record was terminated by a `\r` that may be part of a
+    /// `\r\n`, then consume the `\n` following it, if any.
+    ///
+    /// This is done before noting where the next record starts, since the
+    /// `\n` belongs to the previous record.
+    fn skip_pending_lf(&mut self) -> Result<()> {
+        if self.state.pending_cr.is_none()
+            || self.state.eof != ReaderEofState::NotEof
+        {
+            return Ok(());
+        }
+        self.read_lf(None)
+    }
+
+    /// Consume a `\n` immediately following a record terminated by `\r`,
+    /// since it is part of that record's terminator.
+    ///
+    /// If `raw` is given, then the `\n` is appended to it, so that it is
+    /// captured as part of that record's raw bytes.
+    fn read_lf(&mut self, raw: Option<&mut Vec<u8>>) -> Result<()> {
+        let input_res = self.rdr.fill_buf();
+        if input_res.is_err() {
+            self.state.eof = ReaderEofState::IOError;
+        }
+        let next = input_res?.first().copied();
+        // The `\n` is consumed even if the line ending is inconsistent, so
+        // that the raw bytes of the record stay intact.
+        let result = self.state.resolve_cr(next);
+        if next != Some(b'\n') {
+            return result;
+        }
+        self.consume(1);
+        self.state.last_crlf = true;
+        if let Some(raw) = raw {
+            raw.push(b'\n');
+        }
+        let line = self.core.line() + 1;
+        self.core.set_line(line);
+        let byte = self.state.cur_pos.byte();
+        self.state.cur_pos.set_byte(byte + 1).set_line(line);
+        result
+    }
+
+    /// If the next row is a comment line, possibly preceded by blank lines,
+    /// then consume it and append its contents to `buf`, without the
+    /// comment byte and the line terminator.
+    ///
+    /// If the next row isn't a comment line, then this returns false after
+    /// consuming any blank lines, which the CSV parser would skip anyway.
+    /// This must only be called at the start of a row.
+    fn read_comment(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        if self.state.eof != ReaderEofState::NotEof {
+            return Ok(false);
+        }
+        let start = buf.len();
+        let mut len = start;
+        loop {
+            if len == buf.len() {
+                buf.resize(cmp::max(64, buf.len() * 2), 0);
+            }
+            let (res, nin, nout) = {
+                let input_res = self.rdr.fill_buf();
+                if input_res.is_err() {
+                    self.state.eof = ReaderEofState::IOError;
+                }
+                self.core.read_comment(input_res?, &mut buf[len..])
+            };
+            self.consume(nin);
+            let byte = self.state.cur_pos.byte();
+            self.state
+                .cur_pos
+                .set_byte(byte + nin as u64)
+                .set_line(self.core.line());
+            len += nout;
+            match res {
+                ReadCommentResult::InputEmpty
+                | ReadCommentResult::OutputFull => continue,
+                ReadCommentResult::Comment => {
+                    if len > start && buf[len - 1] == b'\r' {
+                        len -= 1;
+                    }
+                    buf.truncate(len);
+                    return Ok(true);
+                }
+                ReadCommentResult::NotComment => {
+                    buf.truncate(len);
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    /// Returns true if and only if the record most recently read by this
+    /// reader was preceded by a group separator.
+    ///
+    /// This always returns false unless group separators have been enabled
+    /// via
+    /// [`ReaderBuilder::group_separator_blank_lines`](struct.ReaderBuilder.html#method.group_separator_blank_lines).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, StringRecord};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "a\nb\n\n\nc\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .group_separator_blank_lines(2)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut record = StringRecord::new();
+    ///
+    ///     assert!(rdr.read_record(&mut record)?);
+    ///     assert!(!rdr.is_group_start());
+    ///     assert!(rdr.read_record(&mut record)?);
+    ///     assert!(!rdr.is_group_start());
+    ///     assert!(rdr.read_record(&mut record)?);
+    ///     assert!(rdr.is_group_start());
+    ///     assert_eq!(&record[0], "c");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_group_start(&self) -> bool {
+        self.state.group_start
+    }
+
+    /// Read the next group of records into `group`. Returns false when no
+    /// more records could be read.
+    ///
+    /// A group is a sequence of records ending either at a group separator
+    /// or at the end of the data. The given vector is cleared before any
+    /// records are added to it. If group separators have not been enabled
+    /// via
+    /// [`ReaderBuilder::group_separator_blank_lines`](struct.ReaderBuilder.html#method.group_separator_blank_lines),
+    /// then all remaining records are read into a single group.
+    ///
+    /// The end of a group is only known once the first record of the next
+    /// group has been read, so that record is kept by the reader and put at
+    /// the start of the next group. It is only returned by `read_group`, and
+    /// is dropped if this reader is seeked.
+    ///
+    /// If an error occurs, then the records read so far remain in `group`.
+    pub fn read_group(
+        &mut self,
+        group: &mut Vec<StringRecord>,
+    ) -> Result<bool> {
+        group.clear();
+        group.extend(self.state.group_next.take());
+        loop {
+            let mut record = StringRecord::new();
+            if !self.read_record(&mut record)? {
+                break;
+            }
+            if self.state.group_start && !group.is_empty() {
+                self.state.group_next = Some(record);
+                break;
+            }
+            group.push(record);
+        }
+        Ok(!group.is_empty())
+    }
+
+    /// Return the current position of this CSV reader.
+    ///
+    /// The byte offset in the position returned can be used to `seek` this
+    /// reader. In particular, seeking to a position returned here on the same
+    /// data will result in parsing the same subsequent record.
+    ///
+    /// # Example: reading the position
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::{Reader, Position};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let rdr = Reader::from_reader(io::Cursor::new(data));
+    ///     let mut iter = rdr.into_records();
+    ///     let mut pos = Position::new();
+    ///     loop {
+    ///         // Read the position immediately before each record.
+    ///         let next_pos = iter.reader().position().clone();
+    ///         if iter.next().is_none() {
+    ///             break;
+    ///         }
+    ///         pos = next_pos;
+    ///     }
+    ///
+    ///     // `pos` should now be the position immediately before the last
+    ///     // record.
+    ///     assert_eq!(pos.byte(), 51);
+    ///     assert_eq!(pos.line(), 3);
+    ///     assert_eq!(pos.record(), 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn position(&self) -> &Position {
+        &self.state.cur_pos
+    }
+
+    /// Returns the total number of bytes of input consumed by this reader.
+    ///
+    /// This counts every byte fed to the CSV parser, including the bytes of
+    /// headers, comments and blank lines. It only ever increases: unlike
+    /// `position`, it isn't reset or moved by seeking, so it remains useful
+    /// for measuring throughput or for progress reporting when the total
+    /// size of the input is unknown (e.g., when reading a compressed
+    /// stream). Data that has been read into this reader's buffer, but not
+    /// yet parsed, is not counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     assert_eq!(rdr.bytes_consumed(), 0);
+    ///     rdr.headers()?;
+    ///     assert_eq!(rdr.bytes_consumed(), 17);
+    ///     for result in rdr.records() {
+    ///         result?;
+    ///     }
+    ///     assert_eq!(rdr.bytes_consumed(), data.len() as u64);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn bytes_consumed(&self) -> u64 {
+        self.state.bytes_consumed
+    }
+
+    /// Returns the rolling checksum of the records read so far, or `None`
+    /// if [`ReaderBuilder::record_checksum`](struct.ReaderBuilder.html#method.record_checksum)
+    /// is disabled.
+    ///
+    /// Seeking doesn't reset the checksum.
+    pub fn checksum(&self) -> Option<u64> {
+        self.state.checksum
+    }
+
+    /// Returns the kind of line ending that terminated the records read so
+    /// far, or `None` if no record terminated by a line ending has been
+    /// read yet.
+    ///
+    /// This is useful for tools that rewrite CSV data and want to preserve
+    /// its original line endings. Only the terminators of records are
+    /// considered. Line endings inside of quoted fields, and those of blank
+    /// lines and comments, are ignored. The last record in the data doesn't
+    /// need to be terminated.
+    ///
+    /// With the default `Terminator::CRLF`, whether a record terminated by
+    /// `\r` is followed by `\n` is only known once the next record is read,
+    /// so such a record is not counted until then. With `Terminator::Any`,
+    /// only records terminated by `\n` or `\r` are counted, and `\r\n` is
+    /// never reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{DetectedTerminator, Reader};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "city,pop\r\nBoston,4628910\r\nConcord,42695\r\n";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     assert_eq!(rdr.detected_terminator(), None);
+    ///     for result in rdr.records() {
+    ///         result?;
+    ///     }
+    ///     assert_eq!(rdr.detected_terminator(), Some(DetectedTerminator::CRLF));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn detected_terminator(&self) -> Option<DetectedTerminator> {
+        detected_terminator(self.state.terms_seen)
+    }
+
+    /// Returns the bytes that terminated the last record read.
+    ///
+    /// Unlike `detected_terminator`, this reports the terminator of each
+    /// record individually, which makes it possible to faithfully reproduce
+    /// data whose records don't all end the same way. The terminator returned
+    /// is one of `\n`, `\r\n` or `\r` with the default `Terminator::CRLF`,
+    /// or the terminator byte itself with `Terminator::Any`. It is empty if
+    /// no record has been read yet, if the last read reached the end of the
+    /// data, or if the last record wasn't terminated.
+    ///
+    /// With `Terminator::CRLF`, whether a record terminated by `\r` is
+    /// followed by `\n` isn't known until the next byte is read, which is why
+    /// this may need to read from the underlying reader. Reading does not
+    /// otherwise affect the state of this reader.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ByteRecord, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "Boston,4628910\r\nConcord,42695\nSpringfield,155929";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut record = ByteRecord::new();
+    ///
+    ///     assert!(rdr.read_byte_record(&mut record)?);
+    ///     assert_eq!(rdr.last_terminator()?, b"\r\n");
+    ///     assert!(rdr.read_byte_record(&mut record)?);
+    ///     assert_eq!(rdr.last_terminator()?, b"\n");
+    ///     assert!(rdr.read_byte_record(&mut record)?);
+    ///     assert_eq!(rdr.last_terminator()?, b"");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn last_terminator(&mut self) -> Result<&[u8]> {
+        if self.state.pending_cr.is_some()
+            && !self.state.last_crlf
+            && self.state.eof == ReaderEofState::NotEof
+        {
+            let input_res = self.rdr.fill_buf();
+            if input_res.is_err() {
+                self.state.eof = ReaderEofState::IOError;
+            }
+            self.state.last_crlf = input_res?.first() == Some(&b'\n');
+        }
+        Ok(match self.state.last_term {
+            None => b"",
+            Some(b'\r') if self.state.last_crlf => b"\r\n",
+            Some(ref term) => slice::from_ref(term),
+        })
+    }
+
+    /// Returns the row that caused the most recent
+    /// [`UnequalLengths`](enum.ErrorKind.html#variant.UnequalLengths) error
+    /// returned by this reader, if any.
+    ///
+    /// The row is exactly as it was parsed, i.e., before any trimming, and
+    /// its position is set. This is useful for recovering rows from
+    /// iterators, which don't otherwise give access to the record they read
+    /// into.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ErrorKind, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "a,b\nc,d,e\nf,g\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut lost = vec![];
+    ///     let mut records = rdr.records();
+    ///     while let Some(result) = records.next() {
+    ///         if let Err(err) = result {
+    ///             if let ErrorKind::UnequalLengths { .. } = *err.kind() {
+    ///                 let rdr = records.reader();
+    ///                 lost.push(rdr.unequal_record().unwrap().clone());
+    ///             }
+    ///         }
+    ///     }
+    ///     assert_eq!(lost, vec![vec!["c", "d", "e"]]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn unequal_record(&self) -> Option<&ByteRecord> {
+        self.state.unequal_record.as_ref()
+    }
+
+    /// Returns true if and only if this reader has been exhausted.
+    ///
+    /// When this returns true, no more records can be read from this reader
+    /// (unless it has been seeked to another position).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::{Reader, Position};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(io::Cursor::new(data));
+    ///     assert!(!rdr.is_done());
+    ///     for result in rdr.records() {
+    ///         let _ = result?;
+    ///     }
+    ///     assert!(rdr.is_done());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_done(&self) -> bool {
+        self.state.eof != ReaderEofState::NotEof
+    }
+
+    /// Returns true if and only if this reader has been configured to
+    /// interpret the first record as a header record.
+    pub fn has_headers(&self) -> bool {
+        self.state.has_headers
+    }
+
+    /// Returns the field delimiter used by this reader.
+    ///
+    /// If delimiter detection is enabled, then this is the detected
+    /// delimiter once the first record has been read.
+    ///
+    /// Together with the other dialect accessors, this is useful for
+    /// writing data in the same dialect as it was read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, WriterBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "city;pop\nBoston;4628910\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .delimiter(b';')
+    ///         .from_reader(data.as_bytes());
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .delimiter(rdr.delimiter())
+    ///         .quote(rdr.quote())
+    ///         .double_quote(rdr.double_quote())
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(rdr.headers()?)?;
+    ///     for result in rdr.records() {
+    ///         wtr.write_record(&result?)?;
+    ///     }
+    ///     assert_eq!(wtr.into_inner()?, data.as_bytes());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn delimiter(&self) -> u8 {
+        self.core.get_delimiter()
+    }
+
+    /// Returns the quote character used by this reader.
+    ///
+    /// Note that quotes are only interpreted when `quoting` is enabled.
+    pub fn quote(&self) -> u8 {
+        self.core.get_quote()
+    }
+
+    /// Returns true if and only if this reader interprets quotes.
+    pub fn quoting(&self) -> bool {
+        self.core.get_quoting()
+    }
+
+    /// Returns the escape character used by this reader, if any.
+    pub fn escape(&self) -> Option<u8> {
+        self.core.get_escape()
+    }
+
+    /// Returns true if and only if two adjacent quotes in a quoted field are
+    /// interpreted as a single quote by this reader.
+    pub fn double_quote(&self) -> bool {
+        self.core.get_double_quote()
+    }
+
+    /// Returns the record terminator used by this reader.
+    pub fn terminator(&self) -> Terminator {
+        Terminator::from_core(self.core.get_terminator())
+    }
+
+    /// Returns the comment character used by this reader, if any.
+    pub fn comment(&self) -> Option<u8> {
+        self.core.get_comment()
+    }
+
+    /// Set the comment character used by this reader.
+    ///
+    /// This has the same meaning as
+    /// [`ReaderBuilder::comment`](struct.ReaderBuilder.html#method.comment),
+    /// but may be called at any time. The new setting applies to all
+    /// records read after this call. This is useful when different sections
+    /// of the CSV data use different comment conventions.
+    ///
+    /// If the comment character differs from the current one, then the
+    /// underlying parser rebuilds its transition table, which costs about as
+    /// much as building a new reader (without its buffer). So this
+    /// shouldn't be called for every record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// #a,b
+    /// c,d
+    /// #e,f
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut records = rdr.records();
+    ///     assert_eq!(records.next().unwrap()?, vec!["#a", "b"]);
+    ///
+    ///     rdr.set_comment(Some(b'#'));
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records, vec![vec!["c", "d"]]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_comment(&mut self, comment: Option<u8>) {
+        self.core.set_comment(comment);
+        if let Some(ref mut builder) = self.state.sniff {
+            builder.comment(comment);
+        }
+    }
+
+    /// Returns the indices of the key columns configured via
+    /// [`ReaderBuilder::key_columns`](struct.ReaderBuilder.html#method.key_columns).
+    pub fn key_columns(&self) -> &[usize] {
+        &self.state.key_columns
+    }
+
+    /// Returns the key of the given record, built from the key columns
+    /// configured via
+    /// [`ReaderBuilder::key_columns`](struct.ReaderBuilder.html#method.key_columns).
+    ///
+    /// This is equivalent to `record.key(rdr.key_columns())`. See
+    /// [`ByteRecord::key`](struct.ByteRecord.html#method.key) for how keys
+    /// are encoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ByteRecord, ReaderBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,state,pop
+    /// Boston,MA,4628910
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .key_columns(&[1, 0])
+    ///         .from_reader(data.as_bytes());
+    ///     let mut record = ByteRecord::new();
+    ///     assert!(rdr.read_byte_record(&mut record)?);
+    ///     assert_eq!(rdr.record_key(&record), b"MA\x00Boston\x00");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn record_key(&self, record: &ByteRecord) -> Vec<u8> {
+        record.key(&self.state.key_columns)
+    }
+
+    /// Returns the capacity (in bytes) of this reader's internal buffer.
+    ///
+    /// This is always `0` for readers built with `from_buf_reader`, since
+    /// they read directly out of the underlying reader's buffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::ReaderBuilder;
+    ///
+    /// let rdr = ReaderBuilder::new()
+    ///     .buffer_capacity(1 << 16)
+    ///     .from_reader("a,b,c".as_bytes());
+    /// assert_eq!(rdr.buffer_capacity(), 1 << 16);
+    /// ```
+    pub fn buffer_capacity(&self) -> usize {
+        self.rdr.capacity()
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.rdr.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.rdr.get_mut()
+    }
+
+    /// Reset the parsing state of this reader, such that it behaves as if
+    /// it had never been used.
+    ///
+    /// This permits reusing a reader, along with its configuration and
+    /// buffers, to parse a new stream of CSV data. The headers, position,
+    /// EOF state, expected record length, detected line endings and
+    /// checksum are all reset, and delimiter detection (if enabled) is
+    /// attempted again. Any data read from the underlying reader that has
+    /// not been parsed yet is discarded.
+    ///
+    /// This does not touch the underlying reader itself. It must be
+    /// replaced (e.g., via `get_mut`) or repositioned separately. Note that
+    /// a reader created by `from_buf_reader` has no buffer of its own, so
+    /// any data left in the buffer of the underlying reader must be
+    /// discarded separately too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "city,pop\nBoston,4628910\n";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records, vec![vec!["Boston", "4628910"]]);
+    ///
+    ///     *rdr.get_mut() = "name,age\nAlice,30\nBob,25\n".as_bytes();
+    ///     rdr.reset_state();
+    ///     assert_eq!(rdr.headers()?, vec!["name", "age"]);
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records, vec![vec!["Alice", "30"], vec!["Bob", "25"]]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn reset_state(&mut self) {
+        self.rdr.discard_buffer();
+        match self.state.sniff {
+            Some(ref builder) => *self.core = builder.build(),
+            None => self.core.reset(),
+        }
+        let state = &mut self.state;
+        state.headers = None;
+        state.footer = None;
+        state.first_field_count = None;
+        state.cur_pos = Position::new();
+        state.first = false;
+        state.seeked = false;
+        state.eof = ReaderEofState::NotEof;
+        state.group_start = false;
+        state.group_next = None;
+        state.sniffed = false;
+        state.raw_first = None;
+        state.bytes_consumed = 0;
+        if state.checksum.is_some() {
+            state.checksum = Some(CHECKSUM_INIT);
+        }
+        state.terms_seen = 0;
+        state.pending_cr = None;
+        state.last_term = None;
+        state.last_crlf = false;
+    }
+
+    /// Unwraps this CSV reader, returning the underlying reader.
+    ///
+    /// Note that any leftover data inside this reader's internal buffer is
+    /// lost.
+    pub fn into_inner(self) -> R {
+        self.rdr.into_inner()
+    }
+}
+
+impl<R: io::Read + io::Seek> Reader<R> {
+    /// Like `new`, but the reader returned can read the header row from the
+    /// end of the data.
+    fn new_seekable(
+        builder: &ReaderBuilder,
+        capacity: usize,
+        rdr: R,
+    ) -> Reader<R> {
+        let mut rdr = Reader::new(builder, capacity, rdr);
+        rdr.read_footer = Some(Reader::read_footer_headers);
+        rdr
+    }
+
+    /// Read the last row and use it as the headers, and then seek back to
+    /// where reading started.
+    ///
+    /// If the data is empty, then the headers are empty.
+    fn read_footer_headers(&mut self) -> Result<()> {
+        let start = self.rdr.seek(io::SeekFrom::Current(0))?;
+        let pos = self.state.cur_pos.clone();
+        // Only the last row matters here. The lengths of the rows before it
+        // are checked when they are read for real.
+        let flexible = mem::replace(&mut self.state.flexible, true);
+        let scanned = self.scan_footer();
+        self.state.flexible = flexible;
+        // Always seek back, even if the scan failed, so that no records are
+        // lost.
+        self.rdr.seek(io::SeekFrom::Start(start))?;
+        self.core.reset_to(pos.line());
+        self.state.cur_pos = pos;
+        self.state.eof = ReaderEofState::NotEof;
+        self.state.group_start = false;
+        self.state.group_next = None;
+        self.state.pending_cr = None;
+        let (footer, mut last) = scanned?;
+        self.state.footer = footer;
+        last.set_position(None);
+        self.set_headers_impl(Err(last));
+        Ok(())
+    }
+
+    /// Read every remaining row and return the last one, along with the byte
+    /// offset at which it starts.
+    fn scan_footer(&mut self) -> Result<(Option<u64>, ByteRecord)> {
+        let (mut record, mut last) = (ByteRecord::new(), ByteRecord::new());
+        let mut footer = None;
+        while self.read_byte_record_raw_impl(&mut record, None)? {
+            footer = record.position().map(|pos| pos.byte());
+            mem::swap(&mut record, &mut last);
+        }
+        Ok((footer, last))
+    }
+
+    /// Seeks the underlying reader to the position given.
+    ///
+    /// This comes with a few caveats:
+    ///
+    /// * Any internal buffer associated with this reader is cleared.
+    /// * If the given position does not correspond to a position immediately
+    ///   before the start of a record, then the behavior of this reader is
+    ///   unspecified.
+    /// * Any special logic that skips the first record in the CSV reader
+    ///   when reading or iterating over records is disabled.
+    ///
+    /// If the given position has a byte offset equivalent to the current
+    /// position, then no seeking is performed.
+    ///
+    /// If the header row has not already been read, then this will attempt
+    /// to read the header row before seeking. Therefore, it is possible that
+    /// this returns an error associated with reading CSV data.
+    ///
+    /// Note that seeking is performed based only on the byte offset in the
+    /// given position. Namely, the record or line numbers in the position may
+    /// be incorrect, but this will cause any future position generated by
+    /// this CSV reader to be similarly incorrect.
+    ///
+    /// # Example: seek to parse a record twice
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::{Reader, Position};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let rdr = Reader::from_reader(io::Cursor::new(data));
+    ///     let mut iter = rdr.into_records();
+    ///     let mut pos = Position::new();
+    ///     loop {
+    ///         // Read the position immediately before each record.
+    ///         let next_pos = iter.reader().position().clone();
+    ///         if iter.next().is_none() {
+    ///             break;
+    ///         }
+    ///         pos = next_pos;
+    ///     }
+    ///
+    ///     // Now seek the reader back to `pos`. This will let us read the
+    ///     // last record again.
+    ///     iter.reader_mut().seek(pos)?;
+    ///     let mut iter = iter.into_reader().into_records();
+    ///     if let Some(result) = iter.next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Concord", "United States", "42695"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn seek(&mut self, pos: Position) -> Result<()> {
+        self.byte_headers()?;
+        self.state.seeked = true;
+        self.state.pending_cr = None;
+        self.state.last_term = None;
+        if pos.byte() == self.state.cur_pos.byte() {
+            return Ok(());
+        }
+        self.rdr.seek(io::SeekFrom::Start(pos.byte()))?;
+        self.core.reset_to(pos.line());
+        self.state.cur_pos = pos;
+        self.state.eof = ReaderEofState::NotEof;
+        self.state.group_start = false;
+        self.state.group_next = None;
+        Ok(())
+    }
+
+    /// Seeks the underlying reader to the position given and returns a
+    /// borrowed iterator over all records from that position onwards.
+    ///
+    /// This is a convenience for calling `seek` followed by `records`, so the
+    /// same caveats as `seek` apply. In particular, the header row is read
+    /// before seeking if it hasn't been read already, and the returned
+    /// iterator yields every row starting at `pos` as a record, regardless
+    /// of `has_headers`. This means that seeking to the very start of the
+    /// data yields the header row as a record. Use `headers` or
+    /// `byte_headers` to access the header row instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(io::Cursor::new(data));
+    ///     let mut positions = vec![];
+    ///     let mut record = csv::StringRecord::new();
+    ///     while rdr.read_record(&mut record)? {
+    ///         positions.push(record.position().unwrap().clone());
+    ///     }
+    ///
+    ///     // Read the last record again.
+    ///     let mut iter = rdr.records_from(&positions[1])?;
+    ///     let record = iter.next().unwrap()?;
+    ///     assert_eq!(record, vec!["Concord", "United States", "42695"]);
+    ///     assert!(iter.next().is_none());
+    ///
+    ///     // Seeking to the start yields the header row as a record.
+    ///     let mut iter = rdr.records_from(&csv::Position::new())?;
+    ///     let record = iter.next().unwrap()?;
+    ///     assert_eq!(record, vec!["city", "country", "popcount"]);
+    ///     assert_eq!(iter.count(), 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn records_from(
+        &mut self,
+        pos: &Position,
+    ) -> Result<StringRecordsIter<'_, R>> {
+        self.seek(pos.clone())?;
+        Ok(self.records())
+    }
+
+    /// Guesses whether the first row of the CSV data is a header row.
+    ///
+    /// This compares the fields of the first row with the fields of the
+    /// record following it, column by column. A column whose first field is
+    /// text but whose second field is a number suggests a header row, while
+    /// a column of numbers suggests otherwise. If there is no second record,
+    /// then this returns `false`.
+    ///
+    /// This is best-effort. For example, data consisting entirely of text
+    /// can never be guessed to have a header row. The guess doesn't change
+    /// the `has_headers` setting of this reader. Instead, callers may use it
+    /// to decide how to treat the first row, e.g., by calling
+    /// `set_byte_headers`.
+    ///
+    /// No records are consumed. This reads the first row (if it hasn't been
+    /// read already) and the record after the current position, and then
+    /// seeks back to the current position. If records have already been
+    /// read, then the first row is compared with the next record instead.
+    /// Like `seek`, this assumes that the underlying reader started at
+    /// byte offset `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::ReaderBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// ";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(io::Cursor::new(data));
+    ///     assert!(rdr.guess_has_headers()?);
+    ///
+    ///     // Nothing was consumed, so the first row is still a record.
+    ///     let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records.len(), 2);
+    ///     assert_eq!(records[0], vec!["city", "country", "pop"]);
+    ///
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .from_reader(io::Cursor::new("1,2\n3,4\n"));
+    ///     assert!(!rdr.guess_has_headers()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn guess_has_headers(&mut self) -> Result<bool> {
+        let first = self.byte_headers()?.clone();
+        let pos = self.state.cur_pos.clone();
+        let eof = self.state.eof;
+        let group_start = self.state.group_start;
+        let first_field_count = self.state.first_field_count;
+
+        let mut second = ByteRecord::new();
+        let result = self.read_byte_record_impl(&mut second);
+
+        self.rdr.seek(io::SeekFrom::Start(pos.byte()))?;
+        self.core.reset_to(pos.line());
+        self.state.cur_pos = pos;
+        self.state.eof = eof;
+        self.state.group_start = group_start;
+        self.state.first_field_count = first_field_count;
+        if !result? {
+            return Ok(false);
+        }
+        Ok(sniff_has_headers(&first, &second))
+    }
+
+    /// Seeks the underlying reader to the given byte offset.
+    ///
+    /// This is like `seek`, but only requires a byte offset. This is useful
+    /// when the line and record numbers corresponding to the offset are not
+    /// known, e.g., when the offset comes from a custom index. The same
+    /// caveats as `seek` apply. In particular, the byte offset should
+    /// correspond to a position immediately before the start of a record.
+    ///
+    /// Since the line and record numbers are unknown, they are reset to `1`
+    /// and `0`, respectively. As a result, the line and record numbers
+    /// reported by `position` (and by the positions of any records read)
+    /// after seeking are relative to the given offset rather than the start
+    /// of the data. Only the byte offsets remain accurate.
+    ///
+    /// If the given byte offset is equivalent to the current position, then
+    /// no seeking is performed and the current position is left unchanged.
+    ///
+    /// If the header row has not already been read, then this will attempt
+    /// to read the header row before seeking. Therefore, it is possible that
+    /// this returns an error associated with reading CSV data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, io};
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,popcount
+    /// Boston,United States,4628910
+    /// Concord,United States,42695
+    /// ";
+    ///     let mut rdr = Reader::from_reader(io::Cursor::new(data));
+    ///     rdr.seek_to_byte(51)?;
+    ///     assert_eq!(rdr.position().byte(), 51);
+    ///
+    ///     if let Some(result) = rdr.records().next() {
+    ///         let record = result?;
+    ///         assert_eq!(record, vec!["Concord", "United States", "42695"]);
+    ///         Ok(())
+    ///     } else {
+    ///         Err(From::from("expected at least one record but got none"))
+    ///     }
+    /// }
+    /// ```
+    pub fn seek_to_byte(&mut self, byte: u64) -> Result<()> {
+        let mut pos = Position::new();
+        pos.set_byte(byte);
+        self.seek(pos)
+    }
+
+    /// This is like `seek`, but provides direct control over how the seeking
+    /// operation is performed via `io::SeekFrom`.
+    ///
+    /// The `pos` position given *should* correspond the position indicated
+    /// by `seek_from`, but there is no requirement. If the `pos` position
+    /// given is incorrect, then the position information returned by this
+    /// reader will be similarly incorrect.
+    ///
+    /// If the header row has not already been read, then this will attempt
+    /// to read the header row before seeking. Therefore, it is possible that
+    /// this returns an error associated with reading CSV data.
+    ///
+    /// Unlike `seek`, this will always cause an actual seek to be performed.
+    pub fn seek_raw(
+        &mut self,
+        seek_from: io::SeekFrom,
+        pos: Position,
+    ) -> Result<()> {
+        self.byte_headers()?;
+        self.state.seeked = true;
+        self.state.pending_cr = None;
+        self.state.last_term = None;
+        self.rdr.seek(seek_from)?;
+        self.core.reset_to(pos.line());
+        self.state.cur_pos = pos;
+        self.state.eof = ReaderEofState::NotEof;
+        self.state.group_start = false;
+        self.state.group_next = None;
+        Ok(())
+    }
+}
+
+impl ReaderState {
+    /// Whether the header row is read from the end of the data.
+    fn has_footer_headers(&self) -> bool {
+        self.has_headers && self.headers_position == HeaderPosition::Last
+    }
+
+    /// Return the byte that terminated a record, given the last byte consumed
+    /// for it, if any.
+    #[inline(always)]
+    fn record_terminator(
+        &self,
+        term: CoreTerminator,
+        last: Option<u8>,
+    ) -> Option<u8> {
+        match (term, last) {
+            (CoreTerminator::CRLF, Some(b @ (b'\r' | b'\n'))) => Some(b),
+            (CoreTerminator::Any(t), Some(b)) if b == t => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Record the line ending of the record at `pos`, given the last byte
+    /// consumed for it, if any.
+    #[inline(always)]
+    fn observe_terminator(
+        &mut self,
+        term: CoreTerminator,
+        last: Option<u8>,
+        pos: &Position,
+    ) -> Result<()> {
+        match last {
+            Some(b'\n') => self.add_terminator(TERM_LF, pos),
+            Some(b'\r') if matches!(term, CoreTerminator::CRLF) => {
+                self.pending_cr = Some(pos.clone());
+                Ok(())
+            }
+            Some(b'\r') => self.add_terminator(TERM_CR, pos),
+            _ => Ok(()),
+        }
+    }
+
+    /// Given the byte following a record terminated by `\r`, if any, record
+    /// whether its line ending was `\r` or `\r\n`.
+    #[inline(always)]
+    fn resolve_cr(&mut self, next: Option<u8>) -> Result<()> {
+        match self.pending_cr.take() {
+            None => Ok(()),
+            Some(pos) => {
+                let term =
+                    if next == Some(b'\n') { TERM_CRLF } else { TERM_CR };
+                self.add_terminator(term, &pos)
+            }
+        }
+    }
+
+    /// Add a line ending to the set of those seen so far, and return an
+    /// error if it's a new kind and consistent line endings are required.
+    #[inline(always)]
+    fn add_terminator(&mut self, term: u8, pos: &Position) -> Result<()> {
+        let seen = self.terms_seen;
+        self.terms_seen |= term;
+        if !self.consistent_terminator || seen == 0 || seen & term != 0 {
+            return Ok(());
+        }
+        Err(Error::new(ErrorKind::InconsistentTerminator {
+            pos: Some(pos.clone()),
+            expected: detected_terminator(seen).unwrap(),
+            found: detected_terminator(term).unwrap(),
+        }))
+    }
+
+    /// Only the fields of `record` starting at index `start` belong to the
+    /// row that was just read, and `pos` is the position of that row.
+    #[inline(always)]
+    fn add_record(
+        &mut self,
+        record: &ByteRecord,
+        start: usize,
+        pos: Position,
+    ) -> Result<()> {
+        let i = self.cur_pos.record();
+        self.cur_pos.set_record(i.checked_add(1).unwrap());
+        if !self.flexible {
+            let len = (record.len() - start) as u64;
+            match self.first_field_count {
+                None => self.first_field_count = Some(len),
+                Some(expected) => {
+                    if len != expected {
+                        self.keep_unequal_record(record, start, Some(&pos));
+                        return Err(Error::new(ErrorKind::UnequalLengths {
+                            pos: Some(pos),
+                            expected_len: expected,
+                            len,
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remember the fields of `record` starting at index `start` as the row
+    /// that caused an `UnequalLengths` error.
+    #[cold]
+    fn keep_unequal_record(
+        &mut self,
+        record: &ByteRecord,
+        start: usize,
+        pos: Option<&Position>,
+    ) {
+        let row = self.unequal_record.get_or_insert_with(ByteRecord::new);
+        row.clear();
+        row.extend(record.iter().skip(start));
+        row.set_position(pos.cloned());
+    }
+}
+
+/// An owned iterator over deserialized records.
+///
+/// The type parameter `R` refers to the underlying `io::Read` type, and `D`
+/// refers to the type that this iterator will deserialize a record into.
+pub struct DeserializeRecordsIntoIter<R, D> {
+    rdr: Reader<R>,
+    rec: StringRecord,
+    headers: Option<StringRecord>,
+    _priv: PhantomData<D>,
+}
+
+impl<R: io::Read, D: DeserializeOwned> DeserializeRecordsIntoIter<R, D> {
+    fn new(mut rdr: Reader<R>) -> DeserializeRecordsIntoIter<R, D> {
+        let headers = if !rdr.state.has_headers {
+            None
+        } else {
+            rdr.headers().ok().map(Clone::clone)
+        };
+        DeserializeRecordsIntoIter {
+            rdr,
+            rec: StringRecord::new(),
+            headers,
+            _priv: PhantomData,
+        }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Drop this iterator and return the underlying CSV reader.
+    pub fn into_reader(self) -> Reader<R> {
+        self.rdr
+    }
+
+    /// Convert this iterator into an owned iterator over the remaining
+    /// records as strings, without deserializing them.
+    ///
+    /// Iteration resumes at the record following the last one yielded by
+    /// this iterator.
+    pub fn into_records(self) -> StringRecordsIntoIter<R> {
+        StringRecordsIntoIter::new(self.rdr)
+    }
+}
+
+impl<R: io::Read, D: DeserializeOwned> Iterator
+    for DeserializeRecordsIntoIter<R, D>
+{
+    type Item = Result<D>;
+
+    fn next(&mut self) -> Option<Result<D>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(false) => None,
+            Ok(true) => Some(deserialize_string_record_with(
+                &self.rec,
+                self.headers.as_ref(),
+                self.rdr.state.deserialize,
+            )),
+        }
+    }
+}
+
+/// A borrowed iterator over deserialized records.
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`. The type parameter `R` refers to the underlying `io::Read`
+/// type, and `D` refers to the type that this iterator will deserialize a
+/// record into.
+pub struct DeserializeRecordsIter<'r, R: 'r, D> {
+    rdr: &'r mut Reader<R>,
+    rec: StringRecord,
+    headers: Option<StringRecord>,
+    _priv: PhantomData<D>,
+}
+
+impl<'r, R: io::Read, D: DeserializeOwned> DeserializeRecordsIter<'r, R, D> {
+    fn new(rdr: &'r mut Reader<R>) -> DeserializeRecordsIter<'r, R, D> {
+        let headers = if !rdr.state.has_headers {
+            None
+        } else {
+            rdr.headers().ok().map(Clone::clone)
+        };
+        DeserializeRecordsIter {
+            rdr,
+            rec: StringRecord::new(),
+            headers,
+            _priv: PhantomData,
+        }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Convert this iterator into an iterator over the remaining records as
+    /// strings, without deserializing them.
+    ///
+    /// This is useful for falling back to raw records, e.g., after a
+    /// deserialization error. Iteration resumes at the record following the
+    /// last one yielded by this iterator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,pop
+    /// Boston,4628910
+    /// Concord,unknown
+    /// Springfield,NA
+    /// ";
+    ///     let mut rdr = csv::Reader::from_reader(data.as_bytes());
+    ///     let mut iter = rdr.deserialize::<(String, u64)>();
+    ///     assert!(iter.next().unwrap().is_ok());
+    ///     assert!(iter.next().unwrap().is_err());
+    ///
+    ///     let mut records = iter.into_records();
+    ///     assert_eq!(records.next().unwrap()?, vec!["Springfield", "NA"]);
+    ///     assert!(records.next().is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_records(self) -> StringRecordsIter<'r, R> {
+        StringRecordsIter::new(self.rdr)
+    }
+}
+
+impl<'r, R: io::Read, D: DeserializeOwned> Iterator
+    for DeserializeRecordsIter<'r, R, D>
+{
+    type Item = Result<D>;
+
+    fn next(&mut self) -> Option<Result<D>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(false) => None,
+            Ok(true) => Some(deserialize_string_record_with(
+                &self.rec,
+                self.headers.as_ref(),
+                self.rdr.state.deserialize,
+            )),
+        }
+    }
+}
+
+/// An owned iterator over records as strings.
+pub struct StringRecordsIntoIter<R> {
+    rdr: Reader<R>,
+    rec: StringRecord,
+}
+
+impl<R: io::Read> StringRecordsIntoIter<R> {
+    fn new(rdr: Reader<R>) -> StringRecordsIntoIter<R> {
+        StringRecordsIntoIter { rdr, rec: StringRecord::new() }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Drop this iterator and return the underlying CSV reader.
+    pub fn into_reader(self) -> Reader<R> {
+        self.rdr
+    }
+}
+
+impl<R: io::Read> Iterator for StringRecordsIntoIter<R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => Some(Ok(self.rec.clone_truncated())),
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over records as strings.
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct StringRecordsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: StringRecord,
+}
+
+impl<'r, R: io::Read> StringRecordsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>) -> StringRecordsIter<'r, R> {
+        StringRecordsIter { rdr, rec: StringRecord::new() }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Skip records with recoverable data errors instead of yielding them.
+    ///
+    /// A record with an unexpected number of fields or with invalid UTF-8 is
+    /// skipped, and its error is passed to `on_error`. (The position of the
+    /// skipped record is available via
+    /// [`Error::position`](struct.Error.html#method.position).) All other
+    /// errors, such as I/O errors, are still yielded by the iterator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Reader;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "\
+    /// city,country,pop
+    /// Boston,United States,4628910
+    /// Concord,United States
+    /// Springfield,United States,30720
+    /// ";
+    ///     let mut rdr = Reader::from_reader(data.as_bytes());
+    ///     let mut skipped = vec![];
+    ///     let mut records = vec![];
+    ///     let iter = rdr.records().continue_on_data_errors(|err| {
+    ///         skipped.push(err.position().unwrap().line());
+    ///     });
+    ///     for result in iter {
+    ///         records.push(result?);
+    ///     }
+    ///     assert_eq!(records.len(), 2);
+    ///     assert_eq!(&records[1][0], "Springfield");
+    ///     assert_eq!(skipped, vec![3]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn continue_on_data_errors<F: FnMut(Error)>(
+        self,
+        on_error: F,
+    ) -> ContinueOnDataErrors<Self, F> {
+        ContinueOnDataErrors::new(self, on_error)
+    }
+}
+
+impl<'r, R: io::Read> Iterator for StringRecordsIter<'r, R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => Some(Ok(self.rec.clone_truncated())),
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over records as strings, paired with their record
+/// index.
+///
+/// This is created by
+/// [`Reader::enumerate_records`](struct.Reader.html#method.enumerate_records).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct EnumerateRecordsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: StringRecord,
+    /// An error from reading the header row, which is yielded first.
+    err: Option<Error>,
+}
+
+impl<'r, R: io::Read> EnumerateRecordsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>) -> EnumerateRecordsIter<'r, R> {
+        // Read the header row up front, so that the position of the reader
+        // reflects the first record that will be yielded.
+        let err = if rdr.state.has_headers {
+            rdr.byte_headers().err()
+        } else {
+            None
+        };
+        EnumerateRecordsIter { rdr, rec: StringRecord::new(), err }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+}
+
+impl<'r, R: io::Read> Iterator for EnumerateRecordsIter<'r, R> {
+    type Item = (u64, Result<StringRecord>);
+
+    fn next(&mut self) -> Option<(u64, Result<StringRecord>)> {
+        let index = self.rdr.position().record();
+        if let Some(err) = self.err.take() {
+            return Some((index, Err(err)));
+        }
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some((index, Err(err))),
+            Ok(true) => Some((index, Ok(self.rec.clone_truncated()))),
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over at most a fixed number of records as strings.
+///
+/// This is created by
+/// [`Reader::take_records`](struct.Reader.html#method.take_records).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct TakeRecordsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: StringRecord,
+    remaining: usize,
+}
+
+impl<'r, R: io::Read> TakeRecordsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>, n: usize) -> TakeRecordsIter<'r, R> {
+        TakeRecordsIter { rdr, rec: StringRecord::new(), remaining: n }
+    }
+
+    /// Return the number of records this iterator may still yield.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+}
+
+impl<'r, R: io::Read> Iterator for TakeRecordsIter<'r, R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => Some(Ok(self.rec.clone_truncated())),
+            Ok(false) => {
+                self.remaining = 0;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// An item yielded by
+/// [`Reader::records_with_comments`](struct.Reader.html#method.records_with_comments).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecordOrComment {
+    /// A record.
+    Record(StringRecord),
+    /// The contents of a comment line, without the comment byte and the
+    /// line terminator.
+    Comment(String),
+}
+
+impl RecordOrComment {
+    /// Returns true if and only if this is a record.
+    pub fn is_record(&self) -> bool {
+        matches!(*self, RecordOrComment::Record(_))
+    }
+
+    /// Returns true if and only if this is a comment.
+    pub fn is_comment(&self) -> bool {
+        matches!(*self, RecordOrComment::Comment(_))
+    }
+}
+
+/// A borrowed iterator over records as strings, along with any comment lines
+/// between them.
+///
+/// This is created by
+/// [`Reader::records_with_comments`](struct.Reader.html#method.records_with_comments).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct RecordsWithCommentsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: StringRecord,
+    buf: Vec<u8>,
+}
+
+impl<'r, R: io::Read> RecordsWithCommentsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>) -> RecordsWithCommentsIter<'r, R> {
+        RecordsWithCommentsIter { rdr, rec: StringRecord::new(), buf: vec![] }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+
+    /// Read the next comment line, if the next row is one.
+    fn next_comment(&mut self) -> Option<Result<RecordOrComment>> {
+        let pos = self.rdr.position().clone();
+        self.buf.clear();
+        match self.rdr.read_comment(&mut self.buf) {
+            Err(err) => Some(Err(err)),
+            Ok(false) => None,
+            Ok(true) => Some(
+                String::from_utf8(mem::take(&mut self.buf))
+                    .map(RecordOrComment::Comment)
+                    .map_err(|err| {
+                        Error::new(ErrorKind::Utf8 {
+                            pos: Some(pos),
+                            err: new_utf8_error(
+                                0,
+                                err.utf8_error().valid_up_to(),
+                            ),
+                        })
+                    }),
+            ),
+        }
+    }
+}
+
+impl<'r, R: io::Read> Iterator for RecordsWithCommentsIter<'r, R> {
+    type Item = Result<RecordOrComment>;
+
+    fn next(&mut self) -> Option<Result<RecordOrComment>> {
+        let state = &self.rdr.state;
+        // When the first row has already been read as the header row but
+        // hasn't been yielded yet, it must come before any comments.
+        let first_pending = !state.seeked
+            && !state.first
+            && !state.has_headers
+            && state.headers.is_some();
+        if !first_pending {
+            if !state.seeked && state.has_headers && state.headers.is_none() {
+                if let Some(item) = self.next_comment() {
+                    return Some(item);
+                }
+                if let Err(err) = self.rdr.byte_headers() {
+                    return Some(Err(err));
+                }
+            }
+            if let Some(item) = self.next_comment() {
+                return Some(item);
+            }
+        }
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => {
+                Some(Ok(RecordOrComment::Record(self.rec.clone_truncated())))
+            }
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over records as strings that tolerates records of
+/// unequal length.
+///
+/// This is created by
+/// [`Reader::records_lenient`](struct.Reader.html#method.records_lenient).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct LenientRecordsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: ByteRecord,
+    errors: Vec<Error>,
+}
+
+impl<'r, R: io::Read> LenientRecordsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>) -> LenientRecordsIter<'r, R> {
+        LenientRecordsIter { rdr, rec: ByteRecord::new(), errors: vec![] }
+    }
+
+    /// Return the `UnequalLengths` errors recorded so far, in the order in
+    /// which the offending records were read.
+    pub fn length_errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Remove and return the `UnequalLengths` errors recorded so far.
+    pub fn take_length_errors(&mut self) -> Vec<Error> {
+        mem::take(&mut self.errors)
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+}
+
+impl<'r, R: io::Read> Iterator for LenientRecordsIter<'r, R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        match self.rdr.read_byte_record(&mut self.rec) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => match *err.kind() {
+                // The offending record is still in `self.rec`.
+                ErrorKind::UnequalLengths { .. } => self.errors.push(err),
+                _ => return Some(Err(err)),
+            },
+        }
+        let record = self.rec.clone_truncated();
+        let pos = record.position().cloned();
+        Some(StringRecord::from_byte_record(record).map_err(|err| {
+            Error::new(ErrorKind::Utf8 { pos, err: err.utf8_error().clone() })
+        }))
+    }
+}
+
+/// A borrowed iterator over records as strings, restricted to a selection of
+/// columns.
+///
+/// This is created by
+/// [`Reader::select_columns`](struct.Reader.html#method.select_columns).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct ColumnReader<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    columns: Vec<usize>,
+    headers: StringRecord,
+    rec: StringRecord,
+}
+
+impl<'r, R: io::Read> ColumnReader<'r, R> {
+    fn new(
+        rdr: &'r mut Reader<R>,
+        names: &[&str],
+    ) -> Result<ColumnReader<'r, R>> {
+        let mut columns = Vec::with_capacity(names.len());
+        {
+            let headers = rdr.headers()?;
+            for &name in names {
+                match headers.iter().position(|h| h == name) {
+                    Some(i) => columns.push(i),
+                    None => {
+                        return Err(Error::new(ErrorKind::UnknownColumn {
+                            name: name.to_string(),
+                        }))
+                    }
+                }
+            }
+        }
+        let headers = StringRecord::from(names);
+        Ok(ColumnReader { rdr, columns, headers, rec: StringRecord::new() })
+    }
+
+    /// Return the names of the selected columns, in the order in which
+    /// they appear in each record yielded by this iterator.
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+}
+
+impl<'r, R: io::Read> Iterator for ColumnReader<'r, R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => {
+                let mut record = StringRecord::with_capacity(
+                    self.rec.as_slice().len(),
+                    self.columns.len(),
+                );
+                for &i in &self.columns {
+                    record.push_field(self.rec.get(i).unwrap_or(""));
+                }
+                record.set_position(self.rec.position().cloned());
+                Some(Ok(record))
+            }
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over records as strings that checks each record
+/// against a fixed list of column types.
+///
+/// This is created by
+/// [`Reader::validate_schema`](struct.Reader.html#method.validate_schema).
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct SchemaValidator<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    types: Vec<ColumnType>,
+    rec: StringRecord,
+}
+
+impl<'r, R: io::Read> SchemaValidator<'r, R> {
+    fn new(
+        rdr: &'r mut Reader<R>,
+        types: &[ColumnType],
+    ) -> SchemaValidator<'r, R> {
+        SchemaValidator {
+            rdr,
+            types: types.to_vec(),
+            rec: StringRecord::new(),
+        }
+    }
+
+    /// Return the expected type of each column.
+    pub fn types(&self) -> &[ColumnType] {
+        &self.types
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        self.rdr
+    }
+
+    /// Check the record just read against the column types.
+    fn validate(&mut self) -> Result<()> {
+        let pos = || self.rec.position().cloned();
+        if self.rec.len() != self.types.len() {
+            let record = self.rec.as_byte_record();
+            self.rdr.state.keep_unequal_record(record, 0, record.position());
+            return Err(Error::new(ErrorKind::UnequalLengths {
+                pos: pos(),
+                expected_len: self.types.len() as u64,
+                len: self.rec.len() as u64,
+            }));
+        }
+        for (i, (field, &expected)) in
+            self.rec.iter().zip(&self.types).enumerate()
+        {
+            let found = infer_column_type(field);
+            let ok = match (expected, found) {
+                (ColumnType::String, _) => true,
+                (ColumnType::Float, ColumnType::Integer) => true,
+                (expected, found) => expected == found,
+            };
+            if !ok {
+                return Err(Error::new(ErrorKind::SchemaMismatch {
+                    pos: pos(),
+                    field: i as u64,
+                    expected,
+                    found,
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'r, R: io::Read> Iterator for SchemaValidator<'r, R> {
+    type Item = Result<StringRecord>;
+
+    fn next(&mut self) -> Option<Result<StringRecord>> {
+        match self.rdr.read_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => {
+                Some(self.validate().map(|()| self.rec.clone_truncated()))
+            }
+            Ok(false) => None,
+        }
+    }
+}
+
+/// An owned iterator over records as raw bytes.
+pub struct ByteRecordsIntoIter<R> {
+    rdr: Reader<R>,
+    rec: ByteRecord,
+}
+
+impl<R: io::Read> ByteRecordsIntoIter<R> {
+    fn new(rdr: Reader<R>) -> ByteRecordsIntoIter<R> {
+        ByteRecordsIntoIter { rdr, rec: ByteRecord::new() }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Drop this iterator and return the underlying CSV reader.
+    pub fn into_reader(self) -> Reader<R> {
+        self.rdr
+    }
+}
+
+impl<R: io::Read> Iterator for ByteRecordsIntoIter<R> {
+    type Item = Result<ByteRecord>;
+
+    fn next(&mut self) -> Option<Result<ByteRecord>> {
+        match self.rdr.read_byte_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => Some(Ok(self.rec.clone_truncated())),
+            Ok(false) => None,
+        }
+    }
+}
+
+/// A borrowed iterator over records as raw bytes.
+///
+/// The lifetime parameter `'r` refers to the lifetime of the underlying
+/// CSV `Reader`.
+pub struct ByteRecordsIter<'r, R: 'r> {
+    rdr: &'r mut Reader<R>,
+    rec: ByteRecord,
+}
+
+impl<'r, R: io::Read> ByteRecordsIter<'r, R> {
+    fn new(rdr: &'r mut Reader<R>) -> ByteRecordsIter<'r, R> {
+        ByteRecordsIter { rdr, rec: ByteRecord::new() }
+    }
+
+    /// Return a reference to the underlying CSV reader.
+    pub fn reader(&self) -> &Reader<R> {
+        &self.rdr
+    }
+
+    /// Return a mutable reference to the underlying CSV reader.
+    pub fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.rdr
+    }
+
+    /// Skip records with recoverable data errors instead of yielding them.
+    ///
+    /// This is like
+    /// [`StringRecordsIter::continue_on_data_errors`](struct.StringRecordsIter.html#method.continue_on_data_errors),
+    /// except that, since byte records are never checked for UTF-8, only
+    /// records with an unexpected number of fields are skipped.
+    pub fn continue_on_data_errors<F: FnMut(Error)>(
+        self,
+        on_error: F,
+    ) -> ContinueOnDataErrors<Self, F> {
+        ContinueOnDataErrors::new(self, on_error)
+    }
+}
+
+impl<'r, R: io::Read> Iterator for ByteRecordsIter<'r, R> {
+    type Item = Result<ByteRecord>;
+
+    fn next(&mut self) -> Option<Result<ByteRecord>> {
+        match self.rdr.read_byte_record(&mut self.rec) {
+            Err(err) => Some(Err(err)),
+            Ok(true) => Some(Ok(self.rec.clone_truncated())),
+            Ok(false) => None,
+        }
+    }
+}
+
+/// An iterator adapter that skips records with recoverable data errors.
+///
+/// This is created by
+/// [`StringRecordsIter::continue_on_data_errors`](struct.StringRecordsIter.html#method.continue_on_data_errors)
+/// or
+/// [`ByteRecordsIter::continue_on_data_errors`](struct.ByteRecordsIter.html#method.continue_on_data_errors).
+pub struct ContinueOnDataErrors<I, F> {
+    it: I,
+    on_error: F,
+}
+
+impl<I, F> ContinueOnDataErrors<I, F> {
+    fn new(it: I, on_error: F) -> ContinueOnDataErrors<I, F> {
+        ContinueOnDataErrors { it, on_error }
+    }
+
+    /// Return a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.it
+    }
+
+    /// Return a mutable reference to the wrapped iterator.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.it
+    }
+}
+
+impl<T, I, F> Iterator for ContinueOnDataErrors<I, F>
+where
+    I: Iterator<Item = Result<T>>,
+    F: FnMut(Error),
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            match self.it.next()? {
+                Err(err) => match *err.kind() {
+                    ErrorKind::UnequalLengths { .. }
+                    | ErrorKind::Utf8 { .. } => (self.on_error)(err),
+                    _ => return Some(Err(err)),
+                },
+                Ok(rec) => return Some(Ok(rec)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        byte_record::{ByteRecord, CHECKSUM_INIT},
+        error::ErrorKind,
+        string_record::StringRecord,
+    };
+
+    use super::{
+        ErrorAction, HeaderPosition, Position, Reader, ReaderBuilder,
+        RecordOrComment, RecordsWithCommentsIter, Terminator, Trim,
+    };
+
+    fn b(s: &str) -> &[u8] {
+        s.as_bytes()
+    }
+    fn s(b: &[u8]) -> &str {
+        ::std::str::from_utf8(b).unwrap()
+    }
+
+    fn newpos(byte: u64, line: u64, record: u64) -> Position {
+        let mut p = Position::new();
+        p.set_byte(byte).set_line(line).set_record(record);
+        p
+    }
+
+    #[test]
+    fn read_byte_record() {
+        let data = b("foo,\"b,ar\",baz\nabc,mno,xyz");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = ByteRecord::new();
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("foo", s(&rec[0]));
+        assert_eq!("b,ar", s(&rec[1]));
+        assert_eq!("baz", s(&rec[2]));
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("abc", s(&rec[0]));
+        assert_eq!("mno", s(&rec[1]));
+        assert_eq!("xyz", s(&rec[2]));
+
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+    }
+
+    #[test]
+    fn read_trimmed_records_and_headers() {
+        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::All)
+            .from_reader(data);
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!("1", s(&rec[0]));
+        assert_eq!("2", s(&rec[1]));
+        assert_eq!("3", s(&rec[2]));
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!("1", &rec[0]);
+        assert_eq!("", &rec[1]);
+        assert_eq!("3", &rec[2]);
+        {
+            let headers = rdr.headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!("foo", &headers[0]);
+            assert_eq!("bar", &headers[1]);
+            assert_eq!("baz", &headers[2]);
+        }
+    }
+
+    #[test]
+    fn read_trimmed_header() {
+        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::Headers)
+            .from_reader(data);
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!("  1", s(&rec[0]));
+        assert_eq!("  2", s(&rec[1]));
+        assert_eq!("  3", s(&rec[2]));
+        {
+            let headers = rdr.headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!("foo", &headers[0]);
+            assert_eq!("bar", &headers[1]);
+            assert_eq!("baz", &headers[2]);
+        }
+    }
+
+    #[test]
+    fn read_trimed_header_invalid_utf8() {
+        let data = &b"foo,  b\xFFar,\tbaz\na,b,c\nd,e,f"[..];
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::Headers)
+            .from_reader(data);
+        let mut rec = StringRecord::new();
+
+        // force the headers to be read
+        let _ = rdr.read_record(&mut rec);
+        // Check the byte headers are trimmed
+        {
+            let headers = rdr.byte_headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!(b"foo", &headers[0]);
+            assert_eq!(b"b\xFFar", &headers[1]);
+            assert_eq!(b"baz", &headers[2]);
+        }
+        match *rdr.headers().unwrap_err().kind() {
+            ErrorKind::Utf8 { pos: Some(ref pos), ref err } => {
+                assert_eq!(pos, &newpos(0, 1, 0));
+                assert_eq!(err.field(), 1);
+                assert_eq!(err.valid_up_to(), 3);
+            }
+            ref err => panic!("match failed, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn read_trimmed_records() {
+        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::Fields)
+            .from_reader(data);
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!("1", s(&rec[0]));
+        assert_eq!("2", s(&rec[1]));
+        assert_eq!("3", s(&rec[2]));
+        {
+            let headers = rdr.headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!("foo", &headers[0]);
+            assert_eq!("  bar", &headers[1]);
+            assert_eq!("\tbaz", &headers[2]);
+        }
+    }
+
+    #[test]
+    fn read_trimmed_records_without_headers() {
+        let data = b("a1, b1\t,\t c1\t\n");
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .trim(Trim::All)
+            .from_reader(data);
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!("a1", s(&rec[0]));
+        assert_eq!("b1", s(&rec[1]));
+        assert_eq!("c1", s(&rec[2]));
+    }
+
+    #[test]
+    fn read_record_unequal_fails() {
+        let data = b("foo\nbar,baz");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = ByteRecord::new();
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(1, rec.len());
+        assert_eq!("foo", s(&rec[0]));
+
+        match rdr.read_byte_record(&mut rec) {
+            Err(err) => match *err.kind() {
+                ErrorKind::UnequalLengths {
+                    expected_len: 1,
+                    ref pos,
+                    len: 2,
+                    ..
+                } => {
+                    assert_eq!(pos, &Some(newpos(4, 2, 1)));
+                }
+                ref wrong => panic!("match failed, got {:?}", wrong),
+            },
+            wrong => panic!("match failed, got {:?}", wrong),
+        }
+    }
+
+    #[test]
+    fn read_record_unequal_ok() {
+        let data = b("foo\nbar,baz");
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(data);
+        let mut rec = ByteRecord::new();
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(1, rec.len());
+        assert_eq!("foo", s(&rec[0]));
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(2, rec.len());
+        assert_eq!("bar", s(&rec[0]));
+        assert_eq!("baz", s(&rec[1]));
+
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+    }
+
+    // This tests that even if we get a CSV error, we can continue reading
+    // if we want.
+    #[test]
+    fn read_record_unequal_continue() {
+        let data = b("foo\nbar,baz\nquux");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = ByteRecord::new();
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(1, rec.len());
+        assert_eq!("foo", s(&rec[0]));
+
+        match rdr.read_byte_record(&mut rec) {
+            Err(err) => match err.kind() {
+                &ErrorKind::UnequalLengths {
+                    expected_len: 1,
+                    ref pos,
+                    len: 2,
+                    ..
+                } => {
+                    assert_eq!(pos, &Some(newpos(4, 2, 1)));
+                }
+                wrong => panic!("match failed, got {:?}", wrong),
+            },
+            wrong => panic!("match failed, got {:?}", wrong),
+        }
+
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(1, rec.len());
+        assert_eq!("quux", s(&rec[0]));
+
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+    }
+
+    #[test]
+    fn read_record_headers() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f");
+        let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(data);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("a", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("d", &rec[0]);
+
+        assert!(!rdr.read_record(&mut rec).unwrap());
+
+        {
+            let headers = rdr.byte_headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!(b"foo", &headers[0]);
+            assert_eq!(b"bar", &headers[1]);
+            assert_eq!(b"baz", &headers[2]);
+        }
+        {
+            let headers = rdr.headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!("foo", &headers[0]);
+            assert_eq!("bar", &headers[1]);
+            assert_eq!("baz", &headers[2]);
+        }
+    }
+
+    #[test]
+    fn read_record_headers_invalid_utf8() {
+        let data = &b"foo,b\xFFar,baz\na,b,c\nd,e,f"[..];
+        let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(data);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("a", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("d", &rec[0]);
+
+        assert!(!rdr.read_record(&mut rec).unwrap());
+
+        // Check that we can read the headers as raw bytes, but that
+        // if we read them as strings, we get an appropriate UTF-8 error.
+        {
+            let headers = rdr.byte_headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!(b"foo", &headers[0]);
+            assert_eq!(b"b\xFFar", &headers[1]);
+            assert_eq!(b"baz", &headers[2]);
+        }
+        match *rdr.headers().unwrap_err().kind() {
+            ErrorKind::Utf8 { pos: Some(ref pos), ref err } => {
+                assert_eq!(pos, &newpos(0, 1, 0));
+                assert_eq!(err.field(), 1);
+                assert_eq!(err.valid_up_to(), 1);
+            }
+            ref err => panic!("match failed, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn read_record_no_headers_before() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = StringRecord::new();
+
+        {
+            let headers = rdr.headers().unwrap();
+            assert_eq!(3, headers.len());
+            assert_eq!("foo", &headers[0]);
+            assert_eq!("bar", &headers[1]);
+            assert_eq!("baz", &headers[2]);
+        }
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("foo", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("a", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("d", &rec[0]);
+
+        assert!(!rdr.read_record(&mut rec).unwrap());
+    }
+
+    #[test]
+    fn read_record_no_headers_after() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("foo", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("a", &rec[0]);
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("d", &rec[0]);
+
+        assert!(!rdr.read_record(&mut rec).unwrap());
+
+        let headers = rdr.headers().unwrap();
+        assert_eq!(3, headers.len());
+        assert_eq!("foo", &headers[0]);
+        assert_eq!("bar", &headers[1]);
+        assert_eq!("baz", &headers[2]);
+    }
+
+    #[test]
+    fn seek() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        rdr.seek(newpos(18, 3, 2)).unwrap();
+
+        let mut rec = StringRecord::new();
+
+        assert_eq!(18, rdr.position().byte());
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("d", &rec[0]);
+
+        assert_eq!(24, rdr.position().byte());
+        assert_eq!(4, rdr.position().line());
+        assert_eq!(3, rdr.position().record());
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(3, rec.len());
+        assert_eq!("g", &rec[0]);
+
+        assert!(!rdr.read_record(&mut rec).unwrap());
+    }
+
+    // Test that we can read headers after seeking even if the headers weren't
+    // explicit read before seeking.
+    #[test]
+    fn seek_headers_after() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        rdr.seek(newpos(18, 3, 2)).unwrap();
+        assert_eq!(rdr.headers().unwrap(), vec!["foo", "bar", "baz"]);
+    }
+
+    // Test that we can read headers after seeking if the headers were read
+    // before seeking.
+    #[test]
+    fn seek_headers_before_after() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        let headers = rdr.headers().unwrap().clone();
+        rdr.seek(newpos(18, 3, 2)).unwrap();
+        assert_eq!(&headers, rdr.headers().unwrap());
+    }
+
+    // Test that even if we didn't read headers before seeking, if we seek to
+    // the current byte offset, then no seeking is done and therefore we can
+    // still read headers after seeking.
+    #[test]
+    fn seek_headers_no_actual_seek() {
+        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        rdr.seek(Position::new()).unwrap();
+        assert_eq!("foo", &rdr.headers().unwrap()[0]);
+    }
+
+    // Test that position info is reported correctly in absence of headers.
+    #[test]
+    fn positions_no_headers() {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader("a,b,c\nx,y,z".as_bytes())
+            .into_records();
+
+        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
+        assert_eq!(pos.byte(), 0);
+        assert_eq!(pos.line(), 1);
+        assert_eq!(pos.record(), 0);
+
+        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
+        assert_eq!(pos.byte(), 6);
+        assert_eq!(pos.line(), 2);
+        assert_eq!(pos.record(), 1);
+    }
+
+    // Test that position info is reported correctly with headers.
+    #[test]
+    fn positions_headers() {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader("a,b,c\nx,y,z".as_bytes())
+            .into_records();
+
+        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
+        assert_eq!(pos.byte(), 6);
+        assert_eq!(pos.line(), 2);
+        assert_eq!(pos.record(), 1);
+    }
+
+    // Test that newlines inside quoted fields are counted, so that the
+    // positions of the records following them are right. The `\n` of a
+    // `\r\n` terminator belongs to the record it ends, so the next record
+    // starts after it.
+    #[test]
+    fn positions_multiline_quoted_fields() {
+        let data = "a,b\n\"x\ny\",\"\n\n\"\nc,\"z\r\nw\"\r\nd,e\n";
+        for &cap in &[1, 2, 3, 8 * (1 << 10)] {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader_with_capacity(cap, data.as_bytes());
+            let got: Vec<(u64, u64, u64)> = rdr
+                .byte_records()
+                .map(|r| {
+                    let rec = r.unwrap();
+                    let pos = rec.position().unwrap();
+                    (pos.byte(), pos.line(), pos.record())
+                })
+                .collect();
+            assert_eq!(
+                got,
+                vec![(0, 1, 0), (4, 2, 1), (15, 6, 2), (25, 8, 3)],
+                "capacity {}",
+                cap
+            );
+            assert_eq!(rdr.position().line(), 9, "capacity {}", cap);
+        }
+    }
+
+    // Test that a UTF-8 error reports the position of the record it occurs
+    // in, even when the previous record ended with `\r\n`.
+    #[test]
+    fn utf8_error_position_after_crlf() {
+        let data = b"a\r\n\xFF\r\n";
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(&data[..]);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        match *rdr.read_record(&mut rec).unwrap_err().kind() {
+            ErrorKind::Utf8 { ref pos, .. } => {
+                assert_eq!(pos.as_ref().unwrap(), &newpos(3, 2, 1));
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn error_handler_skip() {
+        let data = b"a,b\n1,2\n3\n4,5\n";
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(|_| ErrorAction::Skip))
+            .from_reader(&data[..]);
+        let records =
+            rdr.byte_records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, vec![vec!["1", "2"], vec!["4", "5"]]);
+    }
+
+    #[test]
+    fn error_handler_continue() {
+        let data = b"a,b\n1,2\n3\n4,5\n";
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(|_| ErrorAction::Continue))
+            .from_reader(&data[..]);
+        let records = rdr.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, vec![vec!["1", "2"], vec!["3"], vec!["4", "5"]]);
+    }
+
+    #[test]
+    fn error_handler_abort() {
+        let data = b"a,b\n1,2\n3\n4,5\n";
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(|_| ErrorAction::Abort))
+            .from_reader(&data[..]);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        match *rdr.read_record(&mut rec).unwrap_err().kind() {
+            ErrorKind::UnequalLengths { expected_len: 2, len: 1, .. } => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["4", "5"]);
+    }
+
+    #[test]
+    fn error_handler_utf8() {
+        let data = b"a,b\n\xFF,x\n1,2\n";
+
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(|_| ErrorAction::Skip))
+            .from_reader(&data[..]);
+        let records = rdr.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, vec![vec!["1", "2"]]);
+
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(|_| ErrorAction::Continue))
+            .from_reader(&data[..]);
+        let records = rdr.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, vec![vec!["\u{FFFD}", "x"], vec!["1", "2"]]);
+        assert_eq!(records[0].position(), Some(&newpos(4, 2, 1)));
+    }
+
+    // Test that the handler sees recoverable errors only, and sees each of
+    // them exactly once.
+    #[test]
+    fn error_handler_calls() {
+        use std::sync::{Arc, Mutex};
+
+        let data = b"a,b\n1\n\xFF,x\n2,3,4\n5,6\n";
+        let seen = Arc::new(Mutex::new(vec![]));
+        let handler_seen = Arc::clone(&seen);
+        let mut rdr = ReaderBuilder::new()
+            .error_handler(Box::new(move |err| {
+                handler_seen.lock().unwrap().push(err.to_string());
+                ErrorAction::Skip
+            }))
+            .from_reader(&data[..]);
+        let records = rdr.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, vec![vec!["5", "6"]]);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0].contains("found record with 1 fields"), "{}", seen[0]);
+        assert!(seen[1].contains("invalid utf-8"), "{}", seen[1]);
+        assert!(seen[2].contains("found record with 3 fields"), "{}", seen[2]);
+    }
+
+    // Test that reading headers on empty data yields an empty record.
+    #[test]
+    fn headers_on_empty_data() {
+        let mut rdr = ReaderBuilder::new().from_reader("".as_bytes());
+        let r = rdr.byte_headers().unwrap();
+        assert_eq!(r.len(), 0);
+    }
+
+    // Test that reading the first record on empty data works.
+    #[test]
+    fn no_headers_on_empty_data() {
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader("".as_bytes());
+        assert_eq!(rdr.records().count(), 0);
+    }
+
+    // Test that reading the first record on empty data works, even if
+    // we've tried to read headers before hand.
+    #[test]
+    fn no_headers_on_empty_data_after_headers() {
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader("".as_bytes());
+        assert_eq!(rdr.headers().unwrap().len(), 0);
+        assert_eq!(rdr.records().count(), 0);
+    }
+
+    #[test]
+    fn split_header() {
+        let data = "h1,h2\na,b\nc,d\n";
+        let rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let (headers, mut rdr) = rdr.split_header().unwrap();
+        assert_eq!(headers, vec!["h1", "h2"]);
+        assert!(!rdr.has_headers());
+        // The first data row is not consumed by reading it as the headers.
+        assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["a", "b"]);
+        assert_eq!(rec.position().unwrap().line(), 2);
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["c", "d"]);
+        assert!(!rdr.read_record(&mut rec).unwrap());
+
+        // Records must still have as many fields as the header.
+        let rdr = ReaderBuilder::new().from_reader("h1,h2\na\n".as_bytes());
+        let (_, mut rdr) = rdr.split_header().unwrap();
+        assert!(rdr.read_record(&mut rec).is_err());
+
+        // Without headers, nothing is consumed.
+        let rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let (headers, mut rdr) = rdr.split_header().unwrap();
+        assert!(headers.is_empty());
+        assert_eq!(rdr.records().count(), 3);
+
+        let rdr = ReaderBuilder::new().from_reader("".as_bytes());
+        let (headers, mut rdr) = rdr.split_header().unwrap();
+        assert!(headers.is_empty());
+        assert_eq!(rdr.records().count(), 0);
+    }
+
+    #[test]
+    fn group_separator() {
+        let data = "a\nb\n\n\nc\n\nd\n\n\n\ne\n\n\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .group_separator_blank_lines(2)
+            .from_reader(data.as_bytes());
+        let mut group = vec![];
+
+        let mut groups = vec![];
+        while rdr.read_group(&mut group).unwrap() {
+            let fields: Vec<String> =
+                group.iter().map(|r| r[0].to_string()).collect();
+            groups.push(fields.join(","));
+        }
+        assert_eq!(groups, vec!["a,b", "c,d", "e"]);
+    }
+
+    #[test]
+    fn group_separator_leading_blank_lines() {
+        let data = "\n\na\nb\n\n\nc\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .group_separator_blank_lines(2)
+            .from_reader(data.as_bytes());
+        let mut group = vec![];
+
+        assert!(rdr.read_group(&mut group).unwrap());
+        assert_eq!(group.len(), 2);
+        assert!(rdr.read_group(&mut group).unwrap());
+        assert_eq!(group, vec![StringRecord::from(vec!["c"])]);
+        assert!(!rdr.read_group(&mut group).unwrap());
+    }
+
+    #[test]
+    fn group_separator_crlf() {
+        let data = "a\r\nb\r\n\r\n\r\nc\r\n\r\nd\re\r\r\rf";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .group_separator_blank_lines(2)
+            .from_reader(data.as_bytes());
+        let mut rec = StringRecord::new();
+
+        let mut got = vec![];
+        while rdr.read_record(&mut rec).unwrap() {
+            got.push((rec[0].to_string(), rdr.is_group_start()));
+        }
+        let expected = vec![
+            ("a", false),
+            ("b", false),
+            ("c", true),
+            ("d", false),
+            ("e", false),
+            ("f", true),
+        ];
+        let got: Vec<(&str, bool)> =
+            got.iter().map(|(f, start)| (f.as_str(), *start)).collect();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn group_separator_small_buffer() {
+        let data = "h\na\r\n\r\n\r\nb\n";
+        let mut rdr = ReaderBuilder::new()
+            .buffer_capacity(1)
+            .group_separator_blank_lines(2)
+            .from_reader(data.as_bytes());
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!("a", &rec[0]);
+        assert!(!rdr.is_group_start());
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!("b", &rec[0]);
+        // Like any blank lines, the separator is part of the next record.
+        assert_eq!(rec.position().unwrap(), &newpos(5, 3, 2));
+        assert!(rdr.is_group_start());
+        assert!(!rdr.read_record(&mut rec).unwrap());
+    }
+
+    #[test]
+    fn group_separator_disabled() {
+        let data = "a\n\n\n\nb\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let mut group = vec![];
+
+        assert!(rdr.read_group(&mut group).unwrap());
+        assert_eq!(group.len(), 2);
+        assert!(!rdr.read_group(&mut group).unwrap());
+    }
+
+    #[test]
+    fn group_separator_io_error() {
+        struct FailingReader;
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "boom"))
+            }
+        }
+
+        // The blank lines after "b" are only read by the next read, which
+        // is the one that fails.
+        let data = io::Read::chain("a\nb\n\n".as_bytes(), FailingReader);
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .group_separator_blank_lines(2)
+            .from_reader(data);
+        let mut rec = StringRecord::new();
+
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!("a", &rec[0]);
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!("b", &rec[0]);
+        assert!(!rdr.is_group_start());
+        assert!(rdr.read_record(&mut rec).unwrap_err().is_io_error());
+        assert!(!rdr.read_record(&mut rec).unwrap());
+    }
+
+    #[test]
+    fn buffer_grows_for_long_records() {
+        struct CountReads<'a> {
+            data: &'a [u8],
+            reads: usize,
+        }
+
+        impl<'a> io::Read for CountReads<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.reads += 1;
+                self.data.read(buf)
+            }
+        }
+
+        let long = "x".repeat(1 << 16);
+        let data = format!("a,b\n{},c\nd,e\n", long);
+        let input = CountReads { data: data.as_bytes(), reads: 0 };
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader_with_capacity(16, input);
+        let records: Vec<StringRecord> =
+            rdr.records().collect::<crate::Result<_>>().unwrap();
+        assert_eq!(
+            records,
+            vec![vec!["a", "b"], vec![&*long, "c"], vec!["d", "e"]]
+        );
+        // With a fixed buffer, this would take over 4096 reads.
+        assert!(rdr.get_ref().reads < 20, "{} reads", rdr.get_ref().reads);
+        assert!(rdr.buffer_capacity() > 16);
+
+        // The buffer stops growing at 1 MiB.
+        let long = "x".repeat(3 << 20);
+        let data = format!("{}\n", long);
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec[0].len(), 3 << 20);
+        assert_eq!(rdr.buffer_capacity(), 1 << 20);
+
+        // Records that fit in the buffer never make it grow.
+        let data = "abc,defg\n".repeat(100);
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader_with_capacity(16, data.as_bytes());
+        assert_eq!(rdr.records().count(), 100);
+        assert_eq!(rdr.buffer_capacity(), 16);
+    }
+
+    #[test]
+    fn strip_trailing_cr() {
+        let data = "a,b\r\n\"c\r\",d\r\r\ne,\"f\r\"\n";
+        for &cap in &[1, 2, 3, 8 * (1 << 10)] {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .terminator(Terminator::Any(b'\n'))
+                .strip_trailing_cr(true)
+                .from_reader_with_capacity(cap, data.as_bytes());
+            let records: Vec<StringRecord> =
+                rdr.records().map(|r| r.unwrap()).collect();
+            assert_eq!(
+                records,
+                vec![vec!["a", "b"], vec!["c\r", "d\r"], vec!["e", "f\r"],]
+            );
         }
     }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
-    }
+    #[test]
+    fn deserialize_trimmed_numbers() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Row {
+            name: String,
+            count: i32,
+            ratio: f64,
+            flag: Option<u8>,
+        }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+        let data = "name,count,ratio,flag\n a , 42 ,\t1.5 ,  \n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let got: Result<Row, _> = rdr.deserialize().next().unwrap();
+        assert!(got.is_err());
+
+        let mut rdr = ReaderBuilder::new()
+            .trim(Trim::Fields)
+            .from_reader(data.as_bytes());
+        let got: Row = rdr.deserialize().next().unwrap().unwrap();
+        assert_eq!(
+            got,
+            Row { name: "a".into(), count: 42, ratio: 1.5, flag: None }
+        );
+
+        let mut rdr = ReaderBuilder::new()
+            .trim(Trim::Fields)
+            .from_reader(data.as_bytes());
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        let got: (String, i32, f64, Option<u8>) =
+            rec.deserialize(None).unwrap();
+        assert_eq!(got, ("a".into(), 42, 1.5, None));
     }
 
-    /// Drop this iterator and return the underlying CSV reader.
-    pub fn into_reader(self) -> Reader<R> {
-        self.rdr
+    #[test]
+    fn deserialize_all() {
+        let data = "x,y\n1,2\n3,4\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let rows: Vec<(i32, i32)> = rdr.deserialize_all().unwrap();
+        assert_eq!(rows, vec![(1, 2), (3, 4)]);
+        assert_eq!(rdr.headers().unwrap(), vec!["x", "y"]);
+
+        let rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let rows: Vec<(String, String)> = rdr.into_deserialize_all().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], ("x".to_string(), "y".to_string()));
+
+        let rdr = ReaderBuilder::new().from_reader("x\n1\nz\n".as_bytes());
+        let err = rdr.into_deserialize_all::<(i32,)>().unwrap_err();
+        match *err.kind() {
+            ErrorKind::Deserialize { ref pos, .. } => {
+                assert_eq!(pos.as_ref().unwrap().record(), 2);
+            }
+            ref x => panic!("expected deserialize error, got {:?}", x),
+        }
     }
-}
 
-impl<R: io::Read, D: DeserializeOwned> Iterator
-    for DeserializeRecordsIntoIter<R, D>
-{
-    type Item = Result<D>;
+    #[test]
+    fn comment_quoted_field() {
+        let data = "\"#h1\",h2\n#comment\n\"#a\",b\n";
+        let mut rdr = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), vec!["#h1", "h2"]);
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["#a", "b"]]);
+    }
 
-    fn next(&mut self) -> Option<Result<D>> {
-        match self.rdr.read_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(false) => None,
-            Ok(true) => Some(self.rec.deserialize(self.headers.as_ref())),
+    #[test]
+    fn from_vec_into_iters() {
+        fn records(
+            data: Vec<u8>,
+        ) -> impl Iterator<Item = crate::Result<StringRecord>> + 'static
+        {
+            Reader::from_vec(data).into_records()
         }
-    }
-}
 
-/// A borrowed iterator over deserialized records.
-///
-/// The lifetime parameter `'r` refers to the lifetime of the underlying
-/// CSV `Reader`. The type parameter `R` refers to the underlying `io::Read`
-/// type, and `D` refers to the type that this iterator will deserialize a
-/// record into.
-pub struct DeserializeRecordsIter<'r, R: 'r, D> {
-    rdr: &'r mut Reader<R>,
-    rec: StringRecord,
-    headers: Option<StringRecord>,
-    _priv: PhantomData<D>,
-}
+        fn byte_records(
+            data: Vec<u8>,
+        ) -> impl Iterator<Item = crate::Result<ByteRecord>> + 'static
+        {
+            ReaderBuilder::new()
+                .has_headers(false)
+                .from_vec(data)
+                .into_byte_records()
+        }
 
-impl<'r, R: io::Read, D: DeserializeOwned> DeserializeRecordsIter<'r, R, D> {
-    fn new(rdr: &'r mut Reader<R>) -> DeserializeRecordsIter<'r, R, D> {
-        let headers = if !rdr.state.has_headers {
-            None
-        } else {
-            rdr.headers().ok().map(Clone::clone)
-        };
-        DeserializeRecordsIter {
-            rdr,
-            rec: StringRecord::new(),
-            headers,
-            _priv: PhantomData,
+        fn rows(
+            data: Vec<u8>,
+        ) -> impl Iterator<Item = crate::Result<(String, u64)>> + 'static
+        {
+            Reader::from_vec(data).into_deserialize()
         }
+
+        let data = b"city,pop\nBoston,4628910\nConcord,42695\n".to_vec();
+        let got: Vec<StringRecord> =
+            records(data.clone()).map(|r| r.unwrap()).collect();
+        assert_eq!(
+            got,
+            vec![vec!["Boston", "4628910"], vec!["Concord", "42695"]]
+        );
+
+        assert_eq!(byte_records(data.clone()).count(), 3);
+
+        let got: Vec<(String, u64)> = rows(data).map(|r| r.unwrap()).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("Boston".to_string(), 4628910),
+                ("Concord".to_string(), 42695)
+            ]
+        );
     }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
+    #[test]
+    fn relaxed_quotes() {
+        let data = "a,b\n3\" pipe,\"4\" x, 2\"\n\"\"\"q\"\"\",\"r\"\n";
+        let mut rdr = ReaderBuilder::new()
+            .relaxed_quotes(true)
+            .from_reader(data.as_bytes());
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(
+            records,
+            vec![vec!["3\" pipe", "4\" x, 2"], vec!["\"q\"", "r"]]
+        );
+
+        let mut rdr =
+            ReaderBuilder::new().flexible(true).from_reader(data.as_bytes());
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(
+            records,
+            vec![vec!["3\" pipe", "4 x", " 2\""], vec!["\"q\"", "r"]]
+        );
     }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+    #[test]
+    fn line_continuation() {
+        let data = "h1,h2\na,b\\\nc\n\"d\\\ne\",f\\g\n";
+        let mut rdr = ReaderBuilder::new()
+            .line_continuation(Some(b'\\'))
+            .from_reader(data.as_bytes());
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["a", "bc"]);
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["d\\\ne", "f\\g"]);
+        assert_eq!(rec.position().unwrap().line(), 4);
+        assert!(!rdr.read_record(&mut rec).unwrap());
+
+        // Without a continuation byte, each line is its own record.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader("a,b\\\nc\n".as_bytes());
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["a", "b\\"], vec!["c"]]);
     }
-}
 
-impl<'r, R: io::Read, D: DeserializeOwned> Iterator
-    for DeserializeRecordsIter<'r, R, D>
-{
-    type Item = Result<D>;
+    #[test]
+    fn detect_delimiter() {
+        let data = "a|b|c\n1|\"2|3\"|4\n";
+        let mut rdr = ReaderBuilder::new()
+            .detect_delimiter(true)
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), vec!["a", "b", "c"]);
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["1", "2|3", "4"]]);
+
+        // When nothing is detected, the configured delimiter is used.
+        let data = "a#b\nc#d\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(b'#')
+            .detect_delimiter(true)
+            .from_reader(data.as_bytes());
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["c", "d"]]);
+
+        // The sample isn't limited to the first read, which here contains
+        // only one byte.
+        let data = "abc|def\n1|2\n";
+        let mut rdr = ReaderBuilder::new()
+            .buffer_capacity(1)
+            .detect_delimiter(true)
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), vec!["abc", "def"]);
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["1", "2"]]);
+        assert_eq!(rdr.position().byte(), data.len() as u64);
+    }
 
-    fn next(&mut self) -> Option<Result<D>> {
-        match self.rdr.read_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(false) => None,
-            Ok(true) => Some(self.rec.deserialize(self.headers.as_ref())),
+    #[test]
+    fn deserialize_resume_after_error() {
+        let data = "x,y\n1,2\nfoo,3\n4,5,6\n7,8\n9,10\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut iter = rdr.deserialize::<(i32, i32)>();
+        assert_eq!(iter.next().unwrap().unwrap(), (1, 2));
+        match *iter.next().unwrap().unwrap_err().kind() {
+            ErrorKind::Deserialize { ref pos, .. } => {
+                assert_eq!(pos.as_ref().unwrap().record(), 2);
+            }
+            ref x => panic!("expected deserialize error, got {:?}", x),
         }
-    }
-}
+        match *iter.next().unwrap().unwrap_err().kind() {
+            ErrorKind::UnequalLengths { .. } => {}
+            ref x => panic!("expected unequal lengths error, got {:?}", x),
+        }
+        assert_eq!(iter.next().unwrap().unwrap(), (7, 8));
 
-/// An owned iterator over records as strings.
-pub struct StringRecordsIntoIter<R> {
-    rdr: Reader<R>,
-    rec: StringRecord,
-}
+        let records: Vec<StringRecord> =
+            iter.into_records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["9", "10"]]);
+    }
 
-impl<R: io::Read> StringRecordsIntoIter<R> {
-    fn new(rdr: Reader<R>) -> StringRecordsIntoIter<R> {
-        StringRecordsIntoIter { rdr, rec: StringRecord::new() }
+    #[test]
+    fn deserialize_into_records() {
+        let data = "x,y\n1,2\nfoo,3\n";
+        let rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut iter = rdr.into_deserialize::<(i32, i32)>();
+        assert_eq!(iter.next().unwrap().unwrap(), (1, 2));
+
+        let mut records = iter.into_records();
+        assert_eq!(records.next().unwrap().unwrap(), vec!["foo", "3"]);
+        assert!(records.next().is_none());
     }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
+    #[test]
+    fn invalid_config() {
+        fn check(builder: &mut ReaderBuilder, msg: &str) {
+            let err = builder.try_from_reader("".as_bytes()).unwrap_err();
+            match *err.kind() {
+                ErrorKind::InvalidConfig(ref got) => assert_eq!(got, msg),
+                ref x => panic!("expected config error, got {:?}", x),
+            }
+        }
+
+        check(
+            ReaderBuilder::new().delimiter(b'"'),
+            "the delimiter and quote are both '\"'",
+        );
+        check(
+            ReaderBuilder::new().escape(Some(b';')).delimiter(b';'),
+            "the delimiter and escape are both ';'",
+        );
+        check(
+            ReaderBuilder::new().delimiter(b'\n'),
+            "the delimiter and terminator are both '\\n'",
+        );
+        check(
+            ReaderBuilder::new().terminator(Terminator::Any(b';')).quote(b';'),
+            "the quote and terminator are both ';'",
+        );
+        check(
+            ReaderBuilder::new().escape(Some(b'\r')),
+            "the escape and terminator are both '\\r'",
+        );
+
+        assert!(ReaderBuilder::new()
+            .delimiter(b'"')
+            .quoting(false)
+            .try_from_reader("".as_bytes())
+            .is_ok());
+        assert!(ReaderBuilder::new()
+            .delimiter(b'\n')
+            .terminator(Terminator::Any(b'\x1E'))
+            .try_from_reader("".as_bytes())
+            .is_ok());
     }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+    #[test]
+    fn enumerate_records() {
+        let data = "h1,h2\na,b\nc\nd,e\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let got: Vec<(u64, bool)> =
+            rdr.enumerate_records().map(|(i, r)| (i, r.is_ok())).collect();
+        assert_eq!(got, vec![(1, true), (2, false), (3, true)]);
+
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let got: Vec<u64> = rdr.enumerate_records().map(|(i, _)| i).collect();
+        assert_eq!(got, vec![0, 1, 2, 3]);
     }
 
-    /// Drop this iterator and return the underlying CSV reader.
-    pub fn into_reader(self) -> Reader<R> {
-        self.rdr
+    #[test]
+    fn enumerate_records_after_seek() {
+        let data = "h1,h2\na,b\nc,d\ne,f\n";
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        rdr.seek(newpos(10, 3, 2)).unwrap();
+        let got: Vec<(u64, StringRecord)> =
+            rdr.enumerate_records().map(|(i, r)| (i, r.unwrap())).collect();
+        assert_eq!(got.len(), 2);
+        assert_eq!(got[0].0, 2);
+        assert_eq!(got[0].1, vec!["c", "d"]);
+        assert_eq!(got[1].0, 3);
     }
-}
 
-impl<R: io::Read> Iterator for StringRecordsIntoIter<R> {
-    type Item = Result<StringRecord>;
+    #[test]
+    fn enumerate_records_header_error() {
+        struct FailingReader;
 
-    fn next(&mut self) -> Option<Result<StringRecord>> {
-        match self.rdr.read_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(true) => Some(Ok(self.rec.clone_truncated())),
-            Ok(false) => None,
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "boom"))
+            }
         }
+
+        let mut rdr = ReaderBuilder::new().from_reader(FailingReader);
+        let mut it = rdr.enumerate_records();
+        let (i, result) = it.next().unwrap();
+        assert_eq!(i, 0);
+        assert!(result.unwrap_err().is_io_error());
+        assert!(it.next().is_none());
     }
-}
 
-/// A borrowed iterator over records as strings.
-///
-/// The lifetime parameter `'r` refers to the lifetime of the underlying
-/// CSV `Reader`.
-pub struct StringRecordsIter<'r, R: 'r> {
-    rdr: &'r mut Reader<R>,
-    rec: StringRecord,
-}
+    #[test]
+    fn drop_trailing_empty_field() {
+        let data = "a,b,\n1,2\n3,4,\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .drop_trailing_empty_field(true)
+            .from_reader(data.as_bytes());
+        let got: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(got, vec![vec!["a", "b"], vec!["1", "2"], vec!["3", "4"]]);
 
-impl<'r, R: io::Read> StringRecordsIter<'r, R> {
-    fn new(rdr: &'r mut Reader<R>) -> StringRecordsIter<'r, R> {
-        StringRecordsIter { rdr, rec: StringRecord::new() }
+        let data = "a,b,,\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .drop_trailing_empty_field(true)
+            .from_reader(data.as_bytes());
+        let got: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(got, vec![vec!["a", "b", ""]]);
     }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
+    #[test]
+    fn drop_trailing_empty_field_disabled() {
+        let data = "a,b,\n1,2\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["a", "b", ""]);
+        let err = rdr.read_record(&mut rec).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { expected_len: 3, len: 2, .. } => {}
+            ref wrong => panic!("match failed, got {:?}", wrong),
+        }
     }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+    #[test]
+    fn records_lenient() {
+        let data = "h1,h2\na,b\nc\nd,e\nf,g,h\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut iter = rdr.records_lenient();
+        let got: Vec<StringRecord> =
+            iter.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(
+            got,
+            vec![
+                vec!["a", "b"],
+                vec!["c"],
+                vec!["d", "e"],
+                vec!["f", "g", "h"]
+            ]
+        );
+
+        let errors = iter.take_length_errors();
+        assert!(iter.length_errors().is_empty());
+        let got: Vec<(u64, u64)> = errors
+            .iter()
+            .map(|err| match *err.kind() {
+                ErrorKind::UnequalLengths { ref pos, len, .. } => {
+                    (pos.as_ref().unwrap().line(), len)
+                }
+                ref wrong => panic!("match failed, got {:?}", wrong),
+            })
+            .collect();
+        assert_eq!(got, vec![(3, 1), (5, 3)]);
     }
-}
 
-impl<'r, R: io::Read> Iterator for StringRecordsIter<'r, R> {
-    type Item = Result<StringRecord>;
+    #[test]
+    fn records_lenient_utf8() {
+        let data = b"a,b\n\xFF\nc,d\n";
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(&data[..]);
+        let mut iter = rdr.records_lenient();
+        assert_eq!(iter.next().unwrap().unwrap(), vec!["a", "b"]);
+        match *iter.next().unwrap().unwrap_err().kind() {
+            ErrorKind::Utf8 { ref pos, .. } => {
+                assert_eq!(pos.as_ref().unwrap().line(), 2);
+            }
+            ref wrong => panic!("match failed, got {:?}", wrong),
+        }
+        assert_eq!(iter.next().unwrap().unwrap(), vec!["c", "d"]);
+        assert!(iter.next().is_none());
+        assert_eq!(iter.length_errors().len(), 1);
+    }
 
-    fn next(&mut self) -> Option<Result<StringRecord>> {
-        match self.rdr.read_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(true) => Some(Ok(self.rec.clone_truncated())),
-            Ok(false) => None,
+    #[test]
+    fn unequal_lengths_record() {
+        let data = "a,b\nc, d ,e\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .trim(Trim::All)
+            .from_reader(data.as_bytes());
+        let mut rec = ByteRecord::new();
+        assert!(rdr.unequal_record().is_none());
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        let err = rdr.read_byte_record(&mut rec).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { expected_len: 2, len: 3, .. } => {}
+            ref wrong => panic!("match failed, got {:?}", wrong),
         }
+        let record = rdr.unequal_record().unwrap();
+        assert_eq!(record, &vec!["c", " d ", "e"]);
+        assert_eq!(record.position().unwrap().line(), 2);
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
     }
-}
 
-/// An owned iterator over records as raw bytes.
-pub struct ByteRecordsIntoIter<R> {
-    rdr: Reader<R>,
-    rec: ByteRecord,
-}
+    #[test]
+    fn take_records() {
+        struct CountReads<'a> {
+            data: &'a [u8],
+            reads: usize,
+        }
 
-impl<R: io::Read> ByteRecordsIntoIter<R> {
-    fn new(rdr: Reader<R>) -> ByteRecordsIntoIter<R> {
-        ByteRecordsIntoIter { rdr, rec: ByteRecord::new() }
-    }
+        impl<'a> io::Read for CountReads<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.reads += 1;
+                let n = std::cmp::min(buf.len(), self.data.len());
+                buf[..n].copy_from_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                Ok(n)
+            }
+        }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
+        let src = CountReads { data: b"h\na\nb\nc\n", reads: 0 };
+        let mut rdr = ReaderBuilder::new().from_reader(src);
+        let mut iter = rdr.take_records(2);
+        assert_eq!(iter.next().unwrap().unwrap(), vec!["a"]);
+        assert_eq!(iter.next().unwrap().unwrap(), vec!["b"]);
+        assert_eq!(iter.remaining(), 0);
+        assert!(iter.next().is_none());
+        // Everything fits in the buffer, so reaching EOF would require a
+        // second read.
+        assert_eq!(rdr.get_ref().reads, 1);
+
+        let got: Vec<StringRecord> =
+            rdr.take_records(5).map(|r| r.unwrap()).collect();
+        assert_eq!(got, vec![vec!["c"]]);
+        assert_eq!(rdr.take_records(0).count(), 0);
     }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+    #[test]
+    fn read_raw_record() {
+        let data = "h1,h2\r\n\"a,\"\"b\",c\r\n#x\n\nd,\"e\nf\"\rg,h";
+        let mut rdr = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .from_reader(data.as_bytes());
+        let mut raws = vec![];
+        let mut buf = vec![];
+        while rdr.read_raw_record(&mut buf).unwrap() {
+            raws.push(String::from_utf8(buf.clone()).unwrap());
+            buf.clear();
+        }
+        assert_eq!(
+            raws,
+            vec!["\"a,\"\"b\",c\r\n", "#x\n\nd,\"e\nf\"\r", "g,h"]
+        );
+        assert_eq!(rdr.position().line(), 6);
+        assert_eq!(rdr.headers().unwrap(), vec!["h1", "h2"]);
+
+        // The captured bytes reparse to the same records.
+        let mut expected = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .from_reader(data.as_bytes());
+        for (raw, want) in raws.iter().zip(expected.records()) {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .comment(Some(b'#'))
+                .from_reader(raw.as_bytes());
+            let got: Vec<StringRecord> =
+                rdr.records().map(|r| r.unwrap()).collect();
+            assert_eq!(got, vec![want.unwrap()]);
+        }
     }
 
-    /// Drop this iterator and return the underlying CSV reader.
-    pub fn into_reader(self) -> Reader<R> {
-        self.rdr
+    #[test]
+    fn read_raw_record_no_headers() {
+        let data = "a,b\nc,d\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+        let mut buf = vec![];
+        assert!(rdr.read_raw_record(&mut buf).unwrap());
+        assert!(rdr.read_raw_record(&mut buf).unwrap());
+        assert!(!rdr.read_raw_record(&mut buf).unwrap());
+        assert_eq!(buf, b"a,b\nc,d\n");
     }
-}
 
-impl<R: io::Read> Iterator for ByteRecordsIntoIter<R> {
-    type Item = Result<ByteRecord>;
+    #[test]
+    fn records_from() {
+        let data = "foo,bar,baz\na,b,c\nd,e,f\ng,h,i\n";
+        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
+        let got: Vec<StringRecord> = rdr
+            .records_from(&newpos(18, 3, 2))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(got, vec![vec!["d", "e", "f"], vec!["g", "h", "i"]]);
+        assert_eq!(rdr.headers().unwrap(), vec!["foo", "bar", "baz"]);
 
-    fn next(&mut self) -> Option<Result<ByteRecord>> {
-        match self.rdr.read_byte_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(true) => Some(Ok(self.rec.clone_truncated())),
-            Ok(false) => None,
-        }
+        let got: Vec<StringRecord> = rdr
+            .records_from(&Position::new())
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(got.len(), 4);
+        assert_eq!(got[0], vec!["foo", "bar", "baz"]);
     }
-}
 
-/// A borrowed iterator over records as raw bytes.
-///
-/// The lifetime parameter `'r` refers to the lifetime of the underlying
-/// CSV `Reader`.
-pub struct ByteRecordsIter<'r, R: 'r> {
-    rdr: &'r mut Reader<R>,
-    rec: ByteRecord,
-}
+    #[test]
+    fn read_byte_record_append() {
+        let data = b("a,b\nc,d\n\"e\",f\n");
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut rec = ByteRecord::new();
 
-impl<'r, R: io::Read> ByteRecordsIter<'r, R> {
-    fn new(rdr: &'r mut Reader<R>) -> ByteRecordsIter<'r, R> {
-        ByteRecordsIter { rdr, rec: ByteRecord::new() }
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert_eq!(rec, vec!["a", "b", "c", "d", "e", "f"]);
+        assert_eq!(rec.position().unwrap().line(), 1);
+        assert!(!rdr.read_byte_record_append(&mut rec).unwrap());
+        assert_eq!(rec.len(), 6);
     }
 
-    /// Return a reference to the underlying CSV reader.
-    pub fn reader(&self) -> &Reader<R> {
-        &self.rdr
+    #[test]
+    fn read_byte_record_append_unequal_lengths() {
+        let data = b("h1,h2\na,b\nc\nd,e\n");
+        let mut rdr = ReaderBuilder::new().from_reader(data);
+        let mut rec = ByteRecord::new();
+        rec.push_field(b"x");
+
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert_eq!(rec, vec!["x", "a", "b"]);
+        match *rdr.read_byte_record_append(&mut rec).unwrap_err().kind() {
+            ErrorKind::UnequalLengths { expected_len: 2, len: 1, ref pos } => {
+                assert_eq!(pos.as_ref().unwrap().line(), 3);
+            }
+            ref wrong => panic!("match failed, got {:?}", wrong),
+        }
+        assert_eq!(rdr.unequal_record().unwrap(), &vec!["c"]);
+        assert_eq!(rec, vec!["x", "a", "b", "c"]);
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert_eq!(rec, vec!["x", "a", "b", "c", "d", "e"]);
     }
 
-    /// Return a mutable reference to the underlying CSV reader.
-    pub fn reader_mut(&mut self) -> &mut Reader<R> {
-        &mut self.rdr
+    fn comment_items<R: io::Read>(
+        mut iter: RecordsWithCommentsIter<'_, R>,
+    ) -> Vec<String> {
+        let mut items = vec![];
+        for result in &mut iter {
+            items.push(match result.unwrap() {
+                RecordOrComment::Record(rec) => {
+                    rec.iter().collect::<Vec<_>>().join("|")
+                }
+                RecordOrComment::Comment(c) => format!("#{}", c),
+            });
+        }
+        items
     }
-}
 
-impl<'r, R: io::Read> Iterator for ByteRecordsIter<'r, R> {
-    type Item = Result<ByteRecord>;
+    #[test]
+    fn records_with_comments() {
+        let data = b("\u{feff}#a\r\nh1,h2\r\n\r\n#b \"c\r\n#\nx,y\n\n#d");
+        let mut rdr =
+            ReaderBuilder::new().comment(Some(b'#')).from_reader(data);
+        let items = comment_items(rdr.records_with_comments());
+        assert_eq!(items, vec!["#a", "#b \"c", "#", "x|y", "#d"]);
+        assert_eq!(rdr.headers().unwrap(), vec!["h1", "h2"]);
 
-    fn next(&mut self) -> Option<Result<ByteRecord>> {
-        match self.rdr.read_byte_record(&mut self.rec) {
-            Err(err) => Some(Err(err)),
-            Ok(true) => Some(Ok(self.rec.clone_truncated())),
-            Ok(false) => None,
+        let data = b("#a\nh1,h2\n#b\nx,y\n");
+        let mut rdr = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .has_headers(false)
+            .from_reader(data);
+        let items = comment_items(rdr.records_with_comments());
+        assert_eq!(items, vec!["#a", "h1|h2", "#b", "x|y"]);
+
+        // The header row was already read, so it must be yielded first.
+        let mut rdr = ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .has_headers(false)
+            .from_reader(data);
+        rdr.headers().unwrap();
+        let items = comment_items(rdr.records_with_comments());
+        assert_eq!(items, vec!["h1|h2", "#b", "x|y"]);
+    }
+
+    #[test]
+    fn records_with_comments_positions() {
+        let data = b("h1,h2\n#a\n\n#b\nx,y\n");
+        let mut rdr =
+            ReaderBuilder::new().comment(Some(b'#')).from_reader(data);
+        let mut iter = rdr.records_with_comments();
+        assert!(iter.next().unwrap().unwrap().is_comment());
+        assert!(iter.next().unwrap().unwrap().is_comment());
+        match iter.next().unwrap().unwrap() {
+            RecordOrComment::Record(rec) => {
+                assert_eq!(rec.position().unwrap(), &newpos(13, 5, 1));
+            }
+            item => panic!("expected a record, got {:?}", item),
         }
+        assert!(iter.next().is_none());
+        assert_eq!(rdr.position(), &newpos(17, 6, 2));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io;
+    #[test]
+    fn records_with_comments_disabled() {
+        let data = b("h1,h2\n#a,b\n");
+        let mut rdr = ReaderBuilder::new().from_reader(data);
+        let items = comment_items(rdr.records_with_comments());
+        assert_eq!(items, vec!["#a|b"]);
 
-    use crate::{
-        byte_record::ByteRecord, error::ErrorKind, string_record::StringRecord,
-    };
+        let data = b("h1,h2\n\"a\",b\n");
+        let mut rdr =
+            ReaderBuilder::new().comment(Some(b'"')).from_reader(data);
+        let items = comment_items(rdr.records_with_comments());
+        assert_eq!(items, vec!["a|b"]);
+    }
 
-    use super::{Position, ReaderBuilder, Trim};
+    #[test]
+    fn guess_has_headers() {
+        let data = "name,age\nfoo,5\nbar,6\n";
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(io::Cursor::new(data));
+        assert!(rdr.guess_has_headers().unwrap());
+        // Guessing twice gives the same answer.
+        assert!(rdr.guess_has_headers().unwrap());
+        let records: Vec<StringRecord> =
+            rdr.records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0], vec!["name", "age"]);
+        assert_eq!(records[1], vec!["foo", "5"]);
+        assert_eq!(records[1].position().unwrap(), &newpos(9, 2, 1));
 
-    fn b(s: &str) -> &[u8] {
-        s.as_bytes()
-    }
-    fn s(b: &[u8]) -> &str {
-        ::std::str::from_utf8(b).unwrap()
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(io::Cursor::new(data));
+        assert!(rdr.guess_has_headers().unwrap());
+        let records: Vec<StringRecord> =
+            rdr.records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], vec!["foo", "5"]);
     }
 
-    fn newpos(byte: u64, line: u64, record: u64) -> Position {
-        let mut p = Position::new();
-        p.set_byte(byte).set_line(line).set_record(record);
-        p
+    #[test]
+    fn guess_has_headers_no_headers() {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(io::Cursor::new("1,2\n3,4\n"));
+        assert!(!rdr.guess_has_headers().unwrap());
+        assert_eq!(rdr.records().count(), 2);
+
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(io::Cursor::new("name,age\n"));
+        assert!(!rdr.guess_has_headers().unwrap());
+        assert_eq!(rdr.records().count(), 1);
     }
 
     #[test]
-    fn read_byte_record() {
-        let data = b("foo,\"b,ar\",baz\nabc,mno,xyz");
+    fn set_comment() {
+        let data = b("#a\nb\n#c\n;d\n#e\n");
         let mut rdr =
             ReaderBuilder::new().has_headers(false).from_reader(data);
         let mut rec = ByteRecord::new();
 
         assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("foo", s(&rec[0]));
-        assert_eq!("b,ar", s(&rec[1]));
-        assert_eq!("baz", s(&rec[2]));
-
+        assert_eq!(rec, vec!["#a"]);
+        rdr.set_comment(Some(b'#'));
         assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("abc", s(&rec[0]));
-        assert_eq!("mno", s(&rec[1]));
-        assert_eq!("xyz", s(&rec[2]));
-
+        assert_eq!(rec, vec!["b"]);
+        rdr.set_comment(Some(b';'));
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["#c"]);
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["#e"]);
         assert!(!rdr.read_byte_record(&mut rec).unwrap());
     }
 
     #[test]
-    fn read_trimmed_records_and_headers() {
-        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+    fn strict_eof() {
+        let data = b("a,\"b\"\nc,\"d\ne");
         let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .trim(Trim::All)
+            .has_headers(false)
+            .strict_eof(true)
             .from_reader(data);
         let mut rec = ByteRecord::new();
+
         assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!("1", s(&rec[0]));
-        assert_eq!("2", s(&rec[1]));
-        assert_eq!("3", s(&rec[2]));
-        let mut rec = StringRecord::new();
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!("1", &rec[0]);
-        assert_eq!("", &rec[1]);
-        assert_eq!("3", &rec[2]);
-        {
-            let headers = rdr.headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!("foo", &headers[0]);
-            assert_eq!("bar", &headers[1]);
-            assert_eq!("baz", &headers[2]);
+        assert_eq!(rec, vec!["a", "b"]);
+        let err = rdr.read_byte_record(&mut rec).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnexpectedEof { ref pos } => {
+                assert_eq!(pos.as_ref(), Some(&newpos(6, 2, 1)));
+            }
+            ref x => panic!("expected UnexpectedEof, but got {:?}", x),
         }
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+
+        // Without strict EOF, the truncated field ends with the data.
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["c", "d\ne"]);
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
     }
 
     #[test]
-    fn read_trimmed_header() {
-        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+    fn strict_eof_complete() {
+        let data = b("a,\"b\"\"\"\nc,\"d\"");
         let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .trim(Trim::Headers)
+            .has_headers(false)
+            .strict_eof(true)
             .from_reader(data);
-        let mut rec = ByteRecord::new();
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!("  1", s(&rec[0]));
-        assert_eq!("  2", s(&rec[1]));
-        assert_eq!("  3", s(&rec[2]));
-        {
-            let headers = rdr.headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!("foo", &headers[0]);
-            assert_eq!("bar", &headers[1]);
-            assert_eq!("baz", &headers[2]);
-        }
+        let records: Vec<ByteRecord> =
+            rdr.byte_records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records, vec![vec!["a", "b\""], vec!["c", "d"]]);
     }
 
     #[test]
-    fn read_trimed_header_invalid_utf8() {
-        let data = &b"foo,  b\xFFar,\tbaz\na,b,c\nd,e,f"[..];
+    fn bytes_consumed() {
+        let data = "a,b\r\n\r\n#x\r\nc,d\r\n";
         let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .trim(Trim::Headers)
-            .from_reader(data);
+            .comment(Some(b'#'))
+            .from_reader(io::Cursor::new(data));
         let mut rec = StringRecord::new();
 
-        // force the headers to be read
-        let _ = rdr.read_record(&mut rec);
-        // Check the byte headers are trimmed
-        {
-            let headers = rdr.byte_headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!(b"foo", &headers[0]);
-            assert_eq!(b"b\xFFar", &headers[1]);
-            assert_eq!(b"baz", &headers[2]);
-        }
-        match *rdr.headers().unwrap_err().kind() {
-            ErrorKind::Utf8 { pos: Some(ref pos), ref err } => {
-                assert_eq!(pos, &newpos(0, 1, 0));
-                assert_eq!(err.field(), 1);
-                assert_eq!(err.valid_up_to(), 3);
-            }
-            ref err => panic!("match failed, got {:?}", err),
-        }
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["c", "d"]);
+        let pos = rec.position().unwrap().clone();
+        assert!(!rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rdr.bytes_consumed(), data.len() as u64);
+
+        // Seeking doesn't reset the count.
+        let len = data.len() as u64;
+        rdr.seek(pos.clone()).unwrap();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert!(!rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rdr.bytes_consumed(), len + (len - pos.byte()));
     }
 
     #[test]
-    fn read_trimmed_records() {
-        let data = b("foo,  bar,\tbaz\n  1,  2,  3\n1\t,\t,3\t\t");
+    fn no_double_quote_no_escape() {
+        let data = b("\"a\"b\",c\n\"a\"b,c\"\n\"a,b\",\"\"\n");
         let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .trim(Trim::Fields)
+            .has_headers(false)
+            .double_quote(false)
             .from_reader(data);
-        let mut rec = ByteRecord::new();
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!("1", s(&rec[0]));
-        assert_eq!("2", s(&rec[1]));
-        assert_eq!("3", s(&rec[2]));
-        {
-            let headers = rdr.headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!("foo", &headers[0]);
-            assert_eq!("  bar", &headers[1]);
-            assert_eq!("\tbaz", &headers[2]);
-        }
+        let records: Vec<ByteRecord> =
+            rdr.byte_records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            records,
+            vec![vec!["ab\"", "c"], vec!["ab", "c\""], vec!["a,b", ""]]
+        );
     }
 
     #[test]
-    fn read_trimmed_records_without_headers() {
-        let data = b("a1, b1\t,\t c1\t\n");
+    fn no_double_quote_with_escape() {
+        let data = b("\"a\\\"b\",c\n");
         let mut rdr = ReaderBuilder::new()
             .has_headers(false)
-            .trim(Trim::All)
+            .double_quote(false)
+            .escape(Some(b'\\'))
             .from_reader(data);
-        let mut rec = ByteRecord::new();
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!("a1", s(&rec[0]));
-        assert_eq!("b1", s(&rec[1]));
-        assert_eq!("c1", s(&rec[2]));
+        let records: Vec<ByteRecord> =
+            rdr.byte_records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records, vec![vec!["a\"b", "c"]]);
     }
 
     #[test]
-    fn read_record_unequal_fails() {
-        let data = b("foo\nbar,baz");
+    fn from_buf_reader() {
+        let data = b("foo,\"b\nar\"\nbaz,quux\n");
+        // A tiny buffer forces fields to span multiple reads.
+        let buffered = io::BufReader::with_capacity(2, data);
         let mut rdr =
-            ReaderBuilder::new().has_headers(false).from_reader(data);
-        let mut rec = ByteRecord::new();
-
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(1, rec.len());
-        assert_eq!("foo", s(&rec[0]));
+            ReaderBuilder::new().has_headers(false).from_buf_reader(buffered);
+        assert_eq!(rdr.buffer_capacity(), 0);
 
-        match rdr.read_byte_record(&mut rec) {
-            Err(err) => match *err.kind() {
-                ErrorKind::UnequalLengths {
-                    expected_len: 1,
-                    ref pos,
-                    len: 2,
-                } => {
-                    assert_eq!(pos, &Some(newpos(4, 2, 1)));
-                }
-                ref wrong => panic!("match failed, got {:?}", wrong),
-            },
-            wrong => panic!("match failed, got {:?}", wrong),
-        }
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["foo", "b\nar"]);
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["baz", "quux"]);
+        assert!(!rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rdr.bytes_consumed(), data.len() as u64);
     }
 
     #[test]
-    fn read_record_unequal_ok() {
-        let data = b("foo\nbar,baz");
+    fn from_buf_reader_seek() {
+        let data = b("foo,bar\nbaz,quux\n");
         let mut rdr = ReaderBuilder::new()
             .has_headers(false)
-            .flexible(true)
-            .from_reader(data);
-        let mut rec = ByteRecord::new();
-
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(1, rec.len());
-        assert_eq!("foo", s(&rec[0]));
+            .from_buf_reader(io::Cursor::new(data));
 
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(2, rec.len());
-        assert_eq!("bar", s(&rec[0]));
-        assert_eq!("baz", s(&rec[1]));
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        let pos = rdr.position().clone();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["baz", "quux"]);
 
-        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+        rdr.seek(pos).unwrap();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["baz", "quux"]);
+        assert!(!rdr.read_record(&mut rec).unwrap());
     }
 
-    // This tests that even if we get a CSV error, we can continue reading
-    // if we want.
     #[test]
-    fn read_record_unequal_continue() {
-        let data = b("foo\nbar,baz\nquux");
+    fn continue_on_data_errors() {
+        let data: &[u8] = b"a,b\nc\n\xFF,d\ne,f\n";
         let mut rdr =
             ReaderBuilder::new().has_headers(false).from_reader(data);
-        let mut rec = ByteRecord::new();
+        let mut skipped = vec![];
+        let records = rdr
+            .records()
+            .continue_on_data_errors(|err| {
+                skipped.push(err.position().unwrap().record())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["e", "f"]]);
+        assert_eq!(skipped, vec![1, 2]);
+
+        // Invalid UTF-8 is fine for byte records.
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(data);
+        let mut skipped = vec![];
+        let records = rdr
+            .byte_records()
+            .continue_on_data_errors(|err| {
+                skipped.push(err.position().unwrap().record())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(skipped, vec![1]);
+    }
 
-        assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(1, rec.len());
-        assert_eq!("foo", s(&rec[0]));
+    #[test]
+    fn continue_on_data_errors_io() {
+        struct FailingReader;
 
-        match rdr.read_byte_record(&mut rec) {
-            Err(err) => match err.kind() {
-                &ErrorKind::UnequalLengths {
-                    expected_len: 1,
-                    ref pos,
-                    len: 2,
-                } => {
-                    assert_eq!(pos, &Some(newpos(4, 2, 1)));
-                }
-                wrong => panic!("match failed, got {:?}", wrong),
-            },
-            wrong => panic!("match failed, got {:?}", wrong),
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "boom"))
+            }
         }
 
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(FailingReader);
+        let mut skipped = 0;
+        let mut it = rdr.records().continue_on_data_errors(|_| skipped += 1);
+        assert!(it.next().unwrap().unwrap_err().is_io_error());
+        assert!(it.next().is_none());
+        drop(it);
+        assert_eq!(skipped, 0);
+    }
+
+    #[test]
+    fn record_checksum() {
+        let checksum = |data: &str, has_headers: bool| {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(has_headers)
+                .record_checksum(true)
+                .from_reader(data.as_bytes());
+            assert_eq!(rdr.checksum(), Some(CHECKSUM_INIT));
+            let n = rdr.records().count();
+            (n, rdr.checksum().unwrap())
+        };
+        let (n, sum) = checksum("a,b\nc,d\n", false);
+        assert_eq!(n, 2);
+        assert_eq!(sum, checksum("\"a\",b\r\nc,\"d\"", false).1);
+        assert_ne!(sum, checksum("a,b\nc,e\n", false).1);
+        assert_ne!(sum, checksum("c,d\na,b\n", false).1);
+        assert_ne!(sum, checksum("a,b\nc,d\n", true).1);
+        assert_eq!(checksum("x,y\nc,d\n", true), checksum("z,w\nc,d\n", true));
+
+        let mut rdr =
+            ReaderBuilder::new().from_reader("a,b\nc,d\n".as_bytes());
+        assert_eq!(rdr.records().count(), 1);
+        assert_eq!(rdr.checksum(), None);
+    }
+
+    #[test]
+    fn field_meta() {
+        let metas = |record: &ByteRecord| {
+            (0..record.len())
+                .map(|i| {
+                    let meta = record.field_meta(i).unwrap();
+                    (meta.quoted(), meta.had_escapes())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let data = "h1,\"h2\"\n\"a\"\"b\", c ,\"\"\n\"x\",y\n";
+        let mut rdr = ReaderBuilder::new()
+            .field_meta(true)
+            .flexible(true)
+            .trim(Trim::All)
+            .from_reader(data.as_bytes());
+        assert_eq!(
+            metas(rdr.byte_headers().unwrap()),
+            vec![(false, false), (true, false)]
+        );
+        let mut rec = ByteRecord::new();
         assert!(rdr.read_byte_record(&mut rec).unwrap());
-        assert_eq!(1, rec.len());
-        assert_eq!("quux", s(&rec[0]));
+        assert_eq!(rec, vec!["a\"b", "c", ""]);
+        assert_eq!(
+            metas(&rec),
+            vec![(true, true), (false, false), (true, false)]
+        );
+        assert_eq!(rec.field_meta(3), None);
+
+        // Appended fields have metadata too.
+        assert!(rdr.read_byte_record_append(&mut rec).unwrap());
+        assert_eq!(rec.len(), 5);
+        assert_eq!(metas(&rec)[3..], [(true, false), (false, false)]);
+        let mut retained = rec.clone();
+        retained.retain(|field| field != b"c");
+        assert_eq!(retained, vec!["a\"b", "", "x", "y"]);
+        assert_eq!(
+            metas(&retained),
+            vec![(true, true), (true, false), (true, false), (false, false)]
+        );
+        rec.truncate(1);
+        rec.push_field(b"z");
+        assert_eq!(rec.field_meta(0).map(|m| m.had_escapes()), Some(true));
+        assert_eq!(rec.field_meta(1), None);
+
+        // Disabled by default.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec.field_meta(0), None);
+    }
 
-        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+    #[test]
+    fn detected_terminator() {
+        use crate::DetectedTerminator::*;
+
+        let detect = |data: &str, term: Terminator| {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .terminator(term)
+                .from_reader(data.as_bytes());
+            for result in rdr.byte_records() {
+                result.unwrap();
+            }
+            rdr.detected_terminator()
+        };
+        let crlf = Terminator::CRLF;
+        assert_eq!(detect("a,b\nc,d\n", crlf), Some(LF));
+        assert_eq!(detect("a,b\nc,d", crlf), Some(LF));
+        assert_eq!(detect("a,b\r\nc,d\r\n", crlf), Some(CRLF));
+        assert_eq!(detect("a,b\rc,d\r", crlf), Some(CR));
+        assert_eq!(detect("a,b\r\nc,d\n", crlf), Some(Mixed));
+        assert_eq!(detect("a,b\rc,d\r\n", crlf), Some(Mixed));
+        assert_eq!(detect("a,b", crlf), None);
+        assert_eq!(detect("", crlf), None);
+        // Line endings in quoted fields and blank lines are ignored.
+        assert_eq!(detect("\"a\r\nb\",c\n\r\n\nd,e\n", crlf), Some(LF));
+        assert_eq!(detect("a\n", Terminator::Any(b'\n')), Some(LF));
+        assert_eq!(detect("a\r\n", Terminator::Any(b'\r')), Some(CR));
+        assert_eq!(detect("a;b;", Terminator::Any(b';')), None);
     }
 
     #[test]
-    fn read_record_headers() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f");
-        let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(data);
-        let mut rec = StringRecord::new();
+    fn detected_terminator_pending_cr() {
+        use crate::DetectedTerminator::*;
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("a", &rec[0]);
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader("a\r\nb\r".as_bytes());
+        let mut rec = ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rdr.detected_terminator(), None);
+        assert!(rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rdr.detected_terminator(), Some(CRLF));
+        assert!(!rdr.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rdr.detected_terminator(), Some(Mixed));
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("d", &rec[0]);
+        // The raw reader resolves the line ending right away.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader("a\r\nb\r\n".as_bytes());
+        let mut buf = vec![];
+        assert!(rdr.read_raw_record(&mut buf).unwrap());
+        assert_eq!(rdr.detected_terminator(), Some(CRLF));
+    }
 
-        assert!(!rdr.read_record(&mut rec).unwrap());
+    #[test]
+    fn last_terminator() {
+        let terms = |data: &str, term: Terminator| {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .terminator(term)
+                .from_reader(data.as_bytes());
+            let mut rec = ByteRecord::new();
+            let mut terms = vec![];
+            assert_eq!(rdr.last_terminator().unwrap(), b"");
+            while rdr.read_byte_record(&mut rec).unwrap() {
+                terms.push(rdr.last_terminator().unwrap().to_vec());
+                // Asking again gives the same answer.
+                assert_eq!(
+                    rdr.last_terminator().unwrap(),
+                    &terms[terms.len() - 1][..]
+                );
+            }
+            assert_eq!(rdr.last_terminator().unwrap(), b"");
+            terms
+        };
+        let crlf = Terminator::CRLF;
+        assert_eq!(
+            terms("a\nb\r\nc\rd", crlf),
+            vec![b("\n"), b("\r\n"), b("\r"), b("")]
+        );
+        assert_eq!(
+            terms("a\rb\r\r\nc\r\n", crlf),
+            vec![b("\r"), b("\r"), b("\r\n")]
+        );
+        assert_eq!(terms("\"a\r\nb\",c\r", crlf), vec![b("\r")]);
+        assert_eq!(
+            terms("a\r\nb\n", Terminator::Any(b'\n')),
+            vec![b("\n"), b("\n")]
+        );
+        assert_eq!(terms("a;b", Terminator::Any(b';')), vec![b(";"), b("")]);
+    }
 
-        {
-            let headers = rdr.byte_headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!(b"foo", &headers[0]);
-            assert_eq!(b"bar", &headers[1]);
-            assert_eq!(b"baz", &headers[2]);
-        }
-        {
-            let headers = rdr.headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!("foo", &headers[0]);
-            assert_eq!("bar", &headers[1]);
-            assert_eq!("baz", &headers[2]);
+    #[test]
+    fn last_terminator_raw() {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader("a\r\nb\rc\n".as_bytes());
+        let mut out = vec![];
+        let mut buf = vec![];
+        while rdr.read_raw_record(&mut buf).unwrap() {
+            out.push((buf.clone(), rdr.last_terminator().unwrap().to_vec()));
+            buf.clear();
         }
+        assert_eq!(
+            out,
+            vec![
+                (b("a\r\n").to_vec(), b("\r\n").to_vec()),
+                (b("b\r").to_vec(), b("\r").to_vec()),
+                (b("c\n").to_vec(), b("\n").to_vec()),
+            ]
+        );
     }
 
     #[test]
-    fn read_record_headers_invalid_utf8() {
-        let data = &b"foo,b\xFFar,baz\na,b,c\nd,e,f"[..];
-        let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(data);
-        let mut rec = StringRecord::new();
+    fn footer_headers() {
+        let footer = |data: &str| {
+            let mut rdr = ReaderBuilder::new()
+                .headers_position(HeaderPosition::Last)
+                .from_seekable_reader(io::Cursor::new(data))
+                .unwrap();
+            let headers = rdr.headers().unwrap().clone();
+            let records = rdr
+                .records()
+                .collect::<crate::Result<Vec<StringRecord>>>()
+                .unwrap();
+            (headers, records)
+        };
+        let (headers, records) = footer("a,1\nb,2\nname,n\n");
+        assert_eq!(headers, vec!["name", "n"]);
+        assert_eq!(records, vec![vec!["a", "1"], vec!["b", "2"]]);
+        assert_eq!(records[1].position(), Some(&newpos(4, 2, 1)));
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("a", &rec[0]);
+        let (headers, records) = footer("a,1\r\n\r\nname,n");
+        assert_eq!(headers, vec!["name", "n"]);
+        assert_eq!(records, vec![vec!["a", "1"]]);
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("d", &rec[0]);
+        let (headers, records) = footer("name,n\n");
+        assert_eq!(headers, vec!["name", "n"]);
+        assert!(records.is_empty());
 
-        assert!(!rdr.read_record(&mut rec).unwrap());
+        let (headers, records) = footer("");
+        assert!(headers.is_empty());
+        assert!(records.is_empty());
 
-        // Check that we can read the headers as raw bytes, but that
-        // if we read them as strings, we get an appropriate UTF-8 error.
-        {
-            let headers = rdr.byte_headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!(b"foo", &headers[0]);
-            assert_eq!(b"b\xFFar", &headers[1]);
-            assert_eq!(b"baz", &headers[2]);
-        }
-        match *rdr.headers().unwrap_err().kind() {
-            ErrorKind::Utf8 { pos: Some(ref pos), ref err } => {
-                assert_eq!(pos, &newpos(0, 1, 0));
-                assert_eq!(err.field(), 1);
-                assert_eq!(err.valid_up_to(), 1);
-            }
-            ref err => panic!("match failed, got {:?}", err),
-        }
+        // Rows with the wrong length are only reported when they are read,
+        // and don't cause any other rows to be lost.
+        let mut rdr = ReaderBuilder::new()
+            .headers_position(HeaderPosition::Last)
+            .from_seekable_reader(io::Cursor::new("a,1\nb\nc,3\nname,n\n"))
+            .unwrap();
+        assert_eq!(rdr.headers().unwrap(), vec!["name", "n"]);
+        let mut records = rdr.records();
+        assert_eq!(records.next().unwrap().unwrap(), vec!["a", "1"]);
+        assert!(matches!(
+            *records.next().unwrap().unwrap_err().kind(),
+            ErrorKind::UnequalLengths { expected_len: 2, len: 1, .. }
+        ));
+        assert_eq!(records.next().unwrap().unwrap(), vec!["c", "3"]);
+        assert!(records.next().is_none());
     }
 
     #[test]
-    fn read_record_no_headers_before() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f");
-        let mut rdr =
-            ReaderBuilder::new().has_headers(false).from_reader(data);
-        let mut rec = StringRecord::new();
-
-        {
-            let headers = rdr.headers().unwrap();
-            assert_eq!(3, headers.len());
-            assert_eq!("foo", &headers[0]);
-            assert_eq!("bar", &headers[1]);
-            assert_eq!("baz", &headers[2]);
+    fn footer_headers_read_paths() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Row {
+            name: String,
+            n: u32,
         }
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("foo", &rec[0]);
+        let data = "a,1\nb,2\nname,n\n";
+        let mut builder = ReaderBuilder::new();
+        builder.headers_position(HeaderPosition::Last);
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("a", &rec[0]);
+        // Reading a record first still finds the headers at the end.
+        let mut rdr =
+            builder.from_seekable_reader(io::Cursor::new(data)).unwrap();
+        let rows = rdr.deserialize().collect::<crate::Result<Vec<Row>>>();
+        assert_eq!(
+            rows.unwrap(),
+            vec![
+                Row { name: "a".to_string(), n: 1 },
+                Row { name: "b".to_string(), n: 2 },
+            ]
+        );
+        assert!(rdr.is_done());
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("d", &rec[0]);
+        let mut rdr =
+            builder.from_seekable_reader(io::Cursor::new(data)).unwrap();
+        let mut buf = vec![];
+        while rdr.read_raw_record(&mut buf).unwrap() {}
+        assert_eq!(buf, b("a,1\nb,2\n"));
 
-        assert!(!rdr.read_record(&mut rec).unwrap());
+        // The headers are found again after resetting the reader.
+        let mut rdr =
+            builder.from_seekable_reader(io::Cursor::new(data)).unwrap();
+        assert_eq!(rdr.records().count(), 2);
+        *rdr.get_mut() = io::Cursor::new("x\nid\n");
+        rdr.reset_state();
+        assert_eq!(rdr.headers().unwrap(), vec!["id"]);
+        assert_eq!(rdr.records().count(), 1);
+
+        // Without headers, the last row is just another record.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .headers_position(HeaderPosition::Last)
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.records().count(), 3);
     }
 
     #[test]
-    fn read_record_no_headers_after() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f");
-        let mut rdr =
-            ReaderBuilder::new().has_headers(false).from_reader(data);
+    fn footer_headers_not_seekable() {
+        let mut rdr = ReaderBuilder::new()
+            .headers_position(HeaderPosition::Last)
+            .from_reader("a,1\nname,n\n".as_bytes());
+        match *rdr.headers().unwrap_err().kind() {
+            ErrorKind::InvalidConfig(ref msg) => {
+                assert!(msg.contains("seekable"), "{}", msg)
+            }
+            ref x => panic!("unexpected error: {:?}", x),
+        }
         let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).is_err());
+    }
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("foo", &rec[0]);
+    #[test]
+    fn dialect() {
+        let rdr = ReaderBuilder::new().from_reader("a,b".as_bytes());
+        assert_eq!(rdr.delimiter(), b',');
+        assert_eq!(rdr.quote(), b'"');
+        assert!(rdr.quoting());
+        assert_eq!(rdr.escape(), None);
+        assert!(rdr.double_quote());
+        assert!(matches!(rdr.terminator(), Terminator::CRLF));
+        assert_eq!(rdr.comment(), None);
+
+        let rdr = ReaderBuilder::new()
+            .delimiter(b'|')
+            .quote(b'\'')
+            .quoting(false)
+            .escape(Some(b'\\'))
+            .double_quote(false)
+            .terminator(Terminator::Any(b';'))
+            .comment(Some(b'#'))
+            .from_reader("a|b".as_bytes());
+        assert_eq!(rdr.delimiter(), b'|');
+        assert_eq!(rdr.quote(), b'\'');
+        assert!(!rdr.quoting());
+        assert_eq!(rdr.escape(), Some(b'\\'));
+        assert!(!rdr.double_quote());
+        assert!(matches!(rdr.terminator(), Terminator::Any(b';')));
+        assert_eq!(rdr.comment(), Some(b'#'));
+
+        // A detected delimiter is reported once it has been detected.
+        let mut rdr = ReaderBuilder::new()
+            .detect_delimiter(true)
+            .from_reader("a\tb\nc\td\n".as_bytes());
+        rdr.headers().unwrap();
+        assert_eq!(rdr.delimiter(), b'\t');
+    }
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("a", &rec[0]);
+    #[test]
+    fn require_consistent_terminator() {
+        use crate::DetectedTerminator::*;
+
+        let read = |data: &str| {
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .require_consistent_terminator(true)
+                .from_reader(data.as_bytes());
+            let mut results = vec![];
+            let mut rec = ByteRecord::new();
+            loop {
+                match rdr.read_byte_record(&mut rec) {
+                    Ok(false) => return results,
+                    Ok(true) => results.push(Ok(rec.clone())),
+                    Err(err) => match *err.kind() {
+                        ErrorKind::InconsistentTerminator {
+                            ref pos,
+                            expected,
+                            found,
+                        } => results.push(Err((
+                            pos.as_ref().unwrap().record(),
+                            expected,
+                            found,
+                        ))),
+                        ref x => panic!("unexpected error: {:?}", x),
+                    },
+                }
+            }
+        };
 
-        assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("d", &rec[0]);
+        // Consistent line endings.
+        assert_eq!(read("a\nb\nc").len(), 3);
+        assert_eq!(read("a\r\nb\r\nc\r\n").len(), 3);
+        assert_eq!(read("a\rb\r").len(), 2);
+        // Line endings inside quoted fields and of blank lines don't count.
+        assert_eq!(read("\"a\r\nb\"\n\r\nc\n").len(), 2);
+
+        let got = read("a\r\nb\nc\r\n");
+        assert_eq!(got.len(), 3);
+        assert_eq!(got[0], Ok(ByteRecord::from(vec!["a"])));
+        assert_eq!(got[1], Err((1, CRLF, LF)));
+        assert_eq!(got[2], Ok(ByteRecord::from(vec!["c"])));
+
+        // A `\r\n` line ending is only known once the next record is read,
+        // so its error is returned by that read, before the next record.
+        let got = read("a\nb\r\nc\n");
+        assert_eq!(got.len(), 4);
+        assert_eq!(got[1], Ok(ByteRecord::from(vec!["b"])));
+        assert_eq!(got[2], Err((1, LF, CRLF)));
+        assert_eq!(got[3], Ok(ByteRecord::from(vec!["c"])));
+
+        // A third kind of line ending is reported too.
+        let got = read("a\nb\r\nc\rd");
+        let errs: Vec<_> = got.into_iter().filter_map(|r| r.err()).collect();
+        assert_eq!(errs, vec![(1, LF, CRLF), (2, Mixed, CR)]);
+
+        // The raw reader keeps the full line ending of the bad record.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .require_consistent_terminator(true)
+            .from_reader("a\nb\r\nc\n".as_bytes());
+        let mut buf = vec![];
+        assert!(rdr.read_raw_record(&mut buf).unwrap());
+        assert!(rdr.read_raw_record(&mut buf).is_err());
+        assert_eq!(buf, b"a\nb\r\n");
+        assert!(rdr.read_raw_record(&mut buf).unwrap());
+        assert_eq!(buf, b"a\nb\r\nc\n");
+
+        // Mixed line endings are accepted by default.
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader("a\nb\r\nc\n".as_bytes());
+        assert_eq!(rdr.records().count(), 3);
+        assert_eq!(rdr.detected_terminator(), Some(Mixed));
+    }
 
+    #[test]
+    fn reset_state() {
+        let mut rdr = ReaderBuilder::new()
+            .detect_delimiter(true)
+            .record_checksum(true)
+            .from_reader("a;b\n1;2\n3;4\n".as_bytes());
+        let mut rec = StringRecord::new();
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["1", "2"]);
+        let checksum = rdr.checksum();
+
+        // The rest of the first input is still buffered, but is discarded.
+        *rdr.get_mut() = "x\ty\tz\r\n5\t6\t7\r\n".as_bytes();
+        rdr.reset_state();
+        assert_eq!(rdr.position(), &newpos(0, 1, 0));
+        assert_eq!(rdr.checksum(), Some(CHECKSUM_INIT));
+        assert_eq!(rdr.detected_terminator(), None);
+        assert_eq!(rdr.headers().unwrap(), vec!["x", "y", "z"]);
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["5", "6", "7"]);
+        assert_eq!(rec.position().unwrap().line(), 2);
         assert!(!rdr.read_record(&mut rec).unwrap());
+        assert!(rdr.is_done());
 
-        let headers = rdr.headers().unwrap();
-        assert_eq!(3, headers.len());
-        assert_eq!("foo", &headers[0]);
-        assert_eq!("bar", &headers[1]);
-        assert_eq!("baz", &headers[2]);
+        // Reading the first input again gives the same results.
+        *rdr.get_mut() = "a;b\n1;2\n3;4\n".as_bytes();
+        rdr.reset_state();
+        assert!(!rdr.is_done());
+        assert!(rdr.read_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["1", "2"]);
+        assert_eq!(rdr.checksum(), checksum);
+        assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
     }
 
     #[test]
-    fn seek() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
+    fn seek_to_byte() {
+        let data = "foo,bar,baz\na,b,c\nd,e,f\ng,h,i\n";
         let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
-        rdr.seek(newpos(18, 3, 2)).unwrap();
+        rdr.seek_to_byte(18).unwrap();
+        assert_eq!(rdr.position(), &newpos(18, 1, 0));
 
         let mut rec = StringRecord::new();
-
-        assert_eq!(18, rdr.position().byte());
         assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("d", &rec[0]);
-
-        assert_eq!(24, rdr.position().byte());
-        assert_eq!(4, rdr.position().line());
-        assert_eq!(3, rdr.position().record());
+        assert_eq!(rec, vec!["d", "e", "f"]);
+        assert_eq!(rec.position().unwrap().byte(), 18);
         assert!(rdr.read_record(&mut rec).unwrap());
-        assert_eq!(3, rec.len());
-        assert_eq!("g", &rec[0]);
-
+        assert_eq!(rec, vec!["g", "h", "i"]);
+        assert_eq!(rec.position().unwrap().byte(), 24);
         assert!(!rdr.read_record(&mut rec).unwrap());
-    }
 
-    // Test that we can read headers after seeking even if the headers weren't
-    // explicit read before seeking.
-    #[test]
-    fn seek_headers_after() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
-        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
-        rdr.seek(newpos(18, 3, 2)).unwrap();
+        // Headers are still available after seeking.
         assert_eq!(rdr.headers().unwrap(), vec!["foo", "bar", "baz"]);
     }
 
-    // Test that we can read headers after seeking if the headers were read
-    // before seeking.
     #[test]
-    fn seek_headers_before_after() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
-        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
-        let headers = rdr.headers().unwrap().clone();
-        rdr.seek(newpos(18, 3, 2)).unwrap();
-        assert_eq!(&headers, rdr.headers().unwrap());
+    fn select_columns() {
+        let data = "a,b,c,b\n1,2,3,4\n5,6,7,8\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let got: Vec<StringRecord> = rdr
+            .select_columns(&["c", "a", "b", "c"])
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            got,
+            vec![vec!["3", "1", "2", "3"], vec!["7", "5", "6", "7"]]
+        );
+        assert_eq!(got[1].position().unwrap(), &newpos(16, 3, 2));
     }
 
-    // Test that even if we didn't read headers before seeking, if we seek to
-    // the current byte offset, then no seeking is done and therefore we can
-    // still read headers after seeking.
     #[test]
-    fn seek_headers_no_actual_seek() {
-        let data = b("foo,bar,baz\na,b,c\nd,e,f\ng,h,i");
-        let mut rdr = ReaderBuilder::new().from_reader(io::Cursor::new(data));
-        rdr.seek(Position::new()).unwrap();
-        assert_eq!("foo", &rdr.headers().unwrap()[0]);
+    fn select_columns_unknown() {
+        let data = "a,b,c\n1,2,3\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        match rdr.select_columns(&["a", "z"]) {
+            Err(err) => match *err.kind() {
+                ErrorKind::UnknownColumn { ref name } => assert_eq!(name, "z"),
+                ref wrong => panic!("expected UnknownColumn, got {:?}", wrong),
+            },
+            Ok(_) => panic!("expected error"),
+        }
+        // The reader is still usable.
+        assert_eq!(rdr.records().count(), 1);
     }
 
-    // Test that position info is reported correctly in absence of headers.
     #[test]
-    fn positions_no_headers() {
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_reader("a,b,c\nx,y,z".as_bytes())
-            .into_records();
-
-        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
-        assert_eq!(pos.byte(), 0);
-        assert_eq!(pos.line(), 1);
-        assert_eq!(pos.record(), 0);
-
-        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
-        assert_eq!(pos.byte(), 6);
-        assert_eq!(pos.line(), 2);
-        assert_eq!(pos.record(), 1);
+    fn select_columns_short_record() {
+        let data = "a,b,c\n1,2,3\n4\n";
+        let mut rdr =
+            ReaderBuilder::new().flexible(true).from_reader(data.as_bytes());
+        let got: Vec<StringRecord> = rdr
+            .select_columns(&["c", "a"])
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(got, vec![vec!["3", "1"], vec!["", "4"]]);
     }
 
-    // Test that position info is reported correctly with headers.
     #[test]
-    fn positions_headers() {
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader("a,b,c\nx,y,z".as_bytes())
-            .into_records();
-
-        let pos = rdr.next().unwrap().unwrap().position().unwrap().clone();
-        assert_eq!(pos.byte(), 6);
-        assert_eq!(pos.line(), 2);
-        assert_eq!(pos.record(), 1);
+    fn validate_schema() {
+        use crate::ColumnType::*;
+
+        let data = "\
+s,i,f,b
+x,-5,1.5,true
+,18446744073709551615,7,false
+y,1.5,2,true
+z,3,NaN,yes
+";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut iter = rdr.validate_schema(&[String, Integer, Float, Bool]);
+        assert_eq!(
+            iter.next().unwrap().unwrap(),
+            vec!["x", "-5", "1.5", "true"]
+        );
+        assert_eq!(
+            iter.next().unwrap().unwrap(),
+            vec!["", "18446744073709551615", "7", "false"]
+        );
+
+        let mismatch = |item: Option<crate::Result<StringRecord>>| match *item
+            .unwrap()
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::SchemaMismatch { ref pos, field, expected, found } => {
+                (pos.clone().unwrap(), field, expected, found)
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        };
+        assert_eq!(
+            mismatch(iter.next()),
+            (newpos(52, 4, 3), 1, Integer, Float)
+        );
+        assert_eq!(mismatch(iter.next()), (newpos(65, 5, 4), 3, Bool, String));
+        assert!(iter.next().is_none());
     }
 
-    // Test that reading headers on empty data yields an empty record.
     #[test]
-    fn headers_on_empty_data() {
-        let mut rdr = ReaderBuilder::new().from_reader("".as_bytes());
-        let r = rdr.byte_headers().unwrap();
-        assert_eq!(r.len(), 0);
+    fn validate_schema_lengths() {
+        use crate::ColumnType::*;
+
+        let data = "a,b\n1,2\n3,4\n";
+        let mut rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut iter = rdr.validate_schema(&[Integer, Integer, Integer]);
+        for _ in 0..2 {
+            match *iter.next().unwrap().unwrap_err().kind() {
+                ErrorKind::UnequalLengths { expected_len, len, .. } => {
+                    assert_eq!((expected_len, len), (3, 2));
+                }
+                ref kind => panic!("unexpected error: {:?}", kind),
+            }
+        }
+        assert!(iter.next().is_none());
+
+        let err = Reader::from_reader("a\nxyz\n".as_bytes())
+            .validate_schema(&[Float])
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CSV schema error: record 1 (line: 2, field: 0, byte: 2): \
+             expected a float but found a string"
+        );
     }
 
-    // Test that reading the first record on empty data works.
     #[test]
-    fn no_headers_on_empty_data() {
-        let mut rdr =
-            ReaderBuilder::new().has_headers(false).from_reader("".as_bytes());
-        assert_eq!(rdr.records().count(), 0);
+    fn buffer_capacity() {
+        let data = "a,b,c\nx,y,z\n";
+        let rdr = ReaderBuilder::new().from_reader(data.as_bytes());
+        assert_eq!(rdr.buffer_capacity(), 8 * (1 << 10));
+
+        let mut builder = ReaderBuilder::new();
+        builder.buffer_capacity(100);
+        let mut rdr = builder.from_reader_with_capacity(5, data.as_bytes());
+        assert_eq!(rdr.buffer_capacity(), 5);
+        assert_eq!(rdr.records().count(), 1);
+        assert_eq!(
+            builder.from_reader(data.as_bytes()).buffer_capacity(),
+            100
+        );
     }
 
-    // Test that reading the first record on empty data works, even if
-    // we've tried to read headers before hand.
     #[test]
-    fn no_headers_on_empty_data_after_headers() {
-        let mut rdr =
-            ReaderBuilder::new().has_headers(false).from_reader("".as_bytes());
-        assert_eq!(rdr.headers().unwrap().len(), 0);
-        assert_eq!(rdr.records().count(), 0);
+    fn dialect_presets() {
+        let data = "\u{FEFF}a;b,\"c\"\"d\"\r\ne,f\rg,h\r\n";
+        let mut rdr = ReaderBuilder::new()
+            .delimiter(b';')
+            .escape(Some(b'\\'))
+            .excel()
+            .from_reader(data.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), vec!["a;b", "c\"d"]);
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["e", "f"], vec!["g", "h"]]);
+
+        let mut rdr = ReaderBuilder::new()
+            .flexible(true)
+            .rfc4180()
+            .from_reader("a,b\r\nc\r\n".as_bytes());
+        let err = rdr.records().next().unwrap().unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { .. } => {}
+            ref x => panic!("expected UnequalLengths, got {:?}", x),
+        }
+
+        let mut rdr = ReaderBuilder::new()
+            .terminator(Terminator::CRLF)
+            .unix()
+            .has_headers(false)
+            .from_reader("a\rb,c\nd,e\n".as_bytes());
+        let records: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![vec!["a\rb", "c"], vec!["d", "e"]]);
     }
 }
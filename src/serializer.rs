@@ -142,7 +142,10 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeRecord<'w, W> {
         self,
         name: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        self.wtr.write_field(name)
+        if is_phantom(name) {
+            return Ok(());
+        }
+        self.wtr.write_field([])
     }
 
     fn serialize_unit_variant(
@@ -166,9 +169,12 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeRecord<'w, W> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        if self.wtr.tagged_enums() {
+            self.wtr.write_field(variant)?;
+        }
         value.serialize(self)
     }
 
@@ -208,12 +214,7 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeRecord<'w, W> {
         self,
         _len: Option<usize>,
     ) -> Result<Self::SerializeMap, Self::Error> {
-        // The right behavior for serializing maps isn't clear.
-        Err(Error::custom(
-            "serializing maps is not supported, \
-             if you have a use case, please file an issue at \
-             https://github.com/BurntSushi/rust-csv",
-        ))
+        Ok(self)
     }
 
     fn serialize_struct(
@@ -307,18 +308,19 @@ impl<'a, 'w, W: io::Write> SerializeMap for &'a mut SeRecord<'w, W> {
         &mut self,
         _key: &T,
     ) -> Result<(), Self::Error> {
-        unreachable!()
+        // Like struct field names, map keys are only written as headers.
+        Ok(())
     }
 
     fn serialize_value<T: ?Sized + Serialize>(
         &mut self,
-        _value: &T,
+        value: &T,
     ) -> Result<(), Self::Error> {
-        unreachable!()
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unreachable!()
+        Ok(())
     }
 }
 
@@ -451,11 +453,22 @@ enum HeaderState {
 struct SeHeader<'w, W: 'w + io::Write> {
     wtr: &'w mut Writer<W>,
     state: HeaderState,
+    /// Set when the value of the current struct field turns out to be a
+    /// marker that isn't written, so that its name is skipped too.
+    skip_field: bool,
+    /// The number of tagged enum variants in the value of the current
+    /// struct field. Each one adds a column, which needs a header name.
+    tagged_variants: usize,
 }
 
 impl<'w, W: io::Write> SeHeader<'w, W> {
     fn new(wtr: &'w mut Writer<W>) -> Self {
-        SeHeader { wtr, state: HeaderState::Write }
+        SeHeader {
+            wtr,
+            state: HeaderState::Write,
+            skip_field: false,
+            tagged_variants: 0,
+        }
     }
 
     fn wrote_header(&self) -> bool {
@@ -482,6 +495,14 @@ impl<'w, W: io::Write> SeHeader<'w, W> {
         }
     }
 
+    /// Whether this is in a struct field value and tagged enum variants are
+    /// enabled. The value must then be inspected for tagged variants, each
+    /// of which needs its own header name.
+    fn in_tagged_field(&self) -> bool {
+        matches!(self.state, HeaderState::InStructField)
+            && self.wtr.tagged_enums()
+    }
+
     fn handle_container<T: fmt::Display>(
         &mut self,
         name: T,
@@ -579,8 +600,11 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeHeader<'w, W> {
 
     fn serialize_some<T: ?Sized + Serialize>(
         self,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        if self.in_tagged_field() {
+            return value.serialize(self);
+        }
         self.handle_scalar("Some(_)")
     }
 
@@ -592,6 +616,11 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeHeader<'w, W> {
         self,
         name: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
+        if is_phantom(name) {
+            if let HeaderState::InStructField = self.state {
+                self.skip_field = true;
+            }
+        }
         self.handle_scalar(name)
     }
 
@@ -607,8 +636,11 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeHeader<'w, W> {
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
         name: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        if self.in_tagged_field() {
+            return value.serialize(self);
+        }
         self.handle_scalar(format!("{}(_)", name))
     }
 
@@ -617,8 +649,12 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeHeader<'w, W> {
         name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        if self.in_tagged_field() {
+            self.tagged_variants += 1;
+            return value.serialize(self);
+        }
         self.handle_scalar(format!("{}::{}(_)", name, variant))
     }
 
@@ -658,12 +694,7 @@ impl<'a, 'w, W: io::Write> Serializer for &'a mut SeHeader<'w, W> {
         self,
         _len: Option<usize>,
     ) -> Result<Self::SerializeMap, Self::Error> {
-        // The right behavior for serializing maps isn't clear.
-        Err(Error::custom(
-            "serializing maps is not supported, \
-             if you have a use case, please file an issue at \
-             https://github.com/BurntSushi/rust-csv",
-        ))
+        self.handle_container("map")
     }
 
     fn serialize_struct(
@@ -755,20 +786,51 @@ impl<'a, 'w, W: io::Write> SerializeMap for &'a mut SeHeader<'w, W> {
 
     fn serialize_key<T: ?Sized + Serialize>(
         &mut self,
-        _key: &T,
+        key: &T,
     ) -> Result<(), Self::Error> {
-        unreachable!()
+        // Map keys are treated like struct field names, except that they
+        // are written as soon as they're seen, since there is no way to
+        // hold on to the key until its value has been checked.
+        let old_state =
+            mem::replace(&mut self.state, HeaderState::EncounteredStructField);
+        if let HeaderState::ErrorIfWrite(err) = old_state {
+            return Err(err);
+        }
+        self.state = HeaderState::InStructField;
+        key.serialize(&mut **self)?;
+        self.state = HeaderState::EncounteredStructField;
+        if mem::take(&mut self.skip_field) {
+            return Err(Error::custom("cannot serialize skipped map key"));
+        }
+        if mem::take(&mut self.tagged_variants) > 0 {
+            return Err(Error::custom(
+                "cannot serialize tagged enum variant as map key",
+            ));
+        }
+        key.serialize(&mut SeRecord { wtr: &mut *self.wtr })
     }
 
     fn serialize_value<T: ?Sized + Serialize>(
         &mut self,
-        _value: &T,
+        value: &T,
     ) -> Result<(), Self::Error> {
-        unreachable!()
+        // Check that there aren't any containers in the value.
+        self.state = HeaderState::InStructField;
+        value.serialize(&mut **self)?;
+        self.state = HeaderState::EncounteredStructField;
+        if mem::take(&mut self.skip_field) {
+            return Err(Error::custom("cannot serialize skipped map value"));
+        }
+        if mem::take(&mut self.tagged_variants) > 0 {
+            return Err(Error::custom(
+                "cannot serialize tagged enum variant as map value",
+            ));
+        }
+        Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unreachable!()
+        Ok(())
     }
 }
 
@@ -787,13 +849,23 @@ impl<'a, 'w, W: io::Write> SerializeStruct for &'a mut SeHeader<'w, W> {
         if let HeaderState::ErrorIfWrite(err) = old_state {
             return Err(err);
         }
-        self.wtr.write_field(key)?;
-
-        // Check that there aren't any containers in the value.
+        // Check that there aren't any containers in the value. This also
+        // determines whether the field is skipped entirely.
         self.state = HeaderState::InStructField;
         value.serialize(&mut **self)?;
         self.state = HeaderState::EncounteredStructField;
 
+        let tagged = mem::take(&mut self.tagged_variants);
+        if !mem::take(&mut self.skip_field) {
+            self.wtr.write_field(key)?;
+            if tagged > 0 {
+                let mut name = key.to_string();
+                for _ in 0..tagged {
+                    name.push_str("_value");
+                    self.wtr.write_field(&name)?;
+                }
+            }
+        }
         Ok(())
     }
 
@@ -819,8 +891,17 @@ impl<'a, 'w, W: io::Write> SerializeStructVariant for &'a mut SeHeader<'w, W> {
     }
 }
 
+/// Returns true if the given unit struct name is that of
+/// `std::marker::PhantomData`, whose values are skipped entirely rather than
+/// written as an empty field.
+fn is_phantom(name: &str) -> bool {
+    name == "PhantomData"
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{collections::BTreeMap, marker::PhantomData};
+
     use {
         bstr::ByteSlice,
         serde::{serde_if_integer128, Serialize},
@@ -828,7 +909,7 @@ mod tests {
 
     use crate::{
         error::{Error, ErrorKind},
-        writer::Writer,
+        writer::{Writer, WriterBuilder},
     };
 
     use super::{SeHeader, SeRecord};
@@ -960,6 +1041,48 @@ mod tests {
         assert_eq!(got, "");
     }
 
+    #[test]
+    fn option_struct_fields() {
+        #[derive(Serialize)]
+        struct Foo {
+            a: Option<i32>,
+            b: Option<String>,
+            c: Option<i32>,
+        }
+
+        let row = Foo { a: None, b: Some("x".into()), c: None };
+        let got = serialize(&row);
+        assert_eq!(got, ",x,\n");
+        let (wrote, got) = serialize_header(&row);
+        assert!(wrote);
+        assert_eq!(got, "a,b,c");
+    }
+
+    #[test]
+    fn option_none_as() {
+        #[derive(Serialize)]
+        struct Foo {
+            #[serde(serialize_with = "null")]
+            a: Option<i32>,
+            #[serde(serialize_with = "null")]
+            b: Option<i32>,
+        }
+
+        fn null<S: serde::Serializer>(
+            value: &Option<i32>,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            crate::serialize_none_as(value, "NULL", ser)
+        }
+
+        let row = Foo { a: Some(5), b: None };
+        let got = serialize(&row);
+        assert_eq!(got, "5,NULL\n");
+        let (wrote, got) = serialize_header(&row);
+        assert!(wrote);
+        assert_eq!(got, "a,b");
+    }
+
     #[test]
     fn unit() {
         let got = serialize(());
@@ -981,12 +1104,38 @@ mod tests {
         struct Foo;
 
         let got = serialize(Foo);
-        assert_eq!(got, "Foo\n");
+        assert_eq!(got, "\"\"\n");
         let (wrote, got) = serialize_header(Foo);
         assert!(!wrote);
         assert_eq!(got, "");
     }
 
+    #[test]
+    fn struct_unit_fields() {
+        #[derive(Serialize)]
+        struct Marker;
+
+        #[derive(Serialize)]
+        struct Foo {
+            a: (),
+            b: i32,
+            c: PhantomData<f64>,
+            d: Marker,
+            e: PhantomData<()>,
+        }
+
+        let row =
+            Foo { a: (), b: 5, c: PhantomData, d: Marker, e: PhantomData };
+        let got = serialize(&row);
+        assert_eq!(got, ",5,\n");
+        let (wrote, got) = serialize_header(&row);
+        assert!(wrote);
+        assert_eq!(got, "a,b,d");
+
+        let got = serialize((1, PhantomData::<i32>, 2));
+        assert_eq!(got, "1,2\n");
+    }
+
     #[test]
     fn struct_newtype() {
         #[derive(Serialize)]
@@ -1027,6 +1176,54 @@ mod tests {
         assert_eq!(got, "");
     }
 
+    #[test]
+    fn enum_newtypes_tagged() {
+        #[derive(Serialize)]
+        enum Wat {
+            Foo(i32),
+            Bar(Option<Box<Wat>>),
+            Baz,
+        }
+
+        #[derive(Serialize)]
+        struct Row {
+            a: Wat,
+            b: Wat,
+            c: Wat,
+        }
+
+        #[derive(Serialize)]
+        struct Nested {
+            x: Wat,
+        }
+
+        fn tagged<S: Serialize>(s: S) -> Result<String, Error> {
+            let mut wtr =
+                WriterBuilder::new().tagged_enums(true).from_writer(vec![]);
+            wtr.serialize(s)?;
+            Ok(String::from_utf8(wtr.into_inner().unwrap()).unwrap())
+        }
+
+        let row = Row { a: Wat::Foo(5), b: Wat::Bar(None), c: Wat::Baz };
+        assert_eq!(
+            tagged(row).unwrap(),
+            "a,a_value,b,b_value,c\nFoo,5,Bar,,Baz\n"
+        );
+        let nested = Nested { x: Wat::Bar(Some(Box::new(Wat::Foo(7)))) };
+        assert_eq!(
+            tagged(nested).unwrap(),
+            "x,x_value,x_value_value\nBar,Foo,7\n"
+        );
+        assert_eq!(tagged(Wat::Foo(5)).unwrap(), "Foo,5\n");
+
+        let mut map = BTreeMap::new();
+        map.insert("a", Wat::Foo(5));
+        match *tagged(&map).unwrap_err().kind() {
+            ErrorKind::Serialize(_) => {}
+            ref x => panic!("expected Serialize error, got {:?}", x),
+        }
+    }
+
     #[test]
     fn enum_newtypes() {
         #[derive(Serialize)]
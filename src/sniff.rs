@@ -0,0 +1,213 @@
+use crate::byte_record::{trim_ascii, ByteRecord};
+
+/// The delimiters considered by `sniff_delimiter`, in order of preference.
+const CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// Guess the field delimiter used by a sample of CSV data.
+///
+/// The candidates considered are `,`, `;`, `\t` and `|`. For each candidate,
+/// the number of its occurrences outside of double quotes is counted on each
+/// line of the sample. The candidate that yields the same, non-zero number of
+/// fields on the most lines wins. Ties are broken in favor of the candidate
+/// yielding more fields, and then in the order listed above. Blank lines are
+/// ignored, and if the sample doesn't end with a line terminator, then its
+/// last line is ignored too, since it may have been truncated. (Unless it is
+/// the only line in the sample.)
+///
+/// If none of the candidates appear outside of quotes in the sample, then
+/// `None` is returned.
+///
+/// This is a heuristic, not a guarantee. It can be fooled by samples with
+/// very few lines, by quotes that appear in the middle of unquoted fields or
+/// by data that uses a different quote character.
+///
+/// # Example
+///
+/// ```
+/// let data = "\
+/// city;country;pop
+/// Boston;\"United States; of America\";4628910
+/// Concord;United States;42695
+/// ";
+/// assert_eq!(csv::sniff_delimiter(data.as_bytes()), Some(b';'));
+/// assert_eq!(csv::sniff_delimiter(b"foo\nbar\n"), None);
+/// ```
+pub fn sniff_delimiter(sample: &[u8]) -> Option<u8> {
+    let mut lines: Vec<[u64; 4]> = vec![];
+    let mut counts = [0; 4];
+    let (mut in_quotes, mut blank) = (false, true);
+    for &b in sample {
+        if b == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && (b == b'\n' || b == b'\r') {
+            if !blank {
+                lines.push(counts);
+            }
+            counts = [0; 4];
+            blank = true;
+            continue;
+        } else if !in_quotes {
+            if let Some(i) = CANDIDATES.iter().position(|&c| c == b) {
+                counts[i] += 1;
+            }
+        }
+        blank = false;
+    }
+    if !blank && lines.is_empty() {
+        lines.push(counts);
+    }
+
+    let mut best: Option<(usize, u64, u8)> = None;
+    for (i, &candidate) in CANDIDATES.iter().enumerate() {
+        let (count, consistent) = mode(lines.iter().map(|counts| counts[i]));
+        if count == 0 {
+            continue;
+        }
+        let better = match best {
+            None => true,
+            Some((best_consistent, best_count, _)) => {
+                (consistent, count) > (best_consistent, best_count)
+            }
+        };
+        if better {
+            best = Some((consistent, count, candidate));
+        }
+    }
+    best.map(|(_, _, candidate)| candidate)
+}
+
+/// Returns the most common value in the given sequence along with the number
+/// of times it occurs. Ties are broken in favor of the larger value.
+fn mode<I: Iterator<Item = u64>>(values: I) -> (u64, usize) {
+    let mut values: Vec<u64> = values.collect();
+    values.sort_unstable();
+    let mut best = (0, 0);
+    let mut start = 0;
+    while start < values.len() {
+        let value = values[start];
+        let len = values[start..].iter().take_while(|&&v| v == value).count();
+        if len >= best.1 {
+            best = (value, len);
+        }
+        start += len;
+    }
+    best
+}
+
+/// Guess whether `first` is a header row, based on the record that follows
+/// it.
+///
+/// Each column votes: a column whose first field is text and whose second
+/// field is a number votes for a header, while a column in which both fields
+/// are numbers votes against one. Columns containing empty fields, or text
+/// in both rows, don't vote. A header is guessed if and only if there are
+/// more votes for one than against.
+pub(crate) fn sniff_has_headers(
+    first: &ByteRecord,
+    second: &ByteRecord,
+) -> bool {
+    let mut votes = 0i64;
+    for (field1, field2) in first.iter().zip(second.iter()) {
+        match (classify(field1), classify(field2)) {
+            (Kind::Text, Kind::Number) => votes += 1,
+            (Kind::Number, Kind::Number) => votes -= 1,
+            _ => {}
+        }
+    }
+    votes > 0
+}
+
+/// The kind of value found in a field, as used by `sniff_has_headers`.
+#[derive(Debug, Eq, PartialEq)]
+enum Kind {
+    Empty,
+    Number,
+    Text,
+}
+
+/// Classify the given field. Leading and trailing whitespace is ignored.
+fn classify(field: &[u8]) -> Kind {
+    let field = trim_ascii(field);
+    if field.is_empty() {
+        return Kind::Empty;
+    }
+    // Require a digit so that things like `nan` and `inf`, which parse as
+    // floats, are treated as text.
+    let is_number = field.iter().any(|b| b.is_ascii_digit())
+        && std::str::from_utf8(field)
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .is_some();
+    if is_number {
+        Kind::Number
+    } else {
+        Kind::Text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::byte_record::ByteRecord;
+
+    use super::{sniff_delimiter, sniff_has_headers};
+
+    fn has_headers(first: &[&str], second: &[&str]) -> bool {
+        sniff_has_headers(&ByteRecord::from(first), &ByteRecord::from(second))
+    }
+
+    #[test]
+    fn simple() {
+        assert_eq!(sniff_delimiter(b"a,b,c\nd,e,f\n"), Some(b','));
+        assert_eq!(sniff_delimiter(b"a;b;c\nd;e;f\n"), Some(b';'));
+        assert_eq!(sniff_delimiter(b"a\tb\tc\nd\te\tf\n"), Some(b'\t'));
+        assert_eq!(sniff_delimiter(b"a|b|c\r\nd|e|f\r\n"), Some(b'|'));
+    }
+
+    #[test]
+    fn none() {
+        assert_eq!(sniff_delimiter(b""), None);
+        assert_eq!(sniff_delimiter(b"a\nb\n"), None);
+        assert_eq!(sniff_delimiter(b"\"a,b\"\n\"c;d\"\n"), None);
+    }
+
+    #[test]
+    fn single_line() {
+        assert_eq!(sniff_delimiter(b"a;b;c"), Some(b';'));
+        assert_eq!(sniff_delimiter(b"a;b,c"), Some(b','));
+    }
+
+    #[test]
+    fn quoted() {
+        let data = b"a;\"b,c,d\";e\n\"f\ng,h\";i;j\nk;l;m\n";
+        assert_eq!(sniff_delimiter(data), Some(b';'));
+    }
+
+    #[test]
+    fn most_consistent() {
+        // Commas appear more often, but not consistently.
+        let data = b"a;b,c,d;e\nf;g;h\ni;j,k;l\n";
+        assert_eq!(sniff_delimiter(data), Some(b';'));
+    }
+
+    #[test]
+    fn truncated_last_line() {
+        let data = b"a\tb\tc\nd\te\tf\ng,h";
+        assert_eq!(sniff_delimiter(data), Some(b'\t'));
+    }
+
+    #[test]
+    fn headers() {
+        assert!(has_headers(&["city", "pop"], &["Boston", "4628910"]));
+        assert!(has_headers(&["x", "y", "z"], &["1", "-2.5", "3e4"]));
+        assert!(has_headers(&["name", "x", "y"], &["a", "1", ""]));
+    }
+
+    #[test]
+    fn no_headers() {
+        assert!(!has_headers(&["1", "2"], &["3", "4"]));
+        assert!(!has_headers(&["a", "b"], &["c", "d"]));
+        assert!(!has_headers(&["a", "1", "2"], &["b", "3", "4"]));
+        assert!(!has_headers(&["nan", "inf"], &["nan", "inf"]));
+        assert!(!has_headers(&["a", "b"], &[]));
+    }
+}
@@ -1,17 +1,20 @@
 use std::{
+    collections::HashMap,
     fmt, io,
-    iter::FromIterator,
+    iter::{self, FromIterator},
     ops::{self, Range},
     result, str,
 };
 
-use serde::de::Deserialize;
+use {csv_core::FieldMeta, serde::de::Deserialize};
 
 use crate::{
     byte_record::{ByteRecord, ByteRecordIter, Position},
     deserializer::deserialize_string_record,
     error::{Error, ErrorKind, FromUtf8Error, Result},
     reader::Reader,
+    writer::RecordWriteOptions,
+    ErrorAction,
 };
 
 /// A single CSV record stored as valid UTF-8 bytes.
@@ -315,6 +318,38 @@ impl StringRecord {
         self.into_iter()
     }
 
+    /// Returns an iterator over pairs of header names and fields in this
+    /// record.
+    ///
+    /// Each item is a pair of the header at some index in `headers` and the
+    /// field at the same index in this record. Unlike deserializing a record
+    /// into a map, this preserves the order of fields and yields every pair,
+    /// even when the same header name appears more than once. If this record
+    /// and `headers` have different lengths, then iteration stops at the end
+    /// of the shorter one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let headers = StringRecord::from(vec!["name", "comment", "comment"]);
+    /// let record = StringRecord::from(vec!["Boston", "big", "old"]);
+    /// let pairs: Vec<(&str, &str)> = record.zip_headers(&headers).collect();
+    /// assert_eq!(pairs, vec![
+    ///     ("name", "Boston"),
+    ///     ("comment", "big"),
+    ///     ("comment", "old"),
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn zip_headers<'r>(
+        &'r self,
+        headers: &'r StringRecord,
+    ) -> iter::Zip<StringRecordIter<'r>, StringRecordIter<'r>> {
+        headers.iter().zip(self.iter())
+    }
+
     /// Return the field at index `i`.
     ///
     /// If no field at index `i` exists, then this returns `None`.
@@ -339,6 +374,56 @@ impl StringRecord {
         })
     }
 
+    /// Return the field in the column named `name`, using `headers` to find
+    /// the column's index.
+    ///
+    /// If `name` appears more than once in `headers`, then the field
+    /// corresponding to its first occurrence is returned. If `name` isn't
+    /// in `headers`, or if this record has no field at that index, then this
+    /// returns `None`.
+    ///
+    /// This searches `headers` on every call. For repeated lookups, consider
+    /// building a [`HeaderMap`](struct.HeaderMap.html) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let headers = StringRecord::from(vec!["city", "pop"]);
+    /// let record = StringRecord::from(vec!["Boston", "4628910"]);
+    /// assert_eq!(record.get_by_name(&headers, "pop"), Some("4628910"));
+    /// assert_eq!(record.get_by_name(&headers, "country"), None);
+    /// ```
+    #[inline]
+    pub fn get_by_name(
+        &self,
+        headers: &StringRecord,
+        name: &str,
+    ) -> Option<&str> {
+        headers.iter().position(|h| h == name).and_then(|i| self.get(i))
+    }
+
+    /// Return a 64-bit checksum of the fields in this record.
+    ///
+    /// This is the same as the checksum of the corresponding byte record.
+    /// See [`ByteRecord::checksum`](struct.ByteRecord.html#method.checksum)
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::{ByteRecord, StringRecord};
+    ///
+    /// let record = StringRecord::from(vec!["a", "b", "c"]);
+    /// let byte_record = ByteRecord::from(vec!["a", "b", "c"]);
+    /// assert_eq!(record.checksum(), byte_record.checksum());
+    /// ```
+    #[inline]
+    pub fn checksum(&self) -> u64 {
+        self.0.checksum()
+    }
+
     /// Returns true if and only if this record is empty.
     ///
     /// # Example
@@ -389,6 +474,30 @@ impl StringRecord {
         self.0.truncate(n);
     }
 
+    /// Retain only the fields for which the predicate given returns `true`.
+    ///
+    /// The fields that are kept remain in their original order, along with
+    /// their metadata. This operates in place and visits each field exactly
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let mut record = StringRecord::from(vec!["a", " ", "b", ""]);
+    /// record.retain(|field| !field.trim().is_empty());
+    /// assert_eq!(record, vec!["a", "b"]);
+    /// ```
+    pub fn retain<F: FnMut(&str) -> bool>(&mut self, mut f: F) {
+        self.0.retain(|bytes| {
+            debug_assert!(str::from_utf8(bytes).is_ok());
+            // This is safe because every field of a string record is valid
+            // UTF-8, and removing whole fields preserves that.
+            f(unsafe { str::from_utf8_unchecked(bytes) })
+        });
+    }
+
     /// Clear this record so that it has zero fields.
     ///
     /// Note that it is not necessary to clear the record to reuse it with
@@ -437,6 +546,7 @@ impl StringRecord {
         for field in &*self {
             trimmed.push_field(field.trim());
         }
+        trimmed.0.take_meta(&mut self.0);
         *self = trimmed;
     }
 
@@ -456,6 +566,56 @@ impl StringRecord {
         self.0.push_field(field.as_bytes());
     }
 
+    /// Replace all fields in this record with the fields given.
+    ///
+    /// This is equivalent to calling `clear` followed by `push_field` for
+    /// each field given. The record's existing allocation is reused, which
+    /// makes this useful for filling a single record from many sources in a
+    /// loop without allocating.
+    ///
+    /// The position of this record is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let mut record = StringRecord::from(vec!["a", "b", "c"]);
+    /// record.replace_fields(&["x", "y"]);
+    /// assert_eq!(record, vec!["x", "y"]);
+    /// ```
+    pub fn replace_fields<I, T>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.clear();
+        for field in fields {
+            self.push_field(field.as_ref());
+        }
+    }
+
+    /// Add all fields from `other` to the end of this record.
+    ///
+    /// This is equivalent to calling `push_field` for each field in `other`,
+    /// but copies all of the field data at once.
+    ///
+    /// The position of this record is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let mut record = StringRecord::from(vec!["a", "b"]);
+    /// record.extend_from_record(&StringRecord::from(vec!["c", "d"]));
+    /// assert_eq!(record, vec!["a", "b", "c", "d"]);
+    /// ```
+    #[inline]
+    pub fn extend_from_record(&mut self, other: &StringRecord) {
+        self.0.extend_from_record(&other.0);
+    }
+
     /// Return the position of this record, if available.
     ///
     /// # Example
@@ -518,6 +678,31 @@ impl StringRecord {
         self.0.set_position(pos);
     }
 
+    /// Return a mutable reference to the position of this record, if it
+    /// has one.
+    ///
+    /// This permits adjusting a record's position in place, e.g., when
+    /// renumbering records that are being written to a new file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::{StringRecord, Position};
+    ///
+    /// let mut record = StringRecord::from(vec!["a", "b", "c"]);
+    /// assert!(record.position_mut().is_none());
+    ///
+    /// record.set_position(Some(Position::new()));
+    /// if let Some(pos) = record.position_mut() {
+    ///     pos.set_record(5);
+    /// }
+    /// assert_eq!(record.position().unwrap().record(), 5);
+    /// ```
+    #[inline]
+    pub fn position_mut(&mut self) -> Option<&mut Position> {
+        self.0.position_mut()
+    }
+
     /// Return the start and end position of a field in this record.
     ///
     /// If no such field exists at the given index, then return `None`.
@@ -540,6 +725,40 @@ impl StringRecord {
         self.0.range(i)
     }
 
+    /// Return metadata describing how the field at index `i` appeared in
+    /// the CSV data, such as whether it was quoted.
+    ///
+    /// This is only available for fields read by a reader with
+    /// [`ReaderBuilder::field_meta`](struct.ReaderBuilder.html#method.field_meta)
+    /// enabled. If no such field exists at the given index, or if it wasn't
+    /// read with field metadata enabled, then this returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, StringRecord};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let data = "a,\"b\"\n";
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .field_meta(true)
+    ///         .from_reader(data.as_bytes());
+    ///     let mut record = StringRecord::new();
+    ///     assert!(rdr.read_record(&mut record)?);
+    ///
+    ///     assert!(!record.field_meta(0).unwrap().quoted());
+    ///     assert!(record.field_meta(1).unwrap().quoted());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[inline]
+    pub fn field_meta(&self, i: usize) -> Option<FieldMeta> {
+        self.0.field_meta(i)
+    }
+
     /// Return the entire row as a single string slice. The slice returned
     /// stores all fields contiguously. The boundaries of each field can be
     /// determined via the `range` method.
@@ -561,6 +780,50 @@ impl StringRecord {
         unsafe { str::from_utf8_unchecked(self.0.as_slice()) }
     }
 
+    /// Write this record as a single row of CSV data to `wtr`, with fields
+    /// separated by the given delimiter.
+    ///
+    /// Fields are quoted only when necessary and the record is terminated by
+    /// `\n`. Use `write_to_with` to change the quote character, quoting
+    /// style or terminator.
+    ///
+    /// This is convenient for writing a record now and then, e.g., in tests.
+    /// For writing many records, a [`Writer`](struct.Writer.html) is more
+    /// efficient, since it buffers its output and doesn't need to be
+    /// configured again for every record. Note that `wtr` isn't flushed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::StringRecord;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let record = StringRecord::from(vec!["a", "say \"hi\"", ""]);
+    ///     let mut buf = vec![];
+    ///     record.write_to(&mut buf, b',')?;
+    ///     assert_eq!(String::from_utf8(buf)?, "a,\"say \"\"hi\"\"\",\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_to<W: io::Write>(&self, wtr: W, delimiter: u8) -> Result<()> {
+        self.0.write_to(wtr, delimiter)
+    }
+
+    /// Write this record as a single row of CSV data to `wtr`, using the
+    /// given options.
+    ///
+    /// See [`RecordWriteOptions`](struct.RecordWriteOptions.html) for an
+    /// example.
+    pub fn write_to_with<W: io::Write>(
+        &self,
+        wtr: W,
+        options: &RecordWriteOptions,
+    ) -> Result<()> {
+        self.0.write_to_with(wtr, options)
+    }
+
     /// Return a reference to this record's raw
     /// [`ByteRecord`](struct.ByteRecord.html).
     ///
@@ -610,6 +873,22 @@ impl StringRecord {
         self.0
     }
 
+    /// Convert this `StringRecord` into a vector of its fields.
+    ///
+    /// This is a shortcut for `record.iter().map(String::from).collect()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::StringRecord;
+    ///
+    /// let record = StringRecord::from(vec!["a", "", "c"]);
+    /// assert_eq!(record.into_vec(), vec!["a", "", "c"]);
+    /// ```
+    pub fn into_vec(self) -> Vec<String> {
+        self.iter().map(String::from).collect()
+    }
+
     /// Clone this record, but only copy `fields` up to the end of bounds. This
     /// is useful when one wants to copy a record, but not necessarily any
     /// excess capacity in that record.
@@ -630,25 +909,48 @@ impl StringRecord {
         // blocks in this module. Namely, after calling `read_byte_record`,
         // it is possible for `record` to contain invalid UTF-8. We check for
         // this in the `validate` method, and if it does have invalid UTF-8, we
-        // clear the record. (It is bad for `record` to contain invalid UTF-8
+        // either clear the record or replace it with a lossy conversion
+        // before returning. (It is bad for `record` to contain invalid UTF-8
         // because other accessor methods, like `get`, assume that every field
         // is valid UTF-8.)
-        let pos = rdr.position().clone();
-        let read_res = rdr.read_byte_record(&mut self.0);
-        let utf8_res = match self.0.validate() {
-            Ok(()) => Ok(()),
-            Err(err) => {
-                // If this record isn't valid UTF-8, then completely wipe it.
+        loop {
+            let read_res = rdr.read_byte_record(&mut self.0);
+            let err = match self.0.validate() {
+                Ok(()) => return read_res,
+                Err(err) => err,
+            };
+            if let Err(read_err) = read_res {
                 self.0.clear();
-                Err(err)
+                return Err(read_err);
             }
-        };
-        match (read_res, utf8_res) {
-            (Err(err), _) => Err(err),
-            (Ok(_), Err(err)) => {
-                Err(Error::new(ErrorKind::Utf8 { pos: Some(pos), err }))
+            let pos = self.0.position().cloned();
+            let err = Error::new(ErrorKind::Utf8 { pos, err });
+            match rdr.error_action(&err) {
+                ErrorAction::Skip => {
+                    self.0.clear();
+                }
+                ErrorAction::Abort => {
+                    // If this record isn't valid UTF-8, then completely wipe
+                    // it.
+                    self.0.clear();
+                    return Err(err);
+                }
+                ErrorAction::Continue => {
+                    let mut lossy = ByteRecord::with_capacity(
+                        self.0.as_slice().len(),
+                        self.0.len(),
+                    );
+                    for field in self.0.iter() {
+                        lossy.push_field(
+                            String::from_utf8_lossy(field).as_bytes(),
+                        );
+                    }
+                    lossy.set_position(self.0.position().cloned());
+                    lossy.take_meta(&mut self.0);
+                    self.0 = lossy;
+                    return Ok(true);
+                }
             }
-            (Ok(eof), Ok(())) => Ok(eof),
         }
     }
 }
@@ -744,9 +1046,75 @@ impl<'r> DoubleEndedIterator for StringRecordIter<'r> {
     }
 }
 
+/// A precomputed mapping from header names to column indices.
+///
+/// This is useful for looking up fields by column name in many records,
+/// since it avoids searching the header record on every lookup, as
+/// [`StringRecord::get_by_name`](struct.StringRecord.html#method.get_by_name)
+/// does. If a name appears more than once in the header record, then it maps
+/// to the index of its first occurrence.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+/// use csv::{HeaderMap, Reader};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let data = "\
+/// city,country,pop
+/// Boston,United States,4628910
+/// Concord,United States,42695
+/// ";
+///     let mut rdr = Reader::from_reader(data.as_bytes());
+///     let map = HeaderMap::new(rdr.headers()?);
+///     let mut pops = vec![];
+///     for result in rdr.records() {
+///         let record = result?;
+///         pops.push(map.get(&record, "pop").unwrap().to_string());
+///     }
+///     assert_eq!(pops, vec!["4628910", "42695"]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HeaderMap {
+    map: HashMap<String, usize>,
+}
+
+impl HeaderMap {
+    /// Build a mapping from the names in the given header record to their
+    /// column indices.
+    pub fn new(headers: &StringRecord) -> HeaderMap {
+        let mut map = HashMap::with_capacity(headers.len());
+        for (i, name) in headers.iter().enumerate() {
+            map.entry(name.to_string()).or_insert(i);
+        }
+        HeaderMap { map }
+    }
+
+    /// Return the column index for the given name, if it exists.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.map.get(name).copied()
+    }
+
+    /// Return the field in the column named `name` in the given record.
+    ///
+    /// If `name` isn't a known column, or if `record` has no field at its
+    /// index, then this returns `None`.
+    pub fn get<'r>(
+        &self,
+        record: &'r StringRecord,
+        name: &str,
+    ) -> Option<&'r str> {
+        self.index(name).and_then(|i| record.get(i))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::string_record::StringRecord;
+    use crate::string_record::{HeaderMap, StringRecord};
 
     #[test]
     fn trim_front() {
@@ -784,6 +1152,20 @@ mod tests {
         assert_eq!(rec.get(1), Some("xyz"));
     }
 
+    #[test]
+    fn retain() {
+        let retain = |drop: &[&str]| {
+            let mut rec = StringRecord::from(vec!["fóo", "bar", "quux"]);
+            rec.retain(|field| !drop.contains(&field));
+            rec
+        };
+        assert_eq!(retain(&["fóo"]), vec!["bar", "quux"]);
+        assert_eq!(retain(&["bar"]), vec!["fóo", "quux"]);
+        assert_eq!(retain(&["quux"]), vec!["fóo", "bar"]);
+        assert!(retain(&["fóo", "bar", "quux"]).is_empty());
+        assert_eq!(retain(&["bar"]).as_slice(), "fóoquux");
+    }
+
     #[test]
     fn trim_does_not_panic_on_empty_records_1() {
         let mut rec = StringRecord::from(vec![""]);
@@ -835,4 +1217,59 @@ mod tests {
         let test2 = StringRecord::from(vec!["12", "34"]);
         assert_ne!(test1, test2);
     }
+
+    #[test]
+    fn extend_from_record() {
+        let mut rec = StringRecord::from(vec!["a", "b"]);
+        rec.extend_from_record(&StringRecord::from(vec!["☃", "", "z"]));
+
+        assert_eq!(rec.len(), 5);
+        let fields: Vec<&str> = rec.iter().collect();
+        assert_eq!(fields, vec!["a", "b", "☃", "", "z"]);
+    }
+
+    #[test]
+    fn zip_headers() {
+        let headers = StringRecord::from(vec!["a", "b", "a"]);
+        let rec = StringRecord::from(vec!["1", "2", "3"]);
+        let pairs: Vec<(&str, &str)> = rec.zip_headers(&headers).collect();
+        assert_eq!(pairs, vec![("a", "1"), ("b", "2"), ("a", "3")]);
+
+        let rec = StringRecord::from(vec!["1", "2"]);
+        assert_eq!(rec.zip_headers(&headers).count(), 2);
+    }
+
+    #[test]
+    fn replace_fields() {
+        let mut rec = StringRecord::from(vec!["a", "b", "c"]);
+        rec.replace_fields(vec!["☃".to_string(), "".to_string()]);
+        assert_eq!(rec, vec!["☃", ""]);
+    }
+
+    #[test]
+    fn get_by_name() {
+        let headers = StringRecord::from(vec!["a", "b", "a"]);
+        let rec = StringRecord::from(vec!["1", "2", "3"]);
+        assert_eq!(rec.get_by_name(&headers, "a"), Some("1"));
+        assert_eq!(rec.get_by_name(&headers, "b"), Some("2"));
+        assert_eq!(rec.get_by_name(&headers, "c"), None);
+
+        let short = StringRecord::from(vec!["1"]);
+        assert_eq!(short.get_by_name(&headers, "b"), None);
+    }
+
+    #[test]
+    fn header_map() {
+        let headers = StringRecord::from(vec!["a", "b", "a"]);
+        let map = HeaderMap::new(&headers);
+        assert_eq!(map.index("a"), Some(0));
+        assert_eq!(map.index("b"), Some(1));
+        assert_eq!(map.index("c"), None);
+
+        let rec = StringRecord::from(vec!["1", "2", "3"]);
+        assert_eq!(map.get(&rec, "a"), Some("1"));
+        assert_eq!(map.get(&rec, "b"), Some("2"));
+        assert_eq!(map.get(&rec, "c"), None);
+        assert_eq!(map.get(&StringRecord::from(vec!["1"]), "b"), None);
+    }
 }
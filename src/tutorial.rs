@@ -1052,7 +1052,7 @@ Record { latitude: 65.2419444, longitude: -165.2716667, population: None, city:
 Record { latitude: 60.5544444, longitude: -151.2583333, population: Some(7610), city: "Kenai", state: "AK" }
 Record { latitude: 33.7133333, longitude: -87.3886111, population: None, city: "Oakman", state: "AL" }
 # ... more records
-CSV deserialize error: record 42 (line: 43, byte: 1710): field 2: invalid digit found in string
+CSV deserialize error: record 42 (line: 43, byte: 1710): field 2 (column 'Population'): invalid digit found in string
 ```
 
 Oops! What happened? The program printed several records, but stopped when it
@@ -1,4 +1,4 @@
-use std::{fs::File, io, path::Path, result};
+use std::{borrow::Borrow, cmp, fs::File, io, mem, path::Path, result};
 
 use {
     csv_core::{
@@ -9,10 +9,11 @@ use {
 };
 
 use crate::{
-    byte_record::ByteRecord,
+    byte_record::{ByteRecord, Position},
     error::{Error, ErrorKind, IntoInnerError, Result},
     serializer::{serialize, serialize_header},
-    {QuoteStyle, Terminator},
+    string_record::StringRecord,
+    {check_special_bytes, QuoteStyle, Terminator},
 };
 
 /// Builds a CSV writer with various configuration knobs.
@@ -25,7 +26,13 @@ pub struct WriterBuilder {
     builder: CoreWriterBuilder,
     capacity: usize,
     flexible: bool,
+    pad_records: Option<u64>,
     has_headers: bool,
+    flush_on_drop: bool,
+    reject_unquotable: bool,
+    newline_replacement: Option<Vec<u8>>,
+    bom: bool,
+    tagged_enums: bool,
 }
 
 impl Default for WriterBuilder {
@@ -34,7 +41,13 @@ impl Default for WriterBuilder {
             builder: CoreWriterBuilder::default(),
             capacity: 8 * (1 << 10),
             flexible: false,
+            pad_records: None,
             has_headers: true,
+            flush_on_drop: true,
+            reject_unquotable: false,
+            newline_replacement: None,
+            bom: false,
+            tagged_enums: false,
         }
     }
 }
@@ -72,6 +85,9 @@ impl WriterBuilder {
     /// If there was a problem opening the file at the given path, then this
     /// returns the corresponding error.
     ///
+    /// Like `try_from_writer`, this also returns an error if the
+    /// configuration is invalid.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -88,6 +104,9 @@ impl WriterBuilder {
     /// }
     /// ```
     pub fn from_path<P: AsRef<Path>>(&self, path: P) -> Result<Writer<File>> {
+        // Check first, so that an invalid configuration doesn't truncate
+        // the file.
+        self.check_config()?;
         Ok(Writer::new(self, File::create(path)?))
     }
 
@@ -96,6 +115,12 @@ impl WriterBuilder {
     /// Note that the CSV writer is buffered automatically, so you should not
     /// wrap `wtr` in a buffered writer like `io::BufWriter`.
     ///
+    /// This does not check the configuration. For example, if the delimiter
+    /// is the same as the quote character, then the writer is still built.
+    /// Use
+    /// [`try_from_writer`](struct.WriterBuilder.html#method.try_from_writer)
+    /// to get an error for an invalid configuration instead.
+    ///
     /// # Example
     ///
     /// ```
@@ -117,6 +142,60 @@ impl WriterBuilder {
         Writer::new(self, wtr)
     }
 
+    /// Build a CSV writer from this configuration that writes data to `wtr`,
+    /// returning an error if the configuration is invalid.
+    ///
+    /// This is like `from_writer`, except it checks that the delimiter, quote,
+    /// escape and terminator bytes don't collide. Namely, an error is
+    /// returned if:
+    ///
+    /// * the delimiter is the same as the quote or escape character,
+    /// * or the delimiter, quote or escape character is also a record
+    ///   terminator.
+    ///
+    /// The quote character is ignored when the quote style is
+    /// `QuoteStyle::Never`, and the escape character is ignored unless
+    /// `double_quote` is disabled. Note that `Terminator::CRLF` treats both
+    /// `\r` and `\n` as terminators.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csv::{Terminator, WriterBuilder};
+    ///
+    /// let result = WriterBuilder::new()
+    ///     .delimiter(b';')
+    ///     .terminator(Terminator::Any(b';'))
+    ///     .try_from_writer(vec![]);
+    /// assert!(result.is_err());
+    /// ```
+    pub fn try_from_writer<W: io::Write>(&self, wtr: W) -> Result<Writer<W>> {
+        self.check_config()?;
+        Ok(Writer::new(self, wtr))
+    }
+
+    /// Return an error if the special bytes in this configuration
+    /// collide.
+    fn check_config(&self) -> Result<()> {
+        let core = self.builder.build();
+        let quoting =
+            !matches!(core.get_quote_style(), csv_core::QuoteStyle::Never);
+        let quote = if quoting { Some(core.get_quote()) } else { None };
+        let escape = if quoting && !core.get_double_quote() {
+            Some(core.get_escape())
+        } else {
+            None
+        };
+        // The terminator set by `record_terminator` takes precedence over
+        // `terminator`, so look at the bytes that are actually written.
+        check_special_bytes(
+            core.get_delimiter(),
+            quote,
+            escape,
+            core.get_terminator_bytes(),
+        )
+    }
+
     /// The field delimiter to use when writing CSV.
     ///
     /// The default is `b','`.
@@ -232,6 +311,77 @@ impl WriterBuilder {
         self
     }
 
+    /// Whether to write the variant name of enum newtype variants when
+    /// serializing.
+    ///
+    /// By default, serializing a newtype variant like `Value::Price(1.5)`
+    /// writes only its payload (`1.5`), so the variant can't be recovered
+    /// when reading the data back. When enabled, the variant name and the
+    /// payload are written into two adjacent fields (`Price,1.5`). This
+    /// round-trips with deserialization, which reads a newtype variant from
+    /// a field containing its name followed by a field containing its
+    /// payload.
+    ///
+    /// When a struct field holds such a variant and `has_headers` is
+    /// enabled, two header names are written for it: the field's name for
+    /// the variant name, and the field's name followed by `_value` for the
+    /// payload. Unit variants are still written as a single field, so every
+    /// record should use the same kind of variant in each column to keep
+    /// record lengths consistent.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// use csv::{ReaderBuilder, WriterBuilder};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    /// enum Value {
+    ///     Price(f64),
+    ///     Qty(u64),
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    /// struct Row {
+    ///     item: String,
+    ///     value: Value,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let rows = vec![
+    ///         Row { item: "apple".to_string(), value: Value::Price(1.5) },
+    ///         Row { item: "pear".to_string(), value: Value::Qty(3) },
+    ///     ];
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .tagged_enums(true)
+    ///         .from_writer(vec![]);
+    ///     for row in &rows {
+    ///         wtr.serialize(row)?;
+    ///     }
+    ///
+    ///     let data = wtr.into_inner()?;
+    ///     assert_eq!(data, b"\
+    /// item,value,value_value
+    /// apple,Price,1.5
+    /// pear,Qty,3
+    /// ");
+    ///
+    ///     let mut rdr = ReaderBuilder::new().from_reader(&data[..]);
+    ///     let got = rdr.deserialize().collect::<Result<Vec<Row>, _>>()?;
+    ///     assert_eq!(got, rows);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn tagged_enums(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.tagged_enums = yes;
+        self
+    }
+
     /// Whether the number of fields in records is allowed to change or not.
     ///
     /// When disabled (which is the default), writing CSV data will return an
@@ -290,6 +440,43 @@ impl WriterBuilder {
         self
     }
 
+    /// Pad every record written to at least `len` fields.
+    ///
+    /// When enabled, a record with fewer than `len` fields has empty fields
+    /// appended to it before its terminator is written. This is useful for
+    /// producing rectangular output from ragged input.
+    ///
+    /// Unless `flexible` is enabled, writing a record with more than `len`
+    /// fields returns an `UnequalLengths` error. When `flexible` is enabled,
+    /// such records are written as is.
+    ///
+    /// Records written with `write_record_verbatim` are never padded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .pad_records(3)
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///     wtr.write_record(&["x"])?;
+    ///     wtr.write_record(&["y", "z"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a,b,c\nx,,\ny,z,\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn pad_records(&mut self, len: usize) -> &mut WriterBuilder {
+        self.pad_records = Some(len as u64);
+        self
+    }
+
     /// The record terminator to use when writing CSV.
     ///
     /// A record terminator can be any single byte. The default is `\n`.
@@ -323,6 +510,240 @@ impl WriterBuilder {
         self
     }
 
+    /// Terminate records with a NUL byte (`\0`) when writing CSV.
+    ///
+    /// This is a shortcut for `terminator(Terminator::Any(b'\0'))`. It is
+    /// useful for piping records into tools that expect NUL-delimited
+    /// input, such as `xargs -0`. Fields containing a NUL byte are quoted,
+    /// while fields containing `\r` or `\n` are not (unless they need quotes
+    /// for another reason), since those bytes don't terminate records.
+    ///
+    /// To read such data back, use
+    /// `ReaderBuilder::terminator(Terminator::Any(b'\0'))`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ReaderBuilder, Terminator, WriterBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .nul_terminated()
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b c", "d"])?;
+    ///     wtr.write_record(&["x", "y\0", "z"])?;
+    ///
+    ///     let data = wtr.into_inner()?;
+    ///     assert_eq!(data, b"a,b c,d\0x,\"y\0\",z\0");
+    ///
+    ///     let mut rdr = ReaderBuilder::new()
+    ///         .has_headers(false)
+    ///         .terminator(Terminator::Any(b'\0'))
+    ///         .from_reader(&data[..]);
+    ///     let records = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(records[0], vec!["a", "b c", "d"]);
+    ///     assert_eq!(records[1], vec!["x", "y\0", "z"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn nul_terminated(&mut self) -> &mut WriterBuilder {
+        self.terminator(Terminator::Any(b'\0'))
+    }
+
+    /// Use the given sequence of bytes as the record terminator when writing
+    /// CSV.
+    ///
+    /// This permits multi-byte terminators that can't be expressed with a
+    /// [`Terminator`](enum.Terminator.html), such as `|\n`. The bytes given
+    /// are written verbatim after every record, and fields containing any of
+    /// them are quoted when necessary.
+    ///
+    /// This overrides any terminator previously set with `terminator`, and
+    /// vice versa. Note that this writer's configuration is only checked for
+    /// collisions against the terminator set with `terminator`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `term` is empty or longer than 8 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .delimiter(b'|')
+    ///         .record_terminator(b"|\n")
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///     wtr.write_record(&["x", "y", "z"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a|b|c|\nx|y|z|\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn record_terminator(&mut self, term: &[u8]) -> &mut WriterBuilder {
+        self.builder.record_terminator(term);
+        self
+    }
+
+    /// Whether to write a UTF-8 byte order mark (BOM) at the start of the
+    /// output.
+    ///
+    /// When enabled, the bytes `\xEF\xBB\xBF` are written before anything
+    /// else, including the header row when `has_headers` is enabled. The BOM
+    /// is written exactly once, even if `has_headers` is disabled or no
+    /// records are written at all. Since a CSV `Reader` always strips a
+    /// leading UTF-8 BOM, data written this way reads back unchanged.
+    ///
+    /// This is disabled by default. Some programs, notably Microsoft Excel,
+    /// need a BOM to recognize that CSV data is encoded as UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new().bom(true).from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "\u{FEFF}a,b,c\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn bom(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.bom = yes;
+        self
+    }
+
+    /// A convenience method for specifying a configuration to write CSV data
+    /// for Microsoft Excel.
+    ///
+    /// This sets the following knobs:
+    ///
+    /// * `delimiter(b',')`
+    /// * `terminator(Terminator::CRLF)`
+    /// * `quote(b'"')`
+    /// * `double_quote(true)`
+    /// * `quote_style(QuoteStyle::Necessary)`
+    /// * `bom(true)`
+    ///
+    /// The `has_headers` setting is left unchanged. When it is enabled and
+    /// records are written with `serialize`, the header row is written
+    /// immediately after the BOM.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new().excel().from_writer(vec![]);
+    ///     wtr.write_record(&["city", "note"])?;
+    ///     wtr.write_record(&["Boston", "say \"hi\""])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(
+    ///         data,
+    ///         "\u{FEFF}city,note\r\nBoston,\"say \"\"hi\"\"\"\r\n",
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn excel(&mut self) -> &mut WriterBuilder {
+        self.delimiter(b',')
+            .terminator(Terminator::CRLF)
+            .quote(b'"')
+            .double_quote(true)
+            .quote_style(QuoteStyle::Necessary)
+            .bom(true)
+    }
+
+    /// A convenience method for specifying a configuration to write CSV data
+    /// that strictly follows [RFC 4180](https://tools.ietf.org/html/rfc4180).
+    ///
+    /// This sets the following knobs:
+    ///
+    /// * `delimiter(b',')`
+    /// * `terminator(Terminator::CRLF)`
+    /// * `quote(b'"')`
+    /// * `double_quote(true)`
+    /// * `quote_style(QuoteStyle::Necessary)`
+    /// * `flexible(false)`
+    /// * `bom(false)`
+    ///
+    /// That is, writing a record with a different number of fields than the
+    /// previous records is an error. When `has_headers` is enabled, the
+    /// header row written by `serialize` counts as a record for this check.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new().rfc4180().from_writer(vec![]);
+    ///     wtr.write_record(&["city", "pop"])?;
+    ///     assert!(wtr.write_record(&["Boston"]).is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn rfc4180(&mut self) -> &mut WriterBuilder {
+        self.excel().flexible(false).bom(false)
+    }
+
+    /// A convenience method for specifying a configuration to write CSV data
+    /// with Unix line endings.
+    ///
+    /// This sets the following knobs:
+    ///
+    /// * `delimiter(b',')`
+    /// * `terminator(Terminator::Any(b'\n'))`
+    /// * `quote(b'"')`
+    /// * `double_quote(true)`
+    /// * `quote_style(QuoteStyle::Necessary)`
+    /// * `bom(false)`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new().unix().from_writer(vec![]);
+    ///     wtr.write_record(&["city", "note"])?;
+    ///     wtr.write_record(&["Boston", "a,b"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "city,note\nBoston,\"a,b\"\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn unix(&mut self) -> &mut WriterBuilder {
+        self.delimiter(b',')
+            .terminator(Terminator::Any(b'\n'))
+            .quote(b'"')
+            .double_quote(true)
+            .quote_style(QuoteStyle::Necessary)
+            .bom(false)
+    }
+
     /// The quoting style to use when writing CSV.
     ///
     /// By default, this is set to `QuoteStyle::Necessary`, which will only
@@ -353,6 +774,29 @@ impl WriterBuilder {
     /// }
     /// ```
     ///
+    /// # Example: numeric quoting
+    ///
+    /// This shows how to quote numeric fields, so that consumers treat them
+    /// as text. Other fields are still quoted when necessary.
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{QuoteStyle, WriterBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .quote_style(QuoteStyle::Numeric)
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "007", "c,d"])?;
+    ///     wtr.write_record(&["-3.14", "1e5", "z"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a,\"007\",\"c,d\"\n\"-3.14\",\"1e5\",z\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
     /// # Example: never quote
     ///
     /// This shows how the CSV writer can be made to never write quotes, even
@@ -380,6 +824,93 @@ impl WriterBuilder {
         self
     }
 
+    /// Whether to reject fields that cannot be written without quotes when
+    /// the quoting style is `QuoteStyle::Never`.
+    ///
+    /// By default, `QuoteStyle::Never` writes every field verbatim, even if
+    /// the field contains a delimiter, quote, record terminator or comment
+    /// character. This produces CSV data that cannot be read back correctly.
+    /// When this is enabled, writing such a field instead returns an
+    /// [`ErrorKind::UnquotableField`](enum.ErrorKind.html#variant.UnquotableField)
+    /// error. No part of the offending field is written, but note that
+    /// fields of the record preceding it may have already been written when
+    /// using `write_record` or `write_field`.
+    ///
+    /// This option has no effect with any other quoting style.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{ErrorKind, QuoteStyle, WriterBuilder};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .quote_style(QuoteStyle::Never)
+    ///         .reject_unquotable(true)
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///
+    ///     let err = wtr.write_record(&["x", "y,z"]).unwrap_err();
+    ///     match *err.kind() {
+    ///         ErrorKind::UnquotableField { field } => assert_eq!(field, 1),
+    ///         ref wrong => {
+    ///             panic!("expected UnquotableField but got {:?}", wrong);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn reject_unquotable(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.reject_unquotable = yes;
+        self
+    }
+
+    /// Replace line breaks inside of fields with the given bytes.
+    ///
+    /// By default, a field containing a `\n` or `\r` is written as is, and
+    /// it is quoted so that it can be read back correctly. Some consumers of
+    /// CSV data can't handle fields that span multiple lines, even when they
+    /// are quoted. When a replacement is set, every `\r\n`, `\n` or `\r`
+    /// in a field is replaced with it before the field is written. The
+    /// replacement may be empty, in which case line breaks are removed.
+    ///
+    /// Unlike `QuoteStyle::Never`, this never produces a record that is
+    /// split across lines, and the other special bytes in a field (such as
+    /// delimiters and quotes) are still quoted as usual.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .newline_replacement(Some(b" ".to_vec()))
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "line one\nline two", "c"])?;
+    ///     wtr.write_record(&["x", "y,\r\nz", "w"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a,line one line two,c\nx,\"y, z\",w\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn newline_replacement(
+        &mut self,
+        replacement: Option<Vec<u8>>,
+    ) -> &mut WriterBuilder {
+        self.newline_replacement = replacement;
+        self
+    }
+
     /// The quote character to use when writing CSV.
     ///
     /// The default is `b'"'`.
@@ -500,10 +1031,49 @@ impl WriterBuilder {
 
     /// Set the capacity (in bytes) of the internal buffer used in the CSV
     /// writer. This defaults to a reasonable setting.
+    ///
+    /// The capacity is never smaller than the record terminator.
     pub fn buffer_capacity(&mut self, capacity: usize) -> &mut WriterBuilder {
         self.capacity = capacity;
         self
     }
+
+    /// Whether the writer should flush its buffer when it is dropped.
+    ///
+    /// When this is enabled (the default), dropping a `Writer` attempts to
+    /// flush any buffered data to the underlying writer. Since `Drop` cannot
+    /// return an error, any error that occurs during this flush is silently
+    /// ignored, which can result in truncated output.
+    ///
+    /// When this is disabled, callers are expected to call
+    /// [`Writer::finish`](struct.Writer.html#method.finish) (or `flush` or
+    /// `into_inner`) before the writer is dropped. If a writer is dropped
+    /// with unflushed data, then it panics when debug assertions are enabled.
+    /// Otherwise, the unflushed data is discarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::WriterBuilder;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = WriterBuilder::new()
+    ///         .flush_on_drop(false)
+    ///         .from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///     wtr.write_record(&["x", "y", "z"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.finish()?)?;
+    ///     assert_eq!(data, "a,b,c\nx,y,z\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn flush_on_drop(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.flush_on_drop = yes;
+        self
+    }
 }
 
 /// An already configured CSV writer.
@@ -546,6 +1116,8 @@ struct WriterState {
     header: HeaderState,
     /// Whether inconsistent record lengths are allowed.
     flexible: bool,
+    /// The number of fields that every record is padded to, if any.
+    pad_records: Option<u64>,
     /// The number of fields written in the first record. This is compared
     /// with `fields_written` on all subsequent records to check for
     /// inconsistent record lengths.
@@ -553,10 +1125,27 @@ struct WriterState {
     /// The number of fields written in this record. This is used to report
     /// errors for inconsistent record lengths if `flexible` is disabled.
     fields_written: u64,
+    /// The number of records written so far, including any header row. This
+    /// is used to locate records with inconsistent lengths in errors.
+    records_written: u64,
     /// This is set immediately before flushing the buffer and then unset
     /// immediately after flushing the buffer. This avoids flushing the buffer
     /// twice if the inner writer panics.
     panicked: bool,
+    /// Whether the buffer should be flushed when the writer is dropped.
+    flush_on_drop: bool,
+    /// Whether fields that require quotes should be rejected. This only has
+    /// an effect when the quoting style is `QuoteStyle::Never`.
+    reject_unquotable: bool,
+    /// The bytes that line breaks inside of fields are replaced with, if
+    /// any.
+    newline_replacement: Option<Vec<u8>>,
+    /// A buffer for fields whose line breaks have been replaced. It's reused
+    /// for every such field to avoid an allocation per field.
+    scratch: Vec<u8>,
+    /// Whether enum newtype variants are serialized with their variant
+    /// name.
+    tagged_enums: bool,
 }
 
 /// HeaderState encodes a small state machine for handling header writes.
@@ -588,8 +1177,20 @@ struct Buffer {
 
 impl<W: io::Write> Drop for Writer<W> {
     fn drop(&mut self) {
-        if self.wtr.is_some() && !self.state.panicked {
+        if self.wtr.is_none() || self.state.panicked {
+            return;
+        }
+        if self.state.flush_on_drop {
             let _ = self.flush();
+        } else if cfg!(debug_assertions)
+            && !self.buf.readable().is_empty()
+            && !std::thread::panicking()
+        {
+            panic!(
+                "csv::Writer dropped with {} bytes of unflushed data \
+                 (call Writer::finish or Writer::flush before dropping)",
+                self.buf.len,
+            );
         }
     }
 }
@@ -628,16 +1229,37 @@ impl<W: io::Write> Writer<W> {
         } else {
             HeaderState::None
         };
+        let core = builder.builder.build();
+        // The terminator is written all at once, so it must fit in the
+        // buffer.
+        let capacity =
+            cmp::max(builder.capacity, core.get_terminator_bytes().len());
+        let mut buf = Buffer { buf: vec![0; capacity], len: 0 };
+        if builder.bom {
+            // The BOM is buffered up front so that it precedes everything
+            // else, so make sure it fits even in a tiny buffer.
+            let len = cmp::max(buf.buf.len(), UTF8_BOM.len());
+            buf.buf.resize(len, 0);
+            buf.writable()[..UTF8_BOM.len()].copy_from_slice(UTF8_BOM);
+            buf.written(UTF8_BOM.len());
+        }
         Writer {
-            core: builder.builder.build(),
+            core,
             wtr: Some(wtr),
-            buf: Buffer { buf: vec![0; builder.capacity], len: 0 },
+            buf,
             state: WriterState {
                 header: header_state,
                 flexible: builder.flexible,
+                pad_records: builder.pad_records,
                 first_field_count: None,
                 fields_written: 0,
+                records_written: 0,
                 panicked: false,
+                flush_on_drop: builder.flush_on_drop,
+                reject_unquotable: builder.reject_unquotable,
+                newline_replacement: builder.newline_replacement.clone(),
+                scratch: vec![],
+                tagged_enums: builder.tagged_enums,
             },
         }
     }
@@ -752,11 +1374,15 @@ impl<W: io::Write> Writer<W> {
     /// | option | `Option` | `None` | *empty* |
     /// | option |          | `Some(5)` | `5` |
     /// | unit | `()` | `()` | *empty* |
-    /// | unit struct | `struct Foo;` | `Foo` | `Foo` |
+    /// | unit struct | `struct Foo;` | `Foo` | *empty* |
+    /// | phantom data | `PhantomData<T>` | `PhantomData` | *skipped* |
     /// | unit enum variant | `enum E { A, B }` | `E::A` | `A` |
     /// | newtype struct | `struct Foo(u8);` | `Foo(5)` | `5` |
     /// | newtype enum variant | `enum E { A(u8) }` | `E::A(5)` | `5` |
     ///
+    /// To write `None` as something other than an empty field, use
+    /// [`serialize_none_as`](fn.serialize_none_as.html).
+    ///
     /// Note that this table includes simple structs and enums. For example, to
     /// serialize a field from either an integer or a float type, one can do
     /// this:
@@ -812,7 +1438,11 @@ impl<W: io::Write> Writer<W> {
     /// | tuple struct | `Foo(u8, bool)` | `Foo(5, true)` | `5,true` |
     /// | tuple enum variant | `enum E { A(u8, bool) }` | `E::A(5, true)` | *error* |
     /// | struct enum variant | `enum E { V { a: u8, b: bool } }` | `E::V { a: 5, b: true }` | *error* |
-    /// | map | `BTreeMap<K, V>` | `BTreeMap::new()` | *error* |
+    ///
+    /// A non-struct container serialized on its own, such as a `Vec<String>`
+    /// or an array, is written as a single record of its elements. Since it
+    /// has no field names, no header row is written for it, even if
+    /// `has_headers` is `true`.
     ///
     /// ## Structs
     ///
@@ -874,6 +1504,42 @@ impl<W: io::Write> Writer<W> {
     /// | `Foo { x: 5, y: (6, 7) }` | *error: restriction 1* | `5,6,7` |
     /// | `(5, Foo { x: 6, y: 7 }` | *error: restriction 2* | `5,6,7` |
     /// | `(Foo { x: 5, y: 6 }, true)` | *error: restriction 2* | `5,6,true` |
+    ///
+    /// ## Maps
+    ///
+    /// Maps are treated like structs: keys are written as header names (if
+    /// `has_headers` is `true`) and values are written as fields, in the
+    /// map's iteration order. So the same restrictions apply, and keys must
+    /// be scalars as well. A value of `None` is written as an empty field,
+    /// which keeps the record aligned with its header.
+    ///
+    /// The header row is written from the keys of the first map only, so
+    /// every map should have the same keys in the same order, as is the case
+    /// for `BTreeMap`s with the same keys. Unless the writer is
+    /// [`flexible`](struct.WriterBuilder.html#method.flexible), an error is
+    /// returned for a map with a different number of entries.
+    ///
+    /// ```
+    /// use std::{collections::BTreeMap, error::Error};
+    ///
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     let mut row = BTreeMap::new();
+    ///     row.insert("city", Some("Boston"));
+    ///     row.insert("state", None);
+    ///     wtr.serialize(&row)?;
+    ///     row.insert("city", None);
+    ///     row.insert("state", Some("NH"));
+    ///     wtr.serialize(&row)?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "city,state\nBoston,\n,NH\n");
+    ///     Ok(())
+    /// }
+    /// ```
     pub fn serialize<S: Serialize>(&mut self, record: S) -> Result<()> {
         if let HeaderState::Write = self.state.header {
             let wrote_header = serialize_header(self, &record)?;
@@ -889,6 +1555,280 @@ impl<W: io::Write> Writer<W> {
         Ok(())
     }
 
+    /// Write the header row given, unless a header row was already written.
+    ///
+    /// This returns `true` if the header row was written, and `false`
+    /// otherwise. It is safe to call this any number of times: only the
+    /// first call writes anything. Once this has been called, `serialize`
+    /// and `write_arrow_batch` won't write a header row of their own, and
+    /// vice versa, this won't write a header row after either of them has
+    /// tried to write one.
+    ///
+    /// If `has_headers` is disabled, then this never writes anything. If the
+    /// header row given has no fields, then nothing is written, and no
+    /// header row will be written later.
+    ///
+    /// Note that this doesn't check whether any other records have already
+    /// been written. Writing records with `write_record` or similar doesn't
+    /// count as writing a header row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::{StringRecord, Writer};
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let header = StringRecord::from(vec!["city", "pop"]);
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     assert!(wtr.ensure_header(&header)?);
+    ///     assert!(!wtr.ensure_header(&header)?);
+    ///     wtr.write_record(&["Boston", "4628910"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "city,pop\nBoston,4628910\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ensure_header(&mut self, record: &StringRecord) -> Result<bool> {
+        if let HeaderState::Write = self.state.header {
+            if record.is_empty() {
+                self.state.header = HeaderState::DidNotWrite;
+                return Ok(false);
+            }
+            self.write_record(record)?;
+            self.state.header = HeaderState::DidWrite;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Serialize many records, formatting them in parallel.
+    ///
+    /// This is like calling [`serialize`](#method.serialize) on every record
+    /// yielded by `records`, except records are serialized and quoted on
+    /// worker threads. Records are taken from the iterator in batches. Each
+    /// batch is split into chunks, and each chunk is formatted into its own
+    /// buffer by a separate thread using a copy of this writer's
+    /// configuration. The buffers are then written to the underlying writer
+    /// sequentially, on the calling thread. This is only beneficial when
+    /// there are many records to write and serializing them is expensive.
+    ///
+    /// This requires the `parallel` feature to be enabled.
+    ///
+    /// # Ordering
+    ///
+    /// Records are always written in the order in which they are yielded by
+    /// `records`, exactly as if they had been written with `serialize`. The
+    /// first record is serialized on the calling thread before any others,
+    /// so that if a header row should be written, then it is written exactly
+    /// once and before any data.
+    ///
+    /// # Errors
+    ///
+    /// If serializing a record fails (or its length differs from the
+    /// previous records when this writer isn't flexible), then the error for
+    /// the first such record in iteration order is returned. Every record
+    /// before it is written, and no record after it is written, although
+    /// some records after it may have been serialized on other threads
+    /// before the error was noticed. Errors report the position of the
+    /// record just as `serialize` would. If serializing a record panics, then
+    /// the panic is propagated to the caller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    ///
+    /// use csv::Writer;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Row {
+    ///     id: u64,
+    ///     square: u64,
+    /// }
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.par_serialize((0..3).map(|id| Row { id, square: id * id }))?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "id,square\n0,0\n1,1\n2,4\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn par_serialize<I, S>(&mut self, records: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Serialize + Send,
+    {
+        let mut records = records.into_iter();
+        match records.next() {
+            None => return Ok(()),
+            Some(first) => self.serialize(first)?,
+        }
+        let threads =
+            std::thread::available_parallelism().map_or(1, |n| n.get());
+        loop {
+            let mut chunks: Vec<Vec<S>> = vec![];
+            for _ in 0..threads {
+                let chunk: Vec<S> =
+                    records.by_ref().take(PAR_CHUNK_LEN).collect();
+                if chunk.is_empty() {
+                    break;
+                }
+                chunks.push(chunk);
+            }
+            if chunks.is_empty() {
+                return Ok(());
+            }
+            let mut base = self.state.records_written;
+            let mut workers = vec![];
+            for chunk in &chunks {
+                workers.push(self.par_worker(base));
+                base += chunk.len() as u64;
+            }
+            let results = std::thread::scope(|scope| {
+                let handles: Vec<_> = chunks
+                    .into_iter()
+                    .zip(workers)
+                    .map(|(chunk, wtr)| {
+                        scope.spawn(move || wtr.par_serialize_chunk(chunk))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|h| {
+                        h.join()
+                            .unwrap_or_else(|p| std::panic::resume_unwind(p))
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for (buf, written, result) in results {
+                self.write_raw(&buf)?;
+                self.state.records_written += written;
+                result?;
+            }
+        }
+    }
+
+    /// Returns a writer with the same configuration and state as this one,
+    /// which formats records into memory. The writer returned assumes that
+    /// `records_written` records have already been written.
+    #[cfg(feature = "parallel")]
+    fn par_worker(&self, records_written: u64) -> Writer<Vec<u8>> {
+        Writer {
+            core: self.core.clone(),
+            wtr: Some(vec![]),
+            buf: Buffer { buf: vec![0; self.buf.buf.len()], len: 0 },
+            state: WriterState {
+                header: HeaderState::None,
+                flexible: self.state.flexible,
+                pad_records: self.state.pad_records,
+                first_field_count: self.state.first_field_count,
+                fields_written: 0,
+                records_written,
+                panicked: false,
+                flush_on_drop: true,
+                reject_unquotable: self.state.reject_unquotable,
+                newline_replacement: self.state.newline_replacement.clone(),
+                scratch: vec![],
+                tagged_enums: self.state.tagged_enums,
+            },
+        }
+    }
+
+    /// Write every row of an Apache Arrow `RecordBatch` as a record.
+    ///
+    /// If `has_headers` is enabled (which is the default) and a header row
+    /// hasn't been written yet, then the names of the fields in the batch's
+    /// schema are written as a header row first. This happens even if the
+    /// batch has no rows, so that writing an empty batch still produces a
+    /// header. As with [`serialize`](#method.serialize), only the first
+    /// call writes a header.
+    ///
+    /// Null values are written as empty fields. Other values are formatted
+    /// as follows:
+    ///
+    /// * `Utf8` and `LargeUtf8` values are written as is, and `Binary` and
+    ///   `LargeBinary` values are written as raw bytes.
+    /// * Booleans are written as `true` or `false`, and integers in decimal.
+    /// * Floating point numbers are written in the shortest form that reads
+    ///   back as the same number, e.g., `1.5`, `1.0` or `1e20`.
+    /// * Decimals are written with exactly as many fractional digits as
+    ///   their scale, e.g., `12.30` for a scale of `2`.
+    /// * `Date32` values are written as `2024-02-29`, and `Date64` values
+    ///   as `2024-02-29T00:00:00`. Times are written as `01:02:03` with as
+    ///   many fractional digits as needed.
+    /// * Timestamps are written as `2024-02-29T01:02:03` with as many
+    ///   fractional digits as needed, followed by a UTC offset such as
+    ///   `+01:00` if the timestamp has a time zone. Named time zones, such
+    ///   as `America/New_York`, require Arrow's `chrono-tz` feature;
+    ///   without it, only offsets such as `+01:00` are supported.
+    /// * Durations are written as an integer count of their unit.
+    /// * Any other type is written the way Arrow displays it.
+    ///
+    /// Every type written by this method can be read back by
+    /// [`Reader::arrow_batches`](struct.Reader.html#method.arrow_batches),
+    /// with the exception of types it doesn't support.
+    ///
+    /// This method is only available when the `arrow` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// If a value can't be formatted, then an error of kind
+    /// [`ErrorKind::Serialize`](enum.ErrorKind.html#variant.Serialize) is
+    /// returned. This happens when a column has a type that Arrow can't
+    /// display, in which case nothing is written, or for a value that is out
+    /// of range for its type, in which case the rows before it are written,
+    /// but no part of the row containing it is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{error::Error, sync::Arc};
+    ///
+    /// use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let city: ArrayRef = Arc::new(StringArray::from(vec!["Boston", "Concord"]));
+    ///     let pop: ArrayRef = Arc::new(Int64Array::from(vec![Some(4628910), None]));
+    ///     let batch = RecordBatch::try_from_iter(vec![("city", city), ("pop", pop)])?;
+    ///
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_arrow_batch(&batch)?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "city,pop\nBoston,4628910\nConcord,\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub fn write_arrow_batch(
+        &mut self,
+        batch: &arrow_array::RecordBatch,
+    ) -> Result<()> {
+        if let HeaderState::Write = self.state.header {
+            let schema = batch.schema();
+            if schema.fields().is_empty() {
+                self.state.header = HeaderState::DidNotWrite;
+            } else {
+                for field in schema.fields() {
+                    self.write_field(field.name())?;
+                }
+                self.write_terminator()?;
+                self.state.header = HeaderState::DidWrite;
+            }
+        }
+        crate::arrow::write_batch(self, batch)
+    }
+
     /// Write a single record.
     ///
     /// This method accepts something that can be turned into an iterator that
@@ -921,7 +1861,46 @@ impl<W: io::Write> Writer<W> {
         T: AsRef<[u8]>,
     {
         for field in record.into_iter() {
-            self.write_field_impl(field)?;
+            self.write_field_impl(field.as_ref(), false)?;
+        }
+        self.write_terminator()
+    }
+
+    /// Write a single record whose fields may be missing.
+    ///
+    /// This is like `write_record`, except each field is an `Option` (or a
+    /// reference to one). A `None` field is written as an empty field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_optional_record(&[Some("a"), None, Some("c")])?;
+    ///     wtr.write_optional_record(vec![None, Some("y"), None::<&str>])?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a,,c\n,y,\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_optional_record<I, F, T>(&mut self, record: I) -> Result<()>
+    where
+        I: IntoIterator<Item = F>,
+        F: Borrow<Option<T>>,
+        T: AsRef<[u8]>,
+    {
+        for field in record.into_iter() {
+            match *field.borrow() {
+                Some(ref field) => {
+                    self.write_field_impl(field.as_ref(), false)?
+                }
+                None => self.write_field_impl(b"", false)?,
+            }
         }
         self.write_terminator()
     }
@@ -977,10 +1956,24 @@ impl<W: io::Write> Writer<W> {
             // The maximum number of quotes inserted around each field.
             + (2 * record.len())
             // The maximum number of bytes for the terminator.
-            + 2;
-        if self.buf.writable().len() < upper_bound {
+            + self.core.get_terminator_bytes().len();
+        let needs_padding = matches!(
+            self.state.pad_records,
+            Some(len) if len > record.len() as u64
+        );
+        let needs_replacing = self.state.newline_replacement.is_some()
+            && has_newline(record.as_slice());
+        if self.buf.writable().len() < upper_bound
+            || needs_padding
+            || needs_replacing
+        {
             return self.write_record(record);
         }
+        if self.state.reject_unquotable {
+            for (i, field) in record.iter().enumerate() {
+                self.check_quotable(i as u64, field)?;
+            }
+        }
         let mut first = true;
         for field in record.iter() {
             if !first {
@@ -1013,6 +2006,116 @@ impl<W: io::Write> Writer<W> {
         self.write_terminator_into_buffer()
     }
 
+    /// Write a single pre-formatted record verbatim.
+    ///
+    /// The bytes given are copied directly to the underlying writer, followed
+    /// by a record terminator. `raw` should be a complete record, including
+    /// its field delimiters, but excluding its record terminator. This is
+    /// useful as a fast path in tools that pass most records through
+    /// unchanged, e.g., by writing the bytes of a record exactly as they
+    /// appeared in the input.
+    ///
+    /// No quoting or escaping is performed. The caller is responsible for
+    /// ensuring that `raw` is correctly quoted and escaped for this writer's
+    /// configuration. If it isn't, then the CSV data written will be invalid.
+    ///
+    /// Unless this writer is flexible, the number of fields in `raw` is
+    /// counted so that records with an unequal number of fields are still
+    /// rejected. Fields are counted by finding delimiters that aren't inside
+    /// quotes. Quotes escaped with an escape character (instead of doubled
+    /// quotes) are not recognized when counting fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///     wtr.write_record_verbatim(b"x,\"y,z\",z")?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "a,b,c\nx,\"y,z\",z\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_record_verbatim(&mut self, raw: &[u8]) -> Result<()> {
+        let continuing = self.state.fields_written > 0;
+        let len = self.state.fields_written + self.count_raw_fields(raw);
+        // Check the length before touching any state, so that a rejected
+        // record leaves the writer as it was.
+        self.check_len(len)?;
+        self.state.fields_written = len;
+        if continuing {
+            // Some fields were already written with `write_field`, so the
+            // core writer knows about this record and can terminate it.
+            self.write_delimiter()?;
+            self.write_raw(raw)?;
+            return self.write_terminator();
+        }
+        self.write_raw(raw)?;
+        self.write_raw_terminator()
+    }
+
+    /// Whether enum newtype variants are serialized with their variant name.
+    pub(crate) fn tagged_enums(&self) -> bool {
+        self.state.tagged_enums
+    }
+
+    /// Copy the given bytes to the buffer verbatim, flushing as needed.
+    fn write_raw(&mut self, mut data: &[u8]) -> Result<()> {
+        loop {
+            let n = cmp::min(data.len(), self.buf.writable().len());
+            self.buf.writable()[..n].copy_from_slice(&data[..n]);
+            self.buf.written(n);
+            data = &data[n..];
+            if data.is_empty() {
+                return Ok(());
+            }
+            self.flush_buf()?;
+        }
+    }
+
+    /// Copy the record terminator to the buffer, flushing as needed, and
+    /// finish the current record. Unlike `write_terminator`, this doesn't
+    /// go through the core writer, which doesn't know about verbatim records.
+    fn write_raw_terminator(&mut self) -> Result<()> {
+        let mut i = 0;
+        loop {
+            let term = &self.core.get_terminator_bytes()[i..];
+            let n = cmp::min(term.len(), self.buf.writable().len());
+            self.buf.writable()[..n].copy_from_slice(&term[..n]);
+            self.buf.written(n);
+            i += n;
+            if n == term.len() {
+                break;
+            }
+            self.flush_buf()?;
+        }
+        self.state.fields_written = 0;
+        self.state.records_written += 1;
+        Ok(())
+    }
+
+    /// Count the number of fields in a pre-formatted record.
+    fn count_raw_fields(&self, raw: &[u8]) -> u64 {
+        let (delim, quote) =
+            (self.core.get_delimiter(), self.core.get_quote());
+        let mut in_quotes = false;
+        let mut count = 1;
+        for &b in raw {
+            if b == quote {
+                in_quotes = !in_quotes;
+            } else if b == delim && !in_quotes {
+                count += 1;
+            }
+        }
+        count
+    }
+
     /// Write a single field.
     ///
     /// One should prefer using `write_record` over this method. It is provided
@@ -1046,21 +2149,50 @@ impl<W: io::Write> Writer<W> {
     /// }
     /// ```
     pub fn write_field<T: AsRef<[u8]>>(&mut self, field: T) -> Result<()> {
-        self.write_field_impl(field)
+        self.write_field_impl(field.as_ref(), false)
     }
 
-    /// Implementation of write_field.
+    /// Implementation of write_field and write_field_quoted.
     ///
     /// This is a separate method so we can force the compiler to inline it
     /// into write_record.
     #[inline(always)]
-    fn write_field_impl<T: AsRef<[u8]>>(&mut self, field: T) -> Result<()> {
+    fn write_field_impl(&mut self, field: &[u8], quoted: bool) -> Result<()> {
+        match self.state.newline_replacement {
+            Some(ref replacement) if has_newline(field) => {
+                // The scratch buffer is taken out of the state while the
+                // field is written so that it can be reused by later fields.
+                let mut scratch = mem::take(&mut self.state.scratch);
+                replace_newlines(field, replacement, &mut scratch);
+                let result = self.write_field_bytes(&scratch, quoted);
+                self.state.scratch = scratch;
+                result
+            }
+            _ => self.write_field_bytes(field, quoted),
+        }
+    }
+
+    /// Write a field whose line breaks, if any, have already been replaced.
+    #[inline(always)]
+    fn write_field_bytes(
+        &mut self,
+        mut field: &[u8],
+        quoted: bool,
+    ) -> Result<()> {
+        // This is checked before the delimiter is written, so that nothing
+        // is left behind for a rejected field.
+        if self.state.reject_unquotable && !quoted {
+            self.check_quotable(self.state.fields_written, field)?;
+        }
         if self.state.fields_written > 0 {
             self.write_delimiter()?;
         }
-        let mut field = field.as_ref();
         loop {
-            let (res, nin, nout) = self.core.field(field, self.buf.writable());
+            let (res, nin, nout) = if quoted {
+                self.core.field_quoted(field, self.buf.writable())
+            } else {
+                self.core.field(field, self.buf.writable())
+            };
             field = &field[nin..];
             self.buf.written(nout);
             match res {
@@ -1068,11 +2200,85 @@ impl<W: io::Write> Writer<W> {
                     self.state.fields_written += 1;
                     return Ok(());
                 }
-                WriteResult::OutputFull => self.flush_buf()?,
+                WriteResult::OutputFull => {
+                    self.flush_buf()?;
+                    self.write_field_direct(&mut field)?;
+                }
             }
         }
     }
 
+    /// Write a single field, always enclosing it in quotes.
+    ///
+    /// This is like `write_field`, except the field is quoted regardless of
+    /// the configured quoting style. Any quotes inside the field are escaped
+    /// as usual. This is useful when only some fields (such as an identifier
+    /// column) must always be quoted.
+    ///
+    /// Note that if this API is used, `write_record` should be called with an
+    /// empty iterator to write a record terminator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_field_quoted("0042")?;
+    ///     wtr.write_field("Boston")?;
+    ///     wtr.write_record(None::<&[u8]>)?;
+    ///
+    ///     let data = String::from_utf8(wtr.into_inner()?)?;
+    ///     assert_eq!(data, "\"0042\",Boston\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_field_quoted<T: AsRef<[u8]>>(
+        &mut self,
+        field: T,
+    ) -> Result<()> {
+        self.write_field_impl(field.as_ref(), true)
+    }
+
+    /// Write the rest of a field that is being written directly to the
+    /// underlying writer, bypassing the buffer, if that is faster.
+    ///
+    /// This must only be called when the buffer is empty. Only the bytes
+    /// that would be copied to the buffer unchanged are written, and only
+    /// if there are more of them than fit in the buffer. Otherwise, this
+    /// does nothing. `field` is advanced past the bytes written.
+    fn write_field_direct(&mut self, field: &mut &[u8]) -> io::Result<()> {
+        debug_assert!(self.buf.readable().is_empty());
+        let verbatim = match self.core.field_quoting() {
+            None => return Ok(()),
+            Some(false) => field.len(),
+            Some(true) => memchr::memchr(self.core.get_quote(), field)
+                .unwrap_or(field.len()),
+        };
+        if verbatim < self.buf.buf.len() {
+            return Ok(());
+        }
+        self.state.panicked = true;
+        let result = self.wtr.as_mut().unwrap().write_all(&field[..verbatim]);
+        self.state.panicked = false;
+        result?;
+        *field = &field[verbatim..];
+        Ok(())
+    }
+
+    /// Return an error if the given field cannot be written without quotes.
+    fn check_quotable(&self, index: u64, field: &[u8]) -> Result<()> {
+        if self.core.is_unquotable(field) {
+            return Err(Error::new(ErrorKind::UnquotableField {
+                field: index,
+            }));
+        }
+        Ok(())
+    }
+
     /// Flush the contents of the internal buffer to the underlying writer.
     ///
     /// If there was a problem writing to the underlying writer, then an error
@@ -1096,12 +2302,51 @@ impl<W: io::Write> Writer<W> {
         Ok(())
     }
 
-    /// Return a reference to the underlying writer.
-    pub fn get_ref(&self) -> &W {
-        self.wtr.as_ref().unwrap()
-    }
-
-    /// Flush the contents of the internal buffer and return the underlying
+    /// Returns the CSV data in this writer's internal buffer, which has not
+    /// been written to the underlying writer yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///     assert_eq!(wtr.buffered(), b"a,b,c\n");
+    ///     wtr.flush()?;
+    ///     assert_eq!(wtr.buffered(), b"");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn buffered(&self) -> &[u8] {
+        self.buf.readable()
+    }
+
+    /// Removes and returns the CSV data in this writer's internal buffer,
+    /// which has not been written to the underlying writer yet.
+    ///
+    /// The data returned is never written to the underlying writer. This is
+    /// useful for recovering from errors in the underlying writer, e.g., by
+    /// writing the data somewhere else instead.
+    ///
+    /// If a record is only partially written (e.g., via `write_field`), then
+    /// its fields written so far are returned too, while the writer still
+    /// considers that record to be in progress.
+    pub fn take_buffered(&mut self) -> Vec<u8> {
+        let data = self.buf.readable().to_vec();
+        self.buf.clear();
+        data
+    }
+
+    /// Return a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.wtr.as_ref().unwrap()
+    }
+
+    /// Flush the contents of the internal buffer and return the underlying
     /// writer.
     pub fn into_inner(
         mut self,
@@ -1112,6 +2357,43 @@ impl<W: io::Write> Writer<W> {
         }
     }
 
+    /// Flush the contents of the internal buffer and return the underlying
+    /// writer.
+    ///
+    /// This is like `into_inner`, except any error is returned as a
+    /// [`csv::Error`](struct.Error.html) and the writer is discarded. It is
+    /// the preferred way to finish writing when
+    /// [`WriterBuilder::flush_on_drop`](struct.WriterBuilder.html#method.flush_on_drop)
+    /// is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use csv::Writer;
+    ///
+    /// # fn main() { example().unwrap(); }
+    /// fn example() -> Result<(), Box<dyn Error>> {
+    ///     let mut wtr = Writer::from_writer(vec![]);
+    ///     wtr.write_record(&["a", "b", "c"])?;
+    ///
+    ///     let data = String::from_utf8(wtr.finish()?)?;
+    ///     assert_eq!(data, "a,b,c\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn finish(mut self) -> Result<W> {
+        match self.flush() {
+            Ok(()) => Ok(self.wtr.take().unwrap()),
+            Err(err) => {
+                // The error is reported to the caller, so there's no need
+                // to complain about the unflushed data when dropped.
+                self.buf.clear();
+                Err(Error::from(err))
+            }
+        }
+    }
+
     /// Write a CSV delimiter.
     fn write_delimiter(&mut self) -> Result<()> {
         loop {
@@ -1126,6 +2408,7 @@ impl<W: io::Write> Writer<W> {
 
     /// Write a CSV terminator.
     fn write_terminator(&mut self) -> Result<()> {
+        self.pad_record()?;
         self.check_field_count()?;
         loop {
             let (res, nout) = self.core.terminator(self.buf.writable());
@@ -1133,6 +2416,7 @@ impl<W: io::Write> Writer<W> {
             match res {
                 WriteResult::InputEmpty => {
                     self.state.fields_written = 0;
+                    self.state.records_written += 1;
                     return Ok(());
                 }
                 WriteResult::OutputFull => self.flush_buf()?,
@@ -1145,35 +2429,64 @@ impl<W: io::Write> Writer<W> {
     #[inline(never)]
     fn write_terminator_into_buffer(&mut self) -> Result<()> {
         self.check_field_count()?;
-        match self.core.get_terminator() {
-            csv_core::Terminator::CRLF => {
-                self.buf.writable()[0] = b'\r';
-                self.buf.writable()[1] = b'\n';
-                self.buf.written(2);
-            }
-            csv_core::Terminator::Any(b) => {
-                self.buf.writable()[0] = b;
-                self.buf.written(1);
+        let term = self.core.get_terminator_bytes();
+        self.buf.writable()[..term.len()].copy_from_slice(term);
+        self.buf.written(term.len());
+        self.state.fields_written = 0;
+        self.state.records_written += 1;
+        Ok(())
+    }
+
+    /// Append empty fields to the current record until it has at least as
+    /// many fields as configured by `pad_records`.
+    fn pad_record(&mut self) -> Result<()> {
+        if let Some(len) = self.state.pad_records {
+            while self.state.fields_written < len {
+                self.write_field_impl(b"", false)?;
             }
-            _ => unreachable!(),
         }
-        self.state.fields_written = 0;
         Ok(())
     }
 
+    /// Returns the position of the record currently being written. Only its
+    /// record number is known.
+    fn record_position(&self) -> Position {
+        let mut pos = Position::new();
+        pos.set_record(self.state.records_written);
+        pos
+    }
+
     fn check_field_count(&mut self) -> Result<()> {
+        self.check_len(self.state.fields_written)
+    }
+
+    /// Check that a record with `len` fields may be written, unless this
+    /// writer is flexible.
+    fn check_len(&mut self, len: u64) -> Result<()> {
         if !self.state.flexible {
+            if let Some(pad) = self.state.pad_records {
+                if len > pad {
+                    return Err(Error::record_only(
+                        ErrorKind::UnequalLengths {
+                            pos: Some(self.record_position()),
+                            expected_len: pad,
+                            len,
+                        },
+                    ));
+                }
+            }
             match self.state.first_field_count {
                 None => {
-                    self.state.first_field_count =
-                        Some(self.state.fields_written);
+                    self.state.first_field_count = Some(len);
                 }
-                Some(expected) if expected != self.state.fields_written => {
-                    return Err(Error::new(ErrorKind::UnequalLengths {
-                        pos: None,
-                        expected_len: expected,
-                        len: self.state.fields_written,
-                    }))
+                Some(expected) if expected != len => {
+                    return Err(Error::record_only(
+                        ErrorKind::UnequalLengths {
+                            pos: Some(self.record_position()),
+                            expected_len: expected,
+                            len,
+                        },
+                    ))
                 }
                 Some(_) => {}
             }
@@ -1182,6 +2495,252 @@ impl<W: io::Write> Writer<W> {
     }
 }
 
+/// A destination for CSV records.
+///
+/// This trait permits writing generic code that produces records without
+/// committing to a particular destination. It is implemented by
+/// [`Writer`](struct.Writer.html), and can be implemented by other sinks,
+/// such as one that collects records in memory or forwards them elsewhere.
+///
+/// This trait is object safe, so `Box<dyn RecordSink>` and
+/// `&mut dyn RecordSink` may be used as well.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+/// use csv::{ByteRecord, RecordSink, Writer};
+///
+/// /// Write every record with its fields reversed.
+/// fn reverse_all(
+///     records: &[ByteRecord],
+///     sink: &mut dyn RecordSink,
+/// ) -> csv::Result<()> {
+///     for record in records {
+///         let reversed: ByteRecord = record.iter().rev().collect();
+///         sink.write(&reversed)?;
+///     }
+///     Ok(())
+/// }
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let records = vec![ByteRecord::from(vec!["a", "b", "c"])];
+///     let mut wtr = Writer::from_writer(vec![]);
+///     reverse_all(&records, &mut wtr)?;
+///
+///     let data = String::from_utf8(wtr.into_inner()?)?;
+///     assert_eq!(data, "c,b,a\n");
+///     Ok(())
+/// }
+/// ```
+pub trait RecordSink {
+    /// Write a single record to this sink.
+    fn write(&mut self, record: &ByteRecord) -> Result<()>;
+}
+
+impl<W: io::Write> RecordSink for Writer<W> {
+    fn write(&mut self, record: &ByteRecord) -> Result<()> {
+        self.write_byte_record(record)
+    }
+}
+
+impl<S: RecordSink + ?Sized> RecordSink for &mut S {
+    fn write(&mut self, record: &ByteRecord) -> Result<()> {
+        (**self).write(record)
+    }
+}
+
+impl<S: RecordSink + ?Sized> RecordSink for Box<S> {
+    fn write(&mut self, record: &ByteRecord) -> Result<()> {
+        (**self).write(record)
+    }
+}
+
+/// Options for writing a single record with
+/// [`ByteRecord::write_to_with`](struct.ByteRecord.html#method.write_to_with)
+/// or
+/// [`StringRecord::write_to_with`](struct.StringRecord.html#method.write_to_with).
+///
+/// The defaults match those of a `WriterBuilder`: fields are separated by
+/// `,`, quoted with `"` only when necessary and the record is terminated by
+/// `\n`.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+/// use csv::{QuoteStyle, RecordWriteOptions, StringRecord};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<(), Box<dyn Error>> {
+///     let record = StringRecord::from(vec!["a", "b c"]);
+///     let mut buf = vec![];
+///     record.write_to_with(
+///         &mut buf,
+///         RecordWriteOptions::new()
+///             .delimiter(b'\t')
+///             .quote(b'\'')
+///             .quote_style(QuoteStyle::Always),
+///     )?;
+///     assert_eq!(buf, b"'a'\t'b c'\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordWriteOptions {
+    delimiter: u8,
+    quote: u8,
+    quote_style: QuoteStyle,
+    terminator: Terminator,
+}
+
+impl Default for RecordWriteOptions {
+    fn default() -> RecordWriteOptions {
+        RecordWriteOptions {
+            delimiter: b',',
+            quote: b'"',
+            quote_style: QuoteStyle::default(),
+            terminator: Terminator::Any(b'\n'),
+        }
+    }
+}
+
+impl RecordWriteOptions {
+    /// Create a new set of options with the default settings.
+    pub fn new() -> RecordWriteOptions {
+        RecordWriteOptions::default()
+    }
+
+    /// The field delimiter to use when writing CSV.
+    ///
+    /// The default is `b','`.
+    pub fn delimiter(&mut self, delimiter: u8) -> &mut RecordWriteOptions {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The quote character to use when writing CSV.
+    ///
+    /// The default is `b'"'`.
+    pub fn quote(&mut self, quote: u8) -> &mut RecordWriteOptions {
+        self.quote = quote;
+        self
+    }
+
+    /// The quoting style to use when writing CSV.
+    ///
+    /// The default is `QuoteStyle::Necessary`.
+    pub fn quote_style(
+        &mut self,
+        style: QuoteStyle,
+    ) -> &mut RecordWriteOptions {
+        self.quote_style = style;
+        self
+    }
+
+    /// The record terminator to use when writing CSV.
+    ///
+    /// The default is `b'\n'`. Note that `Terminator::CRLF` writes `\r\n`.
+    pub fn terminator(&mut self, term: Terminator) -> &mut RecordWriteOptions {
+        self.terminator = term;
+        self
+    }
+}
+
+/// Write a single record, including its terminator, to `wtr`.
+pub(crate) fn write_record_to<W: io::Write>(
+    mut wtr: W,
+    record: &ByteRecord,
+    options: &RecordWriteOptions,
+) -> Result<()> {
+    let mut core = CoreWriterBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quote_style(options.quote_style.to_core())
+        .terminator(options.terminator.to_core())
+        .build();
+    // As in `Writer::write_byte_record`, every byte may be a quote that
+    // needs escaping and every field may need to be quoted. The extra two
+    // bytes are for the quotes written for a record with no fields.
+    let upper_bound = (2 * record.as_slice().len())
+        + record.len().saturating_sub(1)
+        + (2 * record.len())
+        + 2
+        + core.get_terminator_bytes().len();
+    let mut buf = vec![0; upper_bound];
+    let mut nout = 0;
+    for (i, field) in record.iter().enumerate() {
+        if i > 0 {
+            nout += core.delimiter(&mut buf[nout..]).1;
+        }
+        let (res, _, n) = core.field(field, &mut buf[nout..]);
+        debug_assert_eq!(res, WriteResult::InputEmpty);
+        nout += n;
+    }
+    nout += core.terminator(&mut buf[nout..]).1;
+    wtr.write_all(&buf[..nout])?;
+    Ok(())
+}
+
+/// The UTF-8 byte order mark written when `WriterBuilder::bom` is enabled.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The number of records formatted by each thread in `par_serialize`.
+#[cfg(feature = "parallel")]
+const PAR_CHUNK_LEN: usize = 1 << 10;
+
+#[cfg(feature = "parallel")]
+impl Writer<Vec<u8>> {
+    /// Serialize the given records into memory, stopping at the first error.
+    ///
+    /// This returns the bytes of every record serialized successfully, the
+    /// number of such records and the result of the last record serialized.
+    fn par_serialize_chunk<S: Serialize>(
+        mut self,
+        chunk: Vec<S>,
+    ) -> (Vec<u8>, u64, Result<()>) {
+        let mut written = 0;
+        let mut result = Ok(());
+        for record in chunk {
+            let mark = self.get_ref().len() + self.buf.len;
+            if let Err(err) = self.serialize(record) {
+                // Drop whatever was written of the failed record.
+                self.flush_buf().unwrap();
+                self.wtr.as_mut().unwrap().truncate(mark);
+                result = Err(err);
+                break;
+            }
+            written += 1;
+        }
+        self.flush_buf().unwrap();
+        (self.wtr.take().unwrap(), written, result)
+    }
+}
+
+/// Returns true if `field` contains a `\n` or a `\r`.
+fn has_newline(field: &[u8]) -> bool {
+    field.iter().any(|&b| b == b'\n' || b == b'\r')
+}
+
+/// Writes a copy of `field` to `out` with every `\r\n`, `\n` and `\r`
+/// replaced with `replacement`. Any previous contents of `out` are removed.
+fn replace_newlines(field: &[u8], replacement: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    let mut i = 0;
+    while i < field.len() {
+        match field[i] {
+            b'\r' if field.get(i + 1) == Some(&b'\n') => {
+                out.extend_from_slice(replacement);
+                i += 1;
+            }
+            b'\n' | b'\r' => out.extend_from_slice(replacement),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+}
+
 impl Buffer {
     /// Returns a slice of the buffer's current contents.
     ///
@@ -1214,15 +2773,19 @@ impl Buffer {
 
 #[cfg(test)]
 mod tests {
-    use std::io::{self, Write};
+    use std::{
+        io::{self, Write},
+        marker::PhantomData,
+    };
 
     use serde::{serde_if_integer128, Serialize};
 
     use crate::{
-        byte_record::ByteRecord, error::ErrorKind, string_record::StringRecord,
+        byte_record::ByteRecord, error::ErrorKind,
+        string_record::StringRecord, QuoteStyle, Terminator,
     };
 
-    use super::{Writer, WriterBuilder};
+    use super::{RecordSink, RecordWriteOptions, Writer, WriterBuilder};
 
     fn wtr_as_string(wtr: Writer<Vec<u8>>) -> String {
         String::from_utf8(wtr.into_inner().unwrap()).unwrap()
@@ -1301,7 +2864,7 @@ mod tests {
         let err = wtr.write_record(&ByteRecord::from(vec!["a"])).unwrap_err();
         match *err.kind() {
             ErrorKind::UnequalLengths { ref pos, expected_len, len } => {
-                assert!(pos.is_none());
+                assert_eq!(pos.as_ref().map(|p| p.record()), Some(1));
                 assert_eq!(expected_len, 3);
                 assert_eq!(len, 1);
             }
@@ -1311,6 +2874,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unequal_records_bad_position() {
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        wtr.write_record_verbatim(b"c,d").unwrap();
+        wtr.write_field("e").unwrap();
+        wtr.write_field("f").unwrap();
+        wtr.write_record(None::<&[u8]>).unwrap();
+        wtr.serialize(("g", "h")).unwrap();
+        let err = wtr.write_record(["x"]).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { ref pos, .. } => {
+                assert_eq!(pos.as_ref().map(|p| p.record()), Some(4));
+            }
+            ref x => {
+                panic!("expected UnequalLengths error, but got '{:?}'", x);
+            }
+        }
+        assert!(err.to_string().starts_with("CSV error: record 4: found"));
+    }
+
     #[test]
     fn raw_unequal_records_bad() {
         let mut wtr = WriterBuilder::new().from_writer(vec![]);
@@ -1319,7 +2903,7 @@ mod tests {
             wtr.write_byte_record(&ByteRecord::from(vec!["a"])).unwrap_err();
         match *err.kind() {
             ErrorKind::UnequalLengths { ref pos, expected_len, len } => {
-                assert!(pos.is_none());
+                assert_eq!(pos.as_ref().map(|p| p.record()), Some(1));
                 assert_eq!(expected_len, 3);
                 assert_eq!(len, 1);
             }
@@ -1345,6 +2929,83 @@ mod tests {
         assert_eq!(wtr_as_string(wtr), "a,b,c\na\n");
     }
 
+    #[test]
+    fn large_fields_bypass_buffer() {
+        struct MarkWrite(Vec<u8>);
+
+        impl Write for MarkWrite {
+            fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+                self.0.write_all(b">")?;
+                self.0.write_all(data)?;
+                self.0.write_all(b"<")?;
+                Ok(data.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut wtr = WriterBuilder::new()
+            .buffer_capacity(4)
+            .from_writer(MarkWrite(vec![]));
+        wtr.write_record(["abcdefghij", "k"]).unwrap();
+        wtr.write_record(["x,yz\"abcdefgh\"ij", "k"]).unwrap();
+        let got = String::from_utf8(wtr.into_inner().unwrap().0).unwrap();
+        assert_eq!(
+            got,
+            ">abcd<>efghij<>,k\n\"<>x,yz<>\"\"ab<>cdefgh<>\"\"ij<>\",k\n<"
+        );
+    }
+
+    #[test]
+    fn large_fields_round_trip() {
+        let fields = [
+            "a".repeat(100),
+            format!("{},{}", "b".repeat(50), "c".repeat(50)),
+            format!("\"{}\"\"{}\"", "d".repeat(40), "e".repeat(40)),
+            "\"".repeat(30),
+            format!("x{}\ny", "f".repeat(60)),
+        ];
+        let write = |configure: fn(&mut WriterBuilder), capacity: usize| {
+            let mut builder = WriterBuilder::new();
+            configure(&mut builder);
+            let mut wtr =
+                builder.buffer_capacity(capacity).from_writer(vec![]);
+            for field in &fields {
+                wtr.write_record([&**field, "z"]).unwrap();
+                wtr.write_field_quoted(field).unwrap();
+                wtr.write_field("z").unwrap();
+                wtr.write_record(None::<&[u8]>).unwrap();
+            }
+            wtr_as_string(wtr)
+        };
+
+        let configs: [fn(&mut WriterBuilder); 4] = [
+            |_| {},
+            |b| {
+                b.quote_style(QuoteStyle::Always);
+            },
+            |b| {
+                b.quote_style(QuoteStyle::Never);
+            },
+            |b| {
+                b.double_quote(false);
+            },
+        ];
+        for &configure in &configs {
+            let expected = write(configure, 1 << 16);
+            for capacity in 2..40 {
+                assert_eq!(
+                    write(configure, capacity),
+                    expected,
+                    "{}",
+                    capacity
+                );
+            }
+        }
+    }
+
     #[test]
     fn full_buffer_should_not_flush_underlying() {
         struct MarkWriteAndFlush(Vec<u8>);
@@ -1400,6 +3061,40 @@ mod tests {
         assert_eq!(wtr_as_string(wtr), "foo,bar,baz\n42,42.5,true\n");
     }
 
+    #[test]
+    fn ensure_header() {
+        #[derive(Serialize)]
+        struct Row {
+            foo: i32,
+            bar: bool,
+        }
+
+        let header = StringRecord::from(vec!["foo", "bar"]);
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        assert!(wtr.ensure_header(&header).unwrap());
+        assert!(!wtr.ensure_header(&header).unwrap());
+        wtr.serialize(Row { foo: 42, bar: true }).unwrap();
+        assert!(!wtr.ensure_header(&header).unwrap());
+        assert_eq!(wtr_as_string(wtr), "foo,bar\n42,true\n");
+
+        // A header written by serialize counts too.
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        wtr.serialize(Row { foo: 42, bar: true }).unwrap();
+        assert!(!wtr.ensure_header(&header).unwrap());
+        assert_eq!(wtr_as_string(wtr), "foo,bar\n42,true\n");
+
+        let mut wtr =
+            WriterBuilder::new().has_headers(false).from_writer(vec![]);
+        assert!(!wtr.ensure_header(&header).unwrap());
+        wtr.serialize(Row { foo: 42, bar: true }).unwrap();
+        assert_eq!(wtr_as_string(wtr), "42,true\n");
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        assert!(!wtr.ensure_header(&StringRecord::new()).unwrap());
+        assert!(!wtr.ensure_header(&header).unwrap());
+        assert_eq!(wtr_as_string(wtr), "");
+    }
+
     #[test]
     fn serialize_no_headers() {
         #[derive(Serialize)]
@@ -1436,6 +3131,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn serialize_unit_fields() {
+        #[derive(Serialize)]
+        struct Marker;
+
+        #[derive(Serialize)]
+        struct Row {
+            foo: i32,
+            unit: (),
+            marker: Marker,
+            phantom: PhantomData<String>,
+            bar: &'static str,
+        }
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let row = Row {
+            foo: 42,
+            unit: (),
+            marker: Marker,
+            phantom: PhantomData,
+            bar: "x",
+        };
+        wtr.serialize(&row).unwrap();
+        wtr.serialize(&row).unwrap();
+        assert_eq!(
+            wtr_as_string(wtr),
+            "foo,unit,marker,bar\n42,,,x\n42,,,x\n"
+        );
+    }
+
+    #[test]
+    fn serialize_seq_single_record() {
+        for &has_headers in &[true, false] {
+            let mut wtr = WriterBuilder::new()
+                .has_headers(has_headers)
+                .from_writer(vec![]);
+            wtr.serialize(vec!["a", "b", "c"]).unwrap();
+            wtr.serialize([1, 2, 3]).unwrap();
+            wtr.serialize(vec![vec![4], vec![5, 6]]).unwrap();
+            assert_eq!(wtr_as_string(wtr), "a,b,c\n1,2,3\n4,5,6\n");
+        }
+    }
+
+    #[test]
+    fn serialize_seq_in_struct_with_headers() {
+        #[derive(Serialize)]
+        struct Row {
+            label: &'static str,
+            values: Vec<i32>,
+        }
+
+        let row = Row { label: "foo", values: vec![1, 2] };
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let err = wtr.serialize(&row).unwrap_err();
+        match *err.kind() {
+            ErrorKind::Serialize(_) => {}
+            ref x => panic!("expected ErrorKind::Serialize but got {:?}", x),
+        }
+
+        let mut wtr =
+            WriterBuilder::new().has_headers(false).from_writer(vec![]);
+        wtr.serialize(&row).unwrap();
+        assert_eq!(wtr_as_string(wtr), "foo,1,2\n");
+    }
+
     #[test]
     fn serialize_tuple() {
         let mut wtr = WriterBuilder::new().from_writer(vec![]);
@@ -1451,4 +3211,821 @@ mod tests {
         let buf = wtr.into_inner().unwrap();
         assert_eq!(String::from_utf8(buf).unwrap(), "\"# comment\",another\n");
     }
+
+    #[test]
+    fn finish() {
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        wtr.write_record(["a", "b", "c"]).unwrap();
+        let buf = wtr.finish().unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "a,b,c\n");
+    }
+
+    #[test]
+    fn finish_error() {
+        #[derive(Debug)]
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "nope"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut wtr = WriterBuilder::new()
+            .flush_on_drop(false)
+            .from_writer(FailingWriter);
+        wtr.write_record(["a", "b", "c"]).unwrap();
+        let err = wtr.finish().unwrap_err();
+        assert!(err.is_io_error());
+    }
+
+    #[test]
+    fn into_inner_error_unflushed() {
+        #[derive(Debug)]
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "nope"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut wtr = WriterBuilder::new().from_writer(FailingWriter);
+        wtr.write_record(["a", "b"]).unwrap();
+        wtr.write_record(["c", "d"]).unwrap();
+        let err = wtr.into_inner().unwrap_err();
+        assert_eq!(err.error().to_string(), "nope");
+        assert_eq!(err.unflushed(), b"a,b\nc,d\n");
+        assert_eq!(err.into_unflushed(), b"a,b\nc,d\n");
+    }
+
+    #[test]
+    fn flush_on_drop_enabled() {
+        let mut buf = vec![];
+        {
+            let mut wtr = WriterBuilder::new().from_writer(&mut buf);
+            wtr.write_record(["a", "b", "c"]).unwrap();
+        }
+        assert_eq!(String::from_utf8(buf).unwrap(), "a,b,c\n");
+    }
+
+    #[test]
+    fn flush_on_drop_disabled_after_flush() {
+        let mut buf = vec![];
+        {
+            let mut wtr = WriterBuilder::new()
+                .flush_on_drop(false)
+                .from_writer(&mut buf);
+            wtr.write_record(["a", "b", "c"]).unwrap();
+            wtr.flush().unwrap();
+        }
+        assert_eq!(String::from_utf8(buf).unwrap(), "a,b,c\n");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "unflushed data")]
+    fn flush_on_drop_disabled_panics() {
+        let mut wtr =
+            WriterBuilder::new().flush_on_drop(false).from_writer(vec![]);
+        wtr.write_record(["a", "b", "c"]).unwrap();
+    }
+
+    #[test]
+    fn reject_unquotable() {
+        for bad in ["x,y", "x\"y", "x\ny", "x\ry"] {
+            let mut wtr = WriterBuilder::new()
+                .quote_style(QuoteStyle::Never)
+                .reject_unquotable(true)
+                .from_writer(vec![]);
+            wtr.write_record(["a", "b"]).unwrap();
+            let err = wtr.write_record(["z", bad]).unwrap_err();
+            match *err.kind() {
+                ErrorKind::UnquotableField { field: 1 } => {}
+                ref x => panic!("expected UnquotableField but got {:?}", x),
+            }
+        }
+    }
+
+    #[test]
+    fn reject_unquotable_no_dangling_delimiter() {
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .reject_unquotable(true)
+            .flexible(true)
+            .from_writer(vec![]);
+        let err = wtr.write_record(["z", "x,y"]).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnquotableField { field: 1 } => {}
+            ref x => panic!("expected UnquotableField but got {:?}", x),
+        }
+        // The field before the rejected one was written, but no delimiter
+        // was written for the rejected one.
+        wtr.write_record(["q"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "z,q\n");
+
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .reject_unquotable(true)
+            .from_writer(vec![]);
+        let err = wtr
+            .write_byte_record(&ByteRecord::from(vec!["a", "b\"c"]))
+            .unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnquotableField { field: 1 } => {}
+            ref x => panic!("expected UnquotableField but got {:?}", x),
+        }
+        wtr.write_byte_record(&ByteRecord::from(vec!["d", "e"])).unwrap();
+        assert_eq!(wtr_as_string(wtr), "d,e\n");
+    }
+
+    #[test]
+    fn reject_unquotable_byte_record() {
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .reject_unquotable(true)
+            .from_writer(vec![]);
+        wtr.write_byte_record(&ByteRecord::from(vec!["a", "b"])).unwrap();
+        let err = wtr
+            .write_byte_record(&ByteRecord::from(vec!["x\ny", "z"]))
+            .unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnquotableField { field: 0 } => {}
+            ref x => panic!("expected UnquotableField but got {:?}", x),
+        }
+        // Nothing from the rejected record should have been written.
+        assert_eq!(wtr_as_string(wtr), "a,b\n");
+    }
+
+    #[test]
+    fn reject_unquotable_other_styles() {
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Necessary)
+            .reject_unquotable(true)
+            .from_writer(vec![]);
+        wtr.write_record(["a,b", "c"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\"a,b\",c\n");
+
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .from_writer(vec![]);
+        wtr.write_record(["a,b", "c"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b,c\n");
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_serialize() {
+        #[derive(Serialize)]
+        struct Row {
+            id: usize,
+            name: String,
+        }
+
+        let rows =
+            || (0..5000).map(|id| Row { id, name: format!("name,{}", id) });
+        let mut expected = Writer::from_writer(vec![]);
+        for row in rows() {
+            expected.serialize(row).unwrap();
+        }
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.par_serialize(rows()).unwrap();
+        // Records written afterwards continue where the others left off.
+        wtr.serialize(Row { id: 5000, name: "z".to_string() }).unwrap();
+        expected.serialize(Row { id: 5000, name: "z".to_string() }).unwrap();
+        assert_eq!(wtr_as_string(wtr), wtr_as_string(expected));
+
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.par_serialize(Vec::<Row>::new()).unwrap();
+        assert_eq!(wtr_as_string(wtr), "");
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_serialize_error() {
+        let rows = (0..5000u64).map(|i| {
+            if i == 2500 || i == 4000 {
+                vec![i]
+            } else {
+                vec![i, i]
+            }
+        });
+        let mut wtr = Writer::from_writer(vec![]);
+        let err = wtr.par_serialize(rows).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { ref pos, expected_len, len } => {
+                assert_eq!(pos.as_ref().unwrap().record(), 2500);
+                assert_eq!((expected_len, len), (2, 1));
+            }
+            ref x => panic!("expected UnequalLengths, got {:?}", x),
+        }
+        let data = wtr_as_string(wtr);
+        assert_eq!(data.lines().count(), 2500);
+        assert_eq!(data.lines().last(), Some("2499,2499"));
+    }
+
+    #[test]
+    fn newline_replacement() {
+        let mut wtr = WriterBuilder::new()
+            .newline_replacement(Some(b"\\n".to_vec()))
+            .flexible(true)
+            .from_writer(vec![]);
+        wtr.write_record(["a\nb", "c\r\nd", "e\rf", "g"]).unwrap();
+        wtr.write_byte_record(&ByteRecord::from(vec!["h\n", "i,\nj"]))
+            .unwrap();
+        wtr.write_field("k\r\n\r\n").unwrap();
+        wtr.write_field_quoted("l\nm").unwrap();
+        wtr.write_record(None::<&[u8]>).unwrap();
+        assert_eq!(
+            wtr_as_string(wtr),
+            "a\\nb,c\\nd,e\\nf,g\nh\\n,\"i,\\nj\"\nk\\n\\n,\"l\\nm\"\n"
+        );
+
+        // An empty replacement removes line breaks, and a field that no
+        // longer needs quotes is accepted by reject_unquotable.
+        let mut wtr = WriterBuilder::new()
+            .newline_replacement(Some(vec![]))
+            .quote_style(QuoteStyle::Never)
+            .reject_unquotable(true)
+            .from_writer(vec![]);
+        wtr.write_record(["a\nb", "c"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "ab,c\n");
+
+        // Without a replacement, line breaks are preserved and quoted.
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        wtr.write_record(["a\nb", "c"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\"a\nb\",c\n");
+    }
+
+    #[test]
+    fn quote_style_roundtrip() {
+        use crate::reader::ReaderBuilder;
+
+        let records = vec![
+            vec!["a", "b,c", "d\"e"],
+            vec!["f\ng", "h\ri", "j\r\nk"],
+            vec!["", "1.5", " l "],
+            vec!["#m", "\"", "\"\""],
+        ];
+        for style in [
+            QuoteStyle::Always,
+            QuoteStyle::Necessary,
+            QuoteStyle::NonNumeric,
+            QuoteStyle::Numeric,
+        ] {
+            let mut wtr = WriterBuilder::new()
+                .quote_style(style)
+                .comment(Some(b'#'))
+                .from_writer(vec![]);
+            for record in &records {
+                wtr.write_record(record).unwrap();
+            }
+            let data = wtr.into_inner().unwrap();
+
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(false)
+                .comment(Some(b'#'))
+                .from_reader(&data[..]);
+            let got: Vec<StringRecord> =
+                rdr.records().map(|r| r.unwrap()).collect();
+            assert_eq!(got, records, "quote style: {:?}", style);
+        }
+    }
+
+    #[test]
+    fn write_field_quoted() {
+        use crate::reader::ReaderBuilder;
+
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .from_writer(vec![]);
+        for (id, name) in [("1", "a"), ("2\"x", ""), ("", "b")] {
+            wtr.write_field_quoted(id).unwrap();
+            wtr.write_field(name).unwrap();
+            wtr.write_record(None::<&[u8]>).unwrap();
+        }
+        let data = wtr.into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(data.clone()).unwrap(),
+            "\"1\",a\n\"2\"\"x\",\n\"\",b\n"
+        );
+
+        let mut rdr =
+            ReaderBuilder::new().has_headers(false).from_reader(&data[..]);
+        let got: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(got, vec![vec!["1", "a"], vec!["2\"x", ""], vec!["", "b"]]);
+    }
+
+    #[test]
+    fn record_terminator_roundtrip() {
+        use crate::reader::ReaderBuilder;
+
+        let records = vec![
+            vec!["a", "b|c", ""],
+            vec!["d\ne", "", "f"],
+            vec!["", "", ""],
+        ];
+        let mut wtr = WriterBuilder::new()
+            .delimiter(b'|')
+            .record_terminator(b"|\r\n")
+            .from_writer(vec![]);
+        for (i, record) in records.iter().enumerate() {
+            if i == 1 {
+                wtr.write_record_verbatim(b"\"d\ne\"||f").unwrap();
+            } else {
+                wtr.write_record(record).unwrap();
+            }
+        }
+        let data = wtr.into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(data.clone()).unwrap(),
+            "a|\"b|c\"||\r\n\"d\ne\"||f|\r\n|||\r\n"
+        );
+
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(b'|')
+            .drop_trailing_empty_field(true)
+            .from_reader(&data[..]);
+        let got: Vec<StringRecord> =
+            rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(got, records);
+    }
+
+    #[test]
+    fn record_sink() {
+        struct Collect(Vec<ByteRecord>);
+
+        impl RecordSink for Collect {
+            fn write(&mut self, record: &ByteRecord) -> crate::Result<()> {
+                self.0.push(record.clone());
+                Ok(())
+            }
+        }
+
+        fn copy<S: RecordSink>(mut sink: S) {
+            sink.write(&ByteRecord::from(vec!["a", "b"])).unwrap();
+            sink.write(&ByteRecord::from(vec!["c", "d"])).unwrap();
+        }
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        copy(&mut wtr);
+        assert_eq!(wtr_as_string(wtr), "a,b\nc,d\n");
+
+        let mut sinks: Vec<Box<dyn RecordSink>> = vec![
+            Box::new(Collect(vec![])),
+            Box::new(WriterBuilder::new().from_writer(io::sink())),
+        ];
+        for sink in &mut sinks {
+            copy(sink);
+        }
+
+        let mut collect = Collect(vec![]);
+        copy(&mut collect);
+        assert_eq!(collect.0, vec![vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn nul_terminated_roundtrip() {
+        let records = vec![
+            vec!["a", "b\nc", ""],
+            vec!["\0", "d,e", "\"f\""],
+            vec!["", "", ""],
+        ];
+        let mut wtr =
+            WriterBuilder::new().nul_terminated().from_writer(vec![]);
+        for record in &records {
+            wtr.write_record(record).unwrap();
+        }
+        let data = wtr.into_inner().unwrap();
+        assert_eq!(data.iter().filter(|&&b| b == b'\n').count(), 1);
+
+        let mut rdr = crate::ReaderBuilder::new()
+            .has_headers(false)
+            .terminator(Terminator::Any(b'\0'))
+            .from_reader(&data[..]);
+        let got: Vec<StringRecord> =
+            rdr.records().collect::<Result<_, _>>().unwrap();
+        assert_eq!(got, records);
+    }
+
+    #[test]
+    fn pad_records_short() {
+        let mut wtr = WriterBuilder::new().pad_records(3).from_writer(vec![]);
+        wtr.write_record(["a"]).unwrap();
+        wtr.write_byte_record(&ByteRecord::from(vec!["b", "c"])).unwrap();
+        wtr.write_record(None::<&[u8]>).unwrap();
+        wtr.write_field("d").unwrap();
+        wtr.write_record(None::<&[u8]>).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,,\nb,c,\n,,\nd,,\n");
+    }
+
+    #[test]
+    fn pad_records_exact() {
+        let mut wtr = WriterBuilder::new().pad_records(2).from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        wtr.write_byte_record(&ByteRecord::from(vec!["c", "d"])).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b\nc,d\n");
+    }
+
+    #[test]
+    fn pad_records_too_long() {
+        let mut wtr = WriterBuilder::new().pad_records(2).from_writer(vec![]);
+        let err = wtr.write_record(["a", "b", "c"]).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { expected_len, len, .. } => {
+                assert_eq!(expected_len, 2);
+                assert_eq!(len, 3);
+            }
+            ref x => panic!("expected UnequalLengths, but got {:?}", x),
+        }
+
+        let mut wtr = WriterBuilder::new()
+            .pad_records(2)
+            .flexible(true)
+            .from_writer(vec![]);
+        wtr.write_record(["a", "b", "c"]).unwrap();
+        wtr.write_record(["d"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b,c\nd,\n");
+    }
+
+    #[test]
+    fn quote_style_numeric() {
+        let mut wtr = WriterBuilder::new()
+            .quote_style(QuoteStyle::Numeric)
+            .from_writer(vec![]);
+        wtr.write_record(["1", "-20", "0.5", "1e-3", "NaN"]).unwrap();
+        wtr.write_record(["x", "1a", "", " 1", "a\"b"]).unwrap();
+        wtr.serialize((5u8, 2.5f64, "abc", true, -1i64)).unwrap();
+
+        assert_eq!(
+            wtr_as_string(wtr),
+            "\"1\",\"-20\",\"0.5\",\"1e-3\",\"NaN\"\n\
+             x,1a,, 1,\"a\"\"b\"\n\
+             \"5\",\"2.5\",abc,true,\"-1\"\n"
+        );
+    }
+
+    #[test]
+    fn serialize_map_with_none() {
+        use std::collections::BTreeMap;
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let mut row: BTreeMap<String, Option<String>> = BTreeMap::new();
+        row.insert("a".into(), Some("1".into()));
+        row.insert("b".into(), None);
+        row.insert("c".into(), Some("3".into()));
+        wtr.serialize(&row).unwrap();
+        row.insert("a".into(), None);
+        row.insert("c".into(), None);
+        wtr.serialize(&row).unwrap();
+        row.insert("b".into(), Some("x,y".into()));
+        wtr.serialize(&row).unwrap();
+
+        assert_eq!(wtr_as_string(wtr), "a,b,c\n1,,3\n,,\n,\"x,y\",\n");
+    }
+
+    #[test]
+    fn serialize_map_unequal_keys() {
+        use std::collections::BTreeMap;
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let mut row: BTreeMap<&str, Option<i32>> = BTreeMap::new();
+        row.insert("a", Some(1));
+        row.insert("b", None);
+        wtr.serialize(&row).unwrap();
+        row.insert("c", Some(3));
+        let err = wtr.serialize(&row).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { expected_len: 2, len: 3, .. } => {}
+            ref x => {
+                panic!("expected UnequalLengths error, but got '{:?}'", x)
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_map_nested() {
+        use std::collections::BTreeMap;
+
+        let mut row = BTreeMap::new();
+        row.insert("a", vec![1, 2]);
+
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        assert!(wtr.serialize(&row).is_err());
+
+        let mut wtr =
+            WriterBuilder::new().has_headers(false).from_writer(vec![]);
+        wtr.serialize(&row).unwrap();
+        assert_eq!(wtr_as_string(wtr), "1,2\n");
+    }
+
+    #[test]
+    fn write_record_verbatim() {
+        let mut wtr = WriterBuilder::new()
+            .buffer_capacity(3)
+            .terminator(Terminator::CRLF)
+            .from_writer(vec![]);
+        wtr.write_record(["a", "b", "c"]).unwrap();
+        wtr.write_record_verbatim(b"x,\"y,\"\"z\",z").unwrap();
+        wtr.write_field("p").unwrap();
+        wtr.write_record_verbatim(b"q,r").unwrap();
+        assert_eq!(
+            wtr_as_string(wtr),
+            "a,b,c\r\nx,\"y,\"\"z\",z\r\np,q,r\r\n"
+        );
+    }
+
+    #[test]
+    fn write_record_verbatim_unequal() {
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        let err = wtr.write_record_verbatim(b"x,\"y,z\",w").unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { expected_len, len, .. } => {
+                assert_eq!(expected_len, 2);
+                assert_eq!(len, 3);
+            }
+            ref x => panic!("expected UnequalLengths error, got '{:?}'", x),
+        }
+        // The rejected record doesn't leave the writer in the middle of a
+        // record.
+        wtr.write_record(["c", "d"]).unwrap();
+        wtr.write_record_verbatim(b"e,f").unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b\nc,d\ne,f\n");
+
+        let mut wtr = WriterBuilder::new().flexible(true).from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        wtr.write_record_verbatim(b"x,y,z").unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b\nx,y,z\n");
+    }
+
+    #[test]
+    fn terminator_longer_than_buffer() {
+        let mut wtr = WriterBuilder::new()
+            .flexible(true)
+            .buffer_capacity(2)
+            .record_terminator(b"|\r\n")
+            .from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        wtr.write_record([""]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b|\r\n\"\"|\r\n");
+    }
+
+    #[test]
+    fn write_record_verbatim_small_buffer() {
+        let mut wtr = WriterBuilder::new()
+            .buffer_capacity(1)
+            .record_terminator(b"|\r\n")
+            .from_writer(vec![]);
+        wtr.write_record_verbatim(b"a,b").unwrap();
+        wtr.write_record_verbatim(b"c,d").unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b|\r\nc,d|\r\n");
+    }
+
+    #[test]
+    fn record_write_to() {
+        let write = |fields: &[&str], options: &RecordWriteOptions| {
+            let mut buf = vec![];
+            ByteRecord::from(fields).write_to_with(&mut buf, options).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        let default = RecordWriteOptions::new();
+        assert_eq!(
+            write(&["a", "b,c", "d\"e"], &default),
+            "a,\"b,c\",\"d\"\"e\"\n"
+        );
+        assert_eq!(write(&[""], &default), "\"\"\n");
+        assert_eq!(write(&[], &default), "\"\"\n");
+        assert_eq!(write(&["", ""], &default), ",\n");
+        assert_eq!(
+            write(
+                &["a b", "1"],
+                RecordWriteOptions::new()
+                    .quote_style(QuoteStyle::NonNumeric)
+                    .terminator(Terminator::CRLF),
+            ),
+            "\"a b\",1\r\n"
+        );
+
+        // Every byte needing escaping is the worst case for the buffer.
+        let quotes = "\"".repeat(100);
+        let expected =
+            format!("\"{}\",\"{}\"\n", "\"".repeat(200), "\"".repeat(200));
+        assert_eq!(write(&[&quotes, &quotes], &default), expected);
+
+        // Matches what a `Writer` writes.
+        let record = StringRecord::from(vec!["x", "y\nz", ""]);
+        let mut buf = vec![];
+        record.write_to(&mut buf, b'\t').unwrap();
+        let mut wtr =
+            WriterBuilder::new().delimiter(b'\t').from_writer(vec![]);
+        wtr.write_record(&record).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), wtr_as_string(wtr));
+    }
+
+    #[test]
+    fn write_optional_record() {
+        let mut wtr = WriterBuilder::new().from_writer(vec![]);
+        let record: &[Option<&str>] = &[Some("a"), None, Some("c,d")];
+        wtr.write_optional_record(record).unwrap();
+        wtr.write_optional_record(vec![None, Some(b"x".to_vec()), None])
+            .unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,,\"c,d\"\n,x,\n");
+
+        let mut wtr = Writer::from_writer(vec![]);
+        wtr.write_optional_record([None::<&str>]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\"\"\n");
+    }
+
+    #[test]
+    fn invalid_config() {
+        let err = WriterBuilder::new()
+            .delimiter(b';')
+            .quote(b';')
+            .try_from_writer(vec![])
+            .unwrap_err();
+        match *err.kind() {
+            ErrorKind::InvalidConfig(ref msg) => {
+                assert_eq!(msg, "the delimiter and quote are both ';'");
+            }
+            ref x => panic!("expected config error, got {:?}", x),
+        }
+
+        assert!(WriterBuilder::new()
+            .double_quote(false)
+            .escape(b'\n')
+            .try_from_writer(vec![])
+            .is_err());
+        assert!(WriterBuilder::new()
+            .escape(b'\n')
+            .try_from_writer(vec![])
+            .is_ok());
+        assert!(WriterBuilder::new()
+            .delimiter(b';')
+            .quote(b';')
+            .quote_style(QuoteStyle::Never)
+            .try_from_writer(vec![])
+            .is_ok());
+
+        // A record terminator replaces the terminator when checking for
+        // collisions.
+        assert!(WriterBuilder::new()
+            .delimiter(b'\n')
+            .record_terminator(b";")
+            .try_from_writer(vec![])
+            .is_ok());
+        assert!(WriterBuilder::new()
+            .delimiter(b';')
+            .record_terminator(b";")
+            .try_from_writer(vec![])
+            .is_err());
+        // Every byte of a longer terminator is checked.
+        assert!(WriterBuilder::new()
+            .delimiter(b'|')
+            .record_terminator(b"|\r\n")
+            .try_from_writer(vec![])
+            .is_err());
+        assert!(WriterBuilder::new()
+            .quote(b'\n')
+            .record_terminator(b"|\r\n")
+            .try_from_writer(vec![])
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_config_from_path_keeps_file() {
+        let path = std::env::temp_dir()
+            .join(format!("csv-invalid-config-{}.csv", std::process::id()));
+        std::fs::write(&path, "a,b\n").unwrap();
+
+        let result =
+            WriterBuilder::new().delimiter(b';').quote(b';').from_path(&path);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+        assert_eq!(contents, "a,b\n");
+    }
+
+    #[test]
+    fn dialect_presets() {
+        let mut wtr = WriterBuilder::new()
+            .delimiter(b';')
+            .quote_style(QuoteStyle::Always)
+            .excel()
+            .from_writer(vec![]);
+        wtr.write_record(["a,b", "c\"d", "e"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\u{FEFF}\"a,b\",\"c\"\"d\",e\r\n");
+
+        let mut wtr =
+            WriterBuilder::new().flexible(true).rfc4180().from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        let err = wtr.write_record(["c"]).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnequalLengths { .. } => {}
+            ref x => panic!("expected UnequalLengths, got {:?}", x),
+        }
+
+        let mut wtr = WriterBuilder::new()
+            .terminator(Terminator::CRLF)
+            .unix()
+            .from_writer(vec![]);
+        wtr.write_record(["a", "b\nc"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,\"b\nc\"\n");
+    }
+    #[test]
+    fn tagged_enums_round_trip() {
+        use serde::Deserialize;
+
+        use crate::reader::ReaderBuilder;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        enum Value {
+            Price(f64),
+            Qty(u64),
+            Name(String),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Row {
+            id: u32,
+            value: Value,
+            note: Option<String>,
+        }
+
+        let rows = vec![
+            Row { id: 1, value: Value::Price(1.5), note: None },
+            Row { id: 2, value: Value::Qty(3), note: Some("a,b".into()) },
+            Row { id: 3, value: Value::Name("x\ny".into()), note: None },
+        ];
+        for &has_headers in &[true, false] {
+            let mut wtr = WriterBuilder::new()
+                .has_headers(has_headers)
+                .tagged_enums(true)
+                .from_writer(vec![]);
+            for row in &rows {
+                wtr.serialize(row).unwrap();
+            }
+            let data = wtr.into_inner().unwrap();
+            let mut rdr = ReaderBuilder::new()
+                .has_headers(has_headers)
+                .from_reader(&data[..]);
+            if has_headers {
+                assert_eq!(
+                    rdr.headers().unwrap(),
+                    vec!["id", "value", "value_value", "note"]
+                );
+            }
+            let got: Vec<Row> =
+                rdr.deserialize().collect::<crate::Result<_>>().unwrap();
+            assert_eq!(got, rows);
+        }
+    }
+
+    #[test]
+    fn bom() {
+        use crate::reader::ReaderBuilder;
+
+        #[derive(Serialize)]
+        struct Row {
+            a: i32,
+        }
+
+        let mut wtr = WriterBuilder::new().bom(true).from_writer(vec![]);
+        wtr.serialize(Row { a: 1 }).unwrap();
+        wtr.serialize(Row { a: 2 }).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\u{FEFF}a\n1\n2\n");
+
+        let mut wtr = WriterBuilder::new()
+            .bom(true)
+            .has_headers(false)
+            .buffer_capacity(1)
+            .from_writer(vec![]);
+        wtr.serialize(Row { a: 1 }).unwrap();
+        assert_eq!(wtr_as_string(wtr), "\u{FEFF}1\n");
+
+        let wtr = WriterBuilder::new().bom(true).from_writer(vec![]);
+        assert_eq!(wtr_as_string(wtr), "\u{FEFF}");
+
+        let mut wtr = WriterBuilder::new().excel().from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        let data = wtr.into_inner().unwrap();
+        assert_eq!(data, b"\xEF\xBB\xBFa,b\r\n");
+        let mut rdr = ReaderBuilder::new().excel().from_reader(&data[..]);
+        assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+
+        let mut wtr =
+            WriterBuilder::new().excel().rfc4180().from_writer(vec![]);
+        wtr.write_record(["a", "b"]).unwrap();
+        assert_eq!(wtr_as_string(wtr), "a,b\r\n");
+    }
 }